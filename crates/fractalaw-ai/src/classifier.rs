@@ -3,14 +3,28 @@
 //! Aggregates section-level embeddings into law-level embeddings, computes
 //! centroids per label (family, domain, subject), and classifies laws by
 //! cosine similarity to the nearest centroid.
+//!
+//! A built classifier can be saved as versioned JSON and reloaded, so that
+//! `classify` runs are reproducible against a known model.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
 use arrow::array::{Array, FixedSizeListArray, Float32Array, LargeStringArray, StringArray};
 use arrow::record_batch::RecordBatch;
+use ring::digest;
+use serde::{Deserialize, Serialize};
 
 use crate::labels::LabelSet;
 
+/// On-disk format version for saved classifiers. Bump on incompatible changes.
+///
+/// 2: the version also covers the centroid values.
+pub const MODEL_FORMAT_VERSION: u32 = 2;
+
+/// Pointer file naming the most recently saved model in a model directory.
+const LATEST_FILE: &str = "LATEST";
+
 /// Centroid-based classifier for legislation.
 ///
 /// Holds pre-computed centroids per label for family, domain, and subject.
@@ -20,7 +34,23 @@ pub struct Classifier {
     family_centroids: HashMap<String, Vec<f32>>,
     domain_centroids: HashMap<String, Vec<f32>>,
     subject_centroids: HashMap<String, Vec<f32>>,
+    /// family → sub_family → centroid, for hierarchical sub_family prediction.
+    sub_family_centroids: HashMap<String, HashMap<String, Vec<f32>>>,
     dim: usize,
+    /// Name of the embedding model that produced the training vectors.
+    embedding_model: String,
+    /// Sorted names of the laws that contributed to at least one centroid.
+    training_laws: Vec<String>,
+    /// SHA-256 (hex) over the training laws and their labels.
+    training_hash: String,
+}
+
+/// Which trained centroid set to rank an embedding against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Taxonomy {
+    Family,
+    Domain,
+    Subject,
 }
 
 /// Agreement status between AI prediction and ground-truth label.
@@ -52,59 +82,44 @@ pub struct Classification {
     /// Single-select: best-matching family.
     pub family: String,
     pub family_confidence: f32,
+    /// Single-select among the predicted family's children (`None` if it has none).
+    pub sub_family: Option<String>,
+    pub sub_family_confidence: f32,
+    /// Agreement with ground-truth sub_family label.
+    pub sub_family_status: ClassificationStatus,
     /// Multi-select: all subjects above threshold, with confidence.
     pub subjects: Vec<(String, f32)>,
     /// Agreement with ground-truth family label.
     pub status: ClassificationStatus,
 }
 
-/// Summary of centroid computation.
-pub struct CentroidSummary {
-    pub family_count: usize,
-    pub domain_count: usize,
-    pub subject_count: usize,
-    pub laws_used: usize,
-}
-
-impl Classifier {
-    /// Build a classifier by computing centroids from labelled law embeddings.
-    ///
-    /// `law_embeddings` maps law_name → normalized 384-dim vector (from
-    /// [`aggregate_law_embeddings`]). Labels come from [`LabelSet`].
-    pub fn build(law_embeddings: &HashMap<String, Vec<f32>>, labels: &LabelSet) -> Self {
-        let dim = law_embeddings
-            .values()
-            .next()
-            .map(|v| v.len())
-            .unwrap_or(384);
+/// Common interface for law classification strategies.
+///
+/// Implementations only rank labels per [`Taxonomy`]; the provided methods turn
+/// rankings into a [`Classification`] the same way for every strategy, so the
+/// DuckDB output and status semantics don't depend on the method used.
+pub trait LawClassifier {
+    /// Short method name, e.g. `centroid`, `knn`, `linear-probe`.
+    fn method(&self) -> &'static str;
 
-        let family_centroids = compute_family_centroids(law_embeddings, labels, dim);
-        let domain_centroids = compute_domain_centroids(law_embeddings, labels, dim);
-        let subject_centroids = compute_subject_centroids(law_embeddings, labels, dim);
+    /// Version string identifying this exact model, recorded in
+    /// `classification_model`.
+    fn version(&self) -> String;
 
-        Self {
-            family_centroids,
-            domain_centroids,
-            subject_centroids,
-            dim,
-        }
-    }
+    /// Rank every known label in `taxonomy` for `embedding`, best first.
+    fn rank(&self, taxonomy: Taxonomy, embedding: &[f32]) -> Vec<(String, f32)>;
 
-    /// Summary of centroid counts.
-    pub fn summary(&self, laws_used: usize) -> CentroidSummary {
-        CentroidSummary {
-            family_count: self.family_centroids.len(),
-            domain_count: self.domain_centroids.len(),
-            subject_count: self.subject_centroids.len(),
-            laws_used,
-        }
-    }
+    /// Rank the sub_families of `family` for `embedding`, best first.
+    ///
+    /// Only children of `family` are considered, so sub_family predictions
+    /// always sit under the predicted family.
+    fn rank_sub_families(&self, family: &str, embedding: &[f32]) -> Vec<(String, f32)>;
 
     /// Classify a single law from its aggregated embedding.
     ///
     /// Compares the AI prediction against ground-truth labels to set
     /// [`ClassificationStatus`]: `predicted`, `confirmed`, or `conflict`.
-    pub fn classify(
+    fn classify(
         &self,
         law_name: &str,
         embedding: &[f32],
@@ -113,13 +128,26 @@ impl Classifier {
         subject_threshold: f32,
     ) -> Classification {
         // Family: single-select (best match).
-        let (family, family_confidence) = best_match(&self.family_centroids, embedding);
+        let (family, family_confidence) = self
+            .rank(Taxonomy::Family, embedding)
+            .into_iter()
+            .next()
+            .unwrap_or((String::new(), f32::NEG_INFINITY));
+
+        // Sub-family: single-select among the predicted family's children.
+        let (sub_family, sub_family_confidence) = self
+            .rank_sub_families(&family, embedding)
+            .into_iter()
+            .next()
+            .map_or((None, 0.0), |(sub, conf)| (Some(sub), conf));
 
         // Domain: multi-select (all above threshold).
-        let domain = above_threshold(&self.domain_centroids, embedding, domain_threshold);
+        let mut domain = self.rank(Taxonomy::Domain, embedding);
+        domain.retain(|(_, score)| *score >= domain_threshold);
 
         // Subjects: multi-select (all above threshold).
-        let subjects = above_threshold(&self.subject_centroids, embedding, subject_threshold);
+        let mut subjects = self.rank(Taxonomy::Subject, embedding);
+        subjects.retain(|(_, score)| *score >= subject_threshold);
 
         // Compute status by comparing against ground-truth family.
         let status = match labels.law_family.get(law_name) {
@@ -127,19 +155,27 @@ impl Classifier {
             Some(gt_family) if gt_family == &family => ClassificationStatus::Confirmed,
             Some(_) => ClassificationStatus::Conflict,
         };
+        let sub_family_status = match labels.law_sub_family.get(law_name) {
+            None => ClassificationStatus::Predicted,
+            Some(gt_sub) if sub_family.as_ref() == Some(gt_sub) => ClassificationStatus::Confirmed,
+            Some(_) => ClassificationStatus::Conflict,
+        };
 
         Classification {
             law_name: law_name.to_string(),
             domain,
             family,
             family_confidence,
+            sub_family,
+            sub_family_confidence,
+            sub_family_status,
             subjects,
             status,
         }
     }
 
     /// Classify a batch of laws from their aggregated embeddings.
-    pub fn classify_batch(
+    fn classify_batch(
         &self,
         law_embeddings: &HashMap<String, Vec<f32>>,
         labels: &LabelSet,
@@ -153,6 +189,281 @@ impl Classifier {
             })
             .collect()
     }
+}
+
+/// Classification strategy, selectable with `fractalaw classify --method`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Cosine similarity to one centroid per label ([`Classifier`]).
+    Centroid,
+    /// Similarity-weighted vote of the `k` nearest labelled laws.
+    Knn { k: usize },
+    /// Logistic regression trained on the embeddings.
+    LinearProbe,
+}
+
+impl Method {
+    /// Hyperparameters of this method as JSON, e.g. `{"k":5}`, recorded
+    /// with evaluation runs.
+    pub fn parameters(&self) -> serde_json::Value {
+        match *self {
+            Self::Centroid => serde_json::json!({}),
+            Self::Knn { k } => serde_json::json!({ "k": k }),
+            Self::LinearProbe => {
+                let config = crate::probe::ProbeConfig::default();
+                serde_json::json!({
+                    "epochs": config.epochs,
+                    "learning_rate": config.learning_rate,
+                    "l2": config.l2,
+                })
+            }
+        }
+    }
+
+    /// Train a classifier of this kind on labelled law embeddings produced
+    /// by `embedding_model` (which becomes part of its version).
+    pub fn build(
+        &self,
+        law_embeddings: &HashMap<String, Vec<f32>>,
+        labels: &LabelSet,
+        embedding_model: &str,
+    ) -> Box<dyn LawClassifier> {
+        match *self {
+            Self::Centroid => Box::new(
+                Classifier::build(law_embeddings, labels).with_embedding_model(embedding_model),
+            ),
+            Self::Knn { k } => Box::new(
+                crate::knn::KnnClassifier::build(law_embeddings, labels, k)
+                    .with_embedding_model(embedding_model),
+            ),
+            Self::LinearProbe => Box::new(
+                crate::probe::LinearProbe::train(
+                    law_embeddings,
+                    labels,
+                    crate::probe::ProbeConfig::default(),
+                )
+                .with_embedding_model(embedding_model),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for Method {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Centroid => write!(f, "centroid"),
+            Self::Knn { k } => write!(f, "knn:{k}"),
+            Self::LinearProbe => write!(f, "linear-probe"),
+        }
+    }
+}
+
+impl std::str::FromStr for Method {
+    type Err = String;
+
+    /// Parse `centroid`, `knn`, `knn:<k>`, or `linear-probe`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "centroid" => Ok(Self::Centroid),
+            "knn" => Ok(Self::Knn {
+                k: crate::knn::DEFAULT_K,
+            }),
+            "linear-probe" => Ok(Self::LinearProbe),
+            _ => match s.strip_prefix("knn:").map(str::parse::<usize>) {
+                Some(Ok(k)) if k > 0 => Ok(Self::Knn { k }),
+                _ => Err(format!(
+                    "unknown method '{s}' (expected centroid, knn, knn:<k>, or linear-probe)"
+                )),
+            },
+        }
+    }
+}
+
+/// Summary of centroid computation.
+pub struct CentroidSummary {
+    pub family_count: usize,
+    pub domain_count: usize,
+    pub subject_count: usize,
+    pub laws_used: usize,
+}
+
+impl Classifier {
+    /// Build a classifier by computing centroids from labelled law embeddings.
+    ///
+    /// `law_embeddings` maps law_name → normalized 384-dim vector (from
+    /// [`aggregate_law_embeddings`]). Labels come from [`LabelSet`].
+    pub fn build(law_embeddings: &HashMap<String, Vec<f32>>, labels: &LabelSet) -> Self {
+        let dim = law_embeddings
+            .values()
+            .next()
+            .map(|v| v.len())
+            .unwrap_or(384);
+
+        let family_centroids = compute_family_centroids(law_embeddings, labels, dim);
+        let domain_centroids = compute_domain_centroids(law_embeddings, labels, dim);
+        let subject_centroids = compute_subject_centroids(law_embeddings, labels, dim);
+        let sub_family_centroids = compute_sub_family_centroids(law_embeddings, labels, dim);
+        let (training_laws, training_hash) = training_set(law_embeddings, labels);
+
+        Self {
+            family_centroids,
+            domain_centroids,
+            subject_centroids,
+            sub_family_centroids,
+            dim,
+            embedding_model: String::new(),
+            training_laws,
+            training_hash,
+        }
+    }
+
+    /// Record the embedding model the training vectors came from.
+    ///
+    /// The model name is part of [`Classifier::version`], so a classifier
+    /// is never silently reused against vectors from a different model.
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    /// Embedding model name (empty if not recorded).
+    pub fn embedding_model(&self) -> &str {
+        &self.embedding_model
+    }
+
+    /// Sorted names of the laws used for training.
+    pub fn training_laws(&self) -> &[String] {
+        &self.training_laws
+    }
+
+    /// SHA-256 (hex) over the training laws and their labels.
+    pub fn training_hash(&self) -> &str {
+        &self.training_hash
+    }
+
+    /// SHA-256 (hex) over every centroid, sub-family centroids included, in
+    /// label order.
+    pub fn centroid_hash(&self) -> String {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        let mut update = |taxonomy: &str, centroids: BTreeMap<String, Vec<f32>>| {
+            for (label, centroid) in centroids {
+                ctx.update(format!("{taxonomy}\t{label}\n").as_bytes());
+                for value in centroid {
+                    ctx.update(&value.to_le_bytes());
+                }
+            }
+        };
+        update("family", sorted(&self.family_centroids));
+        update("domain", sorted(&self.domain_centroids));
+        update("subject", sorted(&self.subject_centroids));
+        let sub_families: BTreeMap<_, _> = self.sub_family_centroids.iter().collect();
+        for (family, subs) in sub_families {
+            update(&format!("sub_family/{family}"), sorted(subs));
+        }
+        hex(ctx.finish().as_ref())
+    }
+
+    /// Write the classifier as JSON to `path`.
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let saved = SavedClassifier {
+            format_version: MODEL_FORMAT_VERSION,
+            version: self.version(),
+            embedding_model: self.embedding_model.clone(),
+            dim: self.dim,
+            training_hash: self.training_hash.clone(),
+            training_laws: self.training_laws.clone(),
+            family_centroids: sorted(&self.family_centroids),
+            domain_centroids: sorted(&self.domain_centroids),
+            subject_centroids: sorted(&self.subject_centroids),
+            sub_family_centroids: self
+                .sub_family_centroids
+                .iter()
+                .map(|(family, subs)| (family.clone(), sorted(subs)))
+                .collect(),
+        };
+        let json = serde_json::to_vec(&saved)?;
+        std::fs::write(path, json)
+            .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", path.display()))
+    }
+
+    /// Load a classifier previously written by [`Classifier::save`].
+    ///
+    /// Rejects unknown format versions and files whose recorded version does
+    /// not match their provenance fields.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bytes = std::fs::read(path)
+            .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+        let saved: SavedClassifier = serde_json::from_slice(&bytes)?;
+        if saved.format_version != MODEL_FORMAT_VERSION {
+            anyhow::bail!(
+                "unsupported classifier format version {} (expected {MODEL_FORMAT_VERSION})",
+                saved.format_version
+            );
+        }
+        let clf = Self {
+            family_centroids: saved.family_centroids.into_iter().collect(),
+            domain_centroids: saved.domain_centroids.into_iter().collect(),
+            subject_centroids: saved.subject_centroids.into_iter().collect(),
+            sub_family_centroids: saved
+                .sub_family_centroids
+                .into_iter()
+                .map(|(family, subs)| (family, subs.into_iter().collect()))
+                .collect(),
+            dim: saved.dim,
+            embedding_model: saved.embedding_model,
+            training_laws: saved.training_laws,
+            training_hash: saved.training_hash,
+        };
+        if clf.version() != saved.version {
+            anyhow::bail!(
+                "classifier {} is corrupt: recorded version {} does not match contents ({})",
+                path.display(),
+                saved.version,
+                clf.version()
+            );
+        }
+        Ok(clf)
+    }
+
+    /// Save as `<dir>/<version>.json` and point `<dir>/LATEST` at it.
+    ///
+    /// Returns the path of the model file.
+    pub fn save_versioned(&self, dir: &Path) -> anyhow::Result<PathBuf> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("failed to create {}: {e}", dir.display()))?;
+        let version = self.version();
+        let path = dir.join(format!("{version}.json"));
+        self.save(&path)?;
+        std::fs::write(dir.join(LATEST_FILE), &version)
+            .map_err(|e| anyhow::anyhow!("failed to update {LATEST_FILE}: {e}"))?;
+        Ok(path)
+    }
+
+    /// Load a model from `dir` by version, or the most recent one for `"latest"`.
+    pub fn load_versioned(dir: &Path, version: &str) -> anyhow::Result<Self> {
+        let version = if version == "latest" {
+            let latest = std::fs::read_to_string(dir.join(LATEST_FILE)).map_err(|_| {
+                anyhow::anyhow!(
+                    "no saved classifier in {} (run `fractalaw classify` first)",
+                    dir.display()
+                )
+            })?;
+            latest.trim().to_string()
+        } else {
+            version.to_string()
+        };
+        Self::load(&dir.join(format!("{version}.json")))
+    }
+
+    /// Summary of centroid counts.
+    pub fn summary(&self, laws_used: usize) -> CentroidSummary {
+        CentroidSummary {
+            family_count: self.family_centroids.len(),
+            domain_count: self.domain_centroids.len(),
+            subject_count: self.subject_centroids.len(),
+            laws_used,
+        }
+    }
 
     /// Number of family centroids.
     pub fn family_count(&self) -> usize {
@@ -164,6 +475,11 @@ impl Classifier {
         self.domain_centroids.len()
     }
 
+    /// Number of sub_family centroids, across all families.
+    pub fn sub_family_count(&self) -> usize {
+        self.sub_family_centroids.values().map(HashMap::len).sum()
+    }
+
     /// Number of subject centroids.
     pub fn subject_count(&self) -> usize {
         self.subject_centroids.len()
@@ -175,15 +491,58 @@ impl Classifier {
     }
 }
 
+impl LawClassifier for Classifier {
+    fn method(&self) -> &'static str {
+        "centroid"
+    }
+
+    /// E.g. `centroid-v2-3f2a9c01b7de`.
+    ///
+    /// Derived from the format version, embedding model, dimensionality,
+    /// training hash, and centroid hash — rebuilding from the same inputs
+    /// yields the same version, and re-embedding the same laws does not.
+    fn version(&self) -> String {
+        let key = format!(
+            "{MODEL_FORMAT_VERSION}\n{}\n{}\n{}\n{}",
+            self.embedding_model,
+            self.dim,
+            self.training_hash,
+            self.centroid_hash()
+        );
+        format!("centroid-v{MODEL_FORMAT_VERSION}-{}", short_hash(&key))
+    }
+
+    /// Rank every label in `taxonomy` by cosine similarity to `embedding`, best first.
+    fn rank(&self, taxonomy: Taxonomy, embedding: &[f32]) -> Vec<(String, f32)> {
+        let centroids = match taxonomy {
+            Taxonomy::Family => &self.family_centroids,
+            Taxonomy::Domain => &self.domain_centroids,
+            Taxonomy::Subject => &self.subject_centroids,
+        };
+        above_threshold(centroids, embedding, f32::NEG_INFINITY)
+    }
+
+    fn rank_sub_families(&self, family: &str, embedding: &[f32]) -> Vec<(String, f32)> {
+        self.sub_family_centroids
+            .get(family)
+            .map(|subs| above_threshold(subs, embedding, f32::NEG_INFINITY))
+            .unwrap_or_default()
+    }
+}
+
 /// Aggregate section-level embeddings into one law-level embedding per law.
 ///
 /// Input: Arrow RecordBatches from LanceDB with `law_name` (Utf8/LargeUtf8)
 /// and `embedding` (FixedSizeList<Float32, 384>) columns.
 ///
 /// For each law, computes mean of all its section embeddings, then L2-normalizes.
+/// If an `embedding_model` column is present, refuses to average vectors from
+/// different models (see [`single_embedding_model`]).
 pub fn aggregate_law_embeddings(
     batches: &[RecordBatch],
 ) -> anyhow::Result<HashMap<String, Vec<f32>>> {
+    single_embedding_model(batches)?;
+
     // Accumulate: law_name → (sum_vector, count).
     let mut accum: HashMap<String, (Vec<f32>, usize)> = HashMap::new();
 
@@ -241,7 +600,134 @@ pub fn aggregate_law_embeddings(
     Ok(result)
 }
 
+/// The one embedding model named in the batches' `embedding_model` column.
+///
+/// Returns `None` when the column is absent or every value is NULL, and an
+/// error if rows come from more than one model — their vectors live in
+/// different spaces and must not be compared or averaged.
+pub fn single_embedding_model(batches: &[RecordBatch]) -> anyhow::Result<Option<String>> {
+    let mut models = std::collections::BTreeSet::new();
+    for batch in batches {
+        let Some(col) = batch.column_by_name("embedding_model") else {
+            continue;
+        };
+        for row in 0..batch.num_rows() {
+            if let Some(model) = get_string(col.as_ref(), row) {
+                models.insert(model);
+            }
+        }
+    }
+    if models.len() > 1 {
+        anyhow::bail!(
+            "embeddings come from multiple models ({}); run `fractalaw migrate-embeddings`",
+            models.into_iter().collect::<Vec<_>>().join(", ")
+        );
+    }
+    Ok(models.into_iter().next())
+}
+
+// ── Persistence ──
+
+/// Serialized form of a [`Classifier`]. BTreeMaps keep the JSON deterministic.
+#[derive(Serialize, Deserialize)]
+struct SavedClassifier {
+    format_version: u32,
+    version: String,
+    embedding_model: String,
+    dim: usize,
+    training_hash: String,
+    training_laws: Vec<String>,
+    family_centroids: BTreeMap<String, Vec<f32>>,
+    domain_centroids: BTreeMap<String, Vec<f32>>,
+    subject_centroids: BTreeMap<String, Vec<f32>>,
+    sub_family_centroids: BTreeMap<String, BTreeMap<String, Vec<f32>>>,
+}
+
+fn sorted(centroids: &HashMap<String, Vec<f32>>) -> BTreeMap<String, Vec<f32>> {
+    centroids
+        .iter()
+        .map(|(k, v)| (k.clone(), v.clone()))
+        .collect()
+}
+
+/// Laws that contribute to any centroid, sorted, plus a SHA-256 over their labels.
+///
+/// Each law hashes as one line: `name \t family \t domains \t subjects`, with
+/// multi-select values sorted so label order in the source table is irrelevant.
+pub(crate) fn training_set(
+    law_embeddings: &HashMap<String, Vec<f32>>,
+    labels: &LabelSet,
+) -> (Vec<String>, String) {
+    let trainable: HashMap<&str, &str> = labels.trainable_laws().collect();
+    let mut laws: Vec<String> = law_embeddings
+        .keys()
+        .filter(|name| {
+            trainable.contains_key(name.as_str())
+                || labels.law_domain.contains_key(name.as_str())
+                || labels.law_subjects.contains_key(name.as_str())
+        })
+        .cloned()
+        .collect();
+    laws.sort();
+
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    for name in &laws {
+        let family = trainable.get(name.as_str()).copied().unwrap_or("");
+        // Sub-family only counts under a trainable family (see `trainable_sub_families`).
+        let sub_family = labels
+            .law_sub_family
+            .get(name)
+            .filter(|_| !family.is_empty());
+        let mut domains = labels.law_domain.get(name).cloned().unwrap_or_default();
+        domains.sort();
+        let mut subjects = labels.law_subjects.get(name).cloned().unwrap_or_default();
+        subjects.sort();
+        let mut line = format!(
+            "{name}\t{family}\t{}\t{}",
+            domains.join(","),
+            subjects.join(",")
+        );
+        if let Some(sub) = sub_family {
+            line.push('\t');
+            line.push_str(sub);
+        }
+        line.push('\n');
+        ctx.update(line.as_bytes());
+    }
+    let hash = hex(ctx.finish().as_ref());
+    (laws, hash)
+}
+
+/// SHA-256 (hex) over the embeddings of `laws`, in the given order, so a
+/// version changes when the same laws are re-embedded.
+pub(crate) fn embeddings_hash(
+    law_embeddings: &HashMap<String, Vec<f32>>,
+    laws: &[String],
+) -> String {
+    let mut ctx = digest::Context::new(&digest::SHA256);
+    for name in laws {
+        ctx.update(format!("{name}\n").as_bytes());
+        for value in &law_embeddings[name] {
+            ctx.update(&value.to_le_bytes());
+        }
+    }
+    hex(ctx.finish().as_ref())
+}
+
+/// First 12 hex characters of the SHA-256 of `key`, for version strings.
+pub(crate) fn short_hash(key: &str) -> String {
+    hex(digest::digest(&digest::SHA256, key.as_bytes()).as_ref())[..12].to_string()
+}
+
+pub(crate) fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
 // ── Centroid computation ──
+//
+// Each centroid is an f32 sum, and f32 addition isn't associative, so laws
+// are always accumulated in name order: `HashMap` iteration order differs
+// between processes, and the centroid bits feed `Classifier::version`.
 
 fn compute_family_centroids(
     law_embeddings: &HashMap<String, Vec<f32>>,
@@ -249,8 +735,10 @@ fn compute_family_centroids(
     dim: usize,
 ) -> HashMap<String, Vec<f32>> {
     let mut accum: HashMap<&str, (Vec<f32>, usize)> = HashMap::new();
+    let mut laws: Vec<(&str, &str)> = labels.trainable_laws().collect();
+    laws.sort_unstable();
 
-    for (name, family) in labels.trainable_laws() {
+    for (name, family) in laws {
         if let Some(emb) = law_embeddings.get(name) {
             let entry = accum
                 .entry(family)
@@ -271,8 +759,9 @@ fn compute_domain_centroids(
     dim: usize,
 ) -> HashMap<String, Vec<f32>> {
     let mut accum: HashMap<&str, (Vec<f32>, usize)> = HashMap::new();
+    let laws: BTreeMap<&String, &Vec<String>> = labels.law_domain.iter().collect();
 
-    for (name, domains) in &labels.law_domain {
+    for (name, domains) in laws {
         if let Some(emb) = law_embeddings.get(name.as_str()) {
             // Multi-select: contribute to each domain's centroid.
             for domain in domains {
@@ -296,8 +785,9 @@ fn compute_subject_centroids(
     dim: usize,
 ) -> HashMap<String, Vec<f32>> {
     let mut accum: HashMap<&str, (Vec<f32>, usize)> = HashMap::new();
+    let laws: BTreeMap<&String, &Vec<String>> = labels.law_subjects.iter().collect();
 
-    for (name, subjects) in &labels.law_subjects {
+    for (name, subjects) in laws {
         if let Some(emb) = law_embeddings.get(name.as_str()) {
             // Multi-select: contribute to each subject's centroid.
             for subject in subjects {
@@ -315,6 +805,35 @@ fn compute_subject_centroids(
     finalize_centroids(accum)
 }
 
+fn compute_sub_family_centroids(
+    law_embeddings: &HashMap<String, Vec<f32>>,
+    labels: &LabelSet,
+    dim: usize,
+) -> HashMap<String, HashMap<String, Vec<f32>>> {
+    let mut by_family: HashMap<&str, HashMap<&str, (Vec<f32>, usize)>> = HashMap::new();
+    let mut laws: Vec<(&str, &str, &str)> = labels.trainable_sub_families().collect();
+    laws.sort_unstable();
+
+    for (name, family, sub_family) in laws {
+        if let Some(emb) = law_embeddings.get(name) {
+            let entry = by_family
+                .entry(family)
+                .or_default()
+                .entry(sub_family)
+                .or_insert_with(|| (vec![0.0f32; dim], 0));
+            for (acc, &val) in entry.0.iter_mut().zip(emb) {
+                *acc += val;
+            }
+            entry.1 += 1;
+        }
+    }
+
+    by_family
+        .into_iter()
+        .map(|(family, accum)| (family.to_string(), finalize_centroids(accum)))
+        .collect()
+}
+
 fn finalize_centroids(accum: HashMap<&str, (Vec<f32>, usize)>) -> HashMap<String, Vec<f32>> {
     let mut result = HashMap::with_capacity(accum.len());
     for (label, (mut sum, count)) in accum {
@@ -331,22 +850,6 @@ fn finalize_centroids(accum: HashMap<&str, (Vec<f32>, usize)>) -> HashMap<String
 
 // ── Classification helpers ──
 
-/// Find the centroid with highest cosine similarity.
-fn best_match(centroids: &HashMap<String, Vec<f32>>, embedding: &[f32]) -> (String, f32) {
-    let mut best_label = String::new();
-    let mut best_sim = f32::NEG_INFINITY;
-
-    for (label, centroid) in centroids {
-        let sim = cosine_sim(embedding, centroid);
-        if sim > best_sim {
-            best_sim = sim;
-            best_label.clone_from(label);
-        }
-    }
-
-    (best_label, best_sim)
-}
-
 /// Find all centroids above a similarity threshold, sorted descending.
 fn above_threshold(
     centroids: &HashMap<String, Vec<f32>>,
@@ -369,12 +872,12 @@ fn above_threshold(
     matches
 }
 
-fn cosine_sim(a: &[f32], b: &[f32]) -> f32 {
+pub(crate) fn cosine_sim(a: &[f32], b: &[f32]) -> f32 {
     a.iter().zip(b).map(|(x, y)| x * y).sum()
 }
 
 /// L2-normalize a vector in place.
-fn normalize(v: &mut [f32]) {
+pub(crate) fn normalize(v: &mut [f32]) {
     let norm: f32 = v.iter().map(|x| x * x).sum::<f32>().sqrt();
     if norm > 0.0 {
         for x in v.iter_mut() {
@@ -383,7 +886,7 @@ fn normalize(v: &mut [f32]) {
     }
 }
 
-fn get_string(col: &dyn Array, row: usize) -> Option<String> {
+pub(crate) fn get_string(col: &dyn Array, row: usize) -> Option<String> {
     if col.is_null(row) {
         return None;
     }
@@ -489,6 +992,40 @@ mod tests {
         assert!((agg["law_b"][1] - 1.0).abs() < 1e-5);
     }
 
+    #[test]
+    fn aggregate_refuses_mixed_models() {
+        let batch = text_batch(&[
+            ("law_a", &[1.0, 0.0, 0.0, 0.0]),
+            ("law_a", &[0.0, 1.0, 0.0, 0.0]),
+        ]);
+        let with_models = |models: Vec<&str>| {
+            let mut fields: Vec<Field> = batch
+                .schema()
+                .fields()
+                .iter()
+                .map(|f| f.as_ref().clone())
+                .collect();
+            fields.push(Field::new("embedding_model", DataType::Utf8, true));
+            let mut columns = batch.columns().to_vec();
+            columns.push(Arc::new(StringArray::from(models)));
+            RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+        };
+
+        let same = with_models(vec!["minilm", "minilm"]);
+        assert_eq!(
+            single_embedding_model(std::slice::from_ref(&same)).unwrap(),
+            Some("minilm".to_string())
+        );
+        assert_eq!(aggregate_law_embeddings(&[same]).unwrap().len(), 1);
+
+        let mixed = with_models(vec!["minilm", "bge-small"]);
+        let err = aggregate_law_embeddings(&[mixed]).unwrap_err();
+        assert!(err.to_string().contains("multiple models"), "{err}");
+
+        // Batches without the column are not checked.
+        assert_eq!(single_embedding_model(&[batch]).unwrap(), None);
+    }
+
     #[test]
     fn aggregate_multiple_sections_mean_pools() {
         let batch = text_batch(&[
@@ -718,6 +1255,260 @@ mod tests {
         assert_eq!(result.status, ClassificationStatus::Conflict);
     }
 
+    #[test]
+    fn hierarchical_sub_family_within_predicted_family() {
+        let mut law_embs = HashMap::new();
+        law_embs.insert("law_a".to_string(), vec![1.0, 0.0, 0.0, 0.0]);
+        law_embs.insert("law_b".to_string(), vec![0.6, 0.8, 0.0, 0.0]);
+        law_embs.insert("law_c".to_string(), vec![0.0, 0.0, 1.0, 0.0]);
+
+        let mut labels = make_labels(
+            &[("law_a", "ENERGY"), ("law_b", "ENERGY"), ("law_c", "WASTE")],
+            &[],
+            &[],
+        );
+        for (name, sub) in [
+            ("law_a", "RENEWABLE"),
+            ("law_b", "NUCLEAR"),
+            ("law_c", "LANDFILL"),
+        ] {
+            labels
+                .law_sub_family
+                .insert(name.to_string(), sub.to_string());
+        }
+
+        let clf = Classifier::build(&law_embs, &labels);
+        assert_eq!(clf.sub_family_count(), 3);
+
+        // Closer to NUCLEAR than RENEWABLE; LANDFILL (a WASTE child) is never considered.
+        let result = clf.classify("law_b", &[0.5, 0.8, 0.3, 0.0], &labels, 0.3, 0.3);
+        assert_eq!(result.family, "ENERGY");
+        assert_eq!(result.sub_family.as_deref(), Some("NUCLEAR"));
+        assert!(result.sub_family_confidence > 0.9);
+        assert_eq!(result.sub_family_status, ClassificationStatus::Confirmed);
+
+        let result = clf.classify("law_a", &[0.7, 0.7, 0.0, 0.0], &labels, 0.3, 0.3);
+        assert_eq!(result.sub_family.as_deref(), Some("NUCLEAR"));
+        assert_eq!(result.sub_family_status, ClassificationStatus::Conflict);
+
+        // A family with no sub_family children predicts none.
+        let mut flat = make_labels(&[("law_a", "ENERGY"), ("law_c", "WASTE")], &[], &[]);
+        flat.law_sub_family
+            .insert("law_c".to_string(), "LANDFILL".to_string());
+        let clf = Classifier::build(&law_embs, &flat);
+        let result = clf.classify("new", &[1.0, 0.0, 0.0, 0.0], &flat, 0.3, 0.3);
+        assert_eq!(result.family, "ENERGY");
+        assert_eq!(result.sub_family, None);
+        assert_eq!(result.sub_family_status, ClassificationStatus::Predicted);
+    }
+
+    #[test]
+    fn rank_orders_all_labels_by_similarity() {
+        let mut law_embs = HashMap::new();
+        law_embs.insert("law_a".to_string(), vec![1.0, 0.0, 0.0, 0.0]);
+        law_embs.insert("law_b".to_string(), vec![0.0, 1.0, 0.0, 0.0]);
+        law_embs.insert("law_c".to_string(), vec![0.0, 0.0, 1.0, 0.0]);
+
+        let labels = make_labels(
+            &[("law_a", "ENERGY"), ("law_b", "WASTE"), ("law_c", "FIRE")],
+            &[("law_a", vec!["environment"])],
+            &[],
+        );
+
+        let clf = Classifier::build(&law_embs, &labels);
+        let ranked = clf.rank(Taxonomy::Family, &[0.1, 0.8, 0.6, 0.0]);
+
+        let order: Vec<&str> = ranked.iter().map(|(l, _)| l.as_str()).collect();
+        assert_eq!(order, vec!["WASTE", "FIRE", "ENERGY"]);
+
+        // Domain ranking uses the domain centroids, not family.
+        let domains = clf.rank(Taxonomy::Domain, &[1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(domains.len(), 1);
+        assert_eq!(domains[0].0, "environment");
+    }
+
+    #[test]
+    fn training_hash_is_stable_and_label_sensitive() {
+        let mut law_embs = HashMap::new();
+        law_embs.insert("law_a".to_string(), vec![1.0, 0.0, 0.0, 0.0]);
+        law_embs.insert("law_b".to_string(), vec![0.0, 1.0, 0.0, 0.0]);
+        law_embs.insert("law_unlabelled".to_string(), vec![0.0, 0.0, 1.0, 0.0]);
+
+        let labels = make_labels(
+            &[("law_a", "ENERGY"), ("law_b", "WASTE")],
+            &[("law_a", vec!["health_safety", "environment"])],
+            &[],
+        );
+        let a = Classifier::build(&law_embs, &labels).with_embedding_model("m1");
+        let b = Classifier::build(&law_embs, &labels).with_embedding_model("m1");
+        assert_eq!(a.training_laws(), ["law_a", "law_b"]);
+        assert_eq!(a.training_hash(), b.training_hash());
+        assert_eq!(a.version(), b.version());
+        assert!(a.version().starts_with("centroid-v2-"));
+
+        // Same labels, different embedding model → different version.
+        let c = Classifier::build(&law_embs, &labels).with_embedding_model("m2");
+        assert_eq!(a.training_hash(), c.training_hash());
+        assert_ne!(a.version(), c.version());
+
+        // Changing a label changes the hash.
+        let relabelled = make_labels(&[("law_a", "ENERGY"), ("law_b", "FIRE")], &[], &[]);
+        let d = Classifier::build(&law_embs, &relabelled).with_embedding_model("m1");
+        assert_ne!(a.training_hash(), d.training_hash());
+    }
+
+    #[test]
+    fn version_covers_centroid_values() {
+        let mut law_embs = HashMap::new();
+        law_embs.insert("law_a".to_string(), vec![1.0, 0.0, 0.0, 0.0]);
+        law_embs.insert("law_b".to_string(), vec![0.0, 1.0, 0.0, 0.0]);
+        let mut labels = make_labels(&[("law_a", "ENERGY"), ("law_b", "WASTE")], &[], &[]);
+        labels
+            .law_sub_family
+            .insert("law_a".to_string(), "RENEWABLE".to_string());
+        let a = Classifier::build(&law_embs, &labels).with_embedding_model("m1");
+
+        // Same laws and labels, different vectors: same training hash, but
+        // the centroids and so the version differ.
+        let mut moved = law_embs.clone();
+        moved.insert("law_a".to_string(), vec![0.0, 0.0, 1.0, 0.0]);
+        let b = Classifier::build(&moved, &labels).with_embedding_model("m1");
+        assert_eq!(a.training_hash(), b.training_hash());
+        assert_ne!(a.centroid_hash(), b.centroid_hash());
+        assert_ne!(a.version(), b.version());
+
+        // A saved model whose centroid values were edited no longer loads.
+        let dir = std::env::temp_dir().join(format!("fractalaw-clf-edit-{}", std::process::id()));
+        let path = a.save_versioned(&dir).unwrap();
+        let mut saved: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        saved["sub_family_centroids"]["ENERGY"]["RENEWABLE"][0] = serde_json::json!(0.5);
+        std::fs::write(&path, serde_json::to_vec(&saved).unwrap()).unwrap();
+        let Err(err) = Classifier::load(&path) else {
+            panic!("edited model loaded");
+        };
+        assert!(err.to_string().contains("corrupt"), "{err}");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn version_is_independent_of_label_order() {
+        // Values chosen so f32 sums round differently depending on order.
+        let vectors = [
+            [1.0e8, 0.1, 3.0, 0.0],
+            [1.0, 0.2, -1.0e8, 0.7],
+            [-1.0e8, 0.3, 1.0e-3, 0.0],
+            [3.3, 1.0e8, 1.0e8, 0.1],
+            [0.5, -1.0e8, 7.0, 1.0e-7],
+        ];
+        let names: Vec<String> = (0..20).map(|i| format!("law_{i:02}")).collect();
+        let families = ["ENERGY", "WASTE"];
+        let build = |order: &[usize]| {
+            let mut law_embs = HashMap::new();
+            let mut labels = make_labels(&[], &[], &[]);
+            for &i in order {
+                let name = names[i].clone();
+                law_embs.insert(name.clone(), vectors[i % vectors.len()].to_vec());
+                labels
+                    .law_family
+                    .insert(name.clone(), families[i % 2].to_string());
+                labels
+                    .law_sub_family
+                    .insert(name.clone(), format!("SUB_{}", i % 3));
+                labels
+                    .law_domain
+                    .insert(name.clone(), vec!["environment".to_string()]);
+                labels
+                    .law_subjects
+                    .insert(name, vec!["emissions".to_string()]);
+            }
+            Classifier::build(&law_embs, &labels).with_embedding_model("m1")
+        };
+
+        let forward: Vec<usize> = (0..names.len()).collect();
+        let reference = build(&forward);
+        let mut shuffled = forward.clone();
+        for round in 0..10 {
+            // Deterministic reshuffle; each fresh HashMap also iterates in
+            // its own random order.
+            shuffled.rotate_left(7);
+            shuffled.swap(round, names.len() - 1 - round);
+            let other = build(&shuffled);
+            assert_eq!(other.centroid_hash(), reference.centroid_hash());
+            assert_eq!(other.version(), reference.version());
+        }
+    }
+
+    #[test]
+    fn save_and_load_roundtrip() {
+        let mut law_embs = HashMap::new();
+        law_embs.insert("law_a".to_string(), vec![1.0, 0.0, 0.0, 0.0]);
+        law_embs.insert("law_b".to_string(), vec![0.0, 1.0, 0.0, 0.0]);
+
+        let mut labels = make_labels(
+            &[("law_a", "ENERGY"), ("law_b", "WASTE")],
+            &[("law_a", vec!["environment"])],
+            &[("law_b", vec!["smoke"])],
+        );
+        labels
+            .law_sub_family
+            .insert("law_a".to_string(), "RENEWABLE".to_string());
+        let clf = Classifier::build(&law_embs, &labels).with_embedding_model("all-MiniLM-L6-v2");
+
+        let dir = std::env::temp_dir().join(format!("fractalaw-clf-{}", std::process::id()));
+        let path = clf.save_versioned(&dir).unwrap();
+        assert_eq!(path, dir.join(format!("{}.json", clf.version())));
+
+        let loaded = Classifier::load_versioned(&dir, "latest").unwrap();
+        assert_eq!(loaded.version(), clf.version());
+        assert_eq!(loaded.embedding_model(), "all-MiniLM-L6-v2");
+        assert_eq!(loaded.dim(), 4);
+        assert_eq!(loaded.family_count(), 2);
+        assert_eq!(loaded.sub_family_count(), 1);
+        assert_eq!(loaded.training_laws(), clf.training_laws());
+        assert_eq!(
+            loaded.rank(Taxonomy::Family, &[1.0, 0.0, 0.0, 0.0]),
+            clf.rank(Taxonomy::Family, &[1.0, 0.0, 0.0, 0.0])
+        );
+
+        // Editing the provenance fields is detected via the recorded version.
+        let json = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, json.replace("all-MiniLM-L6-v2", "other-model")).unwrap();
+        assert!(Classifier::load(&path).is_err());
+
+        assert!(Classifier::load_versioned(&dir, "centroid-v1-missing").is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn method_parses_and_builds() {
+        assert_eq!("centroid".parse::<Method>(), Ok(Method::Centroid));
+        assert_eq!("knn".parse::<Method>(), Ok(Method::Knn { k: 10 }));
+        assert_eq!("knn:3".parse::<Method>(), Ok(Method::Knn { k: 3 }));
+        assert_eq!("linear-probe".parse::<Method>(), Ok(Method::LinearProbe));
+        assert!("knn:0".parse::<Method>().is_err());
+        assert!("svm".parse::<Method>().is_err());
+        assert_eq!(Method::Knn { k: 3 }.to_string(), "knn:3");
+        assert_eq!(
+            Method::Knn { k: 3 }.parameters(),
+            serde_json::json!({ "k": 3 })
+        );
+        assert_eq!(Method::LinearProbe.parameters()["epochs"], 200);
+
+        let mut law_embs = HashMap::new();
+        law_embs.insert("law_a".to_string(), vec![1.0, 0.0, 0.0, 0.0]);
+        law_embs.insert("law_b".to_string(), vec![0.0, 1.0, 0.0, 0.0]);
+        let labels = make_labels(&[("law_a", "ENERGY"), ("law_b", "WASTE")], &[], &[]);
+
+        for method in [Method::Centroid, Method::Knn { k: 1 }, Method::LinearProbe] {
+            let clf = method.build(&law_embs, &labels, "test-model");
+            let c = clf.classify("law_new", &[0.9, 0.1, 0.0, 0.0], &labels, 0.3, 0.3);
+            assert_eq!(c.family, "ENERGY", "method {method}");
+            assert_eq!(c.status, ClassificationStatus::Predicted);
+            assert!(clf.version().starts_with(clf.method()));
+        }
+    }
+
     #[test]
     fn classify_batch_includes_status() {
         let mut law_embs = HashMap::new();
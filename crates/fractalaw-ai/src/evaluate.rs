@@ -0,0 +1,473 @@
+//! Held-out evaluation of family classification.
+//!
+//! `fractalaw classify` scores predictions against the same labels it trained
+//! on, which overstates accuracy. This module splits the laws from
+//! [`LabelSet::trainable_laws`] into folds, trains on all but one fold, and
+//! scores family predictions on the held-out laws only.
+//!
+//! Fold membership is derived from a SHA-256 of the law name, so a given law
+//! always lands in the same fold and runs are comparable over time.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use ring::digest;
+
+use crate::classifier::Method;
+use crate::labels::LabelSet;
+
+/// Number of equal-width confidence bins used for calibration.
+pub const CALIBRATION_BINS: usize = 10;
+
+/// How labelled laws are split into training and test sets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Split {
+    /// k-fold cross-validation: every labelled law is tested exactly once.
+    KFold(usize),
+    /// Single held-out set containing roughly this fraction of laws.
+    Holdout(f32),
+}
+
+impl Split {
+    /// Short description stored alongside results, e.g. `5-fold` or `holdout-0.20`.
+    pub fn describe(&self) -> String {
+        match self {
+            Self::KFold(k) => format!("{k}-fold"),
+            Self::Holdout(frac) => format!("holdout-{frac:.2}"),
+        }
+    }
+}
+
+/// Domain and subject threshold used when classifying held-out laws. Only
+/// the family is scored, so domains and subjects are not kept.
+pub const HELD_OUT_THRESHOLD: f32 = 1.0;
+
+/// A single held-out family prediction.
+pub struct Prediction {
+    pub law_name: String,
+    pub truth: String,
+    pub predicted: String,
+    pub confidence: f32,
+    pub fold: usize,
+}
+
+/// Precision/recall/F1 for one family label.
+pub struct FamilyMetrics {
+    pub family: String,
+    /// Held-out laws whose ground truth is this family.
+    pub support: usize,
+    /// Held-out laws predicted as this family.
+    pub predicted: usize,
+    pub true_positives: usize,
+    pub precision: f32,
+    pub recall: f32,
+    pub f1: f32,
+}
+
+/// One confidence bin: how often predictions in `[lower, upper)` were correct.
+pub struct CalibrationBin {
+    pub lower: f32,
+    pub upper: f32,
+    pub count: usize,
+    pub mean_confidence: f32,
+    pub accuracy: f32,
+}
+
+/// Results of an evaluation run.
+pub struct EvalReport {
+    pub split: Split,
+    pub predictions: Vec<Prediction>,
+    /// Per-family metrics, sorted by family name.
+    pub families: Vec<FamilyMetrics>,
+    /// (truth, predicted) → count.
+    pub confusion: BTreeMap<(String, String), usize>,
+    pub calibration: Vec<CalibrationBin>,
+    pub accuracy: f32,
+    /// Unweighted mean F1 over families with support.
+    pub macro_f1: f32,
+    /// Expected calibration error of `family_confidence`.
+    pub expected_calibration_error: f32,
+}
+
+/// Evaluate family classification with held-out laws.
+///
+/// Only laws that have both an embedding and a trainable family label take
+/// part. Each fold trains a fresh classifier of the given [`Method`] with the
+/// test laws' labels removed, then predicts their family.
+pub fn evaluate(
+    law_embeddings: &HashMap<String, Vec<f32>>,
+    labels: &LabelSet,
+    split: Split,
+    method: Method,
+) -> EvalReport {
+    let laws: Vec<(&str, &str)> = labels
+        .trainable_laws()
+        .filter(|(name, _)| law_embeddings.contains_key(*name))
+        .collect();
+
+    let folds = match split {
+        Split::KFold(k) => k.max(2),
+        Split::Holdout(_) => 1,
+    };
+
+    let mut predictions = Vec::new();
+    for fold in 0..folds {
+        let test: BTreeSet<&str> = laws
+            .iter()
+            .map(|(name, _)| *name)
+            .filter(|name| in_test_set(name, split, fold))
+            .collect();
+        if test.is_empty() {
+            continue;
+        }
+
+        let train_labels = without_laws(labels, &test);
+        if train_labels.trainable_laws().next().is_none() {
+            continue;
+        }
+        // Fold models are never recorded, so their version needs no model name.
+        let classifier = method.build(law_embeddings, &train_labels, "");
+
+        for &(name, truth) in laws.iter().filter(|(name, _)| test.contains(name)) {
+            let c = classifier.classify(
+                name,
+                &law_embeddings[name],
+                &train_labels,
+                HELD_OUT_THRESHOLD,
+                HELD_OUT_THRESHOLD,
+            );
+            predictions.push(Prediction {
+                law_name: name.to_string(),
+                truth: truth.to_string(),
+                predicted: c.family,
+                confidence: c.family_confidence,
+                fold,
+            });
+        }
+    }
+
+    score(split, predictions)
+}
+
+/// Compute metrics from a set of held-out predictions.
+pub fn score(split: Split, predictions: Vec<Prediction>) -> EvalReport {
+    let mut confusion: BTreeMap<(String, String), usize> = BTreeMap::new();
+    for p in &predictions {
+        *confusion
+            .entry((p.truth.clone(), p.predicted.clone()))
+            .or_default() += 1;
+    }
+
+    let families: BTreeSet<&str> = predictions
+        .iter()
+        .flat_map(|p| [p.truth.as_str(), p.predicted.as_str()])
+        .collect();
+    let families: Vec<FamilyMetrics> = families
+        .into_iter()
+        .map(|family| {
+            let support = predictions.iter().filter(|p| p.truth == family).count();
+            let predicted = predictions.iter().filter(|p| p.predicted == family).count();
+            let true_positives = confusion
+                .get(&(family.to_string(), family.to_string()))
+                .copied()
+                .unwrap_or(0);
+            let precision = ratio(true_positives, predicted);
+            let recall = ratio(true_positives, support);
+            let f1 = if precision + recall > 0.0 {
+                2.0 * precision * recall / (precision + recall)
+            } else {
+                0.0
+            };
+            FamilyMetrics {
+                family: family.to_string(),
+                support,
+                predicted,
+                true_positives,
+                precision,
+                recall,
+                f1,
+            }
+        })
+        .collect();
+
+    let correct = predictions
+        .iter()
+        .filter(|p| p.truth == p.predicted)
+        .count();
+    let accuracy = ratio(correct, predictions.len());
+
+    let supported: Vec<&FamilyMetrics> = families.iter().filter(|f| f.support > 0).collect();
+    let macro_f1 = if supported.is_empty() {
+        0.0
+    } else {
+        supported.iter().map(|f| f.f1).sum::<f32>() / supported.len() as f32
+    };
+
+    let calibration = calibrate(&predictions);
+    let expected_calibration_error = calibration
+        .iter()
+        .map(|b| b.count as f32 * (b.accuracy - b.mean_confidence).abs())
+        .sum::<f32>()
+        / predictions.len().max(1) as f32;
+
+    EvalReport {
+        split,
+        predictions,
+        families,
+        confusion,
+        calibration,
+        accuracy,
+        macro_f1,
+        expected_calibration_error,
+    }
+}
+
+/// Bucket predictions into equal-width confidence bins over [0, 1].
+fn calibrate(predictions: &[Prediction]) -> Vec<CalibrationBin> {
+    let mut bins: Vec<(usize, f32, usize)> = vec![(0, 0.0, 0); CALIBRATION_BINS];
+    for p in predictions {
+        let conf = p.confidence.clamp(0.0, 1.0);
+        let idx = ((conf * CALIBRATION_BINS as f32) as usize).min(CALIBRATION_BINS - 1);
+        let bin = &mut bins[idx];
+        bin.0 += 1;
+        bin.1 += conf;
+        if p.truth == p.predicted {
+            bin.2 += 1;
+        }
+    }
+
+    bins.into_iter()
+        .enumerate()
+        .map(|(i, (count, conf_sum, correct))| CalibrationBin {
+            lower: i as f32 / CALIBRATION_BINS as f32,
+            upper: (i + 1) as f32 / CALIBRATION_BINS as f32,
+            count,
+            mean_confidence: if count > 0 {
+                conf_sum / count as f32
+            } else {
+                0.0
+            },
+            accuracy: ratio(correct, count),
+        })
+        .collect()
+}
+
+/// Whether `law_name` is held out in `fold` under `split`.
+fn in_test_set(law_name: &str, split: Split, fold: usize) -> bool {
+    let h = name_hash(law_name);
+    match split {
+        Split::KFold(k) => (h % k.max(2) as u64) as usize == fold,
+        Split::Holdout(frac) => (h as f64 / u64::MAX as f64) < frac as f64,
+    }
+}
+
+/// Stable 64-bit hash of a law name (first 8 bytes of its SHA-256).
+fn name_hash(law_name: &str) -> u64 {
+    let d = digest::digest(&digest::SHA256, law_name.as_bytes());
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&d.as_ref()[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+/// Copy of `labels` with every label for the given laws removed.
+fn without_laws(labels: &LabelSet, excluded: &BTreeSet<&str>) -> LabelSet {
+    fn filter<V: Clone>(map: &HashMap<String, V>, excluded: &BTreeSet<&str>) -> HashMap<String, V> {
+        map.iter()
+            .filter(|(name, _)| !excluded.contains(name.as_str()))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect()
+    }
+    LabelSet {
+        law_family: filter(&labels.law_family, excluded),
+        law_sub_family: filter(&labels.law_sub_family, excluded),
+        law_domain: filter(&labels.law_domain, excluded),
+        law_subjects: filter(&labels.law_subjects, excluded),
+    }
+}
+
+fn ratio(num: usize, den: usize) -> f32 {
+    if den == 0 {
+        0.0
+    } else {
+        num as f32 / den as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels_for(families: &[(&str, &str)]) -> LabelSet {
+        LabelSet {
+            law_family: families
+                .iter()
+                .map(|(n, f)| (n.to_string(), f.to_string()))
+                .collect(),
+            law_sub_family: HashMap::new(),
+            law_domain: HashMap::new(),
+            law_subjects: HashMap::new(),
+        }
+    }
+
+    fn prediction(truth: &str, predicted: &str, confidence: f32) -> Prediction {
+        Prediction {
+            law_name: String::new(),
+            truth: truth.to_string(),
+            predicted: predicted.to_string(),
+            confidence,
+            fold: 0,
+        }
+    }
+
+    /// Two well-separated clusters of laws: ENERGY near +x, WASTE near +y.
+    fn clustered() -> (HashMap<String, Vec<f32>>, LabelSet) {
+        let mut embs = HashMap::new();
+        let mut families = Vec::new();
+        for i in 0..10 {
+            let jitter = i as f32 * 0.01;
+            embs.insert(format!("energy_{i}"), vec![1.0, jitter, 0.0, 0.0]);
+            embs.insert(format!("waste_{i}"), vec![jitter, 1.0, 0.0, 0.0]);
+        }
+        for name in embs.keys() {
+            let family = if name.starts_with("energy") {
+                "ENERGY"
+            } else {
+                "WASTE"
+            };
+            families.push((name.clone(), family));
+        }
+        let pairs: Vec<(&str, &str)> = families.iter().map(|(n, f)| (n.as_str(), *f)).collect();
+        (embs, labels_for(&pairs))
+    }
+
+    #[test]
+    fn kfold_tests_every_law_once() {
+        let (embs, labels) = clustered();
+        let report = evaluate(&embs, &labels, Split::KFold(5), Method::Centroid);
+
+        let tested: BTreeSet<&str> = report
+            .predictions
+            .iter()
+            .map(|p| p.law_name.as_str())
+            .collect();
+        assert_eq!(
+            tested.len(),
+            report.predictions.len(),
+            "no law tested twice"
+        );
+        assert_eq!(tested.len(), 20);
+        assert!((report.accuracy - 1.0).abs() < 1e-6);
+        assert!((report.macro_f1 - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn every_method_evaluates() {
+        let (embs, labels) = clustered();
+        for method in [Method::Knn { k: 3 }, Method::LinearProbe] {
+            let report = evaluate(&embs, &labels, Split::KFold(4), method);
+            assert_eq!(report.predictions.len(), 20, "method {method}");
+            assert!(
+                report.accuracy > 0.9,
+                "method {method}: {}",
+                report.accuracy
+            );
+        }
+    }
+
+    #[test]
+    fn holdout_is_stable_and_partial() {
+        let (embs, labels) = clustered();
+        let a = evaluate(&embs, &labels, Split::Holdout(0.3), Method::Centroid);
+        let b = evaluate(&embs, &labels, Split::Holdout(0.3), Method::Centroid);
+
+        let names = |r: &EvalReport| {
+            r.predictions
+                .iter()
+                .map(|p| p.law_name.clone())
+                .collect::<BTreeSet<_>>()
+        };
+        assert_eq!(names(&a), names(&b));
+        assert!(a.predictions.len() < 20);
+        assert_eq!(Split::Holdout(0.3).describe(), "holdout-0.30");
+    }
+
+    #[test]
+    fn excludes_noise_families_and_unembedded_laws() {
+        let (mut embs, _) = clustered();
+        embs.insert("noise".to_string(), vec![0.0, 0.0, 1.0, 0.0]);
+        let labels = labels_for(&[
+            ("energy_0", "ENERGY"),
+            ("energy_1", "ENERGY"),
+            ("waste_0", "WASTE"),
+            ("waste_1", "WASTE"),
+            ("noise", "X: No Family"),
+            ("no_embedding", "ENERGY"),
+        ]);
+        let report = evaluate(&embs, &labels, Split::KFold(2), Method::Centroid);
+        assert!(
+            report
+                .predictions
+                .iter()
+                .all(|p| p.law_name != "noise" && p.law_name != "no_embedding")
+        );
+    }
+
+    #[test]
+    fn per_family_metrics_and_confusion() {
+        let report = score(
+            Split::KFold(5),
+            vec![
+                prediction("ENERGY", "ENERGY", 0.9),
+                prediction("ENERGY", "WASTE", 0.6),
+                prediction("WASTE", "WASTE", 0.8),
+                prediction("FIRE", "WASTE", 0.55),
+            ],
+        );
+
+        let by_family: HashMap<&str, &FamilyMetrics> = report
+            .families
+            .iter()
+            .map(|f| (f.family.as_str(), f))
+            .collect();
+        let waste = by_family["WASTE"];
+        assert_eq!(waste.support, 1);
+        assert_eq!(waste.predicted, 3);
+        assert!((waste.precision - 1.0 / 3.0).abs() < 1e-6);
+        assert!((waste.recall - 1.0).abs() < 1e-6);
+        assert!((waste.f1 - 0.5).abs() < 1e-6);
+
+        let energy = by_family["ENERGY"];
+        assert!((energy.precision - 1.0).abs() < 1e-6);
+        assert!((energy.recall - 0.5).abs() < 1e-6);
+
+        assert_eq!(by_family["FIRE"].f1, 0.0);
+        assert_eq!(report.confusion[&("ENERGY".into(), "WASTE".into())], 1);
+        assert_eq!(report.confusion[&("FIRE".into(), "WASTE".into())], 1);
+        assert!((report.accuracy - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn calibration_bins_and_ece() {
+        let report = score(
+            Split::KFold(5),
+            vec![
+                prediction("A", "A", 0.95),
+                prediction("A", "B", 0.91),
+                prediction("A", "A", 0.15),
+            ],
+        );
+        assert_eq!(report.calibration.len(), CALIBRATION_BINS);
+
+        let top = &report.calibration[9];
+        assert_eq!(top.count, 2);
+        assert!((top.mean_confidence - 0.93).abs() < 1e-5);
+        assert!((top.accuracy - 0.5).abs() < 1e-6);
+
+        let low = &report.calibration[1];
+        assert_eq!(low.count, 1);
+        assert!((low.accuracy - 1.0).abs() < 1e-6);
+
+        // ECE = (2 * |0.5 - 0.93| + 1 * |1.0 - 0.15|) / 3
+        let expected = (2.0 * 0.43 + 0.85) / 3.0;
+        assert!((report.expected_calibration_error - expected).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,235 @@
+//! Greedy text generation with a local decoder-only ONNX language model.
+//!
+//! Targets small models exported with Hugging Face Optimum
+//! (`optimum-cli export onnx --task text-generation`). The model directory
+//! must contain `model.onnx` and `tokenizer.json`; `generation_config.json`
+//! is read for end-of-sequence tokens when present. The full sequence is
+//! re-run for every new token, so models exported with past key values are
+//! not supported. GGUF models are served through llama.cpp's
+//! OpenAI-compatible server instead.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use ort::session::{Session, SessionInputValue};
+use ort::value::Tensor;
+use tokenizers::Tokenizer;
+use tracing::info;
+
+/// End-of-sequence tokens tried when `generation_config.json` doesn't name one.
+const EOS_TOKENS: &[&str] = &["</s>", "<|endoftext|>", "<|im_end|>", "<|eot_id|>", "<eos>"];
+
+/// Output of a [`Generator::generate`] call.
+#[derive(Debug, Clone)]
+pub struct Generation {
+    pub text: String,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    /// Log-probability of each generated token under the model.
+    pub logprobs: Vec<f32>,
+}
+
+/// Local causal language model using ONNX Runtime.
+pub struct Generator {
+    session: Session,
+    tokenizer: Tokenizer,
+    eos_ids: Vec<u32>,
+    /// Whether the model takes a `position_ids` input.
+    position_ids: bool,
+    model_name: String,
+}
+
+impl Generator {
+    /// Load a model from a directory containing `model.onnx` and `tokenizer.json`.
+    pub fn load(model_dir: &Path) -> anyhow::Result<Self> {
+        let model_path = model_dir.join("model.onnx");
+        let tokenizer_path = model_dir.join("tokenizer.json");
+
+        anyhow::ensure!(model_path.exists(), "model.onnx not found in {model_dir:?}");
+        anyhow::ensure!(
+            tokenizer_path.exists(),
+            "tokenizer.json not found in {model_dir:?}"
+        );
+
+        let session = Session::builder()?.commit_from_file(&model_path)?;
+        let input_names: Vec<&str> = session.inputs().iter().map(|i| i.name()).collect();
+        anyhow::ensure!(
+            !input_names.iter().any(|n| n.starts_with("past_key_values")),
+            "{} expects past key values; export without `--with-past`",
+            model_path.display()
+        );
+        let position_ids = input_names.contains(&"position_ids");
+
+        let tokenizer = Tokenizer::from_file(&tokenizer_path)
+            .map_err(|e| anyhow::anyhow!("load tokenizer: {e}"))?;
+
+        let mut eos_ids = match std::fs::read_to_string(model_dir.join("generation_config.json")) {
+            Ok(json) => eos_ids_from_config(&json)?,
+            Err(_) => Vec::new(),
+        };
+        if eos_ids.is_empty() {
+            eos_ids = EOS_TOKENS
+                .iter()
+                .filter_map(|t| tokenizer.token_to_id(t))
+                .collect();
+        }
+        anyhow::ensure!(
+            !eos_ids.is_empty(),
+            "no end-of-sequence token found for {}",
+            model_dir.display()
+        );
+
+        let model_name = model_dir
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        info!(model = %model_path.display(), ?eos_ids, "loaded generation model");
+        Ok(Self {
+            session,
+            tokenizer,
+            eos_ids,
+            position_ids,
+            model_name,
+        })
+    }
+
+    /// Model name derived from the model directory.
+    pub fn model_name(&self) -> &str {
+        &self.model_name
+    }
+
+    /// Greedily decode up to `max_tokens` tokens following `prompt`.
+    pub fn generate(&mut self, prompt: &str, max_tokens: usize) -> anyhow::Result<Generation> {
+        let encoding = self
+            .tokenizer
+            .encode(prompt, true)
+            .map_err(|e| anyhow::anyhow!("tokenize: {e}"))?;
+        let mut ids: Vec<i64> = encoding.get_ids().iter().map(|&id| id as i64).collect();
+        let prompt_tokens = ids.len();
+        anyhow::ensure!(prompt_tokens > 0, "empty prompt");
+
+        let mut generated: Vec<u32> = Vec::new();
+        let mut logprobs = Vec::new();
+        while generated.len() < max_tokens {
+            let (next, logprob) = self.next_token(&ids)?;
+            if self.eos_ids.contains(&next) {
+                break;
+            }
+            generated.push(next);
+            logprobs.push(logprob);
+            ids.push(next as i64);
+        }
+
+        let text = self
+            .tokenizer
+            .decode(&generated, true)
+            .map_err(|e| anyhow::anyhow!("decode: {e}"))?;
+        Ok(Generation {
+            text,
+            prompt_tokens,
+            completion_tokens: generated.len(),
+            logprobs,
+        })
+    }
+
+    /// Run the model over `ids` and return the most likely next token with
+    /// its log-probability.
+    fn next_token(&mut self, ids: &[i64]) -> anyhow::Result<(u32, f32)> {
+        let seq_len = ids.len();
+        let shape = [1, seq_len as i64];
+
+        let mut inputs: Vec<(Cow<str>, SessionInputValue)> = ort::inputs![
+            "input_ids" => Tensor::from_array((shape, ids.to_vec().into_boxed_slice()))?,
+            "attention_mask" => Tensor::from_array((shape, vec![1i64; seq_len].into_boxed_slice()))?,
+        ];
+        if self.position_ids {
+            let positions: Vec<i64> = (0..seq_len as i64).collect();
+            inputs.push((
+                "position_ids".into(),
+                Tensor::from_array((shape, positions.into_boxed_slice()))?.into(),
+            ));
+        }
+
+        let outputs = self.session.run(inputs)?;
+
+        // Logits: [1, seq_len, vocab]; only the last position matters.
+        let (output_shape, logits) = outputs["logits"].try_extract_tensor::<f32>()?;
+        let dims: &[i64] = output_shape;
+        anyhow::ensure!(
+            dims.len() == 3 && dims[0] == 1 && dims[1] as usize == seq_len,
+            "unexpected logits shape: {dims:?}, expected [1, {seq_len}, vocab]"
+        );
+        let vocab = dims[2] as usize;
+        let last = &logits[(seq_len - 1) * vocab..seq_len * vocab];
+        let next = argmax(last).ok_or_else(|| anyhow::anyhow!("empty logits"))?;
+        Ok((next, log_softmax_at(last, next as usize)))
+    }
+}
+
+/// Index of the largest value.
+fn argmax(values: &[f32]) -> Option<u32> {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.partial_cmp(b.1).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i as u32)
+}
+
+/// `log(softmax(logits)[index])`, computed stably.
+fn log_softmax_at(logits: &[f32], index: usize) -> f32 {
+    let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let sum: f32 = logits.iter().map(|&l| (l - max).exp()).sum();
+    logits[index] - max - sum.ln()
+}
+
+/// `eos_token_id` from a `generation_config.json`, which may be one id or a list.
+fn eos_ids_from_config(json: &str) -> anyhow::Result<Vec<u32>> {
+    let config: serde_json::Value = serde_json::from_str(json)?;
+    let ids: Vec<u64> = match &config["eos_token_id"] {
+        serde_json::Value::Number(n) => n.as_u64().into_iter().collect(),
+        serde_json::Value::Array(ids) => ids.iter().filter_map(|v| v.as_u64()).collect(),
+        _ => Vec::new(),
+    };
+    Ok(ids.into_iter().map(|id| id as u32).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eos_ids_accept_single_or_list() {
+        assert_eq!(
+            eos_ids_from_config(r#"{"eos_token_id": 2}"#).unwrap(),
+            vec![2]
+        );
+        assert_eq!(
+            eos_ids_from_config(r#"{"eos_token_id": [128001, 128009]}"#).unwrap(),
+            vec![128001, 128009]
+        );
+        assert!(
+            eos_ids_from_config(r#"{"bos_token_id": 1}"#)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn argmax_picks_largest_logit() {
+        assert_eq!(argmax(&[0.1, 2.5, -1.0, 2.4]), Some(1));
+        assert_eq!(argmax(&[]), None);
+    }
+
+    #[test]
+    fn log_softmax_matches_probabilities() {
+        let logits = [1.0f32, 2.0, 3.0];
+        let total: f32 = logits.iter().map(|l| l.exp()).sum();
+        for (i, l) in logits.iter().enumerate() {
+            assert!((log_softmax_at(&logits, i) - (l.exp() / total).ln()).abs() < 1e-6);
+        }
+        // Large logits don't overflow.
+        assert!((log_softmax_at(&[1000.0, 1000.0], 0) - 0.5f32.ln()).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,277 @@
+//! JSON Schema validation for structured `ai-inference` output.
+//!
+//! Supports the subset of JSON Schema used to describe model replies:
+//! `type` (a name or list of names), `enum`, `const`, `properties`,
+//! `required`, `additionalProperties` (boolean), `items`, `anyOf`,
+//! `minLength`/`maxLength`, `minimum`/`maximum`, and `minItems`/`maxItems`.
+//! Other keywords are ignored.
+
+use serde_json::Value;
+
+/// Validate `value` against `schema`, returning one message per violation
+/// (each prefixed with the JSON Pointer of the offending value).
+pub fn validate(schema: &Value, value: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    check(schema, value, "", &mut errors);
+    errors
+}
+
+/// Pull the JSON value out of a model reply, tolerating Markdown fences and
+/// prose around a single top-level object or array.
+pub fn extract_json(text: &str) -> Option<Value> {
+    let trimmed = text.trim();
+    if let Ok(value) = serde_json::from_str(trimmed) {
+        return Some(value);
+    }
+    let start = trimmed.find(['{', '['])?;
+    let close = if trimmed[start..].starts_with('{') {
+        '}'
+    } else {
+        ']'
+    };
+    let end = trimmed.rfind(close)?;
+    if end < start {
+        return None;
+    }
+    serde_json::from_str(&trimmed[start..=end]).ok()
+}
+
+/// Follow-up prompt asking the model to fix a reply that failed validation.
+pub fn repair_prompt(original: &str, reply: &str, errors: &[String]) -> String {
+    let problems = errors
+        .iter()
+        .map(|e| format!("- {e}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "{original}\n\n\
+         Your previous reply was:\n{reply}\n\n\
+         It does not satisfy the required JSON schema:\n{problems}\n\n\
+         Reply again with only a JSON value that satisfies the schema."
+    )
+}
+
+fn check(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    let Some(schema) = schema.as_object() else {
+        // `true` accepts anything; `false` accepts nothing.
+        if schema == &Value::Bool(false) {
+            errors.push(format!("{}: not allowed", pointer(path)));
+        }
+        return;
+    };
+
+    if let Some(types) = schema.get("type") {
+        let allowed: Vec<&str> = match types {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| has_type(value, t)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                pointer(path),
+                allowed.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(options) = schema.get("enum").and_then(Value::as_array)
+        && !options.contains(value)
+    {
+        errors.push(format!(
+            "{}: {value} is not one of {options:?}",
+            pointer(path)
+        ));
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != value
+    {
+        errors.push(format!("{}: expected {expected}", pointer(path)));
+    }
+
+    if let Some(branches) = schema.get("anyOf").and_then(Value::as_array)
+        && !branches.iter().any(|b| validate(b, value).is_empty())
+    {
+        errors.push(format!(
+            "{}: matches none of the anyOf alternatives",
+            pointer(path)
+        ));
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(name) {
+                        errors.push(format!(
+                            "{}: missing required property '{name}'",
+                            pointer(path)
+                        ));
+                    }
+                }
+            }
+            for (name, child) in object {
+                let child_path = format!("{path}/{}", name.replace('~', "~0").replace('/', "~1"));
+                match properties.and_then(|p| p.get(name)) {
+                    Some(child_schema) => check(child_schema, child, &child_path, errors),
+                    None => {
+                        if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                            errors.push(format!("{}: unexpected property", pointer(&child_path)));
+                        }
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+                && (items.len() as u64) < min
+            {
+                errors.push(format!("{}: fewer than {min} items", pointer(path)));
+            }
+            if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+                && (items.len() as u64) > max
+            {
+                errors.push(format!("{}: more than {max} items", pointer(path)));
+            }
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    check(item_schema, item, &format!("{path}/{i}"), errors);
+                }
+            }
+        }
+        Value::String(s) => {
+            let len = s.chars().count() as u64;
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                && len < min
+            {
+                errors.push(format!("{}: shorter than {min} characters", pointer(path)));
+            }
+            if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+                && len > max
+            {
+                errors.push(format!("{}: longer than {max} characters", pointer(path)));
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or(f64::NAN);
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+                && n < min
+            {
+                errors.push(format!("{}: less than {min}", pointer(path)));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+                && n > max
+            {
+                errors.push(format!("{}: greater than {max}", pointer(path)));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Object(_) => "object",
+        Value::Array(_) => "array",
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+    }
+}
+
+fn pointer(path: &str) -> &str {
+    if path.is_empty() { "/" } else { path }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "holder": { "type": "string", "minLength": 1 },
+                "qualifier": { "type": ["string", "null"] },
+                "drrp_type": { "enum": ["duty", "right", "responsibility", "power"] },
+                "refs": { "type": "array", "items": { "type": "string" }, "maxItems": 2 }
+            },
+            "required": ["holder", "qualifier", "drrp_type"],
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn accepts_conforming_value() {
+        let value = json!({
+            "holder": "employer",
+            "qualifier": null,
+            "drrp_type": "duty",
+            "refs": ["s.2(1)"]
+        });
+        assert!(validate(&entry_schema(), &value).is_empty());
+    }
+
+    #[test]
+    fn reports_each_violation_with_path() {
+        let value = json!({
+            "holder": "",
+            "drrp_type": "obligation",
+            "refs": ["s.2", 3, "s.4"],
+            "extra": true
+        });
+        let errors = validate(&entry_schema(), &value);
+        let expected = [
+            "/: missing required property 'qualifier'",
+            "/holder: shorter than 1 characters",
+            "/drrp_type: \"obligation\" is not one of",
+            "/refs: more than 2 items",
+            "/refs/1: expected string, got number",
+            "/extra: unexpected property",
+        ];
+        for e in expected {
+            assert!(
+                errors.iter().any(|m| m.starts_with(e)),
+                "{e} not in {errors:?}"
+            );
+        }
+        assert_eq!(errors.len(), expected.len(), "{errors:?}");
+    }
+
+    #[test]
+    fn integer_and_any_of() {
+        let schema = json!({ "anyOf": [{ "type": "integer" }, { "type": "null" }] });
+        assert!(validate(&schema, &json!(3)).is_empty());
+        assert!(validate(&schema, &Value::Null).is_empty());
+        assert_eq!(validate(&schema, &json!(3.5)).len(), 1);
+    }
+
+    #[test]
+    fn extracts_json_from_fenced_reply() {
+        let reply = "Here you go:\n```json\n{\"holder\": \"employer\", \"refs\": [\"a\"]}\n```";
+        assert_eq!(
+            extract_json(reply),
+            Some(json!({ "holder": "employer", "refs": ["a"] }))
+        );
+        assert_eq!(extract_json(" [1, 2] "), Some(json!([1, 2])));
+        assert_eq!(extract_json("no json here"), None);
+    }
+}
@@ -0,0 +1,284 @@
+//! k-nearest-neighbour classification over law embeddings.
+//!
+//! Unlike a single centroid per label, neighbours preserve the shape of
+//! multimodal families: a law close to any cluster of a broad family votes
+//! for it. Each label's score is the similarity-weighted share of the `k`
+//! nearest labelled laws that carry it, so scores fall in `[0, 1]`.
+
+use std::collections::HashMap;
+
+use crate::classifier::{
+    LawClassifier, Taxonomy, cosine_sim, embeddings_hash, short_hash, training_set,
+};
+use crate::labels::LabelSet;
+
+/// Default neighbourhood size.
+pub const DEFAULT_K: usize = 10;
+
+/// A labelled training law.
+struct Neighbour {
+    embedding: Vec<f32>,
+    family: Option<String>,
+    sub_family: Option<String>,
+    domains: Vec<String>,
+    subjects: Vec<String>,
+}
+
+/// k-NN classifier holding every labelled law embedding.
+pub struct KnnClassifier {
+    k: usize,
+    neighbours: Vec<Neighbour>,
+    embedding_model: String,
+    dim: usize,
+    training_hash: String,
+    embeddings_hash: String,
+}
+
+impl KnnClassifier {
+    /// Index every law that has an embedding and at least one label.
+    pub fn build(law_embeddings: &HashMap<String, Vec<f32>>, labels: &LabelSet, k: usize) -> Self {
+        let families: HashMap<&str, &str> = labels.trainable_laws().collect();
+        let sub_families: HashMap<&str, &str> = labels
+            .trainable_sub_families()
+            .map(|(name, _, sub)| (name, sub))
+            .collect();
+        let (training_laws, training_hash) = training_set(law_embeddings, labels);
+        let embeddings_hash = embeddings_hash(law_embeddings, &training_laws);
+        let dim = law_embeddings
+            .values()
+            .next()
+            .map(|v| v.len())
+            .unwrap_or(384);
+
+        let neighbours = training_laws
+            .iter()
+            .map(|name| Neighbour {
+                embedding: law_embeddings[name].clone(),
+                family: families.get(name.as_str()).map(|f| f.to_string()),
+                sub_family: sub_families.get(name.as_str()).map(|s| s.to_string()),
+                domains: labels.law_domain.get(name).cloned().unwrap_or_default(),
+                subjects: labels.law_subjects.get(name).cloned().unwrap_or_default(),
+            })
+            .collect();
+
+        Self {
+            k: k.max(1),
+            neighbours,
+            embedding_model: String::new(),
+            dim,
+            training_hash,
+            embeddings_hash,
+        }
+    }
+
+    /// Record the embedding model the neighbours were produced with.
+    ///
+    /// As for [`crate::classifier::Classifier::with_embedding_model`], the
+    /// model name is part of the version.
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    /// Number of indexed training laws.
+    pub fn len(&self) -> usize {
+        self.neighbours.len()
+    }
+
+    /// Whether no training laws were indexed.
+    pub fn is_empty(&self) -> bool {
+        self.neighbours.is_empty()
+    }
+}
+
+impl LawClassifier for KnnClassifier {
+    fn method(&self) -> &'static str {
+        "knn"
+    }
+
+    /// E.g. `knn-k10-3f2a9c01b7de`.
+    ///
+    /// Derived from `k`, the embedding model, dimensionality, training hash,
+    /// and a hash of the stored neighbour embeddings, so re-embedding the
+    /// same laws yields a new version.
+    fn version(&self) -> String {
+        let key = format!(
+            "{}\n{}\n{}\n{}\n{}",
+            self.k, self.embedding_model, self.dim, self.training_hash, self.embeddings_hash
+        );
+        format!("knn-k{}-{}", self.k, short_hash(&key))
+    }
+
+    fn rank(&self, taxonomy: Taxonomy, embedding: &[f32]) -> Vec<(String, f32)> {
+        self.vote(embedding, |n| match taxonomy {
+            Taxonomy::Family => n.family.iter().cloned().collect(),
+            Taxonomy::Domain => n.domains.clone(),
+            Taxonomy::Subject => n.subjects.clone(),
+        })
+    }
+
+    fn rank_sub_families(&self, family: &str, embedding: &[f32]) -> Vec<(String, f32)> {
+        self.vote(embedding, |n| {
+            if n.family.as_deref() == Some(family) {
+                n.sub_family.iter().cloned().collect()
+            } else {
+                Vec::new()
+            }
+        })
+    }
+}
+
+impl KnnClassifier {
+    /// Similarity-weighted vote of the `k` nearest neighbours that carry any label.
+    fn vote(
+        &self,
+        embedding: &[f32],
+        labels_of: impl Fn(&Neighbour) -> Vec<String>,
+    ) -> Vec<(String, f32)> {
+        // Only laws labelled in this taxonomy can vote.
+        let mut nearest: Vec<(f32, Vec<String>)> = self
+            .neighbours
+            .iter()
+            .map(|n| (n, labels_of(n)))
+            .filter(|(_, labels)| !labels.is_empty())
+            .map(|(n, labels)| (cosine_sim(embedding, &n.embedding), labels))
+            .collect();
+        nearest.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        nearest.truncate(self.k);
+
+        // Negative similarity carries no evidence for a label.
+        let total: f32 = nearest.iter().map(|(sim, _)| sim.max(0.0)).sum();
+        if total <= 0.0 {
+            return Vec::new();
+        }
+
+        let mut votes: HashMap<String, f32> = HashMap::new();
+        for (sim, labels) in nearest {
+            for label in labels {
+                *votes.entry(label).or_default() += sim.max(0.0);
+            }
+        }
+
+        let mut ranked: Vec<(String, f32)> = votes
+            .into_iter()
+            .map(|(label, weight)| (label, weight / total))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(families: &[(&str, &str)], domains: &[(&str, &[&str])]) -> LabelSet {
+        LabelSet {
+            law_family: families
+                .iter()
+                .map(|(n, f)| (n.to_string(), f.to_string()))
+                .collect(),
+            law_sub_family: HashMap::new(),
+            law_domain: domains
+                .iter()
+                .map(|(n, d)| (n.to_string(), d.iter().map(|s| s.to_string()).collect()))
+                .collect(),
+            law_subjects: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn multimodal_family_beats_centroid() {
+        // H&S has two clusters (+x and -x); WASTE sits on +y. A centroid for
+        // H&S would average to ~zero, but neighbours keep both modes.
+        let mut embs = HashMap::new();
+        embs.insert("hs_a".to_string(), vec![1.0, 0.0, 0.0]);
+        embs.insert("hs_b".to_string(), vec![-1.0, 0.0, 0.0]);
+        embs.insert("waste".to_string(), vec![0.0, 1.0, 0.0]);
+        let labels = labels(&[("hs_a", "H&S"), ("hs_b", "H&S"), ("waste", "WASTE")], &[]);
+
+        let knn = KnnClassifier::build(&embs, &labels, 1);
+        assert_eq!(knn.len(), 3);
+
+        let c = knn.classify("new", &[-0.9, 0.3, 0.0], &labels, 0.5, 0.5);
+        assert_eq!(c.family, "H&S");
+        assert!((c.family_confidence - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn sub_family_votes_stay_within_family() {
+        let mut embs = HashMap::new();
+        embs.insert("hs_a".to_string(), vec![1.0, 0.0, 0.0]);
+        embs.insert("hs_b".to_string(), vec![0.9, 0.0, 0.4]);
+        embs.insert("waste".to_string(), vec![0.95, 0.3, 0.0]);
+        let mut labels = labels(&[("hs_a", "H&S"), ("hs_b", "H&S"), ("waste", "WASTE")], &[]);
+        for (name, sub) in [("hs_a", "OH&S"), ("hs_b", "FIRE"), ("waste", "LANDFILL")] {
+            labels
+                .law_sub_family
+                .insert(name.to_string(), sub.to_string());
+        }
+
+        let knn = KnnClassifier::build(&embs, &labels, 3);
+        let subs = knn.rank_sub_families("H&S", &[1.0, 0.1, 0.0]);
+        let names: Vec<&str> = subs.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(names, vec!["OH&S", "FIRE"]);
+        assert!(knn.rank_sub_families("ENERGY", &[1.0, 0.0, 0.0]).is_empty());
+    }
+
+    #[test]
+    fn scores_are_weighted_vote_shares() {
+        let mut embs = HashMap::new();
+        embs.insert("a".to_string(), vec![1.0, 0.0]);
+        embs.insert("b".to_string(), vec![0.8, 0.6]);
+        embs.insert("c".to_string(), vec![0.0, 1.0]);
+        let labels = labels(
+            &[("a", "ENERGY"), ("b", "WASTE"), ("c", "WASTE")],
+            &[
+                ("a", &["environment", "health_safety"]),
+                ("b", &["environment"]),
+            ],
+        );
+
+        let knn = KnnClassifier::build(&embs, &labels, 2);
+        let families = knn.rank(Taxonomy::Family, &[1.0, 0.0]);
+        // Nearest two: a (1.0, ENERGY) and b (0.8, WASTE).
+        assert_eq!(families[0].0, "ENERGY");
+        assert!((families[0].1 - 1.0 / 1.8).abs() < 1e-6);
+        assert!((families[1].1 - 0.8 / 1.8).abs() < 1e-6);
+
+        // Domains: only a and b are labelled; both carry environment.
+        let domains = knn.rank(Taxonomy::Domain, &[1.0, 0.0]);
+        assert_eq!(domains[0], ("environment".to_string(), 1.0));
+        assert_eq!(domains[1].0, "health_safety");
+
+        // No subject labels → nothing to rank.
+        assert!(knn.rank(Taxonomy::Subject, &[1.0, 0.0]).is_empty());
+        assert!(knn.version().starts_with("knn-k2-"));
+    }
+
+    #[test]
+    fn version_covers_model_and_stored_embeddings() {
+        let mut embs = HashMap::new();
+        embs.insert("a".to_string(), vec![1.0, 0.0]);
+        embs.insert("b".to_string(), vec![0.0, 1.0]);
+        let labels = labels(&[("a", "ENERGY"), ("b", "WASTE")], &[]);
+
+        let version = |embs: &HashMap<String, Vec<f32>>, model: &str| {
+            KnnClassifier::build(embs, &labels, 1)
+                .with_embedding_model(model)
+                .version()
+        };
+        let base = version(&embs, "all-MiniLM-L6-v2");
+        assert_eq!(base, version(&embs, "all-MiniLM-L6-v2"));
+        assert_ne!(base, version(&embs, "bge-small-en-v1.5"));
+
+        // Same laws and labels, re-embedded.
+        let mut reembedded = embs.clone();
+        reembedded.insert("a".to_string(), vec![0.8, 0.6]);
+        assert_ne!(base, version(&reembedded, "all-MiniLM-L6-v2"));
+    }
+}
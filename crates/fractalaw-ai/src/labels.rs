@@ -151,6 +151,36 @@ impl LabelSet {
             .filter(|(_, v)| !EXCLUDE_FAMILIES.contains(&v.as_str()))
             .map(|(k, v)| (k.as_str(), v.as_str()))
     }
+
+    /// Iterate over laws with both a trainable family and a sub_family.
+    ///
+    /// Yields `(law_name, family, sub_family)` — the family → sub_family
+    /// hierarchy used for hierarchical classification.
+    pub fn trainable_sub_families(&self) -> impl Iterator<Item = (&str, &str, &str)> {
+        self.trainable_laws().filter_map(|(name, family)| {
+            self.law_sub_family
+                .get(name)
+                .map(|sub| (name, family, sub.as_str()))
+        })
+    }
+
+    /// Replace family labels with reviewer-accepted AI labels.
+    ///
+    /// Takes `(law_name, family)` pairs from accepted review decisions; returns
+    /// how many labels actually changed. Laws without a prior label gain one.
+    pub fn apply_family_overrides(
+        &mut self,
+        overrides: impl IntoIterator<Item = (String, String)>,
+    ) -> usize {
+        let mut changed = 0;
+        for (name, family) in overrides {
+            if self.law_family.get(&name) != Some(&family) {
+                self.law_family.insert(name, family);
+                changed += 1;
+            }
+        }
+        changed
+    }
 }
 
 // ── Arrow extraction helpers ──
@@ -434,6 +464,42 @@ mod tests {
         assert!(trainable.iter().any(|(name, _)| *name == "law_d"));
     }
 
+    #[test]
+    fn trainable_sub_families_follow_family() {
+        let batch = test_batch(
+            &["law_a", "law_b", "law_c"],
+            &[Some("ENERGY"), Some("X: No Family"), Some("WASTE")],
+            &[Some("RENEWABLE"), Some("ORPHAN"), None],
+            &[None, None, None],
+            &[None, None, None],
+        );
+        let labels = LabelSet::from_legislation_batches(&[batch]).unwrap();
+        let subs: Vec<_> = labels.trainable_sub_families().collect();
+        assert_eq!(subs, vec![("law_a", "ENERGY", "RENEWABLE")]);
+    }
+
+    #[test]
+    fn family_overrides_replace_labels() {
+        let batch = test_batch(
+            &["law_a", "law_b"],
+            &[Some("ENERGY"), Some("WASTE")],
+            &[None, None],
+            &[None, None],
+            &[None, None],
+        );
+        let mut labels = LabelSet::from_legislation_batches(&[batch]).unwrap();
+
+        let changed = labels.apply_family_overrides([
+            ("law_a".to_string(), "FIRE".to_string()),
+            ("law_b".to_string(), "WASTE".to_string()), // unchanged
+            ("law_c".to_string(), "ENERGY".to_string()), // previously unlabelled
+        ]);
+        assert_eq!(changed, 2);
+        assert_eq!(labels.law_family["law_a"], "FIRE");
+        assert_eq!(labels.law_family["law_b"], "WASTE");
+        assert_eq!(labels.law_family["law_c"], "ENERGY");
+    }
+
     #[test]
     fn multiple_batches() {
         let batch1 = test_batch(&["law_a"], &[Some("ENERGY")], &[None], &[None], &[None]);
@@ -4,10 +4,31 @@
 mod embedder;
 #[cfg(feature = "onnx")]
 pub use embedder::Embedder;
+#[cfg(feature = "onnx")]
+mod generator;
+#[cfg(feature = "onnx")]
+pub use generator::{Generation, Generator};
 
 pub mod classifier;
+pub mod evaluate;
+pub mod json_schema;
+pub mod knn;
 pub mod labels;
+pub mod probe;
+pub mod rag;
+pub mod response_cache;
+pub mod sections;
 pub use classifier::{
-    CentroidSummary, Classification, ClassificationStatus, Classifier, aggregate_law_embeddings,
+    CentroidSummary, Classification, ClassificationStatus, Classifier, LawClassifier,
+    MODEL_FORMAT_VERSION, Method, Taxonomy, aggregate_law_embeddings, single_embedding_model,
 };
+pub use evaluate::{EvalReport, Split, evaluate};
+pub use knn::KnnClassifier;
 pub use labels::{EXCLUDE_FAMILIES, LabelSet, LabelSummary};
+pub use probe::{LinearProbe, ProbeConfig};
+pub use rag::{Citations, Passage, build_prompt, check_citations, passages_from_batches};
+pub use response_cache::{CachedRequest, CachedResponse, ResponseCache};
+pub use sections::{
+    DRRP_TYPES, Section, SectionClassification, SectionClassifier, SectionLabels, SectionSummary,
+    drrp_candidates, sections_from_batches,
+};
@@ -0,0 +1,356 @@
+//! Logistic-regression linear probe over law embeddings.
+//!
+//! Trains one weight vector per label with full-batch gradient descent:
+//! multinomial (softmax) for the single-select family (and, per family, its
+//! sub_families), and one-vs-rest
+//! sigmoids for multi-select domains and subjects. Scores are probabilities,
+//! so thresholds apply to them directly.
+
+use std::collections::{BTreeSet, HashMap};
+
+use crate::classifier::{LawClassifier, Taxonomy, embeddings_hash, short_hash, training_set};
+use crate::labels::LabelSet;
+
+/// Training hyper-parameters for [`LinearProbe`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProbeConfig {
+    pub epochs: usize,
+    pub learning_rate: f32,
+    /// L2 penalty on weights (not biases).
+    pub l2: f32,
+}
+
+impl Default for ProbeConfig {
+    fn default() -> Self {
+        Self {
+            epochs: 200,
+            learning_rate: 1.0,
+            l2: 1e-4,
+        }
+    }
+}
+
+/// Output activation for a trained layer.
+#[derive(Clone, Copy)]
+enum Activation {
+    /// Mutually exclusive labels; probabilities sum to 1.
+    Softmax,
+    /// Independent labels; each probability in (0, 1).
+    Sigmoid,
+}
+
+/// One linear layer: a weight row and bias per label.
+struct Layer {
+    labels: Vec<String>,
+    weights: Vec<Vec<f32>>,
+    bias: Vec<f32>,
+    activation: Activation,
+}
+
+impl Layer {
+    fn predict(&self, x: &[f32]) -> Vec<f32> {
+        let logits: Vec<f32> = self
+            .weights
+            .iter()
+            .zip(&self.bias)
+            .map(|(w, b)| w.iter().zip(x).map(|(w, x)| w * x).sum::<f32>() + b)
+            .collect();
+        activate(self.activation, &logits)
+    }
+}
+
+/// Linear-probe classifier with separate family, domain, and subject layers,
+/// plus one sub_family layer per family.
+pub struct LinearProbe {
+    family: Option<Layer>,
+    sub_family: HashMap<String, Layer>,
+    domain: Option<Layer>,
+    subject: Option<Layer>,
+    config: ProbeConfig,
+    embedding_model: String,
+    dim: usize,
+    training_hash: String,
+    embeddings_hash: String,
+}
+
+impl LinearProbe {
+    /// Train on every law that has an embedding and labels for a taxonomy.
+    pub fn train(
+        law_embeddings: &HashMap<String, Vec<f32>>,
+        labels: &LabelSet,
+        config: ProbeConfig,
+    ) -> Self {
+        let (training_laws, training_hash) = training_set(law_embeddings, labels);
+        let embeddings_hash = embeddings_hash(law_embeddings, &training_laws);
+        let dim = law_embeddings
+            .values()
+            .next()
+            .map(|v| v.len())
+            .unwrap_or(384);
+
+        let families: Vec<(&str, Vec<String>)> = labels
+            .trainable_laws()
+            .map(|(name, family)| (name, vec![family.to_string()]))
+            .collect();
+        let domains: Vec<(&str, Vec<String>)> = labels
+            .law_domain
+            .iter()
+            .map(|(name, d)| (name.as_str(), d.clone()))
+            .collect();
+        let mut sub_family_examples: HashMap<&str, Vec<(&str, Vec<String>)>> = HashMap::new();
+        for (name, family, sub) in labels.trainable_sub_families() {
+            sub_family_examples
+                .entry(family)
+                .or_default()
+                .push((name, vec![sub.to_string()]));
+        }
+        let subjects: Vec<(&str, Vec<String>)> = labels
+            .law_subjects
+            .iter()
+            .map(|(name, s)| (name.as_str(), s.clone()))
+            .collect();
+
+        Self {
+            family: train_layer(law_embeddings, &families, Activation::Softmax, config),
+            sub_family: sub_family_examples
+                .into_iter()
+                .filter_map(|(family, examples)| {
+                    train_layer(law_embeddings, &examples, Activation::Softmax, config)
+                        .map(|layer| (family.to_string(), layer))
+                })
+                .collect(),
+            domain: train_layer(law_embeddings, &domains, Activation::Sigmoid, config),
+            subject: train_layer(law_embeddings, &subjects, Activation::Sigmoid, config),
+            config,
+            embedding_model: String::new(),
+            dim,
+            training_hash,
+            embeddings_hash,
+        }
+    }
+
+    /// Record the embedding model the probe was trained against; it becomes
+    /// part of the version.
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+}
+
+impl LawClassifier for LinearProbe {
+    fn method(&self) -> &'static str {
+        "linear-probe"
+    }
+
+    fn version(&self) -> String {
+        let key = format!(
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            self.config.epochs,
+            self.config.learning_rate,
+            self.config.l2,
+            self.embedding_model,
+            self.dim,
+            self.training_hash,
+            self.embeddings_hash
+        );
+        format!("linear-probe-{}", short_hash(&key))
+    }
+
+    fn rank(&self, taxonomy: Taxonomy, embedding: &[f32]) -> Vec<(String, f32)> {
+        let layer = match taxonomy {
+            Taxonomy::Family => &self.family,
+            Taxonomy::Domain => &self.domain,
+            Taxonomy::Subject => &self.subject,
+        };
+        layer
+            .as_ref()
+            .map(|layer| ranked(layer, embedding))
+            .unwrap_or_default()
+    }
+
+    fn rank_sub_families(&self, family: &str, embedding: &[f32]) -> Vec<(String, f32)> {
+        self.sub_family
+            .get(family)
+            .map(|layer| ranked(layer, embedding))
+            .unwrap_or_default()
+    }
+}
+
+/// Label probabilities from `layer`, best first.
+fn ranked(layer: &Layer, embedding: &[f32]) -> Vec<(String, f32)> {
+    let mut ranked: Vec<(String, f32)> = layer
+        .labels
+        .iter()
+        .cloned()
+        .zip(layer.predict(embedding))
+        .collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ranked
+}
+
+/// Fit a layer on `(law, labels)` examples; `None` if there is nothing to learn.
+fn train_layer(
+    law_embeddings: &HashMap<String, Vec<f32>>,
+    examples: &[(&str, Vec<String>)],
+    activation: Activation,
+    config: ProbeConfig,
+) -> Option<Layer> {
+    // Sort examples and labels so training is deterministic.
+    let mut examples: Vec<(&str, &Vec<String>)> = examples
+        .iter()
+        .filter(|(name, l)| !l.is_empty() && law_embeddings.contains_key(*name))
+        .map(|(name, l)| (*name, l))
+        .collect();
+    examples.sort_by_key(|(name, _)| *name);
+
+    let label_names: Vec<String> = examples
+        .iter()
+        .flat_map(|(_, l)| l.iter().cloned())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    if examples.is_empty() || label_names.is_empty() {
+        return None;
+    }
+    let index: HashMap<&str, usize> = label_names
+        .iter()
+        .enumerate()
+        .map(|(i, l)| (l.as_str(), i))
+        .collect();
+
+    let xs: Vec<&[f32]> = examples
+        .iter()
+        .map(|(name, _)| law_embeddings[*name].as_slice())
+        .collect();
+    let ys: Vec<Vec<f32>> = examples
+        .iter()
+        .map(|(_, l)| {
+            let mut y = vec![0.0; label_names.len()];
+            for label in l.iter() {
+                y[index[label.as_str()]] = 1.0;
+            }
+            y
+        })
+        .collect();
+
+    let dim = xs[0].len();
+    let classes = label_names.len();
+    let n = xs.len() as f32;
+    let mut layer = Layer {
+        labels: label_names,
+        weights: vec![vec![0.0; dim]; classes],
+        bias: vec![0.0; classes],
+        activation,
+    };
+
+    for _ in 0..config.epochs {
+        let mut grad_w = vec![vec![0.0f32; dim]; classes];
+        let mut grad_b = vec![0.0f32; classes];
+
+        for (x, y) in xs.iter().zip(&ys) {
+            let p = layer.predict(x);
+            for c in 0..classes {
+                // Cross-entropy gradient is (p - y) for both softmax and sigmoid.
+                let err = p[c] - y[c];
+                if err == 0.0 {
+                    continue;
+                }
+                grad_b[c] += err;
+                for (g, &xi) in grad_w[c].iter_mut().zip(x.iter()) {
+                    *g += err * xi;
+                }
+            }
+        }
+
+        for c in 0..classes {
+            for (w, g) in layer.weights[c].iter_mut().zip(&grad_w[c]) {
+                *w -= config.learning_rate * (g / n + config.l2 * *w);
+            }
+            layer.bias[c] -= config.learning_rate * grad_b[c] / n;
+        }
+    }
+
+    Some(layer)
+}
+
+fn activate(activation: Activation, logits: &[f32]) -> Vec<f32> {
+    match activation {
+        Activation::Softmax => {
+            let max = logits.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+            let exps: Vec<f32> = logits.iter().map(|z| (z - max).exp()).collect();
+            let sum: f32 = exps.iter().sum();
+            exps.into_iter().map(|e| e / sum).collect()
+        }
+        Activation::Sigmoid => logits.iter().map(|z| 1.0 / (1.0 + (-z).exp())).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn learns_separable_families_and_domains() {
+        let mut embs = HashMap::new();
+        let mut law_family = HashMap::new();
+        let mut law_domain = HashMap::new();
+        for i in 0..5 {
+            let j = i as f32 * 0.05;
+            embs.insert(format!("e{i}"), vec![1.0, j, 0.0]);
+            embs.insert(format!("w{i}"), vec![j, 1.0, 0.0]);
+            law_family.insert(format!("e{i}"), "ENERGY".to_string());
+            law_family.insert(format!("w{i}"), "WASTE".to_string());
+            // Only energy laws carry the climate domain; all carry environment.
+            law_domain.insert(
+                format!("e{i}"),
+                vec!["environment".to_string(), "climate".to_string()],
+            );
+            law_domain.insert(format!("w{i}"), vec!["environment".to_string()]);
+        }
+        let law_sub_family = (0..5)
+            .map(|i| (format!("e{i}"), "RENEWABLE".to_string()))
+            .collect();
+        let labels = LabelSet {
+            law_family,
+            law_sub_family,
+            law_domain,
+            law_subjects: HashMap::new(),
+        };
+
+        let probe = LinearProbe::train(&embs, &labels, ProbeConfig::default());
+
+        let c = probe.classify("new", &[0.9, 0.1, 0.0], &labels, 0.5, 0.5);
+        assert_eq!(c.family, "ENERGY");
+        assert!(c.family_confidence > 0.8, "got {}", c.family_confidence);
+        let domains: Vec<&str> = c.domain.iter().map(|(d, _)| d.as_str()).collect();
+        assert!(domains.contains(&"environment"));
+        assert!(domains.contains(&"climate"));
+
+        let w = probe.classify("new", &[0.1, 0.9, 0.0], &labels, 0.5, 0.5);
+        assert_eq!(w.family, "WASTE");
+        assert!(!w.domain.iter().any(|(d, _)| d == "climate"));
+
+        // Family probabilities sum to one.
+        let total: f32 = probe
+            .rank(Taxonomy::Family, &[0.5, 0.5, 0.0])
+            .iter()
+            .map(|(_, p)| p)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-5);
+
+        // Sub-families are only ranked within their family.
+        assert_eq!(
+            probe.rank_sub_families("ENERGY", &[1.0, 0.0, 0.0])[0].0,
+            "RENEWABLE"
+        );
+        assert!(
+            probe
+                .rank_sub_families("WASTE", &[0.0, 1.0, 0.0])
+                .is_empty()
+        );
+
+        // No subject labels → no subject layer.
+        assert!(probe.rank(Taxonomy::Subject, &[1.0, 0.0, 0.0]).is_empty());
+        assert!(probe.version().starts_with("linear-probe-"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Retrieval-augmented question answering over legislation text.
+//!
+//! Retrieved `legislation_text` sections are numbered `[1]`, `[2]`, … in the
+//! prompt, each labelled with its `law_name`, `provision`, and `section_id`.
+//! The model cites sources by number; [`check_citations`] maps the numbers
+//! back to sections and reports any citation that doesn't match a retrieved
+//! section, so answers can be verified against `legislation_text`.
+
+use arrow::record_batch::RecordBatch;
+
+use crate::classifier::get_string;
+
+/// System prompt for answering from retrieved sections only.
+pub const SYSTEM_PROMPT: &str = "\
+You answer questions about UK ESH (environment, safety, health) legislation.
+
+Answer ONLY from the numbered sources provided. After every statement, cite the \
+source(s) it relies on by number in square brackets, e.g. [1] or [2][3]. \
+Do not cite anything that is not in the sources. If the sources do not answer \
+the question, say so plainly.";
+
+/// A retrieved `legislation_text` section offered to the model as a source.
+#[derive(Debug, Clone)]
+pub struct Passage {
+    pub section_id: String,
+    pub law_name: String,
+    pub provision: Option<String>,
+    pub text: String,
+}
+
+impl Passage {
+    /// Human-readable reference, e.g. `UK_ukpga_1974_37 provision 2`.
+    pub fn reference(&self) -> String {
+        match &self.provision {
+            Some(p) => format!("{} provision {p}", self.law_name),
+            None => self.law_name.clone(),
+        }
+    }
+}
+
+/// Build passages from `LanceStore::search_text` batches, keeping rank order.
+///
+/// Expects `section_id`, `law_name`, `provision`, and `text` columns.
+pub fn passages_from_batches(batches: &[RecordBatch]) -> anyhow::Result<Vec<Passage>> {
+    let mut passages = Vec::new();
+    for batch in batches {
+        let column = |name: &str| {
+            batch
+                .column_by_name(name)
+                .ok_or_else(|| anyhow::anyhow!("missing '{name}' column"))
+        };
+        let id_col = column("section_id")?;
+        let name_col = column("law_name")?;
+        let provision_col = column("provision")?;
+        let text_col = column("text")?;
+
+        for row in 0..batch.num_rows() {
+            let (Some(section_id), Some(law_name)) = (
+                get_string(id_col.as_ref(), row),
+                get_string(name_col.as_ref(), row),
+            ) else {
+                continue;
+            };
+            passages.push(Passage {
+                section_id,
+                law_name,
+                provision: get_string(provision_col.as_ref(), row),
+                text: get_string(text_col.as_ref(), row).unwrap_or_default(),
+            });
+        }
+    }
+    Ok(passages)
+}
+
+/// User prompt: numbered sources followed by the question.
+pub fn build_prompt(question: &str, passages: &[Passage]) -> String {
+    let mut prompt = String::from("Sources:\n\n");
+    for (i, p) in passages.iter().enumerate() {
+        prompt.push_str(&format!(
+            "[{}] {} ({})\n{}\n\n",
+            i + 1,
+            p.reference(),
+            p.section_id,
+            p.text.trim()
+        ));
+    }
+    prompt.push_str(&format!("Question: {}", question.trim()));
+    prompt
+}
+
+/// Citations found in an answer.
+#[derive(Debug, Default)]
+pub struct Citations {
+    /// Indices into the retrieved passages, in order of first citation.
+    pub cited: Vec<usize>,
+    /// Citation numbers that don't correspond to any retrieved passage.
+    pub invalid: Vec<usize>,
+}
+
+impl Citations {
+    /// Whether the answer cites at least one source and nothing unknown.
+    pub fn is_verifiable(&self) -> bool {
+        !self.cited.is_empty() && self.invalid.is_empty()
+    }
+}
+
+/// Find `[n]` (or `[n, m]`) citations in `answer` and resolve them against
+/// `passage_count` retrieved passages numbered from 1.
+pub fn check_citations(answer: &str, passage_count: usize) -> Citations {
+    let mut citations = Citations::default();
+    let mut rest = answer;
+
+    while let Some(open) = rest.find('[') {
+        rest = &rest[open + 1..];
+        let Some(close) = rest.find(']') else {
+            break;
+        };
+        let inner = &rest[..close];
+        let numbers: Option<Vec<usize>> = inner
+            .split(',')
+            .map(|n| n.trim().parse::<usize>().ok())
+            .collect();
+        // Brackets that aren't purely numeric (e.g. `[E+W]`) aren't citations.
+        if let Some(numbers) = numbers {
+            for n in numbers {
+                let (list, value) = if (1..=passage_count).contains(&n) {
+                    (&mut citations.cited, n - 1)
+                } else {
+                    (&mut citations.invalid, n)
+                };
+                if !list.contains(&value) {
+                    list.push(value);
+                }
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+
+    citations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn passage(id: &str, provision: Option<&str>, text: &str) -> Passage {
+        Passage {
+            section_id: id.to_string(),
+            law_name: id.split(':').next().unwrap().to_string(),
+            provision: provision.map(str::to_string),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn prompt_numbers_and_labels_sources() {
+        let passages = vec![
+            passage(
+                "UK_ukpga_1974_37:s.2(1)",
+                Some("2"),
+                "It shall be the duty of every employer...",
+            ),
+            passage(
+                "UK_uksi_1999_3242:reg.3",
+                Some("3"),
+                " Every employer shall make... ",
+            ),
+        ];
+        let prompt = build_prompt("What must employers do? ", &passages);
+        assert!(prompt.contains("[1] UK_ukpga_1974_37 provision 2 (UK_ukpga_1974_37:s.2(1))\n"));
+        assert!(prompt.contains(
+            "[2] UK_uksi_1999_3242 provision 3 (UK_uksi_1999_3242:reg.3)\nEvery employer shall make...\n"
+        ));
+        assert!(prompt.ends_with("Question: What must employers do?"));
+    }
+
+    #[test]
+    fn citations_resolve_to_passages() {
+        let c = check_citations(
+            "Employers owe a general duty [1]. They must assess risks [2][1]. Scotland differs [E+W].",
+            3,
+        );
+        assert_eq!(c.cited, vec![0, 1]);
+        assert!(c.invalid.is_empty());
+        assert!(c.is_verifiable());
+
+        let c = check_citations("Both apply [1, 3]. Also [7].", 3);
+        assert_eq!(c.cited, vec![0, 2]);
+        assert_eq!(c.invalid, vec![7]);
+        assert!(!c.is_verifiable());
+
+        let c = check_citations("I don't know.", 3);
+        assert!(c.cited.is_empty());
+        assert!(!c.is_verifiable());
+    }
+}
@@ -0,0 +1,240 @@
+//! On-disk record of LLM requests and responses.
+//!
+//! Each response is stored as `<dir>/<key>.json`, where the key is the
+//! SHA-256 of the provider, its endpoint, the model, prompts, and generation
+//! parameters. The file holds
+//! the request alongside the response, so a recorded run can be audited and
+//! replayed exactly without calling the model again.
+
+use std::path::{Path, PathBuf};
+
+use ring::digest;
+use serde::{Deserialize, Serialize};
+
+use crate::classifier::hex;
+
+/// On-disk format version for cache entries. Bump on incompatible changes;
+/// it is part of the key, so older entries are simply never hit.
+pub const CACHE_FORMAT_VERSION: u32 = 3;
+
+/// Everything that determines a model's reply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedRequest {
+    /// Provider kind, e.g. `anthropic`, `openai` or `local`.
+    pub provider: String,
+    /// Where the provider runs: a base URL, or a local model's directory.
+    /// Two servers serving the same model name don't share entries.
+    pub endpoint: String,
+    pub model: String,
+    pub system_prompt: Option<String>,
+    pub user_prompt: String,
+    pub max_tokens: u32,
+    pub temperature: f32,
+    pub json_schema: Option<String>,
+}
+
+impl CachedRequest {
+    /// SHA-256 (hex) identifying this request.
+    pub fn key(&self) -> String {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(format!("v{CACHE_FORMAT_VERSION}\n").as_bytes());
+        // Struct fields serialize in declaration order, so this is canonical.
+        ctx.update(&serde_json::to_vec(self).expect("request serializes"));
+        hex(ctx.finish().as_ref())
+    }
+}
+
+/// A recorded reply.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    pub text: String,
+    pub input_tokens: u32,
+    pub output_tokens: u32,
+    pub cached_tokens: u32,
+    pub confidence: Option<f32>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    format_version: u32,
+    request: CachedRequest,
+    response: CachedResponse,
+}
+
+/// Directory of recorded responses.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    /// Use `dir` as the cache, creating it if needed.
+    pub fn open(dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("failed to create {}: {e}", dir.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The recorded response to `request`, if any.
+    ///
+    /// An entry whose stored request differs from `request` (a hash
+    /// collision or a hand-edited file) is an error, not a hit.
+    pub fn get(&self, request: &CachedRequest) -> anyhow::Result<Option<CachedResponse>> {
+        let path = self.path(&request.key());
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => anyhow::bail!("failed to read {}: {e}", path.display()),
+        };
+        let entry: Entry = serde_json::from_slice(&bytes)
+            .map_err(|e| anyhow::anyhow!("corrupt cache entry {}: {e}", path.display()))?;
+        anyhow::ensure!(
+            entry.format_version == CACHE_FORMAT_VERSION && entry.request == *request,
+            "cache entry {} does not match its key",
+            path.display()
+        );
+        Ok(Some(entry.response))
+    }
+
+    /// Record `response` to `request`, replacing any earlier recording.
+    ///
+    /// Writes to a temporary file and renames it into place, so concurrent
+    /// readers never see a partial entry.
+    pub fn put(&self, request: &CachedRequest, response: &CachedResponse) -> anyhow::Result<()> {
+        let key = request.key();
+        let path = self.path(&key);
+        let entry = Entry {
+            format_version: CACHE_FORMAT_VERSION,
+            request: request.clone(),
+            response: response.clone(),
+        };
+        let tmp = self.dir.join(format!(".{key}.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, serde_json::to_vec_pretty(&entry)?)
+            .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", path.display()))
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(prompt: &str) -> CachedRequest {
+        CachedRequest {
+            provider: "openai".into(),
+            endpoint: "http://localhost:8080".into(),
+            model: "test-model".into(),
+            system_prompt: Some("Be terse.".into()),
+            user_prompt: prompt.into(),
+            max_tokens: 100,
+            temperature: 0.0,
+            json_schema: None,
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "fractalaw-response-cache-{name}-{}",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn key_covers_every_parameter() {
+        let base = request("hello");
+        assert_eq!(base.key(), request("hello").key());
+        assert_eq!(base.key().len(), 64);
+
+        let variants = [
+            CachedRequest {
+                provider: "local".into(),
+                ..base.clone()
+            },
+            CachedRequest {
+                endpoint: "http://gpu-box:8080".into(),
+                ..base.clone()
+            },
+            CachedRequest {
+                model: "other-model".into(),
+                ..base.clone()
+            },
+            CachedRequest {
+                system_prompt: None,
+                ..base.clone()
+            },
+            request("hello!"),
+            CachedRequest {
+                max_tokens: 101,
+                ..base.clone()
+            },
+            CachedRequest {
+                temperature: 0.5,
+                ..base.clone()
+            },
+            CachedRequest {
+                json_schema: Some("{}".into()),
+                ..base.clone()
+            },
+        ];
+        for v in variants {
+            assert_ne!(v.key(), base.key(), "{v:?}");
+        }
+    }
+
+    #[test]
+    fn round_trips_and_misses() {
+        let dir = temp_dir("round-trip");
+        let cache = ResponseCache::open(&dir).unwrap();
+        let response = CachedResponse {
+            text: "Hi.".into(),
+            input_tokens: 12,
+            output_tokens: 7,
+            cached_tokens: 0,
+            confidence: Some(0.9),
+        };
+
+        assert_eq!(cache.get(&request("hello")).unwrap(), None);
+        cache.put(&request("hello"), &response).unwrap();
+        assert_eq!(cache.get(&request("hello")).unwrap(), Some(response));
+        assert_eq!(cache.get(&request("goodbye")).unwrap(), None);
+
+        // Only the entry itself is left behind.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_entry_for_a_different_request() {
+        let dir = temp_dir("mismatch");
+        let cache = ResponseCache::open(&dir).unwrap();
+        let response = CachedResponse {
+            text: "Hi.".into(),
+            input_tokens: 12,
+            output_tokens: 7,
+            cached_tokens: 0,
+            confidence: Some(0.9),
+        };
+        cache.put(&request("hello"), &response).unwrap();
+        std::fs::rename(
+            cache.path(&request("hello").key()),
+            cache.path(&request("other").key()),
+        )
+        .unwrap();
+
+        assert!(cache.get(&request("other")).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
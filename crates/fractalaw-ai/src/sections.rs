@@ -0,0 +1,550 @@
+//! Section-level classification for `legislation_text` rows.
+//!
+//! Law-level classification averages every section away. Here each section
+//! keeps its own embedding and is scored against two centroid sets:
+//!
+//! - **DRRP types** (duty, right, responsibility, power), trained from sections
+//!   cited by the legislation `duties`/`rights`/`responsibilities`/`powers`
+//!   entries and by sertantai's `drrp_annotations`.
+//! - **Subjects**, trained from sections of laws carrying each subject tag.
+//!
+//! Unlabelled sections of DRRP-labelled laws form a background centroid: a
+//! DRRP type is only tagged when it beats the background, so boilerplate
+//! (interpretation, citation, commencement) doesn't pick up a type just by
+//! being closer to `duty` than to `power`.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use arrow::array::{Array, FixedSizeListArray, Float32Array};
+use arrow::record_batch::RecordBatch;
+use ring::digest;
+
+use crate::classifier::{
+    cosine_sim, get_string, hex, normalize, short_hash, single_embedding_model,
+};
+
+/// DRRP types, in the order sertantai reports them.
+pub const DRRP_TYPES: &[&str] = &["duty", "right", "responsibility", "power"];
+
+/// One `legislation_text` row with its embedding.
+pub struct Section {
+    pub section_id: String,
+    pub law_name: String,
+    pub provision: Option<String>,
+    pub text: String,
+    pub embedding: Vec<f32>,
+}
+
+/// Build sections from LanceDB `legislation_text` batches.
+///
+/// Expects `section_id`, `law_name`, `provision`, `text`, and `embedding`
+/// (FixedSizeList<Float32>) columns. Rows without an embedding are skipped.
+/// Like [`aggregate_law_embeddings`](crate::aggregate_law_embeddings), refuses
+/// batches mixing embedding models.
+pub fn sections_from_batches(batches: &[RecordBatch]) -> anyhow::Result<Vec<Section>> {
+    single_embedding_model(batches)?;
+    let mut sections = Vec::new();
+
+    for batch in batches {
+        let column = |name: &str| {
+            batch
+                .column_by_name(name)
+                .ok_or_else(|| anyhow::anyhow!("missing '{name}' column"))
+        };
+        let id_col = column("section_id")?;
+        let name_col = column("law_name")?;
+        let provision_col = column("provision")?;
+        let text_col = column("text")?;
+        let emb_col = column("embedding")?;
+
+        let fsl = emb_col
+            .as_any()
+            .downcast_ref::<FixedSizeListArray>()
+            .ok_or_else(|| anyhow::anyhow!("embedding column is not FixedSizeList"))?;
+        let dim = fsl.value_length() as usize;
+        let flat_values = fsl
+            .values()
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .ok_or_else(|| anyhow::anyhow!("embedding values are not Float32"))?;
+
+        for row in 0..batch.num_rows() {
+            if emb_col.is_null(row) {
+                continue;
+            }
+            let (Some(section_id), Some(law_name)) = (
+                get_string(id_col.as_ref(), row),
+                get_string(name_col.as_ref(), row),
+            ) else {
+                continue;
+            };
+            let offset = row * dim;
+            sections.push(Section {
+                section_id,
+                law_name,
+                provision: get_string(provision_col.as_ref(), row),
+                text: get_string(text_col.as_ref(), row).unwrap_or_default(),
+                embedding: flat_values.values()[offset..offset + dim].to_vec(),
+            });
+        }
+    }
+
+    Ok(sections)
+}
+
+/// Normalise a provision reference to the bare provision number.
+///
+/// DRRP entries cite `section/12` or `regulation/4`, annotations may use
+/// `s.12(1)` or `reg. 4`, and `legislation_text.provision` holds `12` or `4`.
+/// All of these normalise to the provision number (`12`, `4`), lowercased;
+/// sub-provisions like `(1)(a)` are dropped.
+pub fn provision_key(reference: &str) -> String {
+    let last = reference
+        .trim()
+        .rsplit(['/', '.', ' '])
+        .next()
+        .unwrap_or_default();
+    last.split('(').next().unwrap_or_default().to_lowercase()
+}
+
+/// Ground-truth DRRP types per `(law_name, provision)`.
+#[derive(Default)]
+pub struct SectionLabels {
+    drrp: HashMap<(String, String), BTreeSet<String>>,
+}
+
+impl SectionLabels {
+    /// Build from batches with `law_name`, `provision`, and `drrp_type` columns.
+    ///
+    /// Provisions are normalised with [`provision_key`]; DRRP types are
+    /// lowercased, and anything outside [`DRRP_TYPES`] is ignored.
+    pub fn from_batches(batches: &[RecordBatch]) -> anyhow::Result<Self> {
+        let mut labels = Self::default();
+
+        for batch in batches {
+            let column = |name: &str| {
+                batch
+                    .column_by_name(name)
+                    .ok_or_else(|| anyhow::anyhow!("missing '{name}' column"))
+            };
+            let name_col = column("law_name")?;
+            let provision_col = column("provision")?;
+            let type_col = column("drrp_type")?;
+
+            for row in 0..batch.num_rows() {
+                if let (Some(law_name), Some(provision), Some(drrp_type)) = (
+                    get_string(name_col.as_ref(), row),
+                    get_string(provision_col.as_ref(), row),
+                    get_string(type_col.as_ref(), row),
+                ) {
+                    labels.insert(&law_name, &provision, &drrp_type);
+                }
+            }
+        }
+
+        Ok(labels)
+    }
+
+    /// Record that `provision` of `law_name` carries `drrp_type`.
+    pub fn insert(&mut self, law_name: &str, provision: &str, drrp_type: &str) {
+        let drrp_type = drrp_type.trim().to_lowercase();
+        let key = provision_key(provision);
+        if key.is_empty() || !DRRP_TYPES.contains(&drrp_type.as_str()) {
+            return;
+        }
+        self.drrp
+            .entry((law_name.to_string(), key))
+            .or_default()
+            .insert(drrp_type);
+    }
+
+    /// DRRP types recorded for a section, if any.
+    pub fn drrp_types(&self, section: &Section) -> Option<&BTreeSet<String>> {
+        let provision = section.provision.as_deref()?;
+        self.drrp
+            .get(&(section.law_name.clone(), provision_key(provision)))
+    }
+
+    /// Number of labelled `(law, provision)` pairs.
+    pub fn len(&self) -> usize {
+        self.drrp.len()
+    }
+
+    /// Whether no provisions are labelled.
+    pub fn is_empty(&self) -> bool {
+        self.drrp.is_empty()
+    }
+}
+
+/// Section-level classification result.
+pub struct SectionClassification {
+    pub section_id: String,
+    pub law_name: String,
+    pub provision: Option<String>,
+    /// Multi-select: DRRP types above threshold and the background, with confidence.
+    pub drrp: Vec<(String, f32)>,
+    /// Multi-select: subjects above threshold, with confidence.
+    pub subjects: Vec<(String, f32)>,
+}
+
+/// Centroid counts for a built [`SectionClassifier`].
+pub struct SectionSummary {
+    pub drrp_sections: usize,
+    pub background_sections: usize,
+    pub drrp_count: usize,
+    pub subject_count: usize,
+}
+
+/// Centroid classifier over individual `legislation_text` sections.
+pub struct SectionClassifier {
+    drrp_centroids: HashMap<String, Vec<f32>>,
+    /// Centroid of unlabelled sections in DRRP-labelled laws.
+    background: Option<Vec<f32>>,
+    subject_centroids: HashMap<String, Vec<f32>>,
+    dim: usize,
+    embedding_model: String,
+    training_hash: String,
+    summary: SectionSummary,
+}
+
+impl SectionClassifier {
+    /// Compute DRRP, background, and subject centroids from labelled sections.
+    ///
+    /// `law_subjects` maps law names to their subject tags (see
+    /// [`LabelSet::law_subjects`](crate::LabelSet)); each section inherits its
+    /// law's subjects.
+    pub fn build(
+        sections: &[Section],
+        labels: &SectionLabels,
+        law_subjects: &HashMap<String, Vec<String>>,
+    ) -> Self {
+        let dim = sections.first().map_or(0, |s| s.embedding.len());
+        let drrp_laws: HashSet<&str> = labels.drrp.keys().map(|(law, _)| law.as_str()).collect();
+
+        let mut drrp_accum: HashMap<&str, (Vec<f32>, usize)> = HashMap::new();
+        let mut background_accum: HashMap<&str, (Vec<f32>, usize)> = HashMap::new();
+        let mut subject_accum: HashMap<&str, (Vec<f32>, usize)> = HashMap::new();
+        let mut drrp_sections = 0;
+        let mut background_sections = 0;
+        let mut lines = Vec::new();
+
+        for section in sections {
+            let drrp = labels.drrp_types(section);
+            if let Some(types) = drrp {
+                drrp_sections += 1;
+                for drrp_type in types {
+                    accumulate(&mut drrp_accum, drrp_type, &section.embedding, dim);
+                }
+            } else if drrp_laws.contains(section.law_name.as_str()) {
+                background_sections += 1;
+                accumulate(&mut background_accum, "", &section.embedding, dim);
+            }
+
+            let subjects = law_subjects.get(&section.law_name);
+            for subject in subjects.into_iter().flatten() {
+                accumulate(&mut subject_accum, subject, &section.embedding, dim);
+            }
+
+            if drrp.is_some() || subjects.is_some() {
+                let mut subjects = subjects.cloned().unwrap_or_default();
+                subjects.sort();
+                let drrp: Vec<&str> = drrp.into_iter().flatten().map(|t| t.as_str()).collect();
+                lines.push(format!(
+                    "{}\t{}\t{}\n",
+                    section.section_id,
+                    drrp.join(","),
+                    subjects.join(",")
+                ));
+            }
+        }
+
+        // Hash in section order-independent form.
+        lines.sort();
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        for line in &lines {
+            ctx.update(line.as_bytes());
+        }
+        let training_hash = hex(ctx.finish().as_ref());
+
+        let drrp_centroids = finalize(drrp_accum);
+        let subject_centroids = finalize(subject_accum);
+        let summary = SectionSummary {
+            drrp_sections,
+            background_sections,
+            drrp_count: drrp_centroids.len(),
+            subject_count: subject_centroids.len(),
+        };
+
+        Self {
+            drrp_centroids,
+            background: finalize(background_accum).remove(""),
+            subject_centroids,
+            dim,
+            embedding_model: String::new(),
+            training_hash,
+            summary,
+        }
+    }
+
+    /// Record the embedding model the training sections were produced with.
+    pub fn with_embedding_model(mut self, model: impl Into<String>) -> Self {
+        self.embedding_model = model.into();
+        self
+    }
+
+    /// Version string recorded in `section_classifications.model`, e.g.
+    /// `section-centroid-3f2a9c01b7de`.
+    pub fn version(&self) -> String {
+        let key = format!(
+            "{}\n{}\n{}",
+            self.embedding_model, self.dim, self.training_hash
+        );
+        format!("section-centroid-{}", short_hash(&key))
+    }
+
+    /// Centroid and training-set counts.
+    pub fn summary(&self) -> &SectionSummary {
+        &self.summary
+    }
+
+    /// Classify one section.
+    ///
+    /// A DRRP type is tagged when its similarity reaches `drrp_threshold` and
+    /// exceeds the background similarity; subjects only need `subject_threshold`.
+    pub fn classify(
+        &self,
+        section: &Section,
+        drrp_threshold: f32,
+        subject_threshold: f32,
+    ) -> SectionClassification {
+        let background = self
+            .background
+            .as_ref()
+            .map_or(f32::NEG_INFINITY, |b| cosine_sim(&section.embedding, b));
+        let drrp = ranked(&self.drrp_centroids, &section.embedding)
+            .into_iter()
+            .filter(|(_, sim)| *sim >= drrp_threshold && *sim > background)
+            .collect();
+        let subjects = ranked(&self.subject_centroids, &section.embedding)
+            .into_iter()
+            .filter(|(_, sim)| *sim >= subject_threshold)
+            .collect();
+
+        SectionClassification {
+            section_id: section.section_id.clone(),
+            law_name: section.law_name.clone(),
+            provision: section.provision.clone(),
+            drrp,
+            subjects,
+        }
+    }
+
+    /// Classify many sections.
+    pub fn classify_batch(
+        &self,
+        sections: &[Section],
+        drrp_threshold: f32,
+        subject_threshold: f32,
+    ) -> Vec<SectionClassification> {
+        sections
+            .iter()
+            .map(|s| self.classify(s, drrp_threshold, subject_threshold))
+            .collect()
+    }
+}
+
+/// Turn section results into DRRP annotation candidates for the drrp-polisher.
+///
+/// One candidate per `(law, provision, drrp_type)`: the best-scoring section
+/// of that provision supplies the source text and confidence. Provisions that
+/// already carry that type in `labels` are skipped — sertantai has them.
+pub fn drrp_candidates(
+    sections: &[Section],
+    results: &[SectionClassification],
+    labels: &SectionLabels,
+    scraped_at: &str,
+) -> Vec<fractalaw_core::Annotation> {
+    let mut best: HashMap<(&str, &str, &str), (f32, &Section)> = HashMap::new();
+
+    for (section, result) in sections.iter().zip(results) {
+        let Some(provision) = section.provision.as_deref() else {
+            continue;
+        };
+        let known = labels.drrp_types(section);
+        for (drrp_type, confidence) in &result.drrp {
+            if known.is_some_and(|k| k.contains(drrp_type)) {
+                continue;
+            }
+            let entry = best
+                .entry((&section.law_name, provision, drrp_type))
+                .or_insert((*confidence, section));
+            if *confidence > entry.0 {
+                *entry = (*confidence, section);
+            }
+        }
+    }
+
+    let mut candidates: Vec<fractalaw_core::Annotation> = best
+        .into_iter()
+        .map(
+            |((law_name, provision, drrp_type), (confidence, section))| {
+                fractalaw_core::Annotation {
+                    law_name: law_name.to_string(),
+                    provision: provision.to_string(),
+                    drrp_type: drrp_type.to_string(),
+                    source_text: section.text.clone(),
+                    confidence,
+                    scraped_at: scraped_at.to_string(),
+                }
+            },
+        )
+        .collect();
+    candidates.sort_by(|a, b| {
+        b.confidence
+            .partial_cmp(&a.confidence)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    candidates
+}
+
+fn accumulate<'a>(
+    accum: &mut HashMap<&'a str, (Vec<f32>, usize)>,
+    label: &'a str,
+    embedding: &[f32],
+    dim: usize,
+) {
+    let entry = accum.entry(label).or_insert_with(|| (vec![0.0f32; dim], 0));
+    for (acc, &val) in entry.0.iter_mut().zip(embedding) {
+        *acc += val;
+    }
+    entry.1 += 1;
+}
+
+fn finalize(accum: HashMap<&str, (Vec<f32>, usize)>) -> HashMap<String, Vec<f32>> {
+    accum
+        .into_iter()
+        .filter(|(_, (_, count))| *count > 0)
+        .map(|(label, (mut sum, _))| {
+            normalize(&mut sum);
+            (label.to_string(), sum)
+        })
+        .collect()
+}
+
+/// Every centroid's similarity to `embedding`, best first.
+fn ranked(centroids: &HashMap<String, Vec<f32>>, embedding: &[f32]) -> Vec<(String, f32)> {
+    let mut ranked: Vec<(String, f32)> = centroids
+        .iter()
+        .map(|(label, centroid)| (label.clone(), cosine_sim(embedding, centroid)))
+        .collect();
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(id: &str, law: &str, provision: Option<&str>, emb: [f32; 3]) -> Section {
+        Section {
+            section_id: format!("{law}:{id}"),
+            law_name: law.to_string(),
+            provision: provision.map(str::to_string),
+            text: format!("text of {id}"),
+            embedding: emb.to_vec(),
+        }
+    }
+
+    #[test]
+    fn provision_key_normalises_references() {
+        assert_eq!(provision_key("section/12"), "12");
+        assert_eq!(provision_key("regulation/4A"), "4a");
+        assert_eq!(provision_key("s.12"), "12");
+        assert_eq!(provision_key("s.2(1)(a)"), "2");
+        assert_eq!(provision_key("reg. 4"), "4");
+        assert_eq!(provision_key("12"), "12");
+    }
+
+    #[test]
+    fn labels_ignore_unknown_types() {
+        let mut labels = SectionLabels::default();
+        labels.insert("law", "section/2", "Duty");
+        labels.insert("law", "section/2", "power");
+        labels.insert("law", "section/3", "obligation");
+        assert_eq!(labels.len(), 1);
+
+        let s = section("s.2(1)", "law", Some("2"), [1.0, 0.0, 0.0]);
+        let types: Vec<&str> = labels
+            .drrp_types(&s)
+            .unwrap()
+            .iter()
+            .map(|t| t.as_str())
+            .collect();
+        assert_eq!(types, vec!["duty", "power"]);
+    }
+
+    #[test]
+    fn tags_drrp_above_background_and_builds_candidates() {
+        // Duties point along +x, powers along +y, boilerplate along +z.
+        let sections = vec![
+            section("s.2", "hswa", Some("2"), [1.0, 0.0, 0.0]),
+            section("s.3", "hswa", Some("3"), [0.9, 0.1, 0.0]),
+            section("s.20", "hswa", Some("20"), [0.0, 1.0, 0.0]),
+            section("s.1", "hswa", Some("1"), [0.0, 0.0, 1.0]),
+            section("s.53", "hswa", Some("53"), [0.1, 0.0, 1.0]),
+            // Unlabelled law: classified, but not part of the background.
+            section("reg.4", "cdm", Some("4"), [0.95, 0.05, 0.1]),
+            section("reg.1", "cdm", Some("1"), [0.05, 0.05, 1.0]),
+        ];
+        let mut labels = SectionLabels::default();
+        labels.insert("hswa", "section/2", "duty");
+        labels.insert("hswa", "s.3", "duty");
+        labels.insert("hswa", "section/20", "power");
+        let law_subjects = HashMap::from([("hswa".to_string(), vec!["workplace".to_string()])]);
+
+        let classifier = SectionClassifier::build(&sections, &labels, &law_subjects)
+            .with_embedding_model("test-model");
+        let summary = classifier.summary();
+        assert_eq!(summary.drrp_sections, 3);
+        assert_eq!(summary.background_sections, 2);
+        assert_eq!(summary.drrp_count, 2);
+        assert_eq!(summary.subject_count, 1);
+        assert!(classifier.version().starts_with("section-centroid-"));
+
+        let results = classifier.classify_batch(&sections, 0.3, 0.3);
+        let cdm_duty = &results[5];
+        assert_eq!(cdm_duty.drrp[0].0, "duty");
+        assert_eq!(cdm_duty.drrp.len(), 1);
+        // Boilerplate is closer to the background than to any DRRP type.
+        assert!(results[6].drrp.is_empty());
+
+        let candidates = drrp_candidates(&sections, &results, &labels, "2026-01-01T00:00:00Z");
+        // hswa s.2/s.3/s.20 are already labelled; only cdm reg.4 is new.
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].law_name, "cdm");
+        assert_eq!(candidates[0].provision, "4");
+        assert_eq!(candidates[0].drrp_type, "duty");
+        assert_eq!(candidates[0].source_text, "text of reg.4");
+    }
+
+    #[test]
+    fn training_hash_ignores_section_order() {
+        let a = section("s.2", "law", Some("2"), [1.0, 0.0, 0.0]);
+        let b = section("s.3", "law", Some("3"), [0.0, 1.0, 0.0]);
+        let mut labels = SectionLabels::default();
+        labels.insert("law", "2", "duty");
+        labels.insert("law", "3", "right");
+
+        let forward = SectionClassifier::build(&[a, b], &labels, &HashMap::new());
+        let a = section("s.2", "law", Some("2"), [1.0, 0.0, 0.0]);
+        let b = section("s.3", "law", Some("3"), [0.0, 1.0, 0.0]);
+        let reverse = SectionClassifier::build(&[b, a], &labels, &HashMap::new());
+        assert_eq!(forward.version(), reverse.version());
+
+        labels.insert("law", "3", "power");
+        let a = section("s.2", "law", Some("2"), [1.0, 0.0, 0.0]);
+        let b = section("s.3", "law", Some("3"), [0.0, 1.0, 0.0]);
+        let relabelled = SectionClassifier::build(&[a, b], &labels, &HashMap::new());
+        assert_ne!(forward.version(), relabelled.version());
+    }
+}
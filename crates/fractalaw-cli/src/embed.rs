@@ -1,4 +1,7 @@
 //! Embedding pipeline: reads LAT text, generates ONNX embeddings, writes to LanceDB.
+//!
+//! Also re-embeds an existing table under a new model for `migrate-embeddings`,
+//! staging vectors in shadow columns until they are validated and swapped in.
 
 use std::path::Path;
 use std::sync::Arc;
@@ -12,11 +15,11 @@ use arrow::array::{
 use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
 use fractalaw_ai::Embedder;
-use fractalaw_store::LanceStore;
+use fractalaw_store::{
+    LanceStore, SHADOW_EMBEDDING, SHADOW_EMBEDDING_MODEL, SHADOW_TOKEN_IDS, SHADOW_TOKENIZER_MODEL,
+};
 
 const EMBED_BATCH_SIZE: usize = 256;
-const MODEL_NAME: &str = "all-MiniLM-L6-v2";
-const EMBED_DIM: i32 = 384;
 
 pub struct EmbedStats {
     pub total_rows: usize,
@@ -46,7 +49,9 @@ pub async fn run_embed_pipeline(
     }
 
     // 2. Build output schema (fix embedding column types from DuckDB's FLOAT[] to FixedSizeList).
-    let output_schema = build_embedded_schema(&source_batches[0].schema());
+    let dim = embedder.dim() as i32;
+    let model_name = embedder.model_name().to_string();
+    let output_schema = build_embedded_schema(&source_batches[0].schema(), dim);
 
     let now_nanos = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -64,21 +69,15 @@ pub async fn run_embed_pipeline(
         let texts = extract_texts(batch);
 
         // Generate embeddings and token IDs in sub-batches of 256.
-        let mut embeddings = Vec::with_capacity(n);
-        let mut all_token_ids = Vec::with_capacity(n);
-        for chunk in texts.chunks(EMBED_BATCH_SIZE) {
-            let batch_embs = embedder
-                .embed_batch(chunk)
-                .context("generating embeddings")?;
-            let batch_tokens = embedder.tokenize_batch(chunk).context("tokenizing")?;
-            embeddings.extend(batch_embs);
-            all_token_ids.extend(batch_tokens);
-        }
+        let embeddings = embed_texts(embedder, &texts)?;
+        let all_token_ids = tokenize_texts(embedder, &texts)?;
 
         // Build output batch with embeddings and token IDs populated.
         let output = replace_embedding_columns(
             batch,
             &output_schema,
+            &model_name,
+            dim,
             &embeddings,
             &all_token_ids,
             now_nanos,
@@ -107,9 +106,174 @@ pub async fn run_embed_pipeline(
     })
 }
 
-/// Build output schema, replacing DuckDB's `FLOAT[]` with `FixedSizeList<Float32, 384>`
+/// Re-embed and re-tokenize every `legislation_text` row into the shadow
+/// columns.
+///
+/// The live `embedding` and token columns are left alone; the staged vectors
+/// are validated before they are written. Swap them in with
+/// [`LanceStore::swap_shadow_embeddings`].
+pub async fn stage_migration(
+    lance: &LanceStore,
+    embedder: &mut Embedder,
+) -> anyhow::Result<EmbedStats> {
+    let start = Instant::now();
+    let dim = embedder.dim() as i32;
+    let model_name = embedder.model_name().to_string();
+
+    let source_batches = lance
+        .read_legislation_text()
+        .await
+        .context("reading legislation_text from LanceDB")?;
+    let total_rows: usize = source_batches.iter().map(|b| b.num_rows()).sum();
+    eprintln!("  Read {total_rows} rows from LanceDB");
+
+    let mut staged = Vec::with_capacity(source_batches.len());
+    let mut processed = 0usize;
+    for batch in &source_batches {
+        let texts = extract_texts(batch);
+        let embeddings = embed_texts(embedder, &texts)?;
+        let token_ids = tokenize_texts(embedder, &texts)?;
+        staged.push(with_shadow_columns(
+            batch,
+            &model_name,
+            dim,
+            &embeddings,
+            &token_ids,
+        )?);
+
+        processed += batch.num_rows();
+        eprint!(
+            "\r  Embedded {processed}/{total_rows} ({:.1}%)",
+            processed as f64 / total_rows.max(1) as f64 * 100.0
+        );
+    }
+    eprintln!();
+
+    fractalaw_store::validate_shadow_embeddings(&staged, &model_name)
+        .context("validating staged embeddings")?;
+
+    eprintln!("  Staging in {SHADOW_EMBEDDING}...");
+    lance
+        .stage_shadow_embeddings(staged)
+        .await
+        .context("writing staged embeddings to LanceDB")?;
+
+    Ok(EmbedStats {
+        total_rows,
+        elapsed_secs: start.elapsed().as_secs_f64(),
+    })
+}
+
+/// Embed texts in sub-batches of [`EMBED_BATCH_SIZE`].
+fn embed_texts(embedder: &mut Embedder, texts: &[&str]) -> anyhow::Result<Vec<Vec<f32>>> {
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for chunk in texts.chunks(EMBED_BATCH_SIZE) {
+        embeddings.extend(
+            embedder
+                .embed_batch(chunk)
+                .context("generating embeddings")?,
+        );
+    }
+    Ok(embeddings)
+}
+
+/// Token IDs for texts in sub-batches of [`EMBED_BATCH_SIZE`].
+fn tokenize_texts(embedder: &mut Embedder, texts: &[&str]) -> anyhow::Result<Vec<Vec<u32>>> {
+    let mut token_ids = Vec::with_capacity(texts.len());
+    for chunk in texts.chunks(EMBED_BATCH_SIZE) {
+        token_ids.extend(embedder.tokenize_batch(chunk).context("tokenizing")?);
+    }
+    Ok(token_ids)
+}
+
+/// Append (or replace) the shadow embedding and token columns on a
+/// `legislation_text` batch.
+fn with_shadow_columns(
+    batch: &RecordBatch,
+    model_name: &str,
+    dim: i32,
+    embeddings: &[Vec<f32>],
+    token_ids: &[Vec<u32>],
+) -> anyhow::Result<RecordBatch> {
+    const SHADOWS: [&str; 4] = [
+        SHADOW_EMBEDDING,
+        SHADOW_EMBEDDING_MODEL,
+        SHADOW_TOKEN_IDS,
+        SHADOW_TOKENIZER_MODEL,
+    ];
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len() + SHADOWS.len());
+    let mut columns: Vec<Arc<dyn Array>> =
+        Vec::with_capacity(schema.fields().len() + SHADOWS.len());
+    // Drop shadow columns left by an earlier, unswapped staging run.
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        if !SHADOWS.contains(&field.name().as_str()) {
+            fields.push(field.as_ref().clone());
+            columns.push(column.clone());
+        }
+    }
+
+    fields.push(Field::new(
+        SHADOW_EMBEDDING,
+        DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim),
+        true,
+    ));
+    columns.push(Arc::new(build_embeddings(embeddings, dim)));
+    fields.push(Field::new(SHADOW_EMBEDDING_MODEL, DataType::Utf8, true));
+    columns.push(Arc::new(StringArray::from(vec![
+        model_name;
+        batch.num_rows()
+    ])));
+    fields.push(Field::new(SHADOW_TOKEN_IDS, token_ids_type(), true));
+    columns.push(Arc::new(build_token_ids(token_ids)));
+    fields.push(Field::new(SHADOW_TOKENIZER_MODEL, DataType::Utf8, true));
+    columns.push(Arc::new(StringArray::from(vec![
+        model_name;
+        batch.num_rows()
+    ])));
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+fn build_embeddings(embeddings: &[Vec<f32>], dim: i32) -> arrow::array::FixedSizeListArray {
+    let mut emb_builder = FixedSizeListBuilder::new(Float32Builder::new(), dim);
+    for emb in embeddings {
+        let values = emb_builder.values();
+        for &val in emb {
+            values.append_value(val);
+        }
+        emb_builder.append(true);
+    }
+    emb_builder.finish()
+}
+
+/// `token_ids` column type: `List<non-null UInt32>`.
+fn token_ids_type() -> DataType {
+    DataType::List(Arc::new(Field::new("item", DataType::UInt32, false)))
+}
+
+fn build_token_ids(token_ids: &[Vec<u32>]) -> arrow::array::ListArray {
+    let mut list_builder = ListBuilder::new(UInt32Builder::new()).with_field(Field::new(
+        "item",
+        DataType::UInt32,
+        false,
+    ));
+    for ids in token_ids {
+        let values = list_builder.values();
+        for &id in ids {
+            values.append_value(id);
+        }
+        list_builder.append(true);
+    }
+    list_builder.finish()
+}
+
+/// Build output schema, replacing DuckDB's `FLOAT[]` with `FixedSizeList<Float32, dim>`
 /// and ensuring `embedded_at` uses nanosecond timestamps.
-fn build_embedded_schema(source_schema: &Schema) -> Arc<Schema> {
+fn build_embedded_schema(source_schema: &Schema, dim: i32) -> Arc<Schema> {
     let mut fields: Vec<Field> = source_schema
         .fields()
         .iter()
@@ -121,10 +285,7 @@ fn build_embedded_schema(source_schema: &Schema) -> Arc<Schema> {
 
     fields[emb_idx] = Field::new(
         "embedding",
-        DataType::FixedSizeList(
-            Arc::new(Field::new("item", DataType::Float32, true)),
-            EMBED_DIM,
-        ),
+        DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim),
         true,
     );
 
@@ -136,14 +297,7 @@ fn build_embedded_schema(source_schema: &Schema) -> Arc<Schema> {
 
     // Insert token columns after embedded_at (source Parquet doesn't have these).
     let insert_at = ts_idx + 1;
-    fields.insert(
-        insert_at,
-        Field::new(
-            "token_ids",
-            DataType::List(Arc::new(Field::new("item", DataType::UInt32, false))),
-            true,
-        ),
-    );
+    fields.insert(insert_at, Field::new("token_ids", token_ids_type(), true));
     fields.insert(
         insert_at + 1,
         Field::new("tokenizer_model", DataType::Utf8, true),
@@ -156,6 +310,8 @@ fn build_embedded_schema(source_schema: &Schema) -> Arc<Schema> {
 fn replace_embedding_columns(
     batch: &RecordBatch,
     schema: &Arc<Schema>,
+    model_name: &str,
+    dim: i32,
     embeddings: &[Vec<f32>],
     token_ids: &[Vec<u32>],
     now_nanos: i64,
@@ -170,19 +326,11 @@ fn replace_embedding_columns(
     // Clone all columns (cheap Arc clones), then replace embedding ones.
     let mut columns: Vec<Arc<dyn Array>> = batch.columns().to_vec();
 
-    // embedding: FixedSizeList<Float32, 384>
-    let mut emb_builder = FixedSizeListBuilder::new(Float32Builder::new(), EMBED_DIM);
-    for emb in embeddings {
-        let values = emb_builder.values();
-        for &val in emb {
-            values.append_value(val);
-        }
-        emb_builder.append(true);
-    }
-    columns[emb_idx] = Arc::new(emb_builder.finish());
+    // embedding: FixedSizeList<Float32, dim>
+    columns[emb_idx] = Arc::new(build_embeddings(embeddings, dim));
 
     // embedding_model: Utf8
-    columns[model_idx] = Arc::new(StringArray::from(vec![MODEL_NAME; n]));
+    columns[model_idx] = Arc::new(StringArray::from(vec![model_name; n]));
 
     // embedded_at: Timestamp(Nanosecond, UTC)
     columns[ts_idx] =
@@ -192,25 +340,12 @@ fn replace_embedding_columns(
     // (these columns don't exist in the source Parquet).
     let insert_at = ts_idx + 1;
 
-    // token_ids: List<non-null UInt32>
-    let mut list_builder = ListBuilder::new(UInt32Builder::new()).with_field(Field::new(
-        "item",
-        DataType::UInt32,
-        false,
-    ));
-    for ids in token_ids {
-        let values = list_builder.values();
-        for &id in ids {
-            values.append_value(id);
-        }
-        list_builder.append(true);
-    }
-    columns.insert(insert_at, Arc::new(list_builder.finish()));
+    columns.insert(insert_at, Arc::new(build_token_ids(token_ids)));
 
     // tokenizer_model: Utf8
     columns.insert(
         insert_at + 1,
-        Arc::new(StringArray::from(vec![MODEL_NAME; n])),
+        Arc::new(StringArray::from(vec![model_name; n])),
     );
 
     Ok(RecordBatch::try_new(schema.clone(), columns)?)
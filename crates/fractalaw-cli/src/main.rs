@@ -1,5 +1,6 @@
 mod display;
 mod embed;
+mod serve;
 
 use std::path::PathBuf;
 
@@ -58,6 +59,19 @@ enum Command {
         model_dir: PathBuf,
     },
 
+    /// Re-embed legislation text under a new model and swap it in after validation
+    MigrateEmbeddings {
+        /// Path to the new ONNX model directory
+        #[arg(long)]
+        model_dir: PathBuf,
+        /// Stage and validate the new vectors without swapping them in
+        #[arg(long)]
+        stage_only: bool,
+        /// Swap in vectors staged by an earlier `--stage-only` run
+        #[arg(long, conflicts_with = "stage_only")]
+        swap_staged: bool,
+    },
+
     /// Show legislation text sections from LanceDB
     Text {
         /// Legislation name (e.g., UK_ukpga_1974_37)
@@ -79,6 +93,24 @@ enum Command {
         model_dir: PathBuf,
     },
 
+    /// Answer a question from retrieved legislation sections, with citations
+    Ask {
+        /// Natural language question
+        question: String,
+        /// Number of sections retrieved as sources
+        #[arg(long, default_value_t = 5)]
+        limit: usize,
+        /// Path to ONNX model directory
+        #[arg(long, default_value = "./models/all-MiniLM-L6-v2")]
+        model_dir: PathBuf,
+        /// Maximum tokens in the generated answer
+        #[arg(long, default_value_t = 1024)]
+        max_tokens: u32,
+        /// Record responses in, or replay them from, <data-dir>/inference-cache (record, replay)
+        #[arg(long)]
+        inference_cache: Option<fractalaw_host::CacheMode>,
+    },
+
     /// Run validation checks across all data stores
     Validate {
         /// Path to ONNX model directory (for semantic smoke test)
@@ -103,6 +135,44 @@ enum Command {
         /// Subject similarity threshold (0.0–1.0)
         #[arg(long, default_value_t = 0.3)]
         subject_threshold: f32,
+        /// Classification method: centroid, knn, knn:<k>, or linear-probe
+        #[arg(long, default_value = "centroid")]
+        method: fractalaw_ai::Method,
+        /// Reuse a saved classifier version (or `latest`) instead of rebuilding centroids
+        #[arg(long)]
+        model: Option<String>,
+    },
+
+    /// Tag each legislation_text section with likely DRRP types and subjects
+    ClassifySections {
+        /// DRRP similarity threshold (0.0–1.0)
+        #[arg(long, default_value_t = 0.5)]
+        drrp_threshold: f32,
+        /// Subject similarity threshold (0.0–1.0)
+        #[arg(long, default_value_t = 0.3)]
+        subject_threshold: f32,
+        /// Queue new DRRP candidates in drrp_candidates for the drrp-polisher
+        #[arg(long)]
+        queue_candidates: bool,
+    },
+
+    /// Evaluate family classification with k-fold or held-out cross-validation
+    Evaluate {
+        /// Number of cross-validation folds
+        #[arg(long, default_value_t = 5)]
+        folds: usize,
+        /// Evaluate on a single held-out fraction (0.0–1.0) instead of k folds
+        #[arg(long, conflicts_with = "folds")]
+        holdout: Option<f32>,
+        /// Classification method: centroid, knn, knn:<k>, or linear-probe
+        #[arg(long, default_value = "centroid")]
+        method: fractalaw_ai::Method,
+    },
+
+    /// Review classification conflicts and low-confidence predictions
+    Review {
+        #[command(subcommand)]
+        action: ReviewAction,
     },
 
     /// Import (or re-import) Parquet files into persistent DuckDB
@@ -113,9 +183,110 @@ enum Command {
         /// Path to the .wasm component file
         component: PathBuf,
 
-        /// Fuel budget (default: 1 billion = standard tier)
-        #[arg(long, default_value_t = 1_000_000_000)]
-        fuel: u64,
+        /// Fuel budget (default: the tier's)
+        #[arg(long)]
+        fuel: Option<u64>,
+
+        /// Resource tier: lightweight, standard or heavy (default: the manifest's, else standard)
+        #[arg(long)]
+        tier: Option<fractalaw_core::Tier>,
+
+        /// Path to ONNX model directory (enables ai-embeddings host functions)
+        #[arg(long)]
+        model_dir: Option<PathBuf>,
+
+        /// Saved classifier version (or `latest`) for ai-classify `classify-trained`
+        #[arg(long, requires = "model_dir")]
+        classifier: Option<String>,
+
+        /// Maximum tokens the app may generate through ai-inference in this run
+        #[arg(long)]
+        token_budget: Option<u64>,
+
+        /// Record responses in, or replay them from, <data-dir>/inference-cache (record, replay)
+        #[arg(long)]
+        inference_cache: Option<fractalaw_host::CacheMode>,
+
+        /// App manifest granting capabilities (default: <component>.manifest.json,
+        /// else <apps-dir>/<component>.manifest.json; without one nothing is granted)
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+
+        /// Grant every capability without limits instead of reading a manifest
+        #[arg(long, conflicts_with = "manifest")]
+        unrestricted: bool,
+
+        /// Directory of app components and manifests (default: <data-dir>/apps)
+        #[arg(long)]
+        apps_dir: Option<PathBuf>,
+    },
+
+    /// Inspect the event log and deliver events to subscribed micro-apps
+    Events {
+        #[command(subcommand)]
+        action: EventsAction,
+    },
+
+    /// Manage scheduled micro-app runs and run the scheduler
+    Schedule {
+        #[command(subcommand)]
+        action: ScheduleAction,
+    },
+
+    /// Manage micro-app components
+    App {
+        #[command(subcommand)]
+        action: AppAction,
+    },
+
+    /// Check and checkpoint the micro-app audit log
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+
+    /// Serve micro-apps from a long-running runtime over a Unix socket
+    Serve {
+        /// Socket path (default: <data-dir>/fractalaw.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+        /// Directory of app components to preload (default: <data-dir>/apps)
+        #[arg(long)]
+        apps_dir: Option<PathBuf>,
+        /// Default fuel budget per run (default: each app's tier limit)
+        #[arg(long)]
+        fuel: Option<u64>,
+        /// Seconds between event deliveries and scheduled-task checks
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+        /// Seconds between signed audit checkpoints (0 disables them)
+        #[arg(long, default_value_t = 3600)]
+        checkpoint_interval: u64,
+        /// Maximum tokens each run may generate through ai-inference
+        #[arg(long)]
+        token_budget: Option<u64>,
+        /// Maximum tokens each app may generate through ai-inference across all
+        /// its runs by this process
+        #[arg(long)]
+        app_token_budget: Option<u64>,
+        /// Record responses in, or replay them from, <data-dir>/inference-cache (record, replay)
+        #[arg(long)]
+        inference_cache: Option<fractalaw_host::CacheMode>,
+    },
+
+    /// Run an app on a running `fractalaw serve` daemon
+    Call {
+        /// App name (e.g. hello-world)
+        app: String,
+        /// Payload passed to the app as a `request` event
+        #[arg(long)]
+        payload: Option<String>,
+        /// Fuel budget (default: the daemon's)
+        #[arg(long)]
+        fuel: Option<u64>,
+        /// Socket path (default: <data-dir>/fractalaw.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
     },
 
     /// Sync DRRP annotations and polished results with sertantai
@@ -125,6 +296,194 @@ enum Command {
     },
 }
 
+#[derive(Subcommand)]
+enum ReviewAction {
+    /// List conflicts and low-confidence predictions, most uncertain first
+    List {
+        /// Include `predicted` rows below this family confidence
+        #[arg(long, default_value_t = 0.5)]
+        max_confidence: f32,
+        /// Maximum rows to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Accept the AI-predicted family (used as ground truth in the next build)
+    Accept {
+        /// Law name (e.g. UK_ukpga_1974_37)
+        name: String,
+        /// Who is making the decision
+        #[arg(long, env = "USER")]
+        reviewer: String,
+    },
+    /// Keep the ground-truth family and reject the AI label
+    Keep {
+        /// Law name (e.g. UK_ukpga_1974_37)
+        name: String,
+        /// Who is making the decision
+        #[arg(long, env = "USER")]
+        reviewer: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum EventsAction {
+    /// List recent events with their delivery counts
+    List {
+        /// Maximum events to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// List subscriptions
+    Subscriptions,
+    /// Run a component for every later event of a type
+    Subscribe {
+        /// Event type (e.g. law-updated)
+        event_type: String,
+        /// Path to the subscribing .wasm component
+        component: PathBuf,
+        /// Subscriber name (default: the component's app name)
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Remove a subscription and its pending deliveries
+    Unsubscribe {
+        /// Subscriber name
+        subscriber: String,
+        /// Event type
+        event_type: String,
+    },
+    /// Run subscribers for due deliveries, retrying failures with backoff
+    Dispatch {
+        /// Fuel budget per subscriber run (default: each subscriber's tier limit)
+        #[arg(long)]
+        fuel: Option<u64>,
+        /// Maximum subscriber runs in this pass
+        #[arg(long, default_value_t = 100)]
+        limit: usize,
+        /// Failed runs before a delivery is dead-lettered
+        #[arg(long, default_value_t = 5)]
+        max_attempts: u32,
+        /// Maximum tokens each run may generate through ai-inference
+        #[arg(long)]
+        token_budget: Option<u64>,
+        /// Maximum tokens each app may generate through ai-inference across all
+        /// its runs by this process
+        #[arg(long)]
+        app_token_budget: Option<u64>,
+        /// Record responses in, or replay them from, <data-dir>/inference-cache (record, replay)
+        #[arg(long)]
+        inference_cache: Option<fractalaw_host::CacheMode>,
+    },
+    /// List deliveries that exhausted their retries
+    DeadLetters {
+        /// Maximum rows to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+    },
+    /// Queue a dead-lettered delivery for another round of attempts
+    Retry {
+        /// Event id
+        event_id: u64,
+        /// Subscriber name
+        subscriber: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleAction {
+    /// List scheduled tasks, next due first
+    List {
+        /// Include finished and cancelled tasks
+        #[arg(long)]
+        all: bool,
+    },
+    /// Schedule an app to run on a cron schedule or once
+    Add {
+        /// Task name (the app sees it as event type `schedule:<name>`)
+        name: String,
+        /// App to run, found as <apps-dir>/<app>.wasm when the scheduler runs
+        target_app: String,
+        /// Five-field cron expression in UTC (e.g. "0 2 * * *" or @daily)
+        #[arg(long, required_unless_present = "at", conflicts_with = "at")]
+        cron: Option<String>,
+        /// One-shot run time (RFC 3339, e.g. 2026-11-01T02:00:00Z)
+        #[arg(long)]
+        at: Option<String>,
+        /// Payload passed to the app
+        #[arg(long, default_value = "")]
+        payload: String,
+    },
+    /// Cancel a scheduled task
+    Cancel {
+        /// Task id
+        id: u64,
+    },
+    /// Run due tasks, polling until interrupted
+    Run {
+        /// Directory holding app components (default: <data-dir>/apps)
+        #[arg(long)]
+        apps_dir: Option<PathBuf>,
+        /// Fuel budget per run (default: each app's tier limit)
+        #[arg(long)]
+        fuel: Option<u64>,
+        /// Seconds between checks for due tasks
+        #[arg(long, default_value_t = 30)]
+        interval: u64,
+        /// Run the tasks due now and exit
+        #[arg(long)]
+        once: bool,
+        /// Maximum tokens each run may generate through ai-inference
+        #[arg(long)]
+        token_budget: Option<u64>,
+        /// Maximum tokens each app may generate through ai-inference across all
+        /// its runs by this process
+        #[arg(long)]
+        app_token_budget: Option<u64>,
+        /// Record responses in, or replay them from, <data-dir>/inference-cache (record, replay)
+        #[arg(long)]
+        inference_cache: Option<fractalaw_host::CacheMode>,
+    },
+}
+
+#[derive(Subcommand)]
+enum AppAction {
+    /// Compile components ahead of time into <data-dir>/aot-cache
+    Precompile {
+        /// Components to compile (default: every .wasm in the apps directory)
+        components: Vec<PathBuf>,
+        /// Directory of app components (default: <data-dir>/apps)
+        #[arg(long)]
+        apps_dir: Option<PathBuf>,
+        /// Remove cached artifacts for anything not compiled by this run
+        #[arg(long)]
+        prune: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditAction {
+    /// Walk the audit log's hash chain, check checkpoint signatures, and
+    /// report the first tampered entry; fails unless the log is anchored by
+    /// a recent trusted checkpoint
+    Verify {
+        /// Hex public key of another node whose checkpoints to accept (repeatable);
+        /// this node's own key is always trusted
+        #[arg(long = "public-key")]
+        public_keys: Vec<String>,
+        /// Checkpoint the log must still contain, as printed by `audit checkpoint`:
+        /// <entry_id>:<hash> (repeatable)
+        #[arg(long = "expect-checkpoint")]
+        expected: Vec<String>,
+        /// Entries allowed after the last trusted checkpoint
+        #[arg(long, default_value_t = 1000)]
+        max_unanchored: u64,
+    },
+    /// Append a checkpoint signed with this node's key over the log's head
+    Checkpoint,
+    /// Print this node's id and public key
+    Key,
+}
+
 #[derive(Subcommand)]
 enum SyncAction {
     /// Pull new annotations from sertantai outbox
@@ -164,31 +523,295 @@ async fn main() -> anyhow::Result<()> {
         Command::Classify {
             domain_threshold,
             subject_threshold,
+            method,
+            model,
         } => {
             cmd_classify(
                 &open_duck(&data_dir)?,
                 &data_dir,
                 domain_threshold,
                 subject_threshold,
+                method,
+                model.as_deref(),
+            )
+            .await
+        }
+        Command::ClassifySections {
+            drrp_threshold,
+            subject_threshold,
+            queue_candidates,
+        } => {
+            cmd_classify_sections(
+                &open_duck(&data_dir)?,
+                &data_dir,
+                drrp_threshold,
+                subject_threshold,
+                queue_candidates,
             )
             .await
         }
+        Command::Evaluate {
+            folds,
+            holdout,
+            method,
+        } => cmd_evaluate(&open_duck(&data_dir)?, &data_dir, folds, holdout, method).await,
+        Command::Review { action } => {
+            let store = open_duck(&data_dir)?;
+            match action {
+                ReviewAction::List {
+                    max_confidence,
+                    limit,
+                } => cmd_review_list(&store, max_confidence, limit),
+                ReviewAction::Accept { name, reviewer } => cmd_review_decide(
+                    &store,
+                    &name,
+                    fractalaw_core::ReviewDecision::AcceptAi,
+                    &reviewer,
+                ),
+                ReviewAction::Keep { name, reviewer } => cmd_review_decide(
+                    &store,
+                    &name,
+                    fractalaw_core::ReviewDecision::KeepTruth,
+                    &reviewer,
+                ),
+            }
+        }
         Command::Import => cmd_import(&data_dir),
 
         // LanceDB-only commands — no DuckDB needed.
         Command::Embed { model_dir } => cmd_embed(&data_dir, &model_dir).await,
+        Command::MigrateEmbeddings {
+            model_dir,
+            stage_only,
+            swap_staged,
+        } => cmd_migrate_embeddings(&data_dir, &model_dir, stage_only, swap_staged).await,
         Command::Text { name, limit } => cmd_text(&data_dir, &name, limit).await,
         Command::Search {
             query,
             limit,
             model_dir,
         } => cmd_search(&data_dir, &query, limit, &model_dir).await,
+        Command::Ask {
+            question,
+            limit,
+            model_dir,
+            max_tokens,
+            inference_cache,
+        } => {
+            cmd_ask(
+                &data_dir,
+                &question,
+                limit,
+                &model_dir,
+                max_tokens,
+                inference_cache,
+            )
+            .await
+        }
 
         // Model-only commands — no data store needed.
         Command::Tokenize { text, model_dir } => cmd_tokenize(&text, &model_dir),
 
         // WASM micro-app commands.
-        Command::Run { component, fuel } => cmd_run(&data_dir, &component, fuel).await,
+        Command::Run {
+            component,
+            fuel,
+            tier,
+            model_dir,
+            classifier,
+            token_budget,
+            inference_cache,
+            manifest,
+            unrestricted,
+            apps_dir,
+        } => {
+            let manifest = match manifest {
+                Some(path) => Grants::Manifest(path),
+                None if unrestricted => Grants::Unrestricted,
+                None => Grants::Default,
+            };
+            cmd_run(
+                &data_dir,
+                &component,
+                fuel.unwrap_or(fractalaw_host::TIER_FUEL),
+                model_dir.as_deref(),
+                classifier.as_deref(),
+                token_budget,
+                inference_cache,
+                manifest,
+                &apps_dir.unwrap_or_else(|| data_dir.join("apps")),
+                tier,
+            )
+            .await
+        }
+
+        Command::Events { action } => {
+            let store = open_duck(&data_dir)?;
+            match action {
+                EventsAction::List { limit } => cmd_events_list(&store, limit),
+                EventsAction::Subscriptions => cmd_events_subscriptions(&store),
+                EventsAction::Subscribe {
+                    event_type,
+                    component,
+                    name,
+                } => cmd_events_subscribe(&store, &event_type, &component, name.as_deref()),
+                EventsAction::Unsubscribe {
+                    subscriber,
+                    event_type,
+                } => {
+                    if store.unsubscribe(&subscriber, &event_type)? {
+                        println!("Unsubscribed {subscriber} from {event_type}.");
+                    } else {
+                        println!("{subscriber} is not subscribed to {event_type}.");
+                    }
+                    Ok(())
+                }
+                EventsAction::Dispatch {
+                    fuel,
+                    limit,
+                    max_attempts,
+                    token_budget,
+                    app_token_budget,
+                    inference_cache,
+                } => {
+                    cmd_events_dispatch(
+                        &store,
+                        &data_dir,
+                        fuel.unwrap_or(fractalaw_host::TIER_FUEL),
+                        limit,
+                        max_attempts,
+                        token_budget,
+                        app_token_budget,
+                        inference_cache,
+                    )
+                    .await
+                }
+                EventsAction::DeadLetters { limit } => cmd_events_dead_letters(&store, limit),
+                EventsAction::Retry {
+                    event_id,
+                    subscriber,
+                } => {
+                    store.requeue_dead_letter(event_id, &subscriber)?;
+                    println!(
+                        "Queued event {event_id} for {subscriber}; run `fractalaw events dispatch`."
+                    );
+                    Ok(())
+                }
+            }
+        }
+
+        Command::Schedule { action } => {
+            let store = open_duck(&data_dir)?;
+            match action {
+                ScheduleAction::List { all } => cmd_schedule_list(&store, all),
+                ScheduleAction::Add {
+                    name,
+                    target_app,
+                    cron,
+                    at,
+                    payload,
+                } => cmd_schedule_add(&store, name, target_app, cron, at.as_deref(), payload),
+                ScheduleAction::Cancel { id } => {
+                    if store.cancel_scheduled_task(id)? {
+                        println!("Cancelled task {id}.");
+                    } else {
+                        println!("Task {id} is not active.");
+                    }
+                    Ok(())
+                }
+                ScheduleAction::Run {
+                    apps_dir,
+                    fuel,
+                    interval,
+                    once,
+                    token_budget,
+                    app_token_budget,
+                    inference_cache,
+                } => {
+                    let apps_dir = apps_dir.unwrap_or_else(|| data_dir.join("apps"));
+                    cmd_schedule_run(
+                        &store,
+                        &data_dir,
+                        &apps_dir,
+                        fuel.unwrap_or(fractalaw_host::TIER_FUEL),
+                        (!once).then_some(interval),
+                        token_budget,
+                        app_token_budget,
+                        inference_cache,
+                    )
+                    .await
+                }
+            }
+        }
+
+        Command::App { action } => match action {
+            AppAction::Precompile {
+                components,
+                apps_dir,
+                prune,
+            } => {
+                let apps_dir = apps_dir.unwrap_or_else(|| data_dir.join("apps"));
+                cmd_app_precompile(&data_dir, components, &apps_dir, prune).await
+            }
+        },
+        Command::Audit { action } => match action {
+            AuditAction::Verify {
+                public_keys,
+                expected,
+                max_unanchored,
+            } => cmd_audit_verify(
+                &open_duck(&data_dir)?,
+                &node_key(&data_dir)?,
+                &public_keys,
+                &expected,
+                max_unanchored,
+            ),
+            AuditAction::Checkpoint => {
+                cmd_audit_checkpoint(&open_duck(&data_dir)?, &node_key(&data_dir)?)
+            }
+            AuditAction::Key => {
+                let key = node_key(&data_dir)?;
+                println!("Node id:    {}", key.node_id());
+                println!(
+                    "Public key: {}",
+                    fractalaw_core::node::hex(&key.public_key())
+                );
+                Ok(())
+            }
+        },
+        Command::Serve {
+            socket,
+            apps_dir,
+            fuel,
+            interval,
+            checkpoint_interval,
+            token_budget,
+            app_token_budget,
+            inference_cache,
+        } => {
+            let config = serve::ServeConfig {
+                socket: socket.unwrap_or_else(|| data_dir.join("fractalaw.sock")),
+                apps_dir: apps_dir.unwrap_or_else(|| data_dir.join("apps")),
+                fuel: fuel.unwrap_or(fractalaw_host::TIER_FUEL),
+                poll_interval: std::time::Duration::from_secs(interval),
+                checkpoint_interval: (checkpoint_interval > 0)
+                    .then(|| std::time::Duration::from_secs(checkpoint_interval)),
+                token_budget,
+                app_token_budgets: app_token_budget.map(fractalaw_host::AppTokenBudgets::new),
+                inference_cache,
+                data_dir: data_dir.clone(),
+            };
+            serve::serve(open_duck(&data_dir)?, config).await
+        }
+        Command::Call {
+            app,
+            payload,
+            fuel,
+            socket,
+        } => {
+            let socket = socket.unwrap_or_else(|| data_dir.join("fractalaw.sock"));
+            cmd_call(&socket, &app, payload, fuel).await
+        }
 
         // Sync commands.
         Command::Sync { action } => match action {
@@ -225,29 +848,115 @@ fn cmd_import(data_dir: &std::path::Path) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Where `fractalaw run` takes an app's capabilities from.
+enum Grants {
+    /// The manifest the runtime finds for the component, else nothing.
+    Default,
+    /// The manifest at this path.
+    Manifest(PathBuf),
+    /// Every capability, without limits.
+    Unrestricted,
+}
+
+#[allow(clippy::too_many_arguments)]
 async fn cmd_run(
     data_dir: &std::path::Path,
     component: &std::path::Path,
     fuel: u64,
+    model_dir: Option<&std::path::Path>,
+    classifier: Option<&str>,
+    token_budget: Option<u64>,
+    inference_cache: Option<fractalaw_host::CacheMode>,
+    grants: Grants,
+    apps_dir: &std::path::Path,
+    tier: Option<fractalaw_core::Tier>,
 ) -> anyhow::Result<()> {
+    let manifest = match grants {
+        Grants::Default => None,
+        Grants::Manifest(path) => {
+            let json = std::fs::read_to_string(&path)
+                .with_context(|| format!("reading {}", path.display()))?;
+            let manifest = fractalaw_core::AppManifest::from_json(&json)
+                .map_err(anyhow::Error::msg)
+                .with_context(|| path.display().to_string())?;
+            Some(manifest)
+        }
+        Grants::Unrestricted => {
+            tracing::warn!("--unrestricted: granting every capability");
+            Some(fractalaw_core::AppManifest::unrestricted())
+        }
+    };
+
     let duck = open_duck(data_dir)?;
 
-    let inference = std::env::var("ANTHROPIC_API_KEY").ok().map(|key| {
-        let model = std::env::var("ANTHROPIC_MODEL")
-            .unwrap_or_else(|_| "claude-sonnet-4-5-20250929".into());
-        fractalaw_host::InferenceConfig::new(key, model)
-    });
+    let inference = inference_config(data_dir, inference_cache)?;
+
+    let embeddings = match model_dir {
+        Some(dir) => {
+            let dir = dir
+                .canonicalize()
+                .with_context(|| format!("model directory '{}' not found", dir.display()))?;
+            let embedder = fractalaw_ai::Embedder::load(&dir).context("loading embedding model")?;
+            Some(fractalaw_host::EmbeddingsConfig::new(embedder))
+        }
+        None => None,
+    };
+
+    let classifier = match classifier {
+        Some(version) => {
+            use fractalaw_ai::LawClassifier;
+            let clf = fractalaw_ai::Classifier::load_versioned(&classifier_dir(data_dir), version)?;
+            if let Some(cfg) = &embeddings
+                && clf.dim() != cfg.embedder.dim()
+            {
+                anyhow::bail!(
+                    "classifier {} expects {}-dim embeddings, model produces {}",
+                    clf.version(),
+                    clf.dim(),
+                    cfg.embedder.dim()
+                );
+            }
+            Some(clf)
+        }
+        None => None,
+    };
 
     let opts = fractalaw_host::RunOptions {
+        trigger: None,
+        manifest,
+        tier,
+        invocation: None,
         duck: Some(duck),
         inference,
+        token_budget: fractalaw_host::TokenBudget {
+            per_run: token_budget,
+            app: None,
+        },
+        embeddings,
+        classifier,
     };
-    let result = fractalaw_host::run_component(component, fuel, opts).await?;
+    let result = runtime(data_dir)?
+        .with_apps(
+            apps_dir,
+            run_options(data_dir, token_budget, None, inference_cache),
+        )
+        .run(component, fuel, opts)
+        .await?;
 
     match &result.output {
         Ok(msg) => println!("{msg}"),
         Err(err) => eprintln!("Guest error: {err}"),
     }
+    if let Some(exceeded) = &result.limit_exceeded {
+        eprintln!("Limit exceeded: {exceeded}");
+    }
+
+    if !result.emitted_events.is_empty() {
+        println!(
+            "\nEmitted {} events; deliver with `fractalaw events dispatch`.",
+            result.emitted_events.len()
+        );
+    }
 
     if !result.audit_entries.is_empty() {
         println!(
@@ -262,99 +971,483 @@ async fn cmd_run(
         }
     }
 
-    println!("\nFuel consumed: {}", result.fuel_consumed);
+    println!(
+        "\nTier: {} — fuel consumed: {}",
+        result.tier, result.fuel_consumed
+    );
+    let usage = &result.embedding_usage;
+    if usage.calls > 0 {
+        println!(
+            "Embeddings: {} texts in {} calls ({:.1}ms, {} fuel charged)",
+            usage.texts,
+            usage.calls,
+            usage.elapsed.as_secs_f64() * 1000.0,
+            usage.fuel_charged
+        );
+    }
+    let usage = &result.invoke_usage;
+    if usage.calls > 0 {
+        println!(
+            "Invoked apps: {} calls ({} failed, {} fuel charged)",
+            usage.calls, usage.failed, usage.fuel_charged
+        );
+    }
+    let usage = &result.inference_usage;
+    if usage.calls > 0 || usage.refused > 0 {
+        let budget = match usage.budget {
+            Some(limit) => format!(" of {limit} budget"),
+            None => String::new(),
+        };
+        println!(
+            "Inference: {} tokens{budget} in {} calls ({} from cache, {} refused over budget)",
+            usage.tokens_used, usage.calls, usage.cache_hits, usage.refused
+        );
+        println!(
+            "  prompt tokens: {} ({} from provider cache)",
+            usage.input_tokens, usage.cached_tokens
+        );
+    }
     Ok(())
 }
 
-async fn cmd_sync_pull(data_dir: &std::path::Path, url: &str) -> anyhow::Result<()> {
-    let duck = open_duck(data_dir)?;
-    duck.create_drrp_tables()?;
-
-    // Determine the `since` timestamp from the last sync.
-    let since = duck.get_last_sync_at()?;
-    if let Some(ref ts) = since {
-        eprintln!("Last sync: {ts}");
-    } else {
-        eprintln!("First sync — pulling all annotations");
-    }
-
-    let client = fractalaw_sync::SyncClient::new(url.to_string());
-    let since_dt = since
-        .as_deref()
-        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
-        .map(|dt| dt.with_timezone(&chrono::Utc));
-
-    let annotations = client.pull_annotations(since_dt).await?;
-
-    if annotations.is_empty() {
-        println!("No new annotations.");
+fn cmd_events_list(store: &DuckStore, limit: usize) -> anyhow::Result<()> {
+    let batches = store.recent_events(limit)?;
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    if rows == 0 {
+        println!("No events logged.");
         return Ok(());
     }
-
-    let count = duck.insert_annotations(&annotations)?;
-    println!("Pulled and stored {count} annotations.");
+    println!("--- Recent events (newest first) ---\n");
+    print_batches(&batches)?;
     Ok(())
 }
 
-async fn cmd_sync_push(data_dir: &std::path::Path, url: &str) -> anyhow::Result<()> {
-    let duck = open_duck(data_dir)?;
-    duck.create_drrp_tables()?;
-
-    let entries = duck.get_unpushed_polished()?;
-    if entries.is_empty() {
-        println!("Nothing to push.");
+fn cmd_events_subscriptions(store: &DuckStore) -> anyhow::Result<()> {
+    let subscriptions = store.subscriptions()?;
+    if subscriptions.is_empty() {
+        println!("No subscriptions.");
         return Ok(());
     }
+    for sub in &subscriptions {
+        println!(
+            "  {:<24} {:<24} {}",
+            sub.event_type, sub.subscriber, sub.component
+        );
+    }
+    Ok(())
+}
 
-    let client = fractalaw_sync::SyncClient::new(url.to_string());
-    let accepted = client.push_polished(&entries).await?;
+fn cmd_events_subscribe(
+    store: &DuckStore,
+    event_type: &str,
+    component: &std::path::Path,
+    name: Option<&str>,
+) -> anyhow::Result<()> {
+    let component = component
+        .canonicalize()
+        .with_context(|| format!("component '{}' not found", component.display()))?;
+    let subscriber = match name {
+        Some(name) => name.to_string(),
+        None => fractalaw_host::app_name(&component),
+    };
+    store.subscribe(&subscriber, event_type, &component.to_string_lossy())?;
+    println!(
+        "Subscribed {subscriber} to {event_type} ({}).",
+        component.display()
+    );
+    Ok(())
+}
 
-    // Mark each entry as pushed.
-    for entry in &entries {
-        duck.mark_pushed(&entry.law_name, &entry.provision)?;
-    }
+#[allow(clippy::too_many_arguments)]
+async fn cmd_events_dispatch(
+    store: &DuckStore,
+    data_dir: &std::path::Path,
+    fuel: u64,
+    limit: usize,
+    max_attempts: u32,
+    token_budget: Option<u64>,
+    app_token_budget: Option<u64>,
+    inference_cache: Option<fractalaw_host::CacheMode>,
+) -> anyhow::Result<()> {
+    let policy = fractalaw_host::DeliveryPolicy {
+        max_attempts,
+        fuel,
+        ..fractalaw_host::DeliveryPolicy::default()
+    };
+    let options = run_options(
+        data_dir,
+        token_budget,
+        app_token_budget.map(fractalaw_host::AppTokenBudgets::new),
+        inference_cache,
+    );
+    let runtime = runtime(data_dir)?.with_apps(data_dir.join("apps"), options.clone());
+    let report = fractalaw_host::dispatch_events(&runtime, store, &policy, limit, |delivery| {
+        options(&delivery.subscriber)
+    })
+    .await?;
 
     println!(
-        "Pushed {} polished entries ({} accepted by server).",
-        entries.len(),
-        accepted
+        "Delivered {}, {} to retry, {} dead-lettered.",
+        report.delivered, report.retrying, report.dead_lettered
     );
+    if report.dead_lettered > 0 {
+        println!("Inspect with `fractalaw events dead-letters`.");
+    }
     Ok(())
 }
 
-async fn cmd_query(store: &DuckStore, sql: &str) -> anyhow::Result<()> {
-    let fusion = FusionStore::new(store)?;
-    let batches = fusion.query(sql).await?;
-    if batches.is_empty() || batches.iter().all(|b| b.num_rows() == 0) {
-        println!("No results.");
+fn cmd_events_dead_letters(store: &DuckStore, limit: usize) -> anyhow::Result<()> {
+    let batches = store.dead_letters(limit)?;
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    if rows == 0 {
+        println!("No dead-lettered deliveries.");
         return Ok(());
     }
+    println!("--- Dead-lettered deliveries ---\n");
     print_batches(&batches)?;
+    println!("\nRequeue with `fractalaw events retry <event-id> <subscriber>`.");
     Ok(())
 }
 
-fn cmd_law(store: &DuckStore, name: &str) -> anyhow::Result<()> {
-    let batch = store.get_legislation(name).map_err(|e| match e {
-        StoreError::NoResults => anyhow::anyhow!("legislation '{}' not found", name),
-        other => anyhow::anyhow!(other),
-    })?;
-
-    display::print_law_card(&batch)?;
-
-    let edges = store.edges_for_law(name)?;
-    let total_edges: usize = edges.iter().map(|b| b.num_rows()).sum();
-    if total_edges > 0 {
-        println!("--- Relationships ({total_edges} edges) ---\n");
-        print_batches(&edges)?;
+fn cmd_schedule_list(store: &DuckStore, all: bool) -> anyhow::Result<()> {
+    let batches = store.scheduled_tasks(all)?;
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    if rows == 0 {
+        println!("No scheduled tasks.");
+        return Ok(());
     }
-
+    print_batches(&batches)?;
     Ok(())
 }
 
-fn cmd_graph(store: &DuckStore, name: &str, hops: u32) -> anyhow::Result<()> {
-    let batches = store.laws_within_hops(name, hops)?;
-    let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
-    if total_rows == 0 {
+fn cmd_schedule_add(
+    store: &DuckStore,
+    name: String,
+    target_app: String,
+    cron: Option<String>,
+    at: Option<&str>,
+    payload: String,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        fractalaw_host::valid_app_name(&target_app),
+        "invalid app name '{target_app}'"
+    );
+    let run_at = match at {
+        Some(at) => {
+            let t = chrono::DateTime::parse_from_rfc3339(at)
+                .with_context(|| format!("invalid time '{at}'"))?;
+            Some(u64::try_from(t.timestamp()).context("time is before 1970")?)
+        }
+        None => None,
+    };
+    let task = fractalaw_core::ScheduledTask {
+        id: 0,
+        name,
+        cron,
+        run_at,
+        target_app,
+        payload: payload.into_bytes(),
+        owner_app: "cli".into(),
+    };
+    let next_run_at = task
+        .first_run(chrono::Utc::now())
+        .map_err(anyhow::Error::msg)?;
+    let id = store.insert_scheduled_task(&task, next_run_at)?;
+    let next = chrono::DateTime::from_timestamp(next_run_at, 0).context("run time out of range")?;
+    println!(
+        "Scheduled task {id} ({}) to run {}; first run {}.",
+        task.name,
+        task.target_app,
+        next.to_rfc3339()
+    );
+    println!("Tasks run while `fractalaw schedule run` is running.");
+    Ok(())
+}
+
+/// Run due tasks every `interval` seconds, or once if `interval` is `None`.
+#[allow(clippy::too_many_arguments)]
+async fn cmd_schedule_run(
+    store: &DuckStore,
+    data_dir: &std::path::Path,
+    apps_dir: &std::path::Path,
+    fuel: u64,
+    interval: Option<u64>,
+    token_budget: Option<u64>,
+    app_token_budget: Option<u64>,
+    inference_cache: Option<fractalaw_host::CacheMode>,
+) -> anyhow::Result<()> {
+    let options = run_options(
+        data_dir,
+        token_budget,
+        app_token_budget.map(fractalaw_host::AppTokenBudgets::new),
+        inference_cache,
+    );
+    let runtime = runtime(data_dir)?.with_apps(apps_dir, options.clone());
+    let clock = fractalaw_core::SystemClock;
+    if let Some(interval) = interval {
+        eprintln!(
+            "Scheduler running (apps in {}, checking every {interval}s).",
+            apps_dir.display()
+        );
+    }
+    loop {
+        let report =
+            fractalaw_host::run_due_tasks(&runtime, store, &clock, apps_dir, fuel, 1_000, |task| {
+                options(&task.target_app)
+            })
+            .await?;
+        if report.ran + report.failed > 0 || interval.is_none() {
+            println!(
+                "{}: {} runs succeeded, {} failed.",
+                chrono::Utc::now().format("%Y-%m-%d %H:%M:%S"),
+                report.ran,
+                report.failed
+            );
+        }
+        let Some(interval) = interval else {
+            return Ok(());
+        };
+        tokio::time::sleep(std::time::Duration::from_secs(interval)).await;
+    }
+}
+
+fn cmd_audit_verify(
+    store: &DuckStore,
+    key: &fractalaw_core::NodeKey,
+    public_keys: &[String],
+    expected: &[String],
+    max_unanchored: u64,
+) -> anyhow::Result<()> {
+    let mut verifier = fractalaw_core::ChainVerifier::new().trust(key.public_key());
+    for public_key in public_keys {
+        let public_key =
+            fractalaw_core::node::parse_public_key(public_key).map_err(anyhow::Error::msg)?;
+        verifier = verifier.trust(public_key);
+    }
+    for checkpoint in expected {
+        let (entry_id, hash) = fractalaw_core::audit::parse_expected_checkpoint(checkpoint)
+            .map_err(anyhow::Error::msg)?;
+        verifier = verifier.expect(entry_id, hash);
+    }
+    for entry in store.audit_log()? {
+        if let Err(e) = verifier.check(&entry) {
+            anyhow::bail!(
+                "audit log tampered at {e} ({} entries verified before it)",
+                verifier.verified()
+            );
+        }
+    }
+    if let Err(e) = verifier.finish() {
+        anyhow::bail!("audit log cut short: {e}");
+    }
+    println!(
+        "Audit log intact: {} entries, head {}",
+        verifier.verified(),
+        fractalaw_core::node::hex(&verifier.head())
+    );
+    // The chain alone can be rewritten wholesale; only a trusted checkpoint
+    // anchors it.
+    let unanchored = verifier.verified() - verifier.last_checkpoint().unwrap_or(0);
+    match verifier.last_checkpoint() {
+        Some(last) => println!(
+            "Signed checkpoints: {} (last at entry {last}; {unanchored} entries since)",
+            verifier.checkpoints()
+        ),
+        None if verifier.verified() > 0 => anyhow::bail!(
+            "audit log has no trusted checkpoint; the chain is not anchored by a node signature"
+        ),
+        None => {}
+    }
+    if unanchored > max_unanchored {
+        anyhow::bail!(
+            "{unanchored} entries follow the last trusted checkpoint (at most {max_unanchored} \
+             allowed); checkpoints may have been removed"
+        );
+    }
+    Ok(())
+}
+
+fn cmd_audit_checkpoint(store: &DuckStore, key: &fractalaw_core::NodeKey) -> anyhow::Result<()> {
+    match store.append_checkpoint(key)? {
+        Some(checkpoint) => {
+            println!(
+                "Checkpoint {} signed by {} over head {}",
+                checkpoint.entry_id,
+                key.node_id(),
+                fractalaw_core::node::hex(&checkpoint.prev_hash)
+            );
+            println!(
+                "Pin it with: audit verify --expect-checkpoint {}:{}",
+                checkpoint.entry_id,
+                fractalaw_core::node::hex(&checkpoint.hash())
+            );
+        }
+        None => println!("Nothing to checkpoint: the audit log is empty or already checkpointed"),
+    }
+    Ok(())
+}
+
+async fn cmd_app_precompile(
+    data_dir: &std::path::Path,
+    mut components: Vec<PathBuf>,
+    apps_dir: &std::path::Path,
+    prune: bool,
+) -> anyhow::Result<()> {
+    if components.is_empty() {
+        for entry in std::fs::read_dir(apps_dir)
+            .with_context(|| format!("apps directory '{}' not found", apps_dir.display()))?
+        {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "wasm") {
+                components.push(path);
+            }
+        }
+        components.sort();
+    }
+    anyhow::ensure!(!components.is_empty(), "no components to precompile");
+
+    let cache = fractalaw_host::AotCache::open(&aot_cache_dir(data_dir))?;
+    let engine = fractalaw_host::create_engine()?;
+    let mut keys = std::collections::HashSet::new();
+    for path in &components {
+        let start = std::time::Instant::now();
+        let (_, compiled) = cache
+            .load(&engine, path)
+            .await
+            .with_context(|| format!("compiling {}", path.display()))?;
+        let status = if compiled { "compiled" } else { "cached" };
+        println!(
+            "  {:<24} {status} ({:.0}ms)",
+            fractalaw_host::app_name(path),
+            start.elapsed().as_secs_f64() * 1000.0
+        );
+        keys.insert(fractalaw_host::AotCache::key(
+            &engine,
+            &std::fs::read(path)?,
+        ));
+    }
+    if prune {
+        let removed = cache.prune(&keys)?;
+        println!("Pruned {removed} stale artifacts.");
+    }
+    println!("Artifacts in {}.", cache.dir().display());
+    Ok(())
+}
+
+async fn cmd_call(
+    socket: &std::path::Path,
+    app: &str,
+    payload: Option<String>,
+    fuel: Option<u64>,
+) -> anyhow::Result<()> {
+    let request = serde_json::json!({ "op": "run", "app": app, "payload": payload, "fuel": fuel });
+    let reply = serve::call(socket, &request).await?;
+    if reply["ok"] != true {
+        anyhow::bail!("{}", reply["error"].as_str().unwrap_or("daemon error"));
+    }
+    if let Some(output) = reply["output"].as_str() {
+        println!("{output}");
+    }
+    if let Some(error) = reply["guest_error"].as_str() {
+        eprintln!("Guest error: {error}");
+    }
+    println!(
+        "\nTier: {} — fuel consumed: {}",
+        reply["tier"].as_str().unwrap_or("unknown"),
+        reply["fuel_consumed"]
+    );
+    Ok(())
+}
+
+async fn cmd_sync_pull(data_dir: &std::path::Path, url: &str) -> anyhow::Result<()> {
+    let duck = open_duck(data_dir)?;
+    duck.create_drrp_tables()?;
+
+    // Determine the `since` timestamp from the last sync.
+    let since = duck.get_last_sync_at()?;
+    if let Some(ref ts) = since {
+        eprintln!("Last sync: {ts}");
+    } else {
+        eprintln!("First sync — pulling all annotations");
+    }
+
+    let client = fractalaw_sync::SyncClient::new(url.to_string());
+    let since_dt = since
+        .as_deref()
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc));
+
+    let annotations = client.pull_annotations(since_dt).await?;
+
+    if annotations.is_empty() {
+        println!("No new annotations.");
+        return Ok(());
+    }
+
+    let count = duck.insert_annotations(&annotations)?;
+    println!("Pulled and stored {count} annotations.");
+    Ok(())
+}
+
+async fn cmd_sync_push(data_dir: &std::path::Path, url: &str) -> anyhow::Result<()> {
+    let duck = open_duck(data_dir)?;
+    duck.create_drrp_tables()?;
+
+    let entries = duck.get_unpushed_polished()?;
+    if entries.is_empty() {
+        println!("Nothing to push.");
+        return Ok(());
+    }
+
+    let client = fractalaw_sync::SyncClient::new(url.to_string());
+    let accepted = client.push_polished(&entries).await?;
+
+    // Mark each entry as pushed.
+    for entry in &entries {
+        duck.mark_pushed(&entry.law_name, &entry.provision)?;
+    }
+
+    println!(
+        "Pushed {} polished entries ({} accepted by server).",
+        entries.len(),
+        accepted
+    );
+    Ok(())
+}
+
+async fn cmd_query(store: &DuckStore, sql: &str) -> anyhow::Result<()> {
+    let fusion = FusionStore::new(store)?;
+    let batches = fusion.query(sql).await?;
+    if batches.is_empty() || batches.iter().all(|b| b.num_rows() == 0) {
+        println!("No results.");
+        return Ok(());
+    }
+    print_batches(&batches)?;
+    Ok(())
+}
+
+fn cmd_law(store: &DuckStore, name: &str) -> anyhow::Result<()> {
+    let batch = store.get_legislation(name).map_err(|e| match e {
+        StoreError::NoResults => anyhow::anyhow!("legislation '{}' not found", name),
+        other => anyhow::anyhow!(other),
+    })?;
+
+    display::print_law_card(&batch)?;
+
+    let edges = store.edges_for_law(name)?;
+    let total_edges: usize = edges.iter().map(|b| b.num_rows()).sum();
+    if total_edges > 0 {
+        println!("--- Relationships ({total_edges} edges) ---\n");
+        print_batches(&edges)?;
+    }
+
+    Ok(())
+}
+
+fn cmd_graph(store: &DuckStore, name: &str, hops: u32) -> anyhow::Result<()> {
+    let batches = store.laws_within_hops(name, hops)?;
+    let total_rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    if total_rows == 0 {
         println!("No laws found within {hops} hops of '{name}'.");
         return Ok(());
     }
@@ -395,6 +1488,54 @@ async fn cmd_embed(data_dir: &std::path::Path, model_dir: &std::path::Path) -> a
     Ok(())
 }
 
+async fn cmd_migrate_embeddings(
+    data_dir: &std::path::Path,
+    model_dir: &std::path::Path,
+    stage_only: bool,
+    swap_staged: bool,
+) -> anyhow::Result<()> {
+    let model_dir = model_dir
+        .canonicalize()
+        .with_context(|| format!("model directory '{}' not found", model_dir.display()))?;
+
+    println!("=== Embedding Migration ===\n");
+
+    let mut embedder =
+        fractalaw_ai::Embedder::load(&model_dir).context("loading embedding model")?;
+    let target = embedder.model_name().to_string();
+
+    let lance = LanceStore::open(&data_dir.join("lancedb"))
+        .await
+        .context("opening LanceDB")?;
+    let current = lance.embedding_models().await?;
+    println!("  From: {}", current.join(", "));
+    println!("  To:   {target} ({}D)", embedder.dim());
+
+    if !swap_staged {
+        if current == [target.as_str()] {
+            println!("\nlegislation_text is already embedded with {target}.");
+            return Ok(());
+        }
+        let stats = embed::stage_migration(&lance, &mut embedder).await?;
+        println!(
+            "  Staged {} rows in {:.1}s",
+            fmt_num(stats.total_rows),
+            stats.elapsed_secs
+        );
+        if stage_only {
+            println!("\nStaged vectors validated. Swap them in with `--swap-staged`.");
+            return Ok(());
+        }
+    }
+
+    let rows = lance.swap_shadow_embeddings(&target).await?;
+    println!("  Swapped {} rows to {target}", fmt_num(rows));
+    println!(
+        "\nSaved classifiers trained on the old model will be refused; re-run `fractalaw classify`."
+    );
+    Ok(())
+}
+
 async fn cmd_text(data_dir: &std::path::Path, name: &str, limit: usize) -> anyhow::Result<()> {
     let lance = LanceStore::open(&data_dir.join("lancedb"))
         .await
@@ -436,7 +1577,9 @@ async fn cmd_search(
         .context("opening LanceDB")?;
 
     let query_vec = embedder.embed(query).context("embedding query")?;
-    let batches = lance.search_text(&query_vec, limit).await?;
+    let batches = lance
+        .search_text(&query_vec, embedder.model_name(), limit)
+        .await?;
 
     let total: usize = batches.iter().map(|b| b.num_rows()).sum();
     if total == 0 {
@@ -452,6 +1595,114 @@ async fn cmd_search(
     Ok(())
 }
 
+async fn cmd_ask(
+    data_dir: &std::path::Path,
+    question: &str,
+    limit: usize,
+    model_dir: &std::path::Path,
+    max_tokens: u32,
+    inference_cache: Option<fractalaw_host::CacheMode>,
+) -> anyhow::Result<()> {
+    use fractalaw_host::fractal::app::ai_inference::GenerateRequest;
+
+    let inference = inference_config(data_dir, inference_cache)?.context(
+        "no inference backend configured (set ANTHROPIC_API_KEY, or \
+         FRACTALAW_INFERENCE_PROVIDER=openai|local for an offline backend)",
+    )?;
+
+    let model_dir = model_dir
+        .canonicalize()
+        .with_context(|| format!("model directory '{}' not found", model_dir.display()))?;
+    let mut embedder =
+        fractalaw_ai::Embedder::load(&model_dir).context("loading embedding model")?;
+
+    let lance = LanceStore::open(&data_dir.join("lancedb"))
+        .await
+        .context("opening LanceDB")?;
+
+    let query_vec = embedder.embed(question).context("embedding question")?;
+    let batches = lance
+        .search_text(&query_vec, embedder.model_name(), limit)
+        .await?;
+    let passages = fractalaw_ai::passages_from_batches(&batches)?;
+    if passages.is_empty() {
+        println!("No matching sections; nothing to answer from.");
+        return Ok(());
+    }
+
+    let response = inference
+        .generate(GenerateRequest {
+            system_prompt: Some(fractalaw_ai::rag::SYSTEM_PROMPT.to_string()),
+            user_prompt: fractalaw_ai::build_prompt(question, &passages),
+            max_tokens,
+            temperature: 0.0,
+            json_schema: None,
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("inference failed (code {}): {}", e.code, e.message))?;
+
+    let citations = fractalaw_ai::check_citations(&response.text, passages.len());
+
+    // Re-read each cited section so the sources shown are the stored text,
+    // not just what retrieval returned.
+    let cited: Vec<&fractalaw_ai::Passage> =
+        citations.cited.iter().map(|&i| &passages[i]).collect();
+    let filter = cited
+        .iter()
+        .map(|p| format!("'{}'", p.section_id.replace('\'', "''")))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let stored = if cited.is_empty() {
+        Vec::new()
+    } else {
+        let batches = lance
+            .query_legislation_text(&format!("section_id IN ({filter})"), cited.len())
+            .await?;
+        fractalaw_ai::passages_from_batches(&batches)?
+    };
+
+    println!("{}\n", response.text.trim());
+    println!("Sources:");
+    let mut unverified = 0;
+    for &i in &citations.cited {
+        let passage = &passages[i];
+        let verified = stored
+            .iter()
+            .any(|s| s.section_id == passage.section_id && s.text == passage.text);
+        if !verified {
+            unverified += 1;
+        }
+        println!(
+            "  [{}] {} ({}){}",
+            i + 1,
+            passage.reference(),
+            passage.section_id,
+            if verified {
+                ""
+            } else {
+                "  NOT FOUND in legislation_text"
+            }
+        );
+    }
+
+    if !citations.invalid.is_empty() {
+        let numbers: Vec<String> = citations.invalid.iter().map(|n| format!("[{n}]")).collect();
+        anyhow::bail!(
+            "answer cites sources that were not retrieved: {}",
+            numbers.join(" ")
+        );
+    }
+    if citations.cited.is_empty() {
+        anyhow::bail!("answer cites no sources");
+    }
+    if unverified > 0 {
+        anyhow::bail!(
+            "{unverified} cited section(s) could not be verified against legislation_text"
+        );
+    }
+    Ok(())
+}
+
 async fn cmd_validate(
     store: &DuckStore,
     data_dir: &std::path::Path,
@@ -613,7 +1864,9 @@ async fn cmd_validate(
         fractalaw_ai::Embedder::load(&model_dir).context("loading embedding model")?;
     let query_text = "chemical exposure limits";
     let query_vec = embedder.embed(query_text)?;
-    let results = lance.search_text(&query_vec, 5).await?;
+    let results = lance
+        .search_text(&query_vec, embedder.model_name(), 5)
+        .await?;
 
     let mut found = false;
     let mut top_law = String::new();
@@ -846,69 +2099,66 @@ async fn cmd_classify(
     data_dir: &std::path::Path,
     domain_threshold: f32,
     subject_threshold: f32,
+    method: fractalaw_ai::Method,
+    model: Option<&str>,
 ) -> anyhow::Result<()> {
-    use fractalaw_ai::{ClassificationStatus, Classifier, LabelSet, aggregate_law_embeddings};
-    use futures::TryStreamExt;
-    use lancedb::query::{ExecutableQuery, QueryBase, Select};
+    use fractalaw_ai::{ClassificationStatus, Classifier, LawClassifier, Method};
 
     println!("=== Classification Pipeline ===\n");
 
-    // Step 1: Load labels from legislation table.
-    println!("Loading label sets...");
-    let label_batches =
-        store.query_arrow("SELECT name, domain, family, sub_family, subjects FROM legislation")?;
-    let labels = LabelSet::from_legislation_batches(&label_batches)?;
-    let lsummary = labels.summary();
-    println!(
-        "  {} laws with family labels, {} with domain, {} with subjects",
-        fmt_num(lsummary.with_family),
-        fmt_num(lsummary.with_domain),
-        fmt_num(lsummary.with_subjects),
-    );
-
-    // Step 2: Load embeddings from LanceDB (only law_name + embedding columns).
-    println!("Loading embeddings from LanceDB...");
-    let lance = LanceStore::open(&data_dir.join("lancedb"))
-        .await
-        .context("opening LanceDB")?;
-    let table = lance.legislation_text().await?;
-    let query = table.query().select(Select::Columns(vec![
-        "law_name".to_string(),
-        "embedding".to_string(),
-    ]));
-    let stream = query
-        .execute()
-        .await
-        .map_err(|e| anyhow::anyhow!("lance query: {e}"))?;
-    let emb_batches: Vec<RecordBatch> = stream
-        .try_collect()
-        .await
-        .map_err(|e| anyhow::anyhow!("lance collect: {e}"))?;
-
-    let total_sections: usize = emb_batches.iter().map(|b: &RecordBatch| b.num_rows()).sum();
-
-    // Step 3: Aggregate to law-level embeddings.
-    println!("Aggregating section embeddings → law-level...");
-    let law_embeddings = aggregate_law_embeddings(&emb_batches)?;
-    println!(
-        "  {} laws with embeddings (from {} sections)",
-        fmt_num(law_embeddings.len()),
-        fmt_num(total_sections),
-    );
-    drop(emb_batches); // Free memory.
-
-    if law_embeddings.is_empty() {
-        println!("\nNo laws with embeddings found. Run `fractalaw embed` first.");
+    // Steps 1–3: Labels, embeddings, and law-level aggregation.
+    let Some(TrainingData {
+        labels,
+        law_embeddings,
+        embedding_model,
+    }) = load_training_data(store, data_dir).await?
+    else {
         return Ok(());
-    }
+    };
 
-    // Step 4: Build classifier (compute centroids).
-    println!("Computing centroids...");
-    let classifier = Classifier::build(&law_embeddings, &labels);
-    let csummary = classifier.summary(law_embeddings.len());
-    println!("  domain:   {} centroids", csummary.domain_count);
-    println!("  family:   {} centroids", csummary.family_count);
-    println!("  subjects: {} centroids", csummary.subject_count);
+    // Step 4: Load a saved centroid classifier, or train one of the chosen method.
+    // Centroid models are saved so later runs (and `run --classifier`) can reuse them.
+    let model_dir = classifier_dir(data_dir);
+    let classifier: Box<dyn LawClassifier> = match (method, model) {
+        (Method::Centroid, Some(version)) => {
+            println!("Loading classifier {version}...");
+            let classifier = Classifier::load_versioned(&model_dir, version)?;
+            if classifier.embedding_model() != embedding_model {
+                anyhow::bail!(
+                    "classifier {} was trained on '{}' embeddings, but LanceDB holds '{}'",
+                    classifier.version(),
+                    classifier.embedding_model(),
+                    embedding_model
+                );
+            }
+            let actual_dim = law_embeddings.values().next().map_or(0, |v| v.len());
+            if classifier.dim() != actual_dim {
+                anyhow::bail!(
+                    "classifier {} expects {}-dim embeddings, LanceDB holds {actual_dim}-dim",
+                    classifier.version(),
+                    classifier.dim()
+                );
+            }
+            print_centroid_summary(&classifier);
+            Box::new(classifier)
+        }
+        (Method::Centroid, None) => {
+            println!("Computing centroids...");
+            let classifier =
+                Classifier::build(&law_embeddings, &labels).with_embedding_model(&embedding_model);
+            let path = classifier.save_versioned(&model_dir)?;
+            println!("  saved {}", path.display());
+            print_centroid_summary(&classifier);
+            Box::new(classifier)
+        }
+        (_, Some(_)) => anyhow::bail!("--model can only be used with --method centroid"),
+        (method, None) => {
+            println!("Training {method} classifier...");
+            let classifier = method.build(&law_embeddings, &labels, &embedding_model);
+            println!("  model:    {}", classifier.version());
+            classifier
+        }
+    };
 
     // Step 5: Classify all laws with embeddings.
     println!(
@@ -950,9 +2200,25 @@ async fn cmd_classify(
     println!("  with subjects: {}", fmt_num(with_subjects));
     println!("  mean family confidence: {mean_conf:.3}");
 
+    let with_sub_family = results.iter().filter(|c| c.sub_family.is_some()).count();
+    let sub_confirmed = results
+        .iter()
+        .filter(|c| c.sub_family_status == ClassificationStatus::Confirmed)
+        .count();
+    let sub_conflicts = results
+        .iter()
+        .filter(|c| c.sub_family_status == ClassificationStatus::Conflict)
+        .count();
+    println!(
+        "  sub_family: {} predicted, {} confirmed, {} conflict",
+        fmt_num(with_sub_family),
+        fmt_num(sub_confirmed),
+        fmt_num(sub_conflicts),
+    );
+
     // Step 6: Write to DuckDB.
     println!("\nWriting to DuckDB...");
-    write_classifications(store, &results)?;
+    write_classifications(store, &results, &classifier.version())?;
     println!("  {} rows updated", fmt_num(results.len()));
 
     // Print conflict report if any.
@@ -973,11 +2239,444 @@ async fn cmd_classify(
     Ok(())
 }
 
+/// Classify every embedded section by DRRP type and subject.
+async fn cmd_classify_sections(
+    store: &DuckStore,
+    data_dir: &std::path::Path,
+    drrp_threshold: f32,
+    subject_threshold: f32,
+    queue_candidates: bool,
+) -> anyhow::Result<()> {
+    use fractalaw_ai::{DRRP_TYPES, LabelSet, SectionClassifier, SectionLabels};
+    use futures::TryStreamExt;
+    use lancedb::query::{ExecutableQuery, QueryBase, Select};
+
+    println!("=== Section Classification ===\n");
+
+    // Step 1: DRRP labels per provision, and subject labels per law.
+    println!("Loading labels...");
+    let section_labels = SectionLabels::from_batches(&store.section_drrp_labels()?)?;
+    let label_batches = store.query_arrow("SELECT name, subjects FROM legislation")?;
+    let labels = LabelSet::from_legislation_batches(&label_batches)?;
+    println!(
+        "  {} provisions with DRRP labels, {} laws with subjects",
+        fmt_num(section_labels.len()),
+        fmt_num(labels.law_subjects.len()),
+    );
+
+    // Step 2: Section embeddings from LanceDB.
+    println!("Loading section embeddings from LanceDB...");
+    let lance = LanceStore::open(&data_dir.join("lancedb"))
+        .await
+        .context("opening LanceDB")?;
+    let table = lance.legislation_text().await?;
+    let query = table.query().select(Select::Columns(vec![
+        "section_id".to_string(),
+        "law_name".to_string(),
+        "provision".to_string(),
+        "text".to_string(),
+        "embedding".to_string(),
+        "embedding_model".to_string(),
+    ]));
+    let stream = query
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("lance query: {e}"))?;
+    let batches: Vec<RecordBatch> = stream
+        .try_collect()
+        .await
+        .map_err(|e| anyhow::anyhow!("lance collect: {e}"))?;
+    let embedding_model = fractalaw_ai::single_embedding_model(&batches)?.unwrap_or_default();
+    let sections = fractalaw_ai::sections_from_batches(&batches)?;
+    drop(batches); // Free memory.
+    println!("  {} sections with embeddings", fmt_num(sections.len()));
+
+    if sections.is_empty() {
+        println!("\nNo sections with embeddings found. Run `fractalaw embed` first.");
+        return Ok(());
+    }
+
+    // Step 3: Compute centroids.
+    println!("Computing section centroids...");
+    let classifier = SectionClassifier::build(&sections, &section_labels, &labels.law_subjects)
+        .with_embedding_model(&embedding_model);
+    let summary = classifier.summary();
+    println!("  model:    {}", classifier.version());
+    println!(
+        "  drrp:     {} centroids from {} labelled sections ({} background)",
+        summary.drrp_count,
+        fmt_num(summary.drrp_sections),
+        fmt_num(summary.background_sections),
+    );
+    println!("  subjects: {} centroids", summary.subject_count);
+    if summary.drrp_count == 0 {
+        println!("  (no DRRP labels — run `fractalaw sync pull` to fetch sertantai annotations)");
+    }
+
+    // Step 4: Classify every section.
+    println!(
+        "Classifying {} sections (drrp_threshold={drrp_threshold}, subject_threshold={subject_threshold})...",
+        fmt_num(sections.len())
+    );
+    let results = classifier.classify_batch(&sections, drrp_threshold, subject_threshold);
+    for drrp_type in DRRP_TYPES {
+        let tagged = results
+            .iter()
+            .filter(|r| r.drrp.iter().any(|(t, _)| t == drrp_type))
+            .count();
+        println!("  {drrp_type:<15} {}", fmt_num(tagged));
+    }
+    let with_subjects = results.iter().filter(|r| !r.subjects.is_empty()).count();
+    println!("  with subjects:  {}", fmt_num(with_subjects));
+
+    // Step 5: Write to DuckDB.
+    println!("\nWriting to DuckDB...");
+    let rows = write_section_classifications(store, &results, &classifier.version())?;
+    println!(
+        "  {} rows written to section_classifications",
+        fmt_num(rows)
+    );
+
+    // Step 6: Optionally hand new candidates to the drrp-polisher.
+    if queue_candidates {
+        let scraped_at = chrono::Utc::now().to_rfc3339();
+        let candidates =
+            fractalaw_ai::drrp_candidates(&sections, &results, &section_labels, &scraped_at);
+        let queued = store.insert_drrp_candidates(&candidates)?;
+        println!(
+            "  {} new DRRP candidates queued in drrp_candidates",
+            fmt_num(queued)
+        );
+    }
+
+    println!("\n=== Done ===");
+    Ok(())
+}
+
+fn cmd_review_list(store: &DuckStore, max_confidence: f32, limit: usize) -> anyhow::Result<()> {
+    let batches = store.review_queue(max_confidence, limit)?;
+    let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+    if rows == 0 {
+        println!("Review queue is empty.");
+        return Ok(());
+    }
+    println!("--- Review queue (most uncertain first) ---\n");
+    print_batches(&batches)?;
+    println!("\nResolve with `fractalaw review accept <name>` or `fractalaw review keep <name>`.");
+    Ok(())
+}
+
+fn cmd_review_decide(
+    store: &DuckStore,
+    name: &str,
+    decision: fractalaw_core::ReviewDecision,
+    reviewer: &str,
+) -> anyhow::Result<()> {
+    store.record_review(name, decision, reviewer)?;
+    println!("Recorded {} for {name} by {reviewer}.", decision.as_str());
+    if decision == fractalaw_core::ReviewDecision::AcceptAi {
+        println!("The AI family will be used as ground truth in the next `fractalaw classify`.");
+    }
+    Ok(())
+}
+
+/// Print version, provenance, and centroid counts for a centroid classifier.
+fn print_centroid_summary(classifier: &fractalaw_ai::Classifier) {
+    use fractalaw_ai::LawClassifier;
+
+    let csummary = classifier.summary(classifier.training_laws().len());
+    println!("  model:    {}", classifier.version());
+    println!(
+        "  trained on {} laws (sha256 {})",
+        fmt_num(csummary.laws_used),
+        &classifier.training_hash()[..12]
+    );
+    println!("  domain:   {} centroids", csummary.domain_count);
+    println!("  family:   {} centroids", csummary.family_count);
+    println!("  subjects: {} centroids", csummary.subject_count);
+}
+
+/// Ground-truth labels plus law-level embeddings, as used to train classifiers.
+struct TrainingData {
+    labels: fractalaw_ai::LabelSet,
+    law_embeddings: std::collections::HashMap<String, Vec<f32>>,
+    embedding_model: String,
+}
+
+/// Load labels from DuckDB and section embeddings from LanceDB, aggregated per law.
+///
+/// Returns `None` (after printing a hint) when no embeddings exist yet.
+async fn load_training_data(
+    store: &DuckStore,
+    data_dir: &std::path::Path,
+) -> anyhow::Result<Option<TrainingData>> {
+    use fractalaw_ai::{LabelSet, aggregate_law_embeddings};
+    use futures::TryStreamExt;
+    use lancedb::query::{ExecutableQuery, QueryBase, Select};
+
+    // Step 1: Load labels from legislation table.
+    println!("Loading label sets...");
+    let label_batches =
+        store.query_arrow("SELECT name, domain, family, sub_family, subjects FROM legislation")?;
+    let mut labels = LabelSet::from_legislation_batches(&label_batches)?;
+    let lsummary = labels.summary();
+    println!(
+        "  {} laws with family labels, {} with domain, {} with subjects",
+        fmt_num(lsummary.with_family),
+        fmt_num(lsummary.with_domain),
+        fmt_num(lsummary.with_subjects),
+    );
+    let overridden = labels.apply_family_overrides(store.accepted_family_overrides()?);
+    if overridden > 0 {
+        println!(
+            "  {} family labels replaced by accepted reviews",
+            fmt_num(overridden)
+        );
+    }
+
+    // Step 2: Load embeddings from LanceDB (law_name, embedding, embedding_model).
+    println!("Loading embeddings from LanceDB...");
+    let lance = LanceStore::open(&data_dir.join("lancedb"))
+        .await
+        .context("opening LanceDB")?;
+    let table = lance.legislation_text().await?;
+    let query = table.query().select(Select::Columns(vec![
+        "law_name".to_string(),
+        "embedding".to_string(),
+        "embedding_model".to_string(),
+    ]));
+    let stream = query
+        .execute()
+        .await
+        .map_err(|e| anyhow::anyhow!("lance query: {e}"))?;
+    let emb_batches: Vec<RecordBatch> = stream
+        .try_collect()
+        .await
+        .map_err(|e| anyhow::anyhow!("lance collect: {e}"))?;
+
+    let total_sections: usize = emb_batches.iter().map(|b: &RecordBatch| b.num_rows()).sum();
+    let embedding_model = fractalaw_ai::single_embedding_model(&emb_batches)?.unwrap_or_default();
+
+    // Step 3: Aggregate to law-level embeddings.
+    println!("Aggregating section embeddings → law-level...");
+    let law_embeddings = aggregate_law_embeddings(&emb_batches)?;
+    println!(
+        "  {} laws with embeddings (from {} sections)",
+        fmt_num(law_embeddings.len()),
+        fmt_num(total_sections),
+    );
+    drop(emb_batches); // Free memory.
+
+    if law_embeddings.is_empty() {
+        println!("\nNo laws with embeddings found. Run `fractalaw embed` first.");
+        return Ok(None);
+    }
+
+    Ok(Some(TrainingData {
+        labels,
+        law_embeddings,
+        embedding_model,
+    }))
+}
+
+/// Evaluate family classification on held-out laws and record the results.
+async fn cmd_evaluate(
+    store: &DuckStore,
+    data_dir: &std::path::Path,
+    folds: usize,
+    holdout: Option<f32>,
+    method: fractalaw_ai::Method,
+) -> anyhow::Result<()> {
+    use fractalaw_ai::{LawClassifier, Method, Split, evaluate};
+
+    println!("=== Classifier Evaluation ===\n");
+
+    let Some(TrainingData {
+        labels,
+        law_embeddings,
+        embedding_model,
+    }) = load_training_data(store, data_dir).await?
+    else {
+        return Ok(());
+    };
+
+    let split = match holdout {
+        Some(frac) if frac <= 0.0 || frac >= 1.0 => {
+            anyhow::bail!("--holdout must be between 0 and 1 (got {frac})")
+        }
+        Some(frac) => Split::Holdout(frac),
+        None if folds < 2 => anyhow::bail!("--folds must be at least 2 (got {folds})"),
+        None => Split::KFold(folds),
+    };
+
+    println!("Evaluating {method} ({})...", split.describe());
+    let report = evaluate(&law_embeddings, &labels, split, method);
+    if report.predictions.is_empty() {
+        println!("\nNo labelled laws with embeddings to evaluate.");
+        return Ok(());
+    }
+
+    println!("  held-out laws:  {}", fmt_num(report.predictions.len()));
+    println!("  accuracy:       {:.3}", report.accuracy);
+    println!("  macro F1:       {:.3}", report.macro_f1);
+    println!(
+        "  calibration (ECE): {:.3}",
+        report.expected_calibration_error
+    );
+
+    // The model `classify` would train on the same labels, which the
+    // metrics estimate.
+    let model_version = method
+        .build(&law_embeddings, &labels, &embedding_model)
+        .version();
+    let mut parameters = method.parameters();
+    parameters["domain_threshold"] = fractalaw_ai::evaluate::HELD_OUT_THRESHOLD.into();
+    parameters["subject_threshold"] = fractalaw_ai::evaluate::HELD_OUT_THRESHOLD.into();
+
+    let run_id = format!("eval-{}", chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ"));
+    write_evaluation(
+        store,
+        &run_id,
+        &method.to_string(),
+        &embedding_model,
+        &model_version,
+        &parameters.to_string(),
+        &report,
+    )?;
+    println!("  model:          {model_version}");
+    println!("\nWrote run {run_id} to classifier_eval_* tables.");
+
+    println!("\n--- Per-family metrics ---\n");
+    print_batches(&store.query_arrow(&format!(
+        "SELECT family, support, predicted, \
+                round(precision, 3) AS precision, round(recall, 3) AS recall, \
+                round(f1, 3) AS f1 \
+         FROM classifier_eval_families WHERE run_id = '{run_id}' \
+         ORDER BY support DESC, family"
+    ))?)?;
+
+    println!("\n--- Top confusions ---\n");
+    print_batches(&store.query_arrow(&format!(
+        "SELECT true_family, predicted_family, count \
+         FROM classifier_eval_confusion \
+         WHERE run_id = '{run_id}' AND true_family <> predicted_family \
+         ORDER BY count DESC LIMIT 20"
+    ))?)?;
+
+    println!("\n--- Calibration ---\n");
+    print_batches(&store.query_arrow(&format!(
+        "SELECT bin_lower, bin_upper, count, \
+                round(mean_confidence, 3) AS mean_confidence, round(accuracy, 3) AS accuracy \
+         FROM classifier_eval_calibration WHERE run_id = '{run_id}' AND count > 0 \
+         ORDER BY bin_lower"
+    ))?)?;
+
+    println!("\n=== Done ===");
+    Ok(())
+}
+
+/// Append an evaluation run to the `classifier_eval_*` tables.
+fn write_evaluation(
+    store: &DuckStore,
+    run_id: &str,
+    method: &str,
+    embedding_model: &str,
+    model_version: &str,
+    parameters: &str,
+    report: &fractalaw_ai::EvalReport,
+) -> anyhow::Result<()> {
+    let esc = |s: &str| s.replace('\'', "''");
+    store.create_classifier_eval_tables()?;
+
+    store.execute(&format!(
+        "INSERT INTO classifier_eval_runs \
+         (run_id, evaluated_at, method, embedding_model, split, laws, accuracy, macro_f1, ece, \
+          model_version, parameters) VALUES \
+         ('{}', CURRENT_TIMESTAMP, '{}', '{}', '{}', {}, {}, {}, {}, '{}', '{}')",
+        esc(run_id),
+        esc(method),
+        esc(embedding_model),
+        esc(&report.split.describe()),
+        report.predictions.len(),
+        report.accuracy,
+        report.macro_f1,
+        report.expected_calibration_error,
+        esc(model_version),
+        esc(parameters),
+    ))?;
+
+    for chunk in report.families.chunks(100) {
+        let rows: Vec<String> = chunk
+            .iter()
+            .map(|f| {
+                format!(
+                    "('{}', '{}', {}, {}, {}, {}, {})",
+                    esc(run_id),
+                    esc(&f.family),
+                    f.support,
+                    f.predicted,
+                    f.precision,
+                    f.recall,
+                    f.f1
+                )
+            })
+            .collect();
+        store.execute(&format!(
+            "INSERT INTO classifier_eval_families VALUES {}",
+            rows.join(", ")
+        ))?;
+    }
+
+    let confusion: Vec<_> = report.confusion.iter().collect();
+    for chunk in confusion.chunks(100) {
+        let rows: Vec<String> = chunk
+            .iter()
+            .map(|((truth, predicted), count)| {
+                format!(
+                    "('{}', '{}', '{}', {count})",
+                    esc(run_id),
+                    esc(truth),
+                    esc(predicted)
+                )
+            })
+            .collect();
+        store.execute(&format!(
+            "INSERT INTO classifier_eval_confusion VALUES {}",
+            rows.join(", ")
+        ))?;
+    }
+
+    let rows: Vec<String> = report
+        .calibration
+        .iter()
+        .map(|b| {
+            format!(
+                "('{}', {}, {}, {}, {}, {})",
+                esc(run_id),
+                b.lower,
+                b.upper,
+                b.count,
+                b.mean_confidence,
+                b.accuracy
+            )
+        })
+        .collect();
+    store.execute(&format!(
+        "INSERT INTO classifier_eval_calibration VALUES {}",
+        rows.join(", ")
+    ))?;
+
+    Ok(())
+}
+
 /// Write classification results to DuckDB legislation table.
 fn write_classifications(
     store: &DuckStore,
     results: &[fractalaw_ai::Classification],
+    model_version: &str,
 ) -> anyhow::Result<()> {
+    let model_esc = model_version.replace('\'', "''");
+
     // Add columns (idempotent).
     let columns = [
         ("classified_domain", "VARCHAR[]"),
@@ -987,6 +2686,9 @@ fn write_classifications(
         ("classification_model", "VARCHAR"),
         ("classified_at", "TIMESTAMPTZ"),
         ("classification_status", "VARCHAR"),
+        ("classified_sub_family", "VARCHAR"),
+        ("sub_family_confidence", "FLOAT"),
+        ("sub_family_status", "VARCHAR"),
     ];
 
     for (col, dtype) in &columns {
@@ -1006,7 +2708,10 @@ fn write_classifications(
             classification_confidence FLOAT, \
             classification_model VARCHAR, \
             classified_at TIMESTAMPTZ, \
-            classification_status VARCHAR\
+            classification_status VARCHAR, \
+            classified_sub_family VARCHAR, \
+            sub_family_confidence FLOAT, \
+            sub_family_status VARCHAR\
         )",
     )?;
 
@@ -1022,15 +2727,26 @@ fn write_classifications(
             let family_esc = c.family.replace('\'', "''");
             let domain_arr = format_sql_list(c.domain.iter().map(|(d, _)| d.as_str()));
             let subjects_arr = format_sql_list(c.subjects.iter().map(|(s, _)| s.as_str()));
+            let (sub_family_sql, sub_conf_sql) = match &c.sub_family {
+                Some(sub) => (
+                    format!("'{}'", sub.replace('\'', "''")),
+                    c.sub_family_confidence.to_string(),
+                ),
+                None => ("NULL".to_string(), "NULL".to_string()),
+            };
 
             sql.push_str(&format!(
-                "('{}', {}, '{}', {}, {}, 'centroid-v1', CURRENT_TIMESTAMP, '{}')",
+                "('{}', {}, '{}', {}, {}, '{}', CURRENT_TIMESTAMP, '{}', {}, {}, '{}')",
                 name_esc,
                 domain_arr,
                 family_esc,
                 subjects_arr,
                 c.family_confidence,
+                model_esc,
                 c.status.as_str(),
+                sub_family_sql,
+                sub_conf_sql,
+                c.sub_family_status.as_str(),
             ));
         }
         store.execute(&sql)?;
@@ -1045,7 +2761,10 @@ fn write_classifications(
             classification_confidence = c.classification_confidence, \
             classification_model = c.classification_model, \
             classified_at = c.classified_at, \
-            classification_status = c.classification_status \
+            classification_status = c.classification_status, \
+            classified_sub_family = c.classified_sub_family, \
+            sub_family_confidence = c.sub_family_confidence, \
+            sub_family_status = c.sub_family_status \
         FROM _tmp_classifications c \
         WHERE legislation.name = c.name",
     )?;
@@ -1054,6 +2773,121 @@ fn write_classifications(
     Ok(())
 }
 
+/// Replace `section_classifications` with one row per tagged label. Returns rows written.
+fn write_section_classifications(
+    store: &DuckStore,
+    results: &[fractalaw_ai::SectionClassification],
+    model_version: &str,
+) -> anyhow::Result<usize> {
+    let esc = |s: &str| s.replace('\'', "''");
+    store.create_section_classification_tables()?;
+    store.execute("DELETE FROM section_classifications")?;
+
+    let rows: Vec<String> = results
+        .iter()
+        .flat_map(|r| {
+            let drrp = r.drrp.iter().map(|l| ("drrp", l));
+            let subjects = r.subjects.iter().map(|l| ("subject", l));
+            drrp.chain(subjects).map(move |(taxonomy, (label, confidence))| {
+                let provision = r
+                    .provision
+                    .as_deref()
+                    .map_or("NULL".to_string(), |p| format!("'{}'", esc(p)));
+                format!(
+                    "('{}', '{}', {provision}, '{taxonomy}', '{}', {confidence}, '{}', CURRENT_TIMESTAMP)",
+                    esc(&r.section_id),
+                    esc(&r.law_name),
+                    esc(label),
+                    esc(model_version),
+                )
+            })
+        })
+        .collect();
+
+    for chunk in rows.chunks(500) {
+        store.execute(&format!(
+            "INSERT INTO section_classifications VALUES {}",
+            chunk.join(", ")
+        ))?;
+    }
+    Ok(rows.len())
+}
+
+/// Directory holding saved classifier models (`<version>.json` + `LATEST`).
+fn classifier_dir(data_dir: &std::path::Path) -> PathBuf {
+    data_dir.join("models").join("classifiers")
+}
+
+/// Directory holding precompiled components (`<key>.cwasm`).
+fn aot_cache_dir(data_dir: &std::path::Path) -> PathBuf {
+    data_dir.join("aot-cache")
+}
+
+/// Micro-app runtime that loads components through the AOT cache.
+fn runtime(data_dir: &std::path::Path) -> anyhow::Result<fractalaw_host::Runtime> {
+    let cache = fractalaw_host::AotCache::open(&aot_cache_dir(data_dir))?;
+    Ok(fractalaw_host::Runtime::new()?
+        .with_aot_cache(cache)
+        .with_node_id(node_key(data_dir)?.node_id()))
+}
+
+/// Host resources for event, scheduled and invoked runs of any app:
+/// inference from the environment, the app's share of `app_budgets`, and
+/// no store, so each run gets a connection to the one its dispatcher or
+/// caller uses.
+fn run_options(
+    data_dir: &std::path::Path,
+    token_budget: Option<u64>,
+    app_budgets: Option<fractalaw_host::AppTokenBudgets>,
+    inference_cache: Option<fractalaw_host::CacheMode>,
+) -> impl Fn(&str) -> anyhow::Result<fractalaw_host::RunOptions> + Clone + Send + Sync + 'static {
+    let data_dir = data_dir.to_path_buf();
+    move |app| {
+        Ok(fractalaw_host::RunOptions {
+            trigger: None,
+            manifest: None,
+            tier: None,
+            invocation: None,
+            duck: None,
+            inference: inference_config(&data_dir, inference_cache)?,
+            token_budget: fractalaw_host::TokenBudget {
+                per_run: token_budget,
+                app: app_budgets.as_ref().map(|budgets| budgets.get(app)),
+            },
+            embeddings: None,
+            classifier: None,
+        })
+    }
+}
+
+/// This node's signing key, `<data_dir>/node.key`, generated on first use.
+fn node_key(data_dir: &std::path::Path) -> anyhow::Result<fractalaw_core::NodeKey> {
+    fractalaw_core::NodeKey::load_or_generate(&data_dir.join(fractalaw_core::NODE_KEY_FILE))
+        .map_err(anyhow::Error::msg)
+}
+
+/// Inference backend from the environment, with the response cache in
+/// `<data_dir>/inference-cache` when `cache` is set. Replay never contacts
+/// the provider, so it needs no credentials.
+fn inference_config(
+    data_dir: &std::path::Path,
+    cache: Option<fractalaw_host::CacheMode>,
+) -> anyhow::Result<Option<fractalaw_host::InferenceConfig>> {
+    use fractalaw_host::{CacheMode, InferenceConfig};
+
+    let Some(mode) = cache else {
+        return InferenceConfig::from_env();
+    };
+    let cache = fractalaw_ai::ResponseCache::open(&data_dir.join("inference-cache"))?;
+    Ok(match mode {
+        CacheMode::Record => InferenceConfig::from_env()?.map(|c| c.with_cache(cache, mode)),
+        CacheMode::Replay => Some(InferenceConfig::replay(
+            cache,
+            InferenceConfig::provider_id_from_env()?,
+        )),
+    })
+}
+
 /// Format an iterator of strings as a DuckDB array literal: `['a', 'b']` or `NULL`.
 fn format_sql_list<'a>(values: impl Iterator<Item = &'a str>) -> String {
     let items: Vec<String> = values
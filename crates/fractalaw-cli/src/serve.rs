@@ -0,0 +1,330 @@
+//! `fractalaw serve`: a daemon that runs micro-apps on one warm [`Runtime`].
+//!
+//! Components in the apps directory are compiled at startup and stay cached,
+//! so runs skip compilation. The daemon answers run requests on a Unix
+//! socket and, every poll interval, delivers pending events and runs due
+//! scheduled tasks on the same runtime. Every checkpoint interval it signs
+//! the audit log's head with the node key. Runs may call the apps in the
+//! apps directory through `app-invoke`.
+//!
+//! The protocol is newline-delimited JSON; each request line gets one reply
+//! line:
+//!
+//! ```text
+//! {"op":"run","app":"hello-world","fuel":1000000000,"payload":"..."}
+//! {"op":"apps"}
+//! ```
+//!
+//! A run's `payload` reaches the app through `events-receive` as an event of
+//! type `request`. Replies carry `"ok": true` and the result, or
+//! `"ok": false` and an `error`.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use fractalaw_host::{AppTokenBudgets, CacheMode, RunOptions, RunResult, Runtime};
+use fractalaw_store::DuckStore;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+
+pub struct ServeConfig {
+    pub data_dir: PathBuf,
+    pub socket: PathBuf,
+    pub apps_dir: PathBuf,
+    /// Fuel for runs that do not ask for a budget, and for event and
+    /// scheduled runs; lowered to each app's tier limit.
+    pub fuel: u64,
+    /// How often to deliver events and run due tasks.
+    pub poll_interval: Duration,
+    /// How often to append a signed audit checkpoint; `None` never does.
+    pub checkpoint_interval: Option<Duration>,
+    pub token_budget: Option<u64>,
+    /// Tokens each app may generate across all its runs while the daemon
+    /// is up; `None` leaves apps to the per-run budget alone.
+    pub app_token_budgets: Option<AppTokenBudgets>,
+    pub inference_cache: Option<CacheMode>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum Request {
+    Run {
+        app: String,
+        fuel: Option<u64>,
+        payload: Option<String>,
+    },
+    Apps,
+}
+
+/// Serve until interrupted with Ctrl-C.
+pub async fn serve(duck: DuckStore, config: ServeConfig) -> anyhow::Result<()> {
+    let config = Arc::new(config);
+    let invoke_config = config.clone();
+    let runtime = Arc::new(
+        crate::runtime(&config.data_dir)?.with_apps(config.apps_dir.clone(), move |app| {
+            run_options(&invoke_config, app)
+        }),
+    );
+
+    if config.apps_dir.is_dir() {
+        for entry in std::fs::read_dir(&config.apps_dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|e| e == "wasm") {
+                runtime
+                    .preload(&path)
+                    .await
+                    .with_context(|| format!("compiling {}", path.display()))?;
+            }
+        }
+    }
+    eprintln!(
+        "Loaded {} apps from {}.",
+        runtime.loaded().len(),
+        config.apps_dir.display()
+    );
+
+    if config.socket.exists() {
+        std::fs::remove_file(&config.socket)
+            .with_context(|| format!("removing stale socket {}", config.socket.display()))?;
+    }
+    let listener = UnixListener::bind(&config.socket)
+        .with_context(|| format!("binding {}", config.socket.display()))?;
+    eprintln!("Listening on {}.", config.socket.display());
+
+    let result = tokio::select! {
+        r = accept(&listener, &duck, &runtime, &config) => r,
+        r = background(&duck, &runtime, &config) => r,
+        r = tokio::signal::ctrl_c() => r.map_err(Into::into),
+    };
+    let _ = std::fs::remove_file(&config.socket);
+    result
+}
+
+/// Handle each connection on its own task with its own DuckDB connection.
+async fn accept(
+    listener: &UnixListener,
+    duck: &DuckStore,
+    runtime: &Arc<Runtime>,
+    config: &Arc<ServeConfig>,
+) -> anyhow::Result<()> {
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let conn = duck.try_clone()?;
+        let runtime = runtime.clone();
+        let config = config.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, conn, &runtime, &config).await {
+                tracing::warn!("serve connection failed: {e:#}");
+            }
+        });
+    }
+}
+
+/// Deliver events and run due scheduled tasks every poll interval, and
+/// checkpoint the audit log once per checkpoint interval.
+async fn background(
+    duck: &DuckStore,
+    runtime: &Runtime,
+    config: &ServeConfig,
+) -> anyhow::Result<()> {
+    let policy = fractalaw_host::DeliveryPolicy {
+        fuel: config.fuel,
+        ..fractalaw_host::DeliveryPolicy::default()
+    };
+    let clock = fractalaw_core::SystemClock;
+    let key = crate::node_key(&config.data_dir)?;
+    let mut last_checkpoint = std::time::Instant::now();
+    let mut interval = tokio::time::interval(config.poll_interval);
+    loop {
+        interval.tick().await;
+        // A failed pass is retried on the next tick rather than stopping
+        // the daemon.
+        let events = fractalaw_host::dispatch_events(runtime, duck, &policy, 1_000, |delivery| {
+            run_options(config, &delivery.subscriber)
+        })
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("event dispatch failed: {e:#}");
+            fractalaw_host::DispatchReport::default()
+        });
+        let tasks = fractalaw_host::run_due_tasks(
+            runtime,
+            duck,
+            &clock,
+            &config.apps_dir,
+            config.fuel,
+            1_000,
+            |task| run_options(config, &task.target_app),
+        )
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("scheduled task run failed: {e:#}");
+            fractalaw_host::ScheduleReport::default()
+        });
+        if events != fractalaw_host::DispatchReport::default() || tasks.ran + tasks.failed > 0 {
+            tracing::info!(?events, ?tasks, "background pass");
+        }
+        if config
+            .checkpoint_interval
+            .is_some_and(|every| last_checkpoint.elapsed() >= every)
+        {
+            last_checkpoint = std::time::Instant::now();
+            match duck.append_checkpoint(&key) {
+                Ok(Some(checkpoint)) => tracing::info!(
+                    entry_id = checkpoint.entry_id,
+                    hash = %fractalaw_core::node::hex(&checkpoint.hash()),
+                    "signed audit checkpoint"
+                ),
+                Ok(None) => {}
+                Err(e) => tracing::warn!("audit checkpoint failed: {e:#}"),
+            }
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: UnixStream,
+    duck: DuckStore,
+    runtime: &Runtime,
+    config: &ServeConfig,
+) -> anyhow::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => {
+                let conn = duck.try_clone()?;
+                handle_request(request, conn, runtime, config)
+                    .await
+                    .unwrap_or_else(|e| json!({ "ok": false, "error": format!("{e:#}") }))
+            }
+            Err(e) => json!({ "ok": false, "error": format!("invalid request: {e}") }),
+        };
+        let mut bytes = serde_json::to_vec(&reply)?;
+        bytes.push(b'\n');
+        writer.write_all(&bytes).await?;
+    }
+    Ok(())
+}
+
+async fn handle_request(
+    request: Request,
+    duck: DuckStore,
+    runtime: &Runtime,
+    config: &ServeConfig,
+) -> anyhow::Result<Value> {
+    match request {
+        Request::Apps => {
+            let apps: Vec<String> = runtime
+                .loaded()
+                .iter()
+                .map(|p| fractalaw_host::app_name(p))
+                .collect();
+            Ok(json!({ "ok": true, "apps": apps }))
+        }
+        Request::Run { app, fuel, payload } => {
+            let component = fractalaw_host::app_component(&config.apps_dir, &app)?;
+            let mut opts = run_options(config, &app)?;
+            opts.duck = Some(duck);
+            opts.trigger = payload.map(request_trigger);
+            let result = runtime
+                .run(&component, fuel.unwrap_or(config.fuel), opts)
+                .await?;
+            Ok(run_reply(&result))
+        }
+    }
+}
+
+/// Host resources for a run of `app`, which shares its token budget with
+/// the app's other runs.
+fn run_options(config: &ServeConfig, app: &str) -> anyhow::Result<RunOptions> {
+    Ok(RunOptions {
+        trigger: None,
+        manifest: None,
+        tier: None,
+        invocation: None,
+        duck: None,
+        inference: crate::inference_config(&config.data_dir, config.inference_cache)?,
+        token_budget: fractalaw_host::TokenBudget {
+            per_run: config.token_budget,
+            app: config
+                .app_token_budgets
+                .as_ref()
+                .map(|budgets| budgets.get(app)),
+        },
+        embeddings: None,
+        classifier: None,
+    })
+}
+
+fn request_trigger(payload: String) -> fractalaw_host::fractal::app::events_receive::Delivery {
+    use fractalaw_host::fractal::app::{events_emit::DomainEvent, events_receive::Delivery};
+
+    Delivery {
+        id: 0,
+        event: DomainEvent {
+            event_type: "request".into(),
+            payload: payload.into_bytes(),
+            source_app: "serve".into(),
+        },
+        attempt: 1,
+    }
+}
+
+fn run_reply(result: &RunResult) -> Value {
+    let audit: Vec<Value> = result
+        .audit_entries
+        .iter()
+        .map(|e| {
+            json!({
+                "event_type": e.event_type,
+                "resource": e.resource,
+                "detail": e.detail,
+                "timestamp": e.timestamp.to_rfc3339(),
+            })
+        })
+        .collect();
+    let (output, guest_error) = match &result.output {
+        Ok(output) => (Some(output), None),
+        Err(error) => (None, Some(error)),
+    };
+    json!({
+        "ok": true,
+        "output": output,
+        "guest_error": guest_error,
+        "tier": result.tier,
+        "limit_exceeded": result.limit_exceeded,
+        "fuel_consumed": result.fuel_consumed,
+        "tokens_used": result.inference_usage.tokens_used,
+        "invoked_apps": result.invoke_usage.calls,
+        "emitted_events": result.emitted_events,
+        "audit": audit,
+    })
+}
+
+/// Send one request to a running daemon and return its reply.
+pub async fn call(socket: &Path, request: &Value) -> anyhow::Result<Value> {
+    let stream = UnixStream::connect(socket).await.with_context(|| {
+        format!(
+            "connecting to {} (is `fractalaw serve` running?)",
+            socket.display()
+        )
+    })?;
+    let (reader, mut writer) = stream.into_split();
+    let mut bytes = serde_json::to_vec(request)?;
+    bytes.push(b'\n');
+    writer.write_all(&bytes).await?;
+    let line = BufReader::new(reader)
+        .lines()
+        .next_line()
+        .await?
+        .context("daemon closed the connection without replying")?;
+    Ok(serde_json::from_str(&line)?)
+}
@@ -0,0 +1,469 @@
+//! The hash-chained audit log.
+//!
+//! Every entry stores the SHA-256 hash of the entry before it in
+//! `prev_hash` (the first stores [`GENESIS_HASH`]), so altering, removing or
+//! reordering an entry breaks the chain at the next one. [`ChainVerifier`]
+//! walks the log in `entry_id` order and reports the first break.
+//!
+//! A chain alone can be rewritten wholesale, or cut short, and still verify.
+//! Checkpoint entries pin it down: a node periodically appends one whose
+//! `signature` is its [`NodeKey`] signature over the entry, and so over the
+//! head of the chain before it. Rewriting history before a checkpoint then
+//! needs the node's private key, and an auditor holding a checkpoint's
+//! entry id and hash ([`ChainVerifier::expect`]) can tell the log was not
+//! cut short before it.
+//!
+//! Stored in `audit_log`; see [`esh::audit_log_schema`](crate::esh::audit_log_schema).
+
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use ring::digest;
+use ring::signature::{ED25519, UnparsedPublicKey};
+
+use crate::NodeKey;
+use crate::node::{hex, parse_hex32};
+
+/// `prev_hash` of the first entry.
+pub const GENESIS_HASH: [u8; 32] = [0; 32];
+
+/// `event_type` of signed checkpoint entries. Reserved for the node: only
+/// entries with [`NODE_ACTOR_ROLE`] count as checkpoints.
+pub const CHECKPOINT_EVENT: &str = "checkpoint";
+
+/// `actor_role` of entries a node records itself, such as checkpoints.
+pub const NODE_ACTOR_ROLE: &str = "node";
+
+/// One entry in the audit log.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEntry {
+    /// One sequence for the whole log, whichever node recorded the entry:
+    /// contiguous from 1 and assigned when the entry is stored.
+    pub entry_id: u64,
+    /// Hashed and stored to the microsecond.
+    pub timestamp: DateTime<Utc>,
+    pub node_id: String,
+    /// The user or micro-app that acted.
+    pub actor_id: String,
+    pub actor_role: String,
+    pub event_type: String,
+    pub resource: Option<String>,
+    pub detail: Option<String>,
+    /// Hash of the previous entry; assigned when the entry is stored.
+    pub prev_hash: [u8; 32],
+    pub signature: Option<[u8; 64]>,
+}
+
+impl AuditEntry {
+    /// An entry by `actor_id` to be appended, before the store assigns its
+    /// `entry_id` and `prev_hash`.
+    pub fn new(
+        node_id: impl Into<String>,
+        actor_id: impl Into<String>,
+        actor_role: impl Into<String>,
+        event_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            entry_id: 0,
+            timestamp: Utc::now(),
+            node_id: node_id.into(),
+            actor_id: actor_id.into(),
+            actor_role: actor_role.into(),
+            event_type: event_type.into(),
+            resource: None,
+            detail: None,
+            prev_hash: GENESIS_HASH,
+            signature: None,
+        }
+    }
+
+    /// An unsigned checkpoint by `key`; the store assigns its `entry_id` and
+    /// `prev_hash`, then [`sign`](Self::sign)s it.
+    pub fn checkpoint(key: &NodeKey) -> Self {
+        let mut entry = Self::new(
+            key.node_id(),
+            key.node_id(),
+            NODE_ACTOR_ROLE,
+            CHECKPOINT_EVENT,
+        );
+        entry.detail = Some(format!(r#"{{"public_key":"{}"}}"#, hex(&key.public_key())));
+        entry
+    }
+
+    /// Sign the entry, `prev_hash` included, with `key`.
+    pub fn sign(&mut self, key: &NodeKey) {
+        self.signature = None;
+        self.signature = Some(key.sign(&self.hash()));
+    }
+
+    /// Whether the entry is a checkpoint recorded by a node, as opposed to
+    /// an entry from an app that merely uses the event type.
+    pub fn is_checkpoint(&self) -> bool {
+        self.event_type == CHECKPOINT_EVENT && self.actor_role == NODE_ACTOR_ROLE
+    }
+
+    /// Whether the entry carries a signature by `public_key`.
+    pub fn signed_by(&self, public_key: &[u8; 32]) -> bool {
+        let Some(signature) = self.signature else {
+            return false;
+        };
+        let unsigned = Self {
+            signature: None,
+            ..self.clone()
+        };
+        UnparsedPublicKey::new(&ED25519, public_key)
+            .verify(&unsigned.hash(), &signature)
+            .is_ok()
+    }
+
+    /// SHA-256 over every field, which the next entry stores as its
+    /// `prev_hash`.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut ctx = digest::Context::new(&digest::SHA256);
+        ctx.update(&self.prev_hash);
+        ctx.update(&self.entry_id.to_be_bytes());
+        ctx.update(&self.timestamp.timestamp_micros().to_be_bytes());
+        for field in [
+            Some(&self.node_id),
+            Some(&self.actor_id),
+            Some(&self.actor_role),
+            Some(&self.event_type),
+            self.resource.as_ref(),
+            self.detail.as_ref(),
+        ] {
+            // Length-prefixed so adjacent fields cannot trade bytes.
+            match field {
+                Some(value) => {
+                    ctx.update(&[1]);
+                    ctx.update(&(value.len() as u64).to_be_bytes());
+                    ctx.update(value.as_bytes());
+                }
+                None => ctx.update(&[0]),
+            }
+        }
+        match &self.signature {
+            Some(signature) => {
+                ctx.update(&[1]);
+                ctx.update(signature);
+            }
+            None => ctx.update(&[0]),
+        }
+        ctx.finish()
+            .as_ref()
+            .try_into()
+            .expect("SHA-256 digests are 32 bytes")
+    }
+}
+
+/// Where and why an audit chain stops verifying.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChainBreak {
+    pub entry_id: u64,
+    pub reason: String,
+}
+
+impl fmt::Display for ChainBreak {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "entry {}: {}", self.entry_id, self.reason)
+    }
+}
+
+/// Checks audit entries, fed in `entry_id` order, against the chain and
+/// the node keys it trusts to sign checkpoints.
+#[derive(Debug, Clone)]
+pub struct ChainVerifier {
+    next_id: u64,
+    head: [u8; 32],
+    trusted: Vec<[u8; 32]>,
+    expected: Vec<(u64, [u8; 32])>,
+    checkpoints: u64,
+    last_checkpoint: Option<u64>,
+}
+
+impl Default for ChainVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChainVerifier {
+    pub fn new() -> Self {
+        Self {
+            next_id: 1,
+            head: GENESIS_HASH,
+            trusted: Vec::new(),
+            expected: Vec::new(),
+            checkpoints: 0,
+            last_checkpoint: None,
+        }
+    }
+
+    /// Accept checkpoints signed with `public_key`.
+    pub fn trust(mut self, public_key: [u8; 32]) -> Self {
+        self.trusted.push(public_key);
+        self
+    }
+
+    /// Require a checkpoint at `entry_id` hashing to `hash`, as recorded by
+    /// an auditor earlier. See [`finish`](Self::finish).
+    pub fn expect(mut self, entry_id: u64, hash: [u8; 32]) -> Self {
+        self.expected.push((entry_id, hash));
+        self
+    }
+
+    /// Check that `entry` follows the entries checked so far.
+    pub fn check(&mut self, entry: &AuditEntry) -> Result<(), ChainBreak> {
+        if entry.entry_id != self.next_id {
+            return Err(ChainBreak {
+                entry_id: entry.entry_id,
+                reason: format!(
+                    "expected entry {}; entries are missing or out of order",
+                    self.next_id
+                ),
+            });
+        }
+        if entry.prev_hash != self.head {
+            return Err(ChainBreak {
+                entry_id: entry.entry_id,
+                reason: "prev_hash does not match the previous entry, which was altered".into(),
+            });
+        }
+        if entry.is_checkpoint() {
+            if entry.signature.is_none() {
+                return Err(ChainBreak {
+                    entry_id: entry.entry_id,
+                    reason: "checkpoint is not signed".into(),
+                });
+            }
+            if !self.trusted.iter().any(|key| entry.signed_by(key)) {
+                return Err(ChainBreak {
+                    entry_id: entry.entry_id,
+                    reason: "checkpoint signature does not verify with a trusted node key".into(),
+                });
+            }
+            self.checkpoints += 1;
+            self.last_checkpoint = Some(entry.entry_id);
+        }
+        let hash = entry.hash();
+        if self.expected.iter().any(|&(id, expected)| {
+            id == entry.entry_id && (expected != hash || !entry.is_checkpoint())
+        }) {
+            return Err(ChainBreak {
+                entry_id: entry.entry_id,
+                reason: "not the expected checkpoint; the log was rewritten".into(),
+            });
+        }
+        self.head = hash;
+        self.next_id += 1;
+        Ok(())
+    }
+
+    /// Check, once every entry has been checked, that the log reached each
+    /// expected checkpoint rather than being cut short before it.
+    pub fn finish(&self) -> Result<(), ChainBreak> {
+        match self
+            .expected
+            .iter()
+            .map(|&(id, _)| id)
+            .filter(|&id| id >= self.next_id)
+            .min()
+        {
+            Some(entry_id) => Err(ChainBreak {
+                entry_id,
+                reason: format!(
+                    "expected checkpoint is missing; the log ends at entry {}",
+                    self.verified()
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Entries checked so far.
+    pub fn verified(&self) -> u64 {
+        self.next_id - 1
+    }
+
+    /// Hash of the last entry checked, or [`GENESIS_HASH`] before any.
+    pub fn head(&self) -> [u8; 32] {
+        self.head
+    }
+
+    /// Signed checkpoints checked so far.
+    pub fn checkpoints(&self) -> u64 {
+        self.checkpoints
+    }
+
+    /// Entry id of the last signed checkpoint checked.
+    pub fn last_checkpoint(&self) -> Option<u64> {
+        self.last_checkpoint
+    }
+}
+
+/// Parse an expected checkpoint given as `<entry_id>:<hex hash>`.
+pub fn parse_expected_checkpoint(s: &str) -> Result<(u64, [u8; 32]), String> {
+    s.split_once(':')
+        .and_then(|(id, hash)| Some((id.parse().ok()?, parse_hex32(hash)?)))
+        .ok_or_else(|| format!("'{s}' is not <entry_id>:<64-digit hex hash>"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(len: u64) -> Vec<AuditEntry> {
+        let mut prev_hash = GENESIS_HASH;
+        (1..=len)
+            .map(|entry_id| {
+                let mut entry =
+                    AuditEntry::new("node-a", "hello-world", "micro-app", "app-started");
+                entry.entry_id = entry_id;
+                entry.resource = Some(format!("resource-{entry_id}"));
+                entry.prev_hash = prev_hash;
+                prev_hash = entry.hash();
+                entry
+            })
+            .collect()
+    }
+
+    fn verify(entries: &[AuditEntry]) -> Result<u64, ChainBreak> {
+        verify_with(ChainVerifier::new(), entries)
+    }
+
+    fn verify_with(mut verifier: ChainVerifier, entries: &[AuditEntry]) -> Result<u64, ChainBreak> {
+        for entry in entries {
+            verifier.check(entry)?;
+        }
+        Ok(verifier.verified())
+    }
+
+    #[test]
+    fn intact_chain_verifies() {
+        let entries = chain(4);
+        assert_eq!(verify(&entries), Ok(4));
+        assert_eq!(verify(&[]), Ok(0));
+    }
+
+    #[test]
+    fn tampering_breaks_the_chain() {
+        let mut altered = chain(4);
+        altered[1].detail = Some("rewritten".into());
+        assert_eq!(verify(&altered).unwrap_err().entry_id, 3);
+
+        let mut removed = chain(4);
+        removed.remove(2);
+        assert_eq!(verify(&removed).unwrap_err().entry_id, 4);
+
+        // Moving bytes between fields changes the hash too.
+        let mut shifted = chain(2);
+        shifted[0].actor_id = "hello-worldmicro-app".into();
+        shifted[0].actor_role = String::new();
+        assert_eq!(verify(&shifted).unwrap_err().entry_id, 2);
+    }
+
+    /// `chain(len)` plus a checkpoint signed by `key`.
+    fn checkpointed(len: u64, key: &NodeKey) -> Vec<AuditEntry> {
+        let mut entries = chain(len);
+        let mut checkpoint = AuditEntry::checkpoint(key);
+        checkpoint.entry_id = len + 1;
+        checkpoint.prev_hash = entries.last().map_or(GENESIS_HASH, AuditEntry::hash);
+        checkpoint.sign(key);
+        entries.push(checkpoint);
+        entries
+    }
+
+    #[test]
+    fn checkpoints_verify_with_trusted_keys_only() {
+        let (key, _) = NodeKey::generate().unwrap();
+        let (other, _) = NodeKey::generate().unwrap();
+        let entries = checkpointed(3, &key);
+
+        let mut verifier = ChainVerifier::new().trust(key.public_key());
+        for entry in &entries {
+            verifier.check(entry).unwrap();
+        }
+        assert_eq!(verifier.checkpoints(), 1);
+        assert_eq!(verifier.last_checkpoint(), Some(4));
+
+        let untrusted = ChainVerifier::new().trust(other.public_key());
+        assert_eq!(verify_with(untrusted, &entries).unwrap_err().entry_id, 4);
+        assert_eq!(verify(&entries).unwrap_err().entry_id, 4);
+    }
+
+    #[test]
+    fn rewritten_history_cannot_be_resigned_without_the_key() {
+        let (key, _) = NodeKey::generate().unwrap();
+        let (forger, _) = NodeKey::generate().unwrap();
+
+        // Rewrite an entry and rebuild the chain after it: the checkpoint
+        // now covers a different head, so its signature no longer verifies.
+        let mut entries = checkpointed(3, &key);
+        entries[0].detail = Some("rewritten".into());
+        for i in 1..entries.len() {
+            entries[i].prev_hash = entries[i - 1].hash();
+        }
+        let trusted = || ChainVerifier::new().trust(key.public_key());
+        assert_eq!(verify_with(trusted(), &entries).unwrap_err().entry_id, 4);
+
+        // Re-signing with another key does not help.
+        entries[3].sign(&forger);
+        assert_eq!(verify_with(trusted(), &entries).unwrap_err().entry_id, 4);
+
+        let mut unsigned = checkpointed(1, &key);
+        unsigned[1].signature = None;
+        assert_eq!(verify_with(trusted(), &unsigned).unwrap_err().entry_id, 2);
+    }
+
+    #[test]
+    fn expected_checkpoints_catch_a_shortened_log() {
+        let (key, _) = NodeKey::generate().unwrap();
+        let entries = checkpointed(3, &key);
+        let pin = format!("4:{}", hex(&entries[3].hash()));
+        let (entry_id, hash) = parse_expected_checkpoint(&pin).unwrap();
+        let pinned = || {
+            ChainVerifier::new()
+                .trust(key.public_key())
+                .expect(entry_id, hash)
+        };
+
+        let mut verifier = pinned();
+        for entry in &entries {
+            verifier.check(entry).unwrap();
+        }
+        assert_eq!(verifier.finish(), Ok(()));
+
+        // Dropping the checkpoint, or the log back to it, leaves the pin
+        // unreached.
+        let mut verifier = pinned();
+        for entry in &entries[..3] {
+            verifier.check(entry).unwrap();
+        }
+        assert_eq!(verifier.finish().unwrap_err().entry_id, 4);
+
+        // A different entry in its place does not match.
+        assert_eq!(verify_with(pinned(), &chain(4)).unwrap_err().entry_id, 4);
+
+        assert!(parse_expected_checkpoint("4").is_err());
+        assert!(parse_expected_checkpoint("x:00").is_err());
+    }
+
+    #[test]
+    fn apps_cannot_record_checkpoints() {
+        let (key, _) = NodeKey::generate().unwrap();
+
+        // An app entry using the checkpoint event type is an ordinary entry:
+        // it neither breaks verification nor counts as a checkpoint.
+        let mut entries = chain(2);
+        entries[1].event_type = CHECKPOINT_EVENT.into();
+        entries[1].prev_hash = entries[0].hash();
+        assert!(!entries[1].is_checkpoint());
+
+        let mut verifier = ChainVerifier::new().trust(key.public_key());
+        for entry in &entries {
+            verifier.check(entry).unwrap();
+        }
+        assert_eq!(verifier.checkpoints(), 0);
+        assert_eq!(verifier.last_checkpoint(), None);
+        assert!(checkpointed(2, &key)[2].is_checkpoint());
+    }
+}
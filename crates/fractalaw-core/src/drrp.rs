@@ -28,8 +28,17 @@ pub struct PolishedEntry {
     pub text: String,
     pub qualifier: Option<String>,
     pub clause_ref: String,
+    /// The regex scraper's confidence in the source annotation.
     pub confidence: f32,
+    /// The model's own confidence, when its provider reports log
+    /// probabilities.
+    #[serde(default)]
+    pub model_confidence: Option<f32>,
     /// ISO 8601 timestamp string.
     pub polished_at: String,
+    /// Inference provider, e.g. `anthropic`; `None` for entries polished
+    /// before it was recorded.
+    #[serde(default)]
+    pub provider: Option<String>,
     pub model: String,
 }
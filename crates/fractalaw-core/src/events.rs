@@ -0,0 +1,53 @@
+//! Domain events in the local event log.
+
+use serde::{Deserialize, Serialize};
+
+/// A micro-app registered to run when events of one type are emitted.
+///
+/// Stored in `event_subscriptions`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Subscription {
+    /// Subscribing app; deliveries are tracked per app.
+    pub subscriber: String,
+    pub event_type: String,
+    /// Path to the app's `.wasm` component.
+    pub component: String,
+}
+
+/// An event awaiting delivery to one subscriber.
+///
+/// Read from `event_deliveries` joined with `events` and `event_subscriptions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingDelivery {
+    /// Monotonic id assigned when the event was emitted.
+    pub event_id: u64,
+    pub event_type: String,
+    pub payload: Vec<u8>,
+    pub source_app: String,
+    pub subscriber: String,
+    pub component: String,
+    /// Failed delivery attempts so far.
+    pub attempts: u32,
+}
+
+/// State of one event's delivery to one subscriber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Not yet delivered; due now or after a retry delay.
+    Pending,
+    /// The subscriber's run succeeded.
+    Delivered,
+    /// Gave up after too many failed attempts (dead-lettered).
+    Dead,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::Dead => "dead",
+        }
+    }
+}
@@ -1,7 +1,25 @@
+pub mod audit;
 pub mod drrp;
+pub mod events;
+pub mod manifest;
+pub mod node;
+pub mod review;
+pub mod schedule;
 pub mod schema;
 pub mod sort_key;
+pub mod tier;
 
+pub use audit::{
+    AuditEntry, CHECKPOINT_EVENT, ChainBreak, ChainVerifier, GENESIS_HASH, NODE_ACTOR_ROLE,
+};
 pub use drrp::{Annotation, PolishedEntry};
+pub use events::{DeliveryStatus, PendingDelivery, Subscription};
+pub use manifest::{
+    AppGrant, AppManifest, Capabilities, Grant, InferenceGrant, TableGrant, WriteGrant,
+};
+pub use node::{NODE_KEY_FILE, NodeKey};
+pub use review::ReviewDecision;
+pub use schedule::{Clock, Cron, ManualClock, ScheduledTask, SystemClock, TaskStatus};
 pub use schema::esh;
 pub use sort_key::normalize_provision;
+pub use tier::{Limit, LimitExceeded, Tier, TierLimits};
@@ -0,0 +1,319 @@
+//! Micro-app manifests: the host capabilities an app asks for.
+//!
+//! A manifest is a JSON file stored beside the component, or in the apps
+//! directory, named after it (`drrp_polisher.wasm` →
+//! `drrp_polisher.manifest.json`); the ones shipped with the repo's guests
+//! live in `apps/`:
+//!
+//! ```json
+//! {
+//!   "tier": "heavy",
+//!   "capabilities": {
+//!     "data-query": { "tables": ["legislation", "drrp_annotations"] },
+//!     "data-mutate": { "tables": ["polished_drrp"], "ddl": true },
+//!     "ai-inference": { "max-tokens": 20000 },
+//!     "app-invoke": { "apps": ["compliance-checker"] },
+//!     "stdio": {}
+//!   }
+//! }
+//! ```
+//!
+//! Interfaces missing from `capabilities` are denied, as is every interface
+//! for an app without a manifest. `audit-log` is always granted.
+//! `data-query` may read the tables it lists; `data-mutate` may write the
+//! tables it lists, read them and the `data-query` tables, and run DDL on
+//! them only with `"ddl": true`. `app-invoke` may call the apps it lists,
+//! each of which runs with the grants of its own manifest. `tier` picks the
+//! run's resource limits (see [`Tier`]).
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Tier;
+
+/// An app's manifest.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppManifest {
+    /// Resource tier; `None` uses the default, unless the run picks one.
+    #[serde(default)]
+    pub tier: Option<Tier>,
+    #[serde(default)]
+    pub capabilities: Capabilities,
+}
+
+/// Host interfaces granted to an app; `None` means not granted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Capabilities {
+    pub data_query: Option<TableGrant>,
+    pub data_mutate: Option<WriteGrant>,
+    pub ai_embeddings: Option<Grant>,
+    pub ai_classify: Option<Grant>,
+    pub ai_inference: Option<InferenceGrant>,
+    pub events_emit: Option<Grant>,
+    pub events_receive: Option<Grant>,
+    pub events_schedule: Option<Grant>,
+    pub app_invoke: Option<AppGrant>,
+    /// Guest stdout and stderr, passed through to the host's.
+    pub stdio: Option<Grant>,
+}
+
+/// A grant with no parameters.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Grant {}
+
+/// Read access to DuckDB tables.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TableGrant {
+    /// Tables the app may read; `None` allows every table.
+    pub tables: Option<Vec<String>>,
+}
+
+/// Write access to DuckDB tables.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WriteGrant {
+    /// Tables the app may write; `None` allows every table.
+    pub tables: Option<Vec<String>>,
+    /// Whether the app may create, alter and drop those tables.
+    #[serde(default)]
+    pub ddl: bool,
+}
+
+impl TableGrant {
+    /// Whether `table` is one of the granted tables. Names compare
+    /// case-insensitively, as DuckDB identifiers do.
+    pub fn allows(&self, table: &str) -> bool {
+        allows(&self.tables, table)
+    }
+}
+
+impl WriteGrant {
+    /// Whether `table` is one of the granted tables.
+    pub fn allows(&self, table: &str) -> bool {
+        allows(&self.tables, table)
+    }
+}
+
+fn allows(tables: &Option<Vec<String>>, table: &str) -> bool {
+    tables
+        .as_ref()
+        .is_none_or(|tables| tables.iter().any(|t| t.eq_ignore_ascii_case(table)))
+}
+
+/// Calls to other apps.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AppGrant {
+    /// Apps that may be called; `None` allows every app.
+    pub apps: Option<Vec<String>>,
+}
+
+impl AppGrant {
+    /// Whether `app` is one of the granted apps.
+    pub fn allows(&self, app: &str) -> bool {
+        self.apps
+            .as_ref()
+            .is_none_or(|apps| apps.iter().any(|a| a == app))
+    }
+}
+
+/// Access to generative inference.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct InferenceGrant {
+    /// Most tokens the app may generate in one run.
+    pub max_tokens: Option<u64>,
+}
+
+impl AppManifest {
+    /// Parse a manifest from JSON.
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("invalid app manifest: {e}"))
+    }
+
+    /// Manifest path for the component at `wasm_path`.
+    pub fn path_for(wasm_path: &Path) -> PathBuf {
+        wasm_path.with_extension("manifest.json")
+    }
+
+    /// Read the manifest beside `wasm_path`, if there is one.
+    pub fn load_for(wasm_path: &Path) -> Result<Option<Self>, String> {
+        let path = Self::path_for(wasm_path);
+        match std::fs::read_to_string(&path) {
+            Ok(json) => Self::from_json(&json)
+                .map(Some)
+                .map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("failed to read {}: {e}", path.display())),
+        }
+    }
+
+    /// A manifest granting every capability without limits, for runs that
+    /// explicitly opt out of grants (`fractalaw run --unrestricted`).
+    pub fn unrestricted() -> Self {
+        Self {
+            tier: None,
+            capabilities: Capabilities {
+                data_query: Some(TableGrant::default()),
+                data_mutate: Some(WriteGrant {
+                    tables: None,
+                    ddl: true,
+                }),
+                ai_embeddings: Some(Grant {}),
+                ai_classify: Some(Grant {}),
+                ai_inference: Some(InferenceGrant::default()),
+                events_emit: Some(Grant {}),
+                events_receive: Some(Grant {}),
+                events_schedule: Some(Grant {}),
+                app_invoke: Some(AppGrant::default()),
+                stdio: Some(Grant {}),
+            },
+        }
+    }
+}
+
+impl Capabilities {
+    /// Names of the granted capabilities, in manifest order.
+    pub fn granted(&self) -> Vec<&'static str> {
+        [
+            ("data-query", self.data_query.is_some()),
+            ("data-mutate", self.data_mutate.is_some()),
+            ("ai-embeddings", self.ai_embeddings.is_some()),
+            ("ai-classify", self.ai_classify.is_some()),
+            ("ai-inference", self.ai_inference.is_some()),
+            ("events-emit", self.events_emit.is_some()),
+            ("events-receive", self.events_receive.is_some()),
+            ("events-schedule", self.events_schedule.is_some()),
+            ("app-invoke", self.app_invoke.is_some()),
+            ("stdio", self.stdio.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(name, granted)| granted.then_some(name))
+        .collect()
+    }
+
+    /// One-line summary of the grants and their limits, for the audit log.
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        for name in self.granted() {
+            let detail = match name {
+                "data-query" => self
+                    .data_query
+                    .as_ref()
+                    .and_then(|g| tables(&g.tables, false)),
+                "data-mutate" => self
+                    .data_mutate
+                    .as_ref()
+                    .and_then(|g| tables(&g.tables, g.ddl)),
+                "ai-inference" => self
+                    .ai_inference
+                    .as_ref()
+                    .and_then(|g| g.max_tokens)
+                    .map(|n| format!(" (max {n} tokens)")),
+                "app-invoke" => self
+                    .app_invoke
+                    .as_ref()
+                    .and_then(|g| tables(&g.apps, false)),
+                _ => None,
+            };
+            parts.push(format!("{name}{}", detail.unwrap_or_default()));
+        }
+        if parts.is_empty() {
+            "none".into()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// ` (a, b)` for a grant's listed names, and `ddl` when set.
+fn tables(tables: &Option<Vec<String>>, ddl: bool) -> Option<String> {
+    let mut detail: Vec<String> = tables.clone().unwrap_or_default();
+    if ddl {
+        detail.push("ddl".into());
+    }
+    (!detail.is_empty()).then(|| format!(" ({})", detail.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_summarises_grants() {
+        let manifest = AppManifest::from_json(
+            r#"{
+                "tier": "lightweight",
+                "capabilities": {
+                    "data-query": { "tables": ["legislation"] },
+                    "data-mutate": { "tables": ["notes"], "ddl": true },
+                    "ai-inference": { "max-tokens": 500 },
+                    "events-emit": {},
+                    "app-invoke": { "apps": ["compliance-checker"] }
+                }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(manifest.tier, Some(Tier::Lightweight));
+        let caps = &manifest.capabilities;
+        assert_eq!(
+            caps.granted(),
+            vec![
+                "data-query",
+                "data-mutate",
+                "ai-inference",
+                "events-emit",
+                "app-invoke"
+            ]
+        );
+        assert_eq!(
+            caps.summary(),
+            "data-query (legislation), data-mutate (notes, ddl), ai-inference (max 500 tokens), \
+             events-emit, app-invoke (compliance-checker)"
+        );
+        assert!(caps.ai_embeddings.is_none());
+
+        let query = caps.data_query.as_ref().unwrap();
+        assert!(query.allows("Legislation"));
+        assert!(!query.allows("notes"));
+        assert!(TableGrant::default().allows("anything"));
+
+        let invoke = caps.app_invoke.as_ref().unwrap();
+        assert!(invoke.allows("compliance-checker"));
+        assert!(!invoke.allows("report-generator"));
+        assert!(AppGrant::default().allows("anything"));
+
+        assert_eq!(
+            AppManifest::from_json("{}").unwrap(),
+            AppManifest::default()
+        );
+        assert_eq!(AppManifest::default().capabilities.summary(), "none");
+        assert_eq!(AppManifest::unrestricted().capabilities.granted().len(), 10);
+    }
+
+    #[test]
+    fn rejects_unknown_capabilities() {
+        let err = AppManifest::from_json(r#"{"capabilities": {"network": {}}}"#).unwrap_err();
+        assert!(err.contains("network"), "{err}");
+        assert!(
+            AppManifest::from_json(r#"{"capabilities": {"data-query": {"table": []}}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn manifest_sits_beside_component() {
+        assert_eq!(
+            AppManifest::path_for(Path::new("apps/drrp_polisher.wasm")),
+            Path::new("apps/drrp_polisher.manifest.json")
+        );
+        assert_eq!(
+            AppManifest::load_for(Path::new("/nonexistent/app.wasm")),
+            Ok(None)
+        );
+    }
+}
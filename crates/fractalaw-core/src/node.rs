@@ -0,0 +1,137 @@
+//! Node identity: the Ed25519 key a node signs audit checkpoints with.
+//!
+//! The key is generated on first use and kept as PKCS#8 at
+//! `<data-dir>/node.key`, readable only by its owner. The node id is derived
+//! from the public key, so entries a node records name the key that
+//! vouches for them.
+
+use std::io::Write;
+use std::path::Path;
+
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair};
+
+/// File name of the node key inside the data directory.
+pub const NODE_KEY_FILE: &str = "node.key";
+
+/// A node's Ed25519 signing key.
+#[derive(Debug)]
+pub struct NodeKey {
+    pair: Ed25519KeyPair,
+}
+
+impl NodeKey {
+    /// Generate a key, returning it with its PKCS#8 encoding.
+    pub fn generate() -> Result<(Self, Vec<u8>), String> {
+        let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|_| "generating node key failed".to_string())?;
+        let key = Self::from_pkcs8(pkcs8.as_ref())?;
+        Ok((key, pkcs8.as_ref().to_vec()))
+    }
+
+    pub fn from_pkcs8(pkcs8: &[u8]) -> Result<Self, String> {
+        let pair =
+            Ed25519KeyPair::from_pkcs8(pkcs8).map_err(|e| format!("invalid node key: {e}"))?;
+        Ok(Self { pair })
+    }
+
+    /// Load the key at `path`, generating and saving one if there is none.
+    pub fn load_or_generate(path: &Path) -> Result<Self, String> {
+        match std::fs::read(path) {
+            Ok(pkcs8) => Self::from_pkcs8(&pkcs8).map_err(|e| format!("{}: {e}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                let (key, pkcs8) = Self::generate()?;
+                write_private(path, &pkcs8).map_err(|e| format!("{}: {e}", path.display()))?;
+                tracing::info!(node_id = %key.node_id(), path = %path.display(), "generated node key");
+                Ok(key)
+            }
+            Err(e) => Err(format!("{}: {e}", path.display())),
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        self.pair
+            .public_key()
+            .as_ref()
+            .try_into()
+            .expect("Ed25519 public keys are 32 bytes")
+    }
+
+    /// `node-` and the first 8 bytes of the public key, in hex.
+    pub fn node_id(&self) -> String {
+        format!("node-{}", hex(&self.public_key()[..8]))
+    }
+
+    pub fn sign(&self, message: &[u8]) -> [u8; 64] {
+        self.pair
+            .sign(message)
+            .as_ref()
+            .try_into()
+            .expect("Ed25519 signatures are 64 bytes")
+    }
+}
+
+/// Lower-case hex of `bytes`.
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Parse a hex-encoded Ed25519 public key.
+pub fn parse_public_key(s: &str) -> Result<[u8; 32], String> {
+    parse_hex32(s).ok_or_else(|| format!("'{s}' is not a 64-digit hex public key"))
+}
+
+/// Parse 64 hex digits, such as a public key or an entry hash.
+pub(crate) fn parse_hex32(s: &str) -> Option<[u8; 32]> {
+    if s.len() != 64 || !s.is_ascii() {
+        return None;
+    }
+    let mut bytes = [0; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Create `path` with `contents`, readable only by its owner.
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options.open(path)?.write_all(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_is_generated_once_and_reloaded() {
+        let dir = std::env::temp_dir().join(format!("fractalaw-node-key-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join(NODE_KEY_FILE);
+
+        let key = NodeKey::load_or_generate(&path).unwrap();
+        let again = NodeKey::load_or_generate(&path).unwrap();
+        assert_eq!(key.public_key(), again.public_key());
+        assert!(key.node_id().starts_with("node-"));
+        assert_eq!(key.node_id().len(), "node-".len() + 16);
+        assert_eq!(
+            parse_public_key(&hex(&key.public_key())),
+            Ok(key.public_key())
+        );
+        assert!(parse_public_key("abc").is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
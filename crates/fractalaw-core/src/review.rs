@@ -0,0 +1,25 @@
+//! Reviewer decisions on AI classifications.
+
+use serde::{Deserialize, Serialize};
+
+/// How a reviewer resolved a classification conflict or low-confidence prediction.
+///
+/// Recorded in `classification_reviews`; accepted AI labels override the
+/// ground-truth family in the next classifier build.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReviewDecision {
+    /// The AI-predicted family is correct.
+    AcceptAi,
+    /// The existing ground-truth family stands; the AI label is rejected.
+    KeepTruth,
+}
+
+impl ReviewDecision {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::AcceptAi => "accept_ai",
+            Self::KeepTruth => "keep_truth",
+        }
+    }
+}
@@ -0,0 +1,392 @@
+//! Scheduled micro-app runs: cron expressions, tasks, and clocks.
+
+use std::sync::Mutex;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`),
+/// evaluated in UTC.
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`),
+/// and comma lists; months and weekdays also accept names (`jan`, `mon`).
+/// Day-of-week 0 and 7 are both Sunday. As in Vixie cron, when both
+/// day-of-month and day-of-week are restricted a day matching either runs.
+/// `@yearly`, `@monthly`, `@weekly`, `@daily` and `@hourly` are shorthands.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How far ahead [`Cron::next_after`] searches; covers leap-day schedules.
+const SEARCH_YEARS: i64 = 8;
+
+impl std::str::FromStr for Cron {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expanded = match s.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "cron expression '{s}' must have 5 fields (minute hour day month weekday)"
+            ));
+        };
+        let err = |e: String| format!("invalid cron expression '{s}': {e}");
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAYS).map_err(err)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[]).map_err(err)?,
+            hours: parse_field(hour, 0, 23, &[]).map_err(err)?,
+            days: parse_field(day, 1, 31, &[]).map_err(err)?,
+            months: parse_field(month, 1, 12, &MONTHS).map_err(err)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+}
+
+/// Parse one field into a bitmask of allowed values. `names[i]` stands for
+/// `min + i`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        let v = match names.iter().position(|n| *n == lower) {
+            Some(i) => min + i as u32,
+            None => s.parse().map_err(|_| format!("'{s}' is not a number"))?,
+        };
+        if v < min || v > max {
+            return Err(format!("{v} is outside {min}-{max}"));
+        }
+        Ok(v)
+    };
+
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step in '{item}'")),
+            },
+            None => (item, 1),
+        };
+        let (lo, hi) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((lo, hi)) => (value(lo)?, value(hi)?),
+                // `5/15` means every 15 from 5.
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let v = value(range)?;
+                    (v, v)
+                }
+            },
+        };
+        if lo > hi {
+            return Err(format!("range '{range}' is backwards"));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+impl Cron {
+    /// The first matching minute strictly after `after`, or `None` if the
+    /// expression never matches (e.g. `0 0 31 2 *`).
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::days(366 * SEARCH_YEARS);
+        let mut t = start;
+        while t <= limit {
+            if !has(self.months, t.month()) {
+                let (y, m) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = midnight(NaiveDate::from_ymd_opt(y, m, 1)?);
+            } else if !self.day_matches(t) {
+                t = midnight(t.date_naive().succ_opt()?);
+            } else if !has(self.hours, t.hour()) {
+                t = t.with_minute(0)? + Duration::hours(1);
+            } else if !has(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, t: DateTime<Utc>) -> bool {
+        let day = has(self.days, t.day());
+        let weekday = has(self.weekdays, t.weekday().num_days_from_sunday());
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+}
+
+fn has(mask: u64, v: u32) -> bool {
+    mask & (1 << v) != 0
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+}
+
+/// A micro-app run requested for a time or a recurring cron schedule.
+///
+/// Stored in `scheduled_tasks`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScheduledTask {
+    /// Assigned when the task is stored.
+    pub id: u64,
+    pub name: String,
+    /// Recurring schedule; exactly one of `cron` and `run_at` is set.
+    pub cron: Option<String>,
+    /// One-shot run time, in Unix seconds.
+    pub run_at: Option<u64>,
+    /// App to run; it sees `payload` through `events-receive`.
+    pub target_app: String,
+    pub payload: Vec<u8>,
+    /// App (or `cli`) that created the task; only it may cancel the task.
+    pub owner_app: String,
+}
+
+impl ScheduledTask {
+    /// Unix time of the first run after `now`, validating the schedule.
+    ///
+    /// A one-shot time already in the past runs at the next scheduler tick.
+    pub fn first_run(&self, now: DateTime<Utc>) -> Result<i64, String> {
+        match (&self.cron, self.run_at) {
+            (Some(expr), None) => {
+                let cron: Cron = expr.parse()?;
+                cron.next_after(now)
+                    .map(|t| t.timestamp())
+                    .ok_or_else(|| format!("cron expression '{expr}' never matches"))
+            }
+            (None, Some(run_at)) => {
+                i64::try_from(run_at).map_err(|_| format!("run-at {run_at} is out of range"))
+            }
+            _ => Err("exactly one of cron and run-at must be set".into()),
+        }
+    }
+
+    /// Unix time of the run after one made at `now`, or `None` once a
+    /// one-shot task has run.
+    ///
+    /// Runs missed while the host was down are not replayed: a recurring
+    /// task fires once when it is found overdue and then resumes from `now`.
+    pub fn next_run_after(&self, now: DateTime<Utc>) -> Option<i64> {
+        let cron: Cron = self.cron.as_deref()?.parse().ok()?;
+        cron.next_after(now).map(|t| t.timestamp())
+    }
+}
+
+/// Lifecycle of a scheduled task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    /// Waiting for its next run.
+    Active,
+    /// A one-shot task whose run succeeded.
+    Done,
+    /// A one-shot task whose run failed.
+    Failed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Done => "done",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Source of the current time for the scheduler, so tests can control it.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// The system clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when told to.
+#[derive(Debug)]
+pub struct ManualClock(Mutex<DateTime<Utc>>);
+
+impl ManualClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self(Mutex::new(now))
+    }
+
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.0.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.0.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.0.lock().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> Option<DateTime<Utc>> {
+        expr.parse::<Cron>().unwrap().next_after(at(after))
+    }
+
+    #[test]
+    fn finds_next_matching_minute() {
+        assert_eq!(
+            next("30 2 * * *", "2026-03-01T01:00:00Z"),
+            Some(at("2026-03-01T02:30:00Z"))
+        );
+        // Strictly after: the current minute does not count.
+        assert_eq!(
+            next("30 2 * * *", "2026-03-01T02:30:00Z"),
+            Some(at("2026-03-02T02:30:00Z"))
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2026-03-01T10:07:42Z"),
+            Some(at("2026-03-01T10:15:00Z"))
+        );
+        assert_eq!(
+            next("@monthly", "2026-12-15T00:00:00Z"),
+            Some(at("2027-01-01T00:00:00Z"))
+        );
+        assert_eq!(
+            next("0 9 * * mon-fri", "2026-10-17T12:00:00Z"), // a Saturday
+            Some(at("2026-10-19T09:00:00Z"))
+        );
+        assert_eq!(
+            next("0 0 29 feb *", "2026-03-01T00:00:00Z"),
+            Some(at("2028-02-29T00:00:00Z"))
+        );
+        assert_eq!(next("0 0 31 2 *", "2026-03-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // 13th of the month or any Friday.
+        let cron: Cron = "0 0 13 * 5".parse().unwrap();
+        assert_eq!(
+            cron.next_after(at("2026-10-01T00:00:00Z")),
+            Some(at("2026-10-02T00:00:00Z")) // Friday 2nd
+        );
+        assert_eq!(
+            cron.next_after(at("2026-10-12T00:00:00Z")),
+            Some(at("2026-10-13T00:00:00Z")) // Tuesday 13th
+        );
+        // Sunday as 7.
+        assert_eq!(
+            next("0 0 * * 7", "2026-10-17T00:00:00Z"),
+            Some(at("2026-10-18T00:00:00Z"))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() {
+        for expr in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "5-1 * * * *",
+            "*/0 * * * *",
+            "* * * foo *",
+        ] {
+            assert!(expr.parse::<Cron>().is_err(), "{expr:?} parsed");
+        }
+    }
+
+    fn task(cron: Option<&str>, run_at: Option<u64>) -> ScheduledTask {
+        ScheduledTask {
+            id: 0,
+            name: "nightly-polish".into(),
+            cron: cron.map(Into::into),
+            run_at,
+            target_app: "drrp-polisher".into(),
+            payload: Vec::new(),
+            owner_app: "cli".into(),
+        }
+    }
+
+    #[test]
+    fn task_runs() {
+        let now = at("2026-10-18T12:00:00Z");
+        let nightly = task(Some("0 2 * * *"), None);
+        assert_eq!(
+            nightly.first_run(now),
+            Ok(at("2026-10-19T02:00:00Z").timestamp())
+        );
+        assert_eq!(
+            nightly.next_run_after(now),
+            Some(at("2026-10-19T02:00:00Z").timestamp())
+        );
+
+        let once = task(None, Some(1_800_000_000));
+        assert_eq!(once.first_run(now), Ok(1_800_000_000));
+        assert_eq!(once.next_run_after(now), None);
+
+        assert!(task(None, None).first_run(now).is_err());
+        assert!(task(Some("@daily"), Some(1)).first_run(now).is_err());
+        assert!(task(Some("bad"), None).first_run(now).is_err());
+    }
+
+    #[test]
+    fn manual_clock_moves_only_when_told() {
+        let clock = ManualClock::new(at("2026-10-18T12:00:00Z"));
+        assert_eq!(clock.now(), at("2026-10-18T12:00:00Z"));
+        clock.advance(Duration::hours(1));
+        assert_eq!(clock.now(), at("2026-10-18T13:00:00Z"));
+    }
+}
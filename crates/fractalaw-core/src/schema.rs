@@ -136,7 +136,7 @@ pub mod esh {
             Field::new("total_extents", DataType::Int32, true),
             // 1.12 Change Logs (1)
             Field::new("change_log", DataType::Utf8, true),
-            // 1.13 AI Classification (7)
+            // 1.13 AI Classification (10)
             Field::new("classified_domain", list_utf8.clone(), true),
             Field::new("classified_family", DataType::Utf8, true),
             Field::new("classified_subjects", list_utf8, true),
@@ -144,6 +144,9 @@ pub mod esh {
             Field::new("classification_model", DataType::Utf8, true),
             Field::new("classified_at", timestamp_ns_utc(), true),
             Field::new("classification_status", DataType::Utf8, true),
+            Field::new("classified_sub_family", DataType::Utf8, true),
+            Field::new("sub_family_confidence", DataType::Float32, true),
+            Field::new("sub_family_status", DataType::Utf8, true),
             // 1.14 Timestamps (2)
             Field::new("created_at", timestamp_ns_utc(), false),
             Field::new("updated_at", timestamp_ns_utc(), false),
@@ -318,7 +321,7 @@ mod tests {
 
     #[test]
     fn legislation_schema_field_count() {
-        assert_eq!(esh::legislation_schema().fields().len(), 85);
+        assert_eq!(esh::legislation_schema().fields().len(), 88);
     }
 
     #[test]
@@ -615,6 +618,9 @@ mod tests {
         assert!(schema.field_with_name("classification_model").is_ok());
         assert!(schema.field_with_name("classified_at").is_ok());
         assert!(schema.field_with_name("classification_status").is_ok());
+        assert!(schema.field_with_name("classified_sub_family").is_ok());
+        assert!(schema.field_with_name("sub_family_confidence").is_ok());
+        assert!(schema.field_with_name("sub_family_status").is_ok());
     }
 
     #[test]
@@ -641,6 +647,9 @@ mod tests {
             "classification_model",
             "classified_at",
             "classification_status",
+            "classified_sub_family",
+            "sub_family_confidence",
+            "sub_family_status",
         ] {
             assert!(
                 schema.field_with_name(col).unwrap().is_nullable(),
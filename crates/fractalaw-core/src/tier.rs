@@ -0,0 +1,193 @@
+//! Micro-app resource tiers.
+//!
+//! A tier bundles the limits a run gets: fuel, wall-clock time, linear
+//! memory, distinct DuckDB tables touched, and tokens generated through
+//! `ai-inference`. Apps pick a tier in their manifest (`"tier": "heavy"`);
+//! `standard` is the default.
+//!
+//! | Tier          | Memory | Fuel | Deadline | Tables | Inference tokens |
+//! |---------------|--------|------|----------|--------|------------------|
+//! | `lightweight` | 16 MB  | 100M | 5s       | 4      | 10,000           |
+//! | `standard`    | 64 MB  | 1B   | 30s      | 16     | 100,000          |
+//! | `heavy`       | 256 MB | 10B  | 120s     | 64     | 1,000,000        |
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// A named bundle of run limits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Tier {
+    /// Classifiers, validators, transformers.
+    Lightweight,
+    /// Report generators, compliance checkers.
+    #[default]
+    Standard,
+    /// Large document importers, batch processors.
+    Heavy,
+}
+
+/// The limits of a [`Tier`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TierLimits {
+    pub fuel: u64,
+    pub deadline: Duration,
+    /// Largest linear memory an instance may grow to, in bytes.
+    pub memory_bytes: usize,
+    /// Distinct tables a run may read or write.
+    pub tables: usize,
+    pub inference_tokens: u64,
+}
+
+impl Tier {
+    pub const ALL: [Tier; 3] = [Tier::Lightweight, Tier::Standard, Tier::Heavy];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Lightweight => "lightweight",
+            Self::Standard => "standard",
+            Self::Heavy => "heavy",
+        }
+    }
+
+    pub fn limits(&self) -> TierLimits {
+        const MIB: usize = 1024 * 1024;
+        match self {
+            Self::Lightweight => TierLimits {
+                fuel: 100_000_000,
+                deadline: Duration::from_secs(5),
+                memory_bytes: 16 * MIB,
+                tables: 4,
+                inference_tokens: 10_000,
+            },
+            Self::Standard => TierLimits {
+                fuel: 1_000_000_000,
+                deadline: Duration::from_secs(30),
+                memory_bytes: 64 * MIB,
+                tables: 16,
+                inference_tokens: 100_000,
+            },
+            Self::Heavy => TierLimits {
+                fuel: 10_000_000_000,
+                deadline: Duration::from_secs(120),
+                memory_bytes: 256 * MIB,
+                tables: 64,
+                inference_tokens: 1_000_000,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Tier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Tier {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|tier| tier.as_str() == s)
+            .ok_or_else(|| format!("unknown tier '{s}' (expected lightweight, standard or heavy)"))
+    }
+}
+
+/// Which tier limit a run exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Limit {
+    Fuel,
+    Deadline,
+    Memory,
+    Tables,
+    InferenceTokens,
+}
+
+impl Limit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fuel => "fuel",
+            Self::Deadline => "deadline",
+            Self::Memory => "memory",
+            Self::Tables => "tables",
+            Self::InferenceTokens => "inference_tokens",
+        }
+    }
+}
+
+/// A run stopped or refused for exceeding one of its tier's limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitExceeded {
+    pub tier: Tier,
+    pub limit: Limit,
+    /// The limit's value: fuel units, seconds, bytes, tables or tokens.
+    pub max: u64,
+}
+
+impl LimitExceeded {
+    pub fn new(tier: Tier, limit: Limit, limits: &TierLimits) -> Self {
+        let max = match limit {
+            Limit::Fuel => limits.fuel,
+            Limit::Deadline => limits.deadline.as_secs(),
+            Limit::Memory => limits.memory_bytes as u64,
+            Limit::Tables => limits.tables as u64,
+            Limit::InferenceTokens => limits.inference_tokens,
+        };
+        Self { tier, limit, max }
+    }
+}
+
+impl fmt::Display for LimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let max = match self.limit {
+            Limit::Fuel => format!("{} fuel", self.max),
+            Limit::Deadline => format!("{}s", self.max),
+            Limit::Memory => format!("{} MiB of memory", self.max / (1024 * 1024)),
+            Limit::Tables => format!("{} tables", self.max),
+            Limit::InferenceTokens => format!("{} inference tokens", self.max),
+        };
+        write!(f, "exceeded the {} tier limit of {max}", self.tier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tiers_parse_and_scale() {
+        assert_eq!("heavy".parse::<Tier>(), Ok(Tier::Heavy));
+        assert!("huge".parse::<Tier>().is_err());
+        assert_eq!(Tier::default(), Tier::Standard);
+
+        let limits: Vec<TierLimits> = Tier::ALL.iter().map(Tier::limits).collect();
+        for pair in limits.windows(2) {
+            assert!(pair[0].fuel < pair[1].fuel);
+            assert!(pair[0].deadline < pair[1].deadline);
+            assert!(pair[0].memory_bytes < pair[1].memory_bytes);
+            assert!(pair[0].tables < pair[1].tables);
+            assert!(pair[0].inference_tokens < pair[1].inference_tokens);
+        }
+    }
+
+    #[test]
+    fn limit_exceeded_describes_the_limit() {
+        let tier = Tier::Lightweight;
+        let exceeded = LimitExceeded::new(tier, Limit::Memory, &tier.limits());
+        assert_eq!(exceeded.max, 16 * 1024 * 1024);
+        assert_eq!(
+            exceeded.to_string(),
+            "exceeded the lightweight tier limit of 16 MiB of memory"
+        );
+        assert_eq!(
+            serde_json::to_value(exceeded).unwrap(),
+            serde_json::json!({"tier": "lightweight", "limit": "memory", "max": 16777216})
+        );
+    }
+}
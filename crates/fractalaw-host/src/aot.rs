@@ -0,0 +1,121 @@
+//! On-disk cache of ahead-of-time compiled components.
+//!
+//! Compiling a component with Cranelift takes far longer than loading a
+//! precompiled `.cwasm` artifact, so compiled components are serialized to
+//! `<dir>/<key>.cwasm`. The key covers the component's SHA-256, a hash of the
+//! engine's compilation settings (including the Wasmtime version), and the
+//! target architecture: a changed component or engine gets a new key, and
+//! [`AotCache::prune`] removes the entries nothing uses any more.
+
+use std::collections::HashSet;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use ring::digest;
+use wasmtime::Engine;
+use wasmtime::component::Component;
+
+/// Directory of precompiled components.
+#[derive(Debug, Clone)]
+pub struct AotCache {
+    dir: PathBuf,
+}
+
+impl AotCache {
+    /// Use `dir` as the cache, creating it if needed.
+    pub fn open(dir: &Path) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| anyhow::anyhow!("failed to create {}: {e}", dir.display()))?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Cache key for component `bytes` compiled by `engine`.
+    pub fn key(engine: &Engine, bytes: &[u8]) -> String {
+        let component: String = digest::digest(&digest::SHA256, bytes)
+            .as_ref()
+            .iter()
+            .map(|b| format!("{b:02x}"))
+            .collect();
+        let mut hasher = DefaultHasher::new();
+        engine.precompile_compatibility_hash().hash(&mut hasher);
+        format!(
+            "{component}-{:016x}-{}",
+            hasher.finish(),
+            std::env::consts::ARCH
+        )
+    }
+
+    /// Load the component at `wasm_path`, from its precompiled artifact if
+    /// one is cached and compiling and caching it otherwise. Returns the
+    /// component and whether it had to be compiled.
+    pub async fn load(
+        &self,
+        engine: &Engine,
+        wasm_path: &Path,
+    ) -> anyhow::Result<(Component, bool)> {
+        let bytes = tokio::fs::read(wasm_path).await?;
+        let key = Self::key(engine, &bytes);
+        let cwasm = self.path(&key);
+
+        if cwasm.is_file() {
+            // SAFETY: artifacts are only written by `store` below, from
+            // `Component::serialize` on an engine with the same settings
+            // (the key covers them). Wasmtime also rejects artifacts built
+            // for another version or configuration.
+            match unsafe { Component::deserialize_file(engine, &cwasm) } {
+                Ok(component) => return Ok((component, false)),
+                Err(e) => tracing::warn!(
+                    path = %cwasm.display(),
+                    "discarding unusable precompiled component: {e:#}"
+                ),
+            }
+        }
+
+        let component = Component::new(engine, &bytes)?;
+        self.store(&key, &component)?;
+        tracing::debug!(path = %wasm_path.display(), %key, "precompiled component");
+        Ok((component, true))
+    }
+
+    /// Write the artifact to a temporary file and rename it into place, so
+    /// concurrent loaders never see a partial artifact.
+    fn store(&self, key: &str, component: &Component) -> anyhow::Result<()> {
+        let path = self.path(key);
+        let tmp = self.dir.join(format!(".{key}.{}.tmp", std::process::id()));
+        std::fs::write(&tmp, component.serialize()?)
+            .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| anyhow::anyhow!("failed to write {}: {e}", path.display()))
+    }
+
+    /// Remove every artifact whose key is not in `keep`, e.g. those of
+    /// replaced components or an older engine. Returns how many were removed.
+    pub fn prune(&self, keep: &HashSet<String>) -> anyhow::Result<usize> {
+        let mut removed = 0;
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(key) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".cwasm"))
+            else {
+                continue;
+            };
+            if !keep.contains(key) {
+                std::fs::remove_file(&path)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.cwasm"))
+    }
+}
@@ -0,0 +1,211 @@
+//! Linking host interfaces according to an app's [`Capabilities`].
+//!
+//! [`create_linker_for`] links the real implementation of each granted
+//! interface and a denying stub for every other one, so components that
+//! import an interface they were not granted still instantiate. A denied call
+//! returns [`CAPABILITY_DENIED`] (or no trigger, for `events-receive`), logs a
+//! warning, and adds a `capability-denied` entry to the run's audit log.
+//! `audit-log` is always linked, as are the WASI p2 interfaces the wasip1
+//! adapter needs; guest stdio only reaches the host with the `stdio` grant.
+
+use fractalaw_core::Capabilities;
+use wasmtime::Engine;
+use wasmtime::component::{HasData, HasSelf, Linker};
+
+use crate::fractal::app::{
+    ai_classify, ai_embeddings, ai_inference, app_invoke, audit_log, data_mutate, data_query,
+    events_emit, events_receive, events_schedule,
+};
+use crate::{AuditRecord, HostState};
+
+/// Error code returned by host functions the app was not granted.
+pub const CAPABILITY_DENIED: u32 = 403;
+
+/// Create a linker exposing only the host interfaces in `capabilities`.
+pub fn create_linker_for(
+    engine: &Engine,
+    capabilities: &Capabilities,
+) -> anyhow::Result<Linker<HostState>> {
+    let mut linker = Linker::new(engine);
+    audit_log::add_to_linker::<HostState, HasSelf<HostState>>(&mut linker, |state| state)?;
+
+    macro_rules! link {
+        ($grant:expr, $iface:ident) => {
+            if $grant.is_some() {
+                $iface::add_to_linker::<HostState, HasSelf<HostState>>(&mut linker, |state| state)?;
+            } else {
+                $iface::add_to_linker::<HostState, DeniedData>(&mut linker, |state| Denied(state))?;
+            }
+        };
+    }
+    link!(capabilities.data_query, data_query);
+    link!(capabilities.data_mutate, data_mutate);
+    link!(capabilities.ai_embeddings, ai_embeddings);
+    link!(capabilities.ai_classify, ai_classify);
+    link!(capabilities.ai_inference, ai_inference);
+    link!(capabilities.events_emit, events_emit);
+    link!(capabilities.events_receive, events_receive);
+    link!(capabilities.events_schedule, events_schedule);
+    link!(capabilities.app_invoke, app_invoke);
+
+    // Wire up WASI p2 interfaces (cli, io, filesystem, clocks) required by the wasip1 adapter
+    wasmtime_wasi::p2::add_to_linker_async(&mut linker)?;
+    Ok(linker)
+}
+
+/// Host functions of an interface the app was not granted.
+struct Denied<'a>(&'a mut HostState);
+
+struct DeniedData;
+
+impl HasData for DeniedData {
+    type Data<'a> = Denied<'a>;
+}
+
+impl Denied<'_> {
+    /// Audit a call to `function` of the ungranted `capability` and return
+    /// the message for the guest.
+    fn deny(&mut self, capability: &str, function: &str) -> String {
+        let message = format!("capability '{capability}' not granted");
+        tracing::warn!(
+            app = %self.0.app_name,
+            capability,
+            function,
+            "capability denied"
+        );
+        self.0.audit_entries.push(AuditRecord {
+            event_type: "capability-denied".into(),
+            resource: capability.into(),
+            detail: format!("{function} called without the '{capability}' grant"),
+            timestamp: chrono::Utc::now(),
+        });
+        message
+    }
+
+    fn ai_error(&mut self, capability: &str, function: &str) -> ai_embeddings::AiError {
+        ai_embeddings::AiError {
+            code: CAPABILITY_DENIED,
+            message: self.deny(capability, function),
+        }
+    }
+
+    fn event_error(&mut self, capability: &str, function: &str) -> events_emit::EventError {
+        events_emit::EventError {
+            code: CAPABILITY_DENIED,
+            message: self.deny(capability, function),
+        }
+    }
+}
+
+impl data_query::Host for Denied<'_> {
+    async fn query(&mut self, _sql: String) -> Result<Vec<u8>, data_query::QueryError> {
+        Err(data_query::QueryError {
+            code: CAPABILITY_DENIED,
+            message: self.deny("data-query", "query"),
+        })
+    }
+}
+
+impl data_mutate::Host for Denied<'_> {
+    async fn insert(
+        &mut self,
+        _table: String,
+        _data: Vec<u8>,
+    ) -> Result<u64, data_mutate::MutateError> {
+        Err(data_mutate::MutateError {
+            code: CAPABILITY_DENIED,
+            message: self.deny("data-mutate", "insert"),
+        })
+    }
+
+    async fn execute(&mut self, _sql: String) -> Result<u64, data_mutate::MutateError> {
+        Err(data_mutate::MutateError {
+            code: CAPABILITY_DENIED,
+            message: self.deny("data-mutate", "execute"),
+        })
+    }
+}
+
+impl ai_embeddings::Host for Denied<'_> {
+    async fn embed(&mut self, _text: String) -> Result<Vec<f32>, ai_embeddings::AiError> {
+        Err(self.ai_error("ai-embeddings", "embed"))
+    }
+
+    async fn embed_batch(
+        &mut self,
+        _texts: Vec<String>,
+    ) -> Result<Vec<Vec<f32>>, ai_embeddings::AiError> {
+        Err(self.ai_error("ai-embeddings", "embed-batch"))
+    }
+}
+
+impl ai_classify::Host for Denied<'_> {
+    async fn classify(
+        &mut self,
+        _text: String,
+        _categories: Vec<String>,
+    ) -> Result<Vec<ai_classify::Classification>, ai_embeddings::AiError> {
+        Err(self.ai_error("ai-classify", "classify"))
+    }
+
+    async fn classify_trained(
+        &mut self,
+        _text: String,
+        _taxonomy: ai_classify::Taxonomy,
+    ) -> Result<Vec<ai_classify::Classification>, ai_embeddings::AiError> {
+        Err(self.ai_error("ai-classify", "classify-trained"))
+    }
+}
+
+impl ai_inference::Host for Denied<'_> {
+    async fn generate(
+        &mut self,
+        _request: ai_inference::GenerateRequest,
+    ) -> Result<ai_inference::GenerateResponse, ai_embeddings::AiError> {
+        Err(self.ai_error("ai-inference", "generate"))
+    }
+}
+
+impl events_emit::Host for Denied<'_> {
+    async fn emit(
+        &mut self,
+        _event: events_emit::DomainEvent,
+    ) -> Result<events_emit::EventId, events_emit::EventError> {
+        Err(self.event_error("events-emit", "emit"))
+    }
+}
+
+impl events_receive::Host for Denied<'_> {
+    async fn trigger(&mut self) -> Option<events_receive::Delivery> {
+        self.deny("events-receive", "trigger");
+        None
+    }
+}
+
+impl events_schedule::Host for Denied<'_> {
+    async fn schedule(
+        &mut self,
+        _task: events_schedule::ScheduledTask,
+    ) -> Result<events_schedule::TaskId, events_emit::EventError> {
+        Err(self.event_error("events-schedule", "schedule"))
+    }
+
+    async fn cancel(
+        &mut self,
+        _id: events_schedule::TaskId,
+    ) -> Result<(), events_emit::EventError> {
+        Err(self.event_error("events-schedule", "cancel"))
+    }
+}
+
+impl app_invoke::Host for Denied<'_> {
+    async fn invoke(
+        &mut self,
+        _request: app_invoke::InvokeRequest,
+    ) -> Result<app_invoke::InvokeResponse, app_invoke::InvokeError> {
+        Err(app_invoke::InvokeError {
+            code: CAPABILITY_DENIED,
+            message: self.deny("app-invoke", "invoke"),
+        })
+    }
+}
@@ -0,0 +1,164 @@
+//! Delivery of logged events to subscribed micro-apps.
+//!
+//! `events-emit` appends to the DuckDB event log and queues a delivery per
+//! subscriber (see [`DuckStore::append_event`]). [`dispatch_events`] runs
+//! each subscriber's component for its due deliveries, exposing the event
+//! through `events-receive`. A delivery is marked done only after the run
+//! succeeds, so delivery is at-least-once: a failed run, or a host that
+//! stops mid-run, leaves it pending for a later dispatch. Failed runs are
+//! retried with exponential backoff and dead-lettered after
+//! [`DeliveryPolicy::max_attempts`].
+
+use std::path::Path;
+use std::time::Duration;
+
+use fractalaw_core::PendingDelivery;
+use fractalaw_store::DuckStore;
+
+use crate::fractal::app::events_emit::DomainEvent;
+use crate::fractal::app::events_receive::Delivery;
+use crate::{RunOptions, Runtime, TIER_FUEL};
+
+/// Retry and resource limits for subscriber runs.
+#[derive(Debug, Clone)]
+pub struct DeliveryPolicy {
+    /// Failed runs before a delivery is dead-lettered.
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubles on each further failure.
+    pub retry_delay: Duration,
+    /// Fuel budget for each subscriber run, lowered to the subscriber's tier
+    /// limit.
+    pub fuel: u64,
+}
+
+impl Default for DeliveryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            retry_delay: Duration::from_secs(30),
+            fuel: TIER_FUEL,
+        }
+    }
+}
+
+impl DeliveryPolicy {
+    /// Delay before retrying a delivery that has now failed `attempts` times.
+    pub fn retry_after(&self, attempts: u32) -> Duration {
+        self.retry_delay
+            .saturating_mul(1u32 << attempts.saturating_sub(1).min(16))
+    }
+}
+
+/// Outcome of a [`dispatch_events`] pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DispatchReport {
+    pub delivered: usize,
+    /// Failed runs that will be retried.
+    pub retrying: usize,
+    pub dead_lettered: usize,
+}
+
+/// Run subscribers for due deliveries until none remain or `limit` runs
+/// have been made.
+///
+/// Components run on `runtime`, so each is compiled once per runtime.
+/// `options` supplies the host resources for each run; the dispatcher sets
+/// the trigger, and attaches a connection to `duck` when no store is given.
+/// Events emitted by subscribers are delivered in the same pass.
+pub async fn dispatch_events<F>(
+    runtime: &Runtime,
+    duck: &DuckStore,
+    policy: &DeliveryPolicy,
+    limit: usize,
+    mut options: F,
+) -> anyhow::Result<DispatchReport>
+where
+    F: FnMut(&PendingDelivery) -> anyhow::Result<RunOptions>,
+{
+    let mut report = DispatchReport::default();
+    let mut runs = 0;
+
+    while runs < limit {
+        let due = duck.due_deliveries(limit - runs)?;
+        if due.is_empty() {
+            break;
+        }
+        for pending in due {
+            runs += 1;
+            let mut opts = options(&pending)?;
+            if opts.duck.is_none() {
+                opts.duck = Some(duck.try_clone()?);
+            }
+            opts.trigger = Some(delivery(&pending));
+
+            let outcome = match runtime
+                .run(Path::new(&pending.component), policy.fuel, opts)
+                .await
+            {
+                Ok(result) => result.output,
+                Err(e) => Err(format!("{e:#}")),
+            };
+
+            match outcome {
+                Ok(_) => {
+                    duck.mark_delivered(pending.event_id, &pending.subscriber)?;
+                    report.delivered += 1;
+                }
+                Err(error) => {
+                    let attempts = pending.attempts + 1;
+                    let dead = attempts >= policy.max_attempts;
+                    let retry_after = policy.retry_after(attempts);
+                    duck.record_delivery_failure(
+                        pending.event_id,
+                        &pending.subscriber,
+                        &error,
+                        retry_after.as_secs(),
+                        dead,
+                    )?;
+                    tracing::warn!(
+                        event_id = pending.event_id,
+                        subscriber = %pending.subscriber,
+                        attempts,
+                        dead,
+                        %error,
+                        "event delivery failed"
+                    );
+                    if dead {
+                        report.dead_lettered += 1;
+                    } else {
+                        report.retrying += 1;
+                    }
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+fn delivery(pending: &PendingDelivery) -> Delivery {
+    Delivery {
+        id: pending.event_id,
+        event: DomainEvent {
+            event_type: pending.event_type.clone(),
+            payload: pending.payload.clone(),
+            source_app: pending.source_app.clone(),
+        },
+        attempt: pending.attempts + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles() {
+        let policy = DeliveryPolicy {
+            retry_delay: Duration::from_secs(10),
+            ..DeliveryPolicy::default()
+        };
+        assert_eq!(policy.retry_after(1), Duration::from_secs(10));
+        assert_eq!(policy.retry_after(2), Duration::from_secs(20));
+        assert_eq!(policy.retry_after(4), Duration::from_secs(80));
+    }
+}
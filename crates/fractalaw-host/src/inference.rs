@@ -0,0 +1,1247 @@
+//! Pluggable backends for the `ai-inference` host function.
+//!
+//! [`InferenceProvider`] abstracts over where generation runs: the Anthropic
+//! Messages API, an OpenAI-compatible server (llama.cpp, vLLM, Ollama), or a
+//! local ONNX model. HTTP providers each take their own base URL, so an
+//! air-gapped hub can point at a server on its own network and tests can
+//! point at a stub.
+//!
+//! When a request carries a `json-schema`, providers constrain the reply
+//! natively where they can (Anthropic tool use, OpenAI `response_format`), and
+//! [`InferenceConfig::generate`] validates it, re-prompting with the
+//! validation errors until it conforms or the repair budget runs out.
+//!
+//! Every provider call goes through the host-wide [`RateLimiter`] and is
+//! retried on transient failures (HTTP 429/5xx, connection errors) with
+//! exponential backoff and jitter per the config's [`RetryPolicy`].
+//!
+//! With a [`ResponseCache`] attached, every successful request/response pair
+//! is recorded on disk. In [`CacheMode::Replay`] responses are served only
+//! from the cache and the provider is never called, so a recorded run can be
+//! reproduced offline, e.g. for an audit or in CI.
+
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use fractalaw_ai::json_schema;
+use fractalaw_ai::{CachedRequest, CachedResponse, ResponseCache};
+use serde_json::Value;
+
+use crate::fractal::app::ai_embeddings::AiError;
+use crate::fractal::app::ai_inference::{GenerateRequest, GenerateResponse, TokenUsage};
+
+/// Default base URL of the Claude Messages API.
+pub const DEFAULT_INFERENCE_URL: &str = "https://api.anthropic.com";
+
+/// Default model when `ANTHROPIC_MODEL` is unset.
+pub const DEFAULT_INFERENCE_MODEL: &str = "claude-sonnet-4-5-20250929";
+
+/// Default base URL for OpenAI-compatible servers (llama.cpp's `llama-server`).
+pub const DEFAULT_OPENAI_URL: &str = "http://localhost:8080";
+
+/// Default number of repair prompts after a reply fails schema validation.
+pub const DEFAULT_MAX_REPAIRS: u32 = 2;
+
+/// Tool name used to force structured output from the Messages API.
+const RESPONSE_TOOL: &str = "respond";
+
+/// Default maximum concurrent provider calls across the host.
+pub const DEFAULT_MAX_CONCURRENT_INFERENCE: usize = 4;
+
+/// Boxed future returned by [`InferenceProvider::generate`].
+pub type GenerateFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<GenerateResponse, ProviderError>> + Send + 'a>>;
+
+/// A failed provider call.
+#[derive(Debug)]
+pub struct ProviderError {
+    pub error: AiError,
+    /// Whether the same request may succeed if retried (rate limits,
+    /// overloaded or unreachable servers).
+    pub retryable: bool,
+    /// Delay requested by the server (`Retry-After`).
+    pub retry_after: Option<Duration>,
+}
+
+impl ProviderError {
+    fn transient(error: AiError, retry_after: Option<Duration>) -> Self {
+        Self {
+            error,
+            retryable: true,
+            retry_after,
+        }
+    }
+}
+
+impl From<AiError> for ProviderError {
+    fn from(error: AiError) -> Self {
+        Self {
+            error,
+            retryable: false,
+            retry_after: None,
+        }
+    }
+}
+
+/// A text-generation backend for `ai-inference`.
+///
+/// Errors use the `ai-error` codes: 2 when the backend fails, 3 when its
+/// response can't be parsed.
+pub trait InferenceProvider: Send + Sync {
+    /// Short provider name for logs and cache keys (e.g. `anthropic`).
+    fn name(&self) -> &str;
+
+    /// Where the provider generates: its base URL, or a local model's
+    /// directory.
+    fn endpoint(&self) -> &str;
+
+    /// Model the provider generates with.
+    fn model(&self) -> &str;
+
+    fn generate(&self, request: GenerateRequest) -> GenerateFuture<'_>;
+
+    /// Name, endpoint, and model, as recorded in cache keys.
+    fn id(&self) -> ProviderId {
+        ProviderId {
+            provider: self.name().to_string(),
+            endpoint: self.endpoint().to_string(),
+            model: self.model().to_string(),
+        }
+    }
+}
+
+/// Which backend generated a response. Part of every [`ResponseCache`] key,
+/// so backends that serve the same model name never share entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProviderId {
+    pub provider: String,
+    pub endpoint: String,
+    pub model: String,
+}
+
+/// How an [`InferenceConfig`] uses its [`ResponseCache`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMode {
+    /// Serve recorded responses; on a miss, call the provider and record.
+    Record,
+    /// Serve recorded responses only; a miss fails without calling the provider.
+    Replay,
+}
+
+impl std::str::FromStr for CacheMode {
+    type Err = String;
+
+    /// Parse `record` or `replay`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "record" => Ok(Self::Record),
+            "replay" => Ok(Self::Replay),
+            _ => Err(format!(
+                "unknown cache mode '{s}' (expected record or replay)"
+            )),
+        }
+    }
+}
+
+/// The inference backend attached to a [`crate::HostState`].
+pub struct InferenceConfig {
+    provider: Box<dyn InferenceProvider>,
+    /// Repair prompts sent after a reply fails `json-schema` validation.
+    pub max_repairs: u32,
+    pub retry: RetryPolicy,
+    limiter: RateLimiter,
+    cache: Option<(ResponseCache, CacheMode)>,
+}
+
+impl InferenceConfig {
+    pub fn new(provider: impl InferenceProvider + 'static) -> Self {
+        Self {
+            provider: Box::new(provider),
+            max_repairs: DEFAULT_MAX_REPAIRS,
+            retry: RetryPolicy::default(),
+            limiter: RateLimiter::host().clone(),
+            cache: None,
+        }
+    }
+
+    /// Serve-only config for [`CacheMode::Replay`]: no provider is contacted,
+    /// so no credentials are needed. `id` must match the recorded runs.
+    pub fn replay(cache: ResponseCache, id: ProviderId) -> Self {
+        Self::new(ReplayProvider { id }).with_cache(cache, CacheMode::Replay)
+    }
+
+    pub fn with_max_repairs(mut self, max_repairs: u32) -> Self {
+        self.max_repairs = max_repairs;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Use `limiter` instead of the host-wide [`RateLimiter::host`].
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.limiter = limiter;
+        self
+    }
+
+    /// Look requests up in `cache` before calling the provider.
+    pub fn with_cache(mut self, cache: ResponseCache, mode: CacheMode) -> Self {
+        self.cache = Some((cache, mode));
+        self
+    }
+
+    /// Choose a provider from the environment.
+    ///
+    /// `FRACTALAW_INFERENCE_PROVIDER` selects the backend (default `anthropic`):
+    ///
+    /// - `anthropic`: `ANTHROPIC_API_KEY`, `ANTHROPIC_MODEL`, `ANTHROPIC_BASE_URL`.
+    ///   Returns `None` when no API key is set.
+    /// - `openai`: `OPENAI_BASE_URL` (default [`DEFAULT_OPENAI_URL`]),
+    ///   `OPENAI_MODEL`, and optionally `OPENAI_API_KEY`.
+    /// - `local`: `FRACTALAW_INFERENCE_MODEL_DIR`, an ONNX model directory
+    ///   (requires the `local-inference` feature).
+    ///
+    /// `FRACTALAW_INFERENCE_MAX_RETRIES` overrides the retry count.
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        let config = Self::provider_from_env()?;
+        let Some(retries) = env_var("FRACTALAW_INFERENCE_MAX_RETRIES") else {
+            return Ok(config);
+        };
+        let max_retries = retries
+            .parse()
+            .map_err(|e| anyhow::anyhow!("FRACTALAW_INFERENCE_MAX_RETRIES: {e}"))?;
+        Ok(config.map(|c| {
+            let retry = RetryPolicy {
+                max_retries,
+                ..c.retry
+            };
+            c.with_retry(retry)
+        }))
+    }
+
+    fn provider_from_env() -> anyhow::Result<Option<Self>> {
+        let var = env_var;
+        let provider = var("FRACTALAW_INFERENCE_PROVIDER").unwrap_or_else(|| "anthropic".into());
+
+        match provider.as_str() {
+            "anthropic" => {
+                let Some(key) = var("ANTHROPIC_API_KEY") else {
+                    return Ok(None);
+                };
+                let model = Self::provider_id_from_env()?.model;
+                let mut provider = AnthropicProvider::new(key, model);
+                if let Some(url) = var("ANTHROPIC_BASE_URL") {
+                    provider = provider.with_base_url(url);
+                }
+                Ok(Some(Self::new(provider)))
+            }
+            "openai" => {
+                let url = var("OPENAI_BASE_URL").unwrap_or_else(|| DEFAULT_OPENAI_URL.into());
+                let model = Self::provider_id_from_env()?.model;
+                let mut provider = OpenAiProvider::new(url, model);
+                if let Some(key) = var("OPENAI_API_KEY") {
+                    provider = provider.with_api_key(key);
+                }
+                Ok(Some(Self::new(provider)))
+            }
+            "local" => {
+                #[cfg(feature = "local-inference")]
+                {
+                    let dir = var("FRACTALAW_INFERENCE_MODEL_DIR").ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "FRACTALAW_INFERENCE_MODEL_DIR must be set for local inference"
+                        )
+                    })?;
+                    let provider = LocalProvider::load(std::path::Path::new(&dir))?;
+                    Ok(Some(Self::new(provider)))
+                }
+                #[cfg(not(feature = "local-inference"))]
+                {
+                    anyhow::bail!("local inference support not compiled in")
+                }
+            }
+            other => anyhow::bail!(
+                "unknown FRACTALAW_INFERENCE_PROVIDER '{other}' (expected anthropic, openai, or local)"
+            ),
+        }
+    }
+
+    /// The [`ProviderId`] of the provider selected by
+    /// `FRACTALAW_INFERENCE_PROVIDER`, without connecting to it or loading
+    /// it. Pass this to [`InferenceConfig::replay`] to replay runs recorded
+    /// with that provider.
+    pub fn provider_id_from_env() -> anyhow::Result<ProviderId> {
+        let provider =
+            env_var("FRACTALAW_INFERENCE_PROVIDER").unwrap_or_else(|| "anthropic".into());
+        let id = |endpoint: String, model: String| ProviderId {
+            provider: provider.clone(),
+            endpoint,
+            model,
+        };
+        match provider.as_str() {
+            "anthropic" => Ok(id(
+                anthropic_base_url(
+                    &env_var("ANTHROPIC_BASE_URL").unwrap_or_else(|| DEFAULT_INFERENCE_URL.into()),
+                ),
+                env_var("ANTHROPIC_MODEL").unwrap_or_else(|| DEFAULT_INFERENCE_MODEL.into()),
+            )),
+            "openai" => Ok(id(
+                openai_base_url(
+                    &env_var("OPENAI_BASE_URL").unwrap_or_else(|| DEFAULT_OPENAI_URL.into()),
+                ),
+                env_var("OPENAI_MODEL").unwrap_or_else(|| "default".into()),
+            )),
+            "local" => {
+                let dir = env_var("FRACTALAW_INFERENCE_MODEL_DIR").ok_or_else(|| {
+                    anyhow::anyhow!("FRACTALAW_INFERENCE_MODEL_DIR must be set for local inference")
+                })?;
+                // Matches `Generator::model_name`.
+                let model = std::path::Path::new(&dir)
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string();
+                Ok(id(dir, model))
+            }
+            other => anyhow::bail!(
+                "unknown FRACTALAW_INFERENCE_PROVIDER '{other}' (expected anthropic, openai, or local)"
+            ),
+        }
+    }
+
+    pub fn provider(&self) -> &dyn InferenceProvider {
+        self.provider.as_ref()
+    }
+
+    /// Generate with the configured provider. Used by the `ai-inference` host
+    /// function and directly by host-side commands such as `fractalaw ask`.
+    ///
+    /// With a `json-schema`, the returned text is the validated JSON value.
+    pub async fn generate(&self, request: GenerateRequest) -> Result<GenerateResponse, AiError> {
+        self.generate_cached(request, None)
+            .await
+            .map(|(response, _)| response)
+    }
+
+    /// Like [`generate`](Self::generate), also reporting whether the response
+    /// was served from the cache.
+    ///
+    /// A request sent to the provider has its `max-tokens` clamped to
+    /// `token_limit`, and a `json-schema` request's repair attempts stop once
+    /// they have generated that many tokens between them. The cache key
+    /// keeps the request's own `max-tokens`, so replay does not depend on
+    /// how much budget was left when the response was recorded.
+    pub(crate) async fn generate_cached(
+        &self,
+        request: GenerateRequest,
+        token_limit: Option<u64>,
+    ) -> Result<(GenerateResponse, bool), AiError> {
+        let (mut response, cached) = self.lookup_or_generate(request, token_limit).await?;
+        response.provider = self.provider.name().to_string();
+        response.model = self.provider.model().to_string();
+        Ok((response, cached))
+    }
+
+    async fn lookup_or_generate(
+        &self,
+        mut request: GenerateRequest,
+        token_limit: Option<u64>,
+    ) -> Result<(GenerateResponse, bool), AiError> {
+        let clamp = |request: &mut GenerateRequest| {
+            if let Some(limit) = token_limit {
+                request.max_tokens = request.max_tokens.min(limit.min(u32::MAX as u64) as u32);
+            }
+        };
+        let Some((cache, mode)) = &self.cache else {
+            clamp(&mut request);
+            return Ok((self.generate_uncached(request, token_limit).await?, false));
+        };
+        let ProviderId {
+            provider,
+            endpoint,
+            model,
+        } = self.provider.id();
+        let key = CachedRequest {
+            provider,
+            endpoint,
+            model,
+            system_prompt: request.system_prompt.clone(),
+            user_prompt: request.user_prompt.clone(),
+            max_tokens: request.max_tokens,
+            temperature: request.temperature,
+            json_schema: request.json_schema.clone(),
+        };
+
+        match cache.get(&key) {
+            Ok(Some(hit)) => {
+                tracing::debug!(key = %key.key(), "inference served from cache");
+                let usage = TokenUsage {
+                    input_tokens: hit.input_tokens,
+                    output_tokens: hit.output_tokens,
+                    cached_tokens: hit.cached_tokens,
+                };
+                return Ok((response(hit.text, usage, hit.confidence), true));
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "ignoring unreadable inference cache entry"),
+        }
+        if *mode == CacheMode::Replay {
+            return Err(AiError {
+                code: 2,
+                message: format!(
+                    "no recorded response for request {} in {} (replay mode)",
+                    key.key(),
+                    cache.dir().display()
+                ),
+            });
+        }
+
+        clamp(&mut request);
+        let response = self.generate_uncached(request, token_limit).await?;
+        let recorded = CachedResponse {
+            text: response.text.clone(),
+            input_tokens: response.usage.input_tokens,
+            output_tokens: response.usage.output_tokens,
+            cached_tokens: response.usage.cached_tokens,
+            confidence: response.confidence,
+        };
+        if let Err(e) = cache.put(&key, &recorded) {
+            tracing::warn!(error = %e, "failed to record inference response");
+        }
+        Ok((response, false))
+    }
+
+    async fn generate_uncached(
+        &self,
+        request: GenerateRequest,
+        token_limit: Option<u64>,
+    ) -> Result<GenerateResponse, AiError> {
+        let response = match request.json_schema.as_deref() {
+            Some(schema) => {
+                let schema = parse_schema(schema)?;
+                self.generate_structured(request, &schema, token_limit)
+                    .await?
+            }
+            None => self.call(request).await?,
+        };
+        tracing::info!(
+            provider = self.provider.name(),
+            model = %self.provider.model(),
+            input_tokens = response.usage.input_tokens,
+            output_tokens = response.usage.output_tokens,
+            cached_tokens = response.usage.cached_tokens,
+            confidence = ?response.confidence,
+            "inference complete"
+        );
+        Ok(response)
+    }
+
+    async fn generate_structured(
+        &self,
+        mut request: GenerateRequest,
+        schema: &Value,
+        token_limit: Option<u64>,
+    ) -> Result<GenerateResponse, AiError> {
+        let original = request.user_prompt.clone();
+        let max_tokens = request.max_tokens;
+        let mut usage = TokenUsage {
+            input_tokens: 0,
+            output_tokens: 0,
+            cached_tokens: 0,
+        };
+        let mut errors = Vec::new();
+
+        for attempt in 0..=self.max_repairs {
+            if let Some(limit) = token_limit {
+                let left = limit.saturating_sub(usage.output_tokens as u64);
+                if left == 0 {
+                    return Err(AiError {
+                        code: 5,
+                        message: format!(
+                            "token budget exhausted after {attempt} attempt(s) at the JSON \
+                             schema: {}",
+                            errors.join("; ")
+                        ),
+                    });
+                }
+                request.max_tokens = max_tokens.min(left.min(u32::MAX as u64) as u32);
+            }
+            let reply = self.call(request.clone()).await?;
+            usage.input_tokens = usage.input_tokens.saturating_add(reply.usage.input_tokens);
+            usage.output_tokens = usage
+                .output_tokens
+                .saturating_add(reply.usage.output_tokens);
+            usage.cached_tokens = usage
+                .cached_tokens
+                .saturating_add(reply.usage.cached_tokens);
+
+            errors = match json_schema::extract_json(&reply.text) {
+                Some(value) => {
+                    let errors = json_schema::validate(schema, &value);
+                    if errors.is_empty() {
+                        // Confidence describes the accepted reply only.
+                        return Ok(response(value.to_string(), usage, reply.confidence));
+                    }
+                    errors
+                }
+                None => vec!["reply is not valid JSON".to_string()],
+            };
+            tracing::debug!(attempt, ?errors, "reply failed JSON schema validation");
+            request.user_prompt = json_schema::repair_prompt(&original, &reply.text, &errors);
+        }
+
+        Err(AiError {
+            code: 3,
+            message: format!(
+                "reply did not satisfy the JSON schema after {} attempt(s): {}",
+                self.max_repairs + 1,
+                errors.join("; ")
+            ),
+        })
+    }
+
+    /// One provider call under the rate limiter, retried on transient errors.
+    async fn call(&self, request: GenerateRequest) -> Result<GenerateResponse, AiError> {
+        let mut attempt = 0;
+        loop {
+            let result = {
+                let _permit = self.limiter.acquire().await;
+                self.provider.generate(request.clone()).await
+            };
+            match result {
+                Ok(response) => return Ok(response),
+                Err(e) if e.retryable && attempt < self.retry.max_retries => {
+                    let delay = e
+                        .retry_after
+                        .unwrap_or_else(|| self.retry.backoff(attempt))
+                        .min(self.retry.max_delay);
+                    attempt += 1;
+                    tracing::warn!(
+                        provider = self.provider.name(),
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %e.error.message,
+                        "retrying inference"
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e.error),
+            }
+        }
+    }
+}
+
+fn env_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+// ── Retry and rate limiting ──
+
+/// Exponential backoff with full jitter for transient provider failures.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt; 0 disables retrying.
+    pub max_retries: u32,
+    /// Upper bound of the first backoff; doubles on each retry.
+    pub base_delay: Duration,
+    /// Cap on any single delay, including a server's `Retry-After`.
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retry number `attempt + 1`: uniform in `[0, base * 2^attempt]`,
+    /// capped at `max_delay`.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .base_delay
+            .saturating_mul(1u32 << attempt.min(16))
+            .min(self.max_delay);
+        ceiling.mul_f64(jitter())
+    }
+}
+
+/// A uniform sample in `[0, 1)`; `RandomState` is randomly seeded per instance.
+fn jitter() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default(),
+    );
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Limits provider calls across every run sharing it: at most
+/// `max_concurrent` in flight, and optionally at most `requests_per_minute`
+/// started.
+#[derive(Clone)]
+pub struct RateLimiter {
+    permits: Arc<Semaphore>,
+    /// Minimum spacing between call starts.
+    interval: Option<Duration>,
+    next_start: Arc<tokio::sync::Mutex<tokio::time::Instant>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_concurrent: usize, requests_per_minute: Option<u32>) -> Self {
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            interval: requests_per_minute
+                .filter(|&rpm| rpm > 0)
+                .map(|rpm| Duration::from_secs(60) / rpm),
+            next_start: Arc::new(tokio::sync::Mutex::new(tokio::time::Instant::now())),
+        }
+    }
+
+    /// The limiter shared by every [`InferenceConfig`] in this process,
+    /// configured on first use from `FRACTALAW_INFERENCE_CONCURRENCY`
+    /// (default [`DEFAULT_MAX_CONCURRENT_INFERENCE`]) and
+    /// `FRACTALAW_INFERENCE_RPM` (default unlimited).
+    pub fn host() -> &'static RateLimiter {
+        static HOST: OnceLock<RateLimiter> = OnceLock::new();
+        HOST.get_or_init(|| {
+            let concurrency = env_var("FRACTALAW_INFERENCE_CONCURRENCY")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_CONCURRENT_INFERENCE);
+            let rpm = env_var("FRACTALAW_INFERENCE_RPM").and_then(|v| v.parse().ok());
+            RateLimiter::new(concurrency, rpm)
+        })
+    }
+
+    /// Wait for a concurrency slot and, if rate-limited, the next start time.
+    async fn acquire(&self) -> OwnedSemaphorePermit {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("rate limiter semaphore is never closed");
+        if let Some(interval) = self.interval {
+            let start = {
+                let mut next = self.next_start.lock().await;
+                let start = (*next).max(tokio::time::Instant::now());
+                *next = start + interval;
+                start
+            };
+            tokio::time::sleep_until(start).await;
+        }
+        permit
+    }
+}
+
+// ── Token budgets ──
+
+/// Token allowance shared by every run of one app, e.g. across scheduled
+/// runs in a long-lived host. Clones share the same counter.
+#[derive(Debug, Clone)]
+pub struct AppTokenBudget {
+    pub app: String,
+    pub limit: u64,
+    spent: Arc<AtomicU64>,
+}
+
+impl AppTokenBudget {
+    pub fn new(app: impl Into<String>, limit: u64) -> Self {
+        Self {
+            app: app.into(),
+            limit,
+            spent: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn spent(&self) -> u64 {
+        self.spent.load(Ordering::Relaxed)
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.limit.saturating_sub(self.spent())
+    }
+
+    pub(crate) fn charge(&self, tokens: u64) {
+        self.spent.fetch_add(tokens, Ordering::Relaxed);
+    }
+}
+
+/// One [`AppTokenBudget`] per app, each with the same limit, for a host that
+/// runs many apps (e.g. `fractalaw serve`). Clones share the budgets.
+#[derive(Debug, Clone)]
+pub struct AppTokenBudgets {
+    pub limit: u64,
+    budgets: Arc<Mutex<HashMap<String, AppTokenBudget>>>,
+}
+
+impl AppTokenBudgets {
+    pub fn new(limit: u64) -> Self {
+        Self {
+            limit,
+            budgets: Arc::default(),
+        }
+    }
+
+    /// The budget shared by every run of `app`.
+    pub fn get(&self, app: &str) -> AppTokenBudget {
+        self.budgets
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(app.to_string())
+            .or_insert_with(|| AppTokenBudget::new(app, self.limit))
+            .clone()
+    }
+}
+
+/// Limits on tokens generated through `ai-inference`.
+///
+/// Before each call the request's `max-tokens` is clamped to what remains,
+/// and calls are refused with `ai-error` code 5 once nothing does. A
+/// `json-schema` request's repair attempts share what remains, and fail
+/// with code 5 once they have used it up.
+#[derive(Debug, Clone, Default)]
+pub struct TokenBudget {
+    /// Maximum tokens generated by one run.
+    pub per_run: Option<u64>,
+    /// Allowance shared across runs of the same app.
+    pub app: Option<AppTokenBudget>,
+}
+
+/// Stands in for the recorded provider of a [`CacheMode::Replay`] config,
+/// which never calls it.
+struct ReplayProvider {
+    id: ProviderId,
+}
+
+impl InferenceProvider for ReplayProvider {
+    fn name(&self) -> &str {
+        &self.id.provider
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.id.endpoint
+    }
+
+    fn model(&self) -> &str {
+        &self.id.model
+    }
+
+    fn generate(&self, _request: GenerateRequest) -> GenerateFuture<'_> {
+        Box::pin(async {
+            Err(AiError {
+                code: 2,
+                message: "no provider configured (replay mode)".into(),
+            }
+            .into())
+        })
+    }
+}
+
+/// A response whose `tokens-used` is `usage.output-tokens`. The provider
+/// and model are filled in by [`InferenceConfig::generate_cached`].
+fn response(text: String, usage: TokenUsage, confidence: Option<f32>) -> GenerateResponse {
+    GenerateResponse {
+        text,
+        tokens_used: usage.output_tokens,
+        usage,
+        confidence,
+        provider: String::new(),
+        model: String::new(),
+    }
+}
+
+/// Geometric mean of token probabilities from their log-probabilities, or
+/// `None` when there are none.
+fn confidence_from_logprobs(logprobs: impl IntoIterator<Item = f64>) -> Option<f32> {
+    let (sum, count) = logprobs
+        .into_iter()
+        .fold((0.0, 0usize), |(sum, count), lp| (sum + lp, count + 1));
+    (count > 0).then(|| (sum / count as f64).exp() as f32)
+}
+
+/// Parse a request's `json-schema`.
+fn parse_schema(schema: &str) -> Result<Value, AiError> {
+    serde_json::from_str(schema).map_err(|e| AiError {
+        code: 3,
+        message: format!("invalid json-schema: {e}"),
+    })
+}
+
+// ── Anthropic ──
+
+/// Claude Messages API (`{base_url}/v1/messages`).
+///
+/// A `json-schema` becomes the input schema of a forced tool call, so it must
+/// describe an object.
+pub struct AnthropicProvider {
+    pub api_key: String,
+    pub model: String,
+    /// Base URL the `/v1/messages` path is appended to.
+    pub base_url: String,
+    pub client: reqwest::Client,
+}
+
+impl AnthropicProvider {
+    pub fn new(api_key: String, model: String) -> Self {
+        Self {
+            api_key,
+            model,
+            base_url: DEFAULT_INFERENCE_URL.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Send requests to `base_url` instead of the public API (e.g. a local mock server).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = anthropic_base_url(&base_url.into());
+        self
+    }
+}
+
+fn anthropic_base_url(base_url: &str) -> String {
+    base_url.trim_end_matches('/').to_string()
+}
+
+impl InferenceProvider for AnthropicProvider {
+    fn name(&self) -> &str {
+        "anthropic"
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.base_url
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate(&self, request: GenerateRequest) -> GenerateFuture<'_> {
+        Box::pin(async move {
+            let mut body = serde_json::json!({
+                "model": self.model,
+                "max_tokens": request.max_tokens,
+                "messages": [
+                    { "role": "user", "content": request.user_prompt }
+                ],
+            });
+            if let Some(system) = &request.system_prompt {
+                body["system"] = serde_json::json!(system);
+            }
+            if request.temperature > 0.0 {
+                body["temperature"] = serde_json::json!(request.temperature);
+            }
+            if let Some(schema) = request.json_schema.as_deref() {
+                body["tools"] = serde_json::json!([{
+                    "name": RESPONSE_TOOL,
+                    "description": "Record the response.",
+                    "input_schema": parse_schema(schema)?,
+                }]);
+                body["tool_choice"] = serde_json::json!({ "type": "tool", "name": RESPONSE_TOOL });
+            }
+
+            let builder = self
+                .client
+                .post(format!("{}/v1/messages", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", "2023-06-01");
+            let (parsed, raw) = post_json(builder, &body, "Claude API").await?;
+
+            let tool_use = parsed["content"]
+                .as_array()
+                .and_then(|blocks| blocks.iter().find(|b| b["type"] == "tool_use"));
+            let text = match tool_use {
+                Some(block) => block["input"].to_string(),
+                None => parsed["content"][0]["text"]
+                    .as_str()
+                    .ok_or_else(|| unexpected(&raw, "content[0].text"))?
+                    .to_string(),
+            };
+            // `input_tokens` excludes prompt-cache reads and writes.
+            let count = |field: &str| parsed["usage"][field].as_u64().unwrap_or(0) as u32;
+            let cached_tokens = count("cache_read_input_tokens");
+            let usage = TokenUsage {
+                input_tokens: count("input_tokens")
+                    + cached_tokens
+                    + count("cache_creation_input_tokens"),
+                output_tokens: count("output_tokens"),
+                cached_tokens,
+            };
+
+            // The Messages API doesn't expose log probabilities.
+            Ok(response(text, usage, None))
+        })
+    }
+}
+
+// ── OpenAI-compatible ──
+
+/// OpenAI-compatible chat completions (`{base_url}/v1/chat/completions`),
+/// as served by llama.cpp, vLLM, and Ollama.
+///
+/// Requests token log probabilities, which these servers return, to report
+/// a confidence for each reply.
+pub struct OpenAiProvider {
+    /// Sent as a bearer token when set; local servers usually need none.
+    pub api_key: Option<String>,
+    /// Whether to request `logprobs`; disable for servers that reject it.
+    pub logprobs: bool,
+    pub model: String,
+    /// Base URL the `/v1/chat/completions` path is appended to.
+    pub base_url: String,
+    pub client: reqwest::Client,
+}
+
+impl OpenAiProvider {
+    /// `base_url` may include a trailing `/v1`, as Ollama and vLLM document it.
+    pub fn new(base_url: impl Into<String>, model: String) -> Self {
+        Self {
+            api_key: None,
+            logprobs: true,
+            model,
+            base_url: openai_base_url(&base_url.into()),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn with_api_key(mut self, api_key: String) -> Self {
+        self.api_key = Some(api_key);
+        self
+    }
+
+    pub fn with_logprobs(mut self, logprobs: bool) -> Self {
+        self.logprobs = logprobs;
+        self
+    }
+}
+
+fn openai_base_url(base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    base_url.strip_suffix("/v1").unwrap_or(base_url).to_string()
+}
+
+impl InferenceProvider for OpenAiProvider {
+    fn name(&self) -> &str {
+        "openai"
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.base_url
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate(&self, request: GenerateRequest) -> GenerateFuture<'_> {
+        Box::pin(async move {
+            let mut messages = Vec::new();
+            if let Some(system) = &request.system_prompt {
+                messages.push(serde_json::json!({ "role": "system", "content": system }));
+            }
+            messages.push(serde_json::json!({ "role": "user", "content": request.user_prompt }));
+            // Always send temperature: local servers default to sampling.
+            let mut body = serde_json::json!({
+                "model": self.model,
+                "max_tokens": request.max_tokens,
+                "temperature": request.temperature,
+                "messages": messages,
+            });
+            if self.logprobs {
+                body["logprobs"] = serde_json::json!(true);
+            }
+            if let Some(schema) = request.json_schema.as_deref() {
+                body["response_format"] = serde_json::json!({
+                    "type": "json_schema",
+                    "json_schema": { "name": "response", "schema": parse_schema(schema)? },
+                });
+            }
+
+            let mut builder = self
+                .client
+                .post(format!("{}/v1/chat/completions", self.base_url));
+            if let Some(key) = &self.api_key {
+                builder = builder.bearer_auth(key);
+            }
+            let (parsed, raw) = post_json(builder, &body, "inference server").await?;
+
+            let text = parsed["choices"][0]["message"]["content"]
+                .as_str()
+                .ok_or_else(|| unexpected(&raw, "choices[0].message.content"))?
+                .to_string();
+            let usage = &parsed["usage"];
+            let count = |v: &Value| v.as_u64().unwrap_or(0) as u32;
+            let usage = TokenUsage {
+                input_tokens: count(&usage["prompt_tokens"]),
+                output_tokens: count(&usage["completion_tokens"]),
+                cached_tokens: count(&usage["prompt_tokens_details"]["cached_tokens"]),
+            };
+            let confidence = parsed["choices"][0]["logprobs"]["content"]
+                .as_array()
+                .and_then(|tokens| {
+                    confidence_from_logprobs(tokens.iter().filter_map(|t| t["logprob"].as_f64()))
+                });
+
+            Ok(response(text, usage, confidence))
+        })
+    }
+}
+
+// ── Local ONNX ──
+
+/// In-process generation with a local ONNX model (see [`fractalaw_ai::Generator`]).
+///
+/// Decoding is greedy, so `temperature` is ignored. There is no constrained
+/// decoding: a `json-schema` is included in the prompt and enforced by
+/// validation and repair.
+#[cfg(feature = "local-inference")]
+pub struct LocalProvider {
+    generator: std::sync::Arc<std::sync::Mutex<fractalaw_ai::Generator>>,
+    model: String,
+    /// Directory the model was loaded from, if any.
+    model_dir: String,
+}
+
+#[cfg(feature = "local-inference")]
+impl LocalProvider {
+    pub fn new(generator: fractalaw_ai::Generator) -> Self {
+        Self {
+            model: generator.model_name().to_string(),
+            generator: std::sync::Arc::new(std::sync::Mutex::new(generator)),
+            model_dir: String::new(),
+        }
+    }
+
+    /// Load a model directory containing `model.onnx` and `tokenizer.json`.
+    pub fn load(model_dir: &std::path::Path) -> anyhow::Result<Self> {
+        Ok(Self {
+            model_dir: model_dir.display().to_string(),
+            ..Self::new(fractalaw_ai::Generator::load(model_dir)?)
+        })
+    }
+}
+
+#[cfg(feature = "local-inference")]
+impl InferenceProvider for LocalProvider {
+    fn name(&self) -> &str {
+        "local"
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.model_dir
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+
+    fn generate(&self, request: GenerateRequest) -> GenerateFuture<'_> {
+        let generator = self.generator.clone();
+        Box::pin(async move {
+            let mut prompt = match &request.system_prompt {
+                Some(system) => format!("{system}\n\n{}", request.user_prompt),
+                None => request.user_prompt.clone(),
+            };
+            if let Some(schema) = &request.json_schema {
+                prompt.push_str(&format!(
+                    "\n\nRespond with only JSON that satisfies this JSON Schema:\n{schema}\n"
+                ));
+            }
+            let max_tokens = request.max_tokens as usize;
+            let generation = tokio::task::spawn_blocking(move || {
+                let mut generator = generator
+                    .lock()
+                    .map_err(|_| anyhow::anyhow!("generator lock poisoned"))?;
+                generator.generate(&prompt, max_tokens)
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("generation task failed: {e}"))
+            .and_then(|r| r)
+            .map_err(|e| AiError {
+                code: 2,
+                message: format!("local generation failed: {e}"),
+            })?;
+
+            let usage = TokenUsage {
+                input_tokens: generation.prompt_tokens as u32,
+                output_tokens: generation.completion_tokens as u32,
+                cached_tokens: 0,
+            };
+            let confidence =
+                confidence_from_logprobs(generation.logprobs.iter().map(|&lp| lp as f64));
+            Ok(response(generation.text, usage, confidence))
+        })
+    }
+}
+
+// ── HTTP helpers ──
+
+/// POST `body` as JSON and parse the JSON response. Returns the parsed value
+/// and the raw body (for error messages).
+///
+/// Connection failures, timeouts, 429, and 5xx responses are reported as
+/// retryable.
+async fn post_json(
+    builder: reqwest::RequestBuilder,
+    body: &serde_json::Value,
+    service: &str,
+) -> Result<(serde_json::Value, String), ProviderError> {
+    let resp = builder
+        .header("content-type", "application/json")
+        .json(body)
+        .send()
+        .await
+        .map_err(|e| {
+            let error = AiError {
+                code: 2,
+                message: format!("HTTP request failed: {e}"),
+            };
+            if e.is_connect() || e.is_timeout() {
+                ProviderError::transient(error, None)
+            } else {
+                error.into()
+            }
+        })?;
+
+    let status = resp.status();
+    let retry_after = resp
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    let raw = resp.text().await.map_err(|e| AiError {
+        code: 2,
+        message: format!("failed to read response body: {e}"),
+    })?;
+
+    if !status.is_success() {
+        let error = AiError {
+            code: 2,
+            message: format!("{service} error ({status}): {raw}"),
+        };
+        return Err(
+            if status == reqwest::StatusCode::TOO_MANY_REQUESTS || status.is_server_error() {
+                ProviderError::transient(error, retry_after)
+            } else {
+                error.into()
+            },
+        );
+    }
+
+    let parsed = serde_json::from_str(&raw).map_err(|e| AiError {
+        code: 3,
+        message: format!("failed to parse response JSON: {e}"),
+    })?;
+    Ok((parsed, raw))
+}
+
+fn unexpected(raw: &str, field: &str) -> AiError {
+    AiError {
+        code: 3,
+        message: format!(
+            "unexpected response structure (no {field}): {}",
+            raw.chars().take(200).collect::<String>()
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_grows_within_cap() {
+        let policy = RetryPolicy {
+            max_retries: 5,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1000),
+        };
+        for attempt in 0..8 {
+            let ceiling = Duration::from_millis(100 * (1 << attempt)).min(policy.max_delay);
+            for _ in 0..20 {
+                assert!(policy.backoff(attempt) <= ceiling);
+            }
+        }
+        // Jitter spreads retries rather than synchronising them.
+        let delays: std::collections::HashSet<Duration> =
+            (0..20).map(|_| policy.backoff(3)).collect();
+        assert!(delays.len() > 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_spaces_call_starts() {
+        let limiter = RateLimiter::new(8, Some(1200));
+        let start = tokio::time::Instant::now();
+        for _ in 0..3 {
+            drop(limiter.acquire().await);
+        }
+        // 1200 rpm = one start every 50ms; the first starts immediately.
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_caps_concurrency() {
+        let limiter = RateLimiter::new(1, None);
+        let held = limiter.acquire().await;
+        assert_eq!(limiter.permits.available_permits(), 0);
+        drop(held);
+        assert_eq!(limiter.permits.available_permits(), 1);
+    }
+
+    #[test]
+    fn app_budget_clones_share_spend() {
+        let budget = AppTokenBudget::new("drrp-polisher", 100);
+        budget.clone().charge(30);
+        assert_eq!(budget.spent(), 30);
+        assert_eq!(budget.remaining(), 70);
+        budget.charge(100);
+        assert_eq!(budget.remaining(), 0);
+    }
+
+    #[test]
+    fn app_budgets_are_kept_per_app() {
+        let budgets = AppTokenBudgets::new(100);
+        budgets.get("drrp-polisher").charge(30);
+        assert_eq!(budgets.clone().get("drrp-polisher").remaining(), 70);
+        assert_eq!(budgets.get("hello-world").remaining(), 100);
+    }
+
+    #[test]
+    fn confidence_is_geometric_mean_probability() {
+        assert_eq!(confidence_from_logprobs([]), None);
+        let c = confidence_from_logprobs([0.5f64.ln(), 0.5f64.ln()]).unwrap();
+        assert!((c - 0.5).abs() < 1e-6);
+        let c = confidence_from_logprobs([0.0, 0.25f64.ln()]).unwrap();
+        assert!((c - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn openai_base_url_accepts_v1_suffix() {
+        for url in [
+            "http://localhost:11434",
+            "http://localhost:11434/",
+            "http://localhost:11434/v1",
+            "http://localhost:11434/v1/",
+        ] {
+            let provider = OpenAiProvider::new(url, "llama3".into());
+            assert_eq!(provider.base_url, "http://localhost:11434");
+        }
+    }
+}
@@ -1,13 +1,50 @@
 //! Wasmtime host runtime: micro-app lifecycle, instance pooling, WIT interface bridge.
 
+use std::collections::BTreeSet;
 use std::path::Path;
-use wasmtime::component::{Component, HasSelf, ResourceTable};
-use wasmtime::{Config, Engine, InstanceAllocationStrategy, PoolingAllocationConfig, Store};
+use wasmtime::component::{Component, ResourceTable};
+use wasmtime::{Config, Engine, InstanceAllocationStrategy, PoolingAllocationConfig};
 use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView};
 
+use fractalaw_core::{AppManifest, Limit, LimitExceeded, Tier, TierLimits};
 #[cfg(feature = "duckdb")]
 use fractalaw_store::DuckStore;
 
+mod aot;
+pub use aot::AotCache;
+
+mod capabilities;
+pub use capabilities::{CAPABILITY_DENIED, create_linker_for};
+
+#[cfg(feature = "duckdb")]
+mod sql_policy;
+#[cfg(feature = "duckdb")]
+use sql_policy::{SqlAccess, SqlRejection};
+
+#[cfg(feature = "duckdb")]
+mod events;
+#[cfg(feature = "duckdb")]
+pub use events::{DeliveryPolicy, DispatchReport, dispatch_events};
+#[cfg(feature = "duckdb")]
+mod scheduler;
+#[cfg(feature = "duckdb")]
+pub use scheduler::{ScheduleReport, run_due_tasks};
+#[cfg(feature = "inference")]
+mod inference;
+mod runtime;
+#[cfg(feature = "local-inference")]
+pub use inference::LocalProvider;
+#[cfg(feature = "inference")]
+pub use inference::{
+    AnthropicProvider, AppTokenBudget, AppTokenBudgets, CacheMode, DEFAULT_INFERENCE_MODEL,
+    DEFAULT_INFERENCE_URL, DEFAULT_MAX_CONCURRENT_INFERENCE, DEFAULT_OPENAI_URL, GenerateFuture,
+    InferenceConfig, InferenceProvider, OpenAiProvider, ProviderError, ProviderId, RateLimiter,
+    RetryPolicy, TokenBudget,
+};
+pub use runtime::{
+    APP_ACTOR_ROLE, DEFAULT_NODE_ID, MAX_INVOKE_DEPTH, Runtime, app_component, valid_app_name,
+};
+
 wasmtime::component::bindgen!({
     world: "micro-app",
     path: "../../wit",
@@ -28,37 +65,183 @@ pub struct AuditRecord {
 pub struct RunResult {
     pub output: Result<String, String>,
     pub audit_entries: Vec<AuditRecord>,
+    /// Guest fuel consumed plus fuel charged by host functions (e.g.
+    /// embeddings, and apps called through app-invoke), all taken from the
+    /// run's budget.
     pub fuel_consumed: u64,
+    pub embedding_usage: EmbeddingUsage,
+    pub inference_usage: InferenceUsage,
+    pub invoke_usage: InvokeUsage,
+    /// Ids of the events the run emitted, in order.
+    pub emitted_events: Vec<u64>,
+    /// The tier whose limits the run had.
+    pub tier: Tier,
+    /// The last tier limit the run hit, if any. Fuel, deadline and memory stop
+    /// the run, leaving the message in `output`; table and inference-token
+    /// limits refuse the host call and leave the guest to carry on.
+    pub limit_exceeded: Option<LimitExceeded>,
 }
 
-/// Configuration for the Claude API inference backend.
-#[cfg(feature = "inference")]
-pub struct InferenceConfig {
-    pub api_key: String,
-    pub model: String,
-    pub client: reqwest::Client,
+/// Host-side cost of `ai-embeddings` calls made during a run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EmbeddingUsage {
+    /// Number of `embed` / `embed-batch` calls that succeeded.
+    pub calls: u64,
+    /// Total texts embedded across all calls.
+    pub texts: u64,
+    /// Fuel charged against the run budget for embedding work.
+    pub fuel_charged: u64,
+    /// Wall-clock time spent in the ONNX embedding model.
+    pub elapsed: std::time::Duration,
 }
 
-#[cfg(feature = "inference")]
-impl InferenceConfig {
-    pub fn new(api_key: String, model: String) -> Self {
+/// Host-side cost of `ai-inference` calls made during a run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InferenceUsage {
+    /// Number of `generate` calls that succeeded.
+    pub calls: u64,
+    /// Calls answered from the response cache without reaching the provider.
+    pub cache_hits: u64,
+    /// Tokens generated across all calls (including schema repairs). Cache
+    /// hits generate nothing and are not counted.
+    pub tokens_used: u64,
+    /// Prompt tokens sent across all calls, likewise excluding cache hits.
+    pub input_tokens: u64,
+    /// Prompt tokens the provider served from its prompt cache.
+    pub cached_tokens: u64,
+    /// Calls refused because the token budget was exhausted.
+    pub refused: u64,
+    /// The run's token budget, if any.
+    pub budget: Option<u64>,
+}
+
+/// Apps called through `app-invoke` during a run.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InvokeUsage {
+    /// Calls whose callee ran, successfully or not.
+    pub calls: u64,
+    /// Calls whose callee returned an error or hit a limit.
+    pub failed: u64,
+    /// Fuel the callees consumed, charged against the run budget.
+    pub fuel_charged: u64,
+}
+
+/// Default maximum number of texts accepted by a single `embed-batch` call.
+#[cfg(feature = "embeddings")]
+pub const DEFAULT_MAX_EMBED_BATCH: usize = 256;
+
+/// Default fuel charged per embedded text.
+#[cfg(feature = "embeddings")]
+pub const DEFAULT_EMBED_FUEL_PER_TEXT: u64 = 1_000_000;
+
+/// Configuration for the local ONNX embedding backend.
+#[cfg(feature = "embeddings")]
+pub struct EmbeddingsConfig {
+    pub embedder: fractalaw_ai::Embedder,
+    /// Maximum number of texts accepted by a single `embed-batch` call.
+    pub max_batch_size: usize,
+    /// Fuel charged against the run budget for each embedded text.
+    pub fuel_per_text: u64,
+}
+
+#[cfg(feature = "embeddings")]
+impl EmbeddingsConfig {
+    pub fn new(embedder: fractalaw_ai::Embedder) -> Self {
         Self {
-            api_key,
-            model,
-            client: reqwest::Client::new(),
+            embedder,
+            max_batch_size: DEFAULT_MAX_EMBED_BATCH,
+            fuel_per_text: DEFAULT_EMBED_FUEL_PER_TEXT,
+        }
+    }
+}
+
+/// Error code returned by data-query and data-mutate when a call would take a
+/// run past its tier's table limit.
+pub const LIMIT_EXCEEDED: u32 = 429;
+
+/// Fuel to pass to [`Runtime::run`] for as much as the app's tier allows.
+pub const TIER_FUEL: u64 = u64::MAX;
+
+/// Refuses linear memory growth beyond a tier's limit and notes that it did.
+#[derive(Debug, Default)]
+struct MemoryLimiter {
+    max_bytes: usize,
+    exceeded: bool,
+}
+
+impl wasmtime::ResourceLimiter for MemoryLimiter {
+    fn memory_growing(
+        &mut self,
+        _current: usize,
+        desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        if desired > self.max_bytes {
+            self.exceeded = true;
+            return Ok(false);
         }
+        Ok(true)
+    }
+
+    fn table_growing(
+        &mut self,
+        _current: usize,
+        _desired: usize,
+        _maximum: Option<usize>,
+    ) -> anyhow::Result<bool> {
+        Ok(true)
     }
 }
 
 /// State held in the Wasmtime [`Store`](wasmtime::Store) for each guest execution.
 pub struct HostState {
+    /// Name of the running app, recorded as the source of emitted events.
+    pub app_name: String,
     pub audit_entries: Vec<AuditRecord>,
     pub wasi_ctx: WasiCtx,
     pub table: ResourceTable,
+    /// Fuel budget for the run; host functions that charge fuel may not exceed it.
+    pub fuel_limit: u64,
+    /// Guest fuel left when the current host call began, kept by the
+    /// store's call hook (see [`Runtime::run`]). `None` outside a store,
+    /// where host charges are checked against `fuel_limit` alone.
+    fuel_left: Option<u64>,
+    /// Fuel charged during the current host call, taken from the store's
+    /// fuel when the call returns.
+    fuel_pending: u64,
+    pub embedding_usage: EmbeddingUsage,
+    pub inference_usage: InferenceUsage,
+    /// The event delivery that started this run, if any.
+    pub trigger: Option<fractal::app::events_receive::Delivery>,
+    pub emitted_events: Vec<u64>,
+    /// Time source for `events-schedule`.
+    pub clock: std::sync::Arc<dyn fractalaw_core::Clock>,
+    /// Grants checked by host functions, e.g. the tables data-query may read.
+    pub capabilities: fractalaw_core::Capabilities,
+    pub tier: Tier,
+    /// The tier's limits, with fuel lowered to the run's budget.
+    pub limits: TierLimits,
+    /// Distinct tables read or written so far, counted against `limits.tables`.
+    pub tables_used: BTreeSet<String>,
+    pub limit_exceeded: Option<LimitExceeded>,
+    /// Holds guest memory to `limits.memory_bytes`; see [`Runtime::run`].
+    limiter: MemoryLimiter,
+    /// The app-invoke call that started this run, if any.
+    pub invocation: Option<Invocation>,
+    pub invoke_usage: InvokeUsage,
+    /// Runs the apps called through app-invoke; set by [`Runtime::run`]
+    /// when its runtime has an apps directory.
+    runtime: Option<Runtime>,
     #[cfg(feature = "duckdb")]
     pub duck: Option<DuckStore>,
     #[cfg(feature = "inference")]
     pub inference: Option<InferenceConfig>,
+    #[cfg(feature = "inference")]
+    pub token_budget: TokenBudget,
+    #[cfg(feature = "embeddings")]
+    pub embeddings: Option<EmbeddingsConfig>,
+    #[cfg(feature = "embeddings")]
+    pub classifier: Option<fractalaw_ai::Classifier>,
 }
 
 impl Default for HostState {
@@ -68,23 +251,204 @@ impl Default for HostState {
 }
 
 impl HostState {
+    /// State granting no capabilities; see
+    /// [`with_capabilities`](Self::with_capabilities).
     pub fn new() -> Self {
-        let wasi_ctx = WasiCtxBuilder::new()
-            .inherit_stdout()
-            .inherit_stderr()
-            .build();
+        let wasi_ctx = WasiCtxBuilder::new().build();
         Self {
+            app_name: String::new(),
             audit_entries: Vec::new(),
             wasi_ctx,
             table: ResourceTable::new(),
+            fuel_limit: u64::MAX,
+            fuel_left: None,
+            fuel_pending: 0,
+            embedding_usage: EmbeddingUsage::default(),
+            inference_usage: InferenceUsage::default(),
+            trigger: None,
+            emitted_events: Vec::new(),
+            clock: std::sync::Arc::new(fractalaw_core::SystemClock),
+            capabilities: fractalaw_core::Capabilities::default(),
+            tier: Tier::default(),
+            limits: Tier::default().limits(),
+            tables_used: BTreeSet::new(),
+            limit_exceeded: None,
+            limiter: MemoryLimiter {
+                max_bytes: Tier::default().limits().memory_bytes,
+                exceeded: false,
+            },
+            invocation: None,
+            invoke_usage: InvokeUsage::default(),
+            runtime: None,
             #[cfg(feature = "duckdb")]
             duck: None,
             #[cfg(feature = "inference")]
             inference: None,
+            #[cfg(feature = "inference")]
+            token_budget: TokenBudget::default(),
+            #[cfg(feature = "embeddings")]
+            embeddings: None,
+            #[cfg(feature = "embeddings")]
+            classifier: None,
+        }
+    }
+
+    /// State for a run of `app_name` with a `fuel` budget and the host
+    /// resources in `opts`.
+    pub fn from_options(app_name: String, fuel: u64, opts: RunOptions) -> Self {
+        let mut state = Self::new().with_app_name(app_name);
+        state.fuel_limit = fuel;
+        if let Some(delivery) = opts.trigger {
+            state = state.with_trigger(delivery);
+        }
+        state.invocation = opts.invocation;
+        #[cfg(feature = "duckdb")]
+        if let Some(store) = opts.duck {
+            state = state.with_duck(store);
+        }
+        #[cfg(feature = "inference")]
+        if let Some(config) = opts.inference {
+            state = state.with_inference(config);
+        }
+        #[cfg(feature = "inference")]
+        {
+            state = state.with_token_budget(opts.token_budget);
+        }
+        #[cfg(feature = "embeddings")]
+        if let Some(config) = opts.embeddings {
+            state = state.with_embeddings(config);
+        }
+        #[cfg(feature = "embeddings")]
+        if let Some(classifier) = opts.classifier {
+            state = state.with_classifier(classifier);
+        }
+        if let Some(manifest) = opts.manifest {
+            state = state.with_capabilities(&manifest.capabilities);
+        }
+        state.with_tier(opts.tier.unwrap_or_default())
+    }
+
+    pub fn with_app_name(mut self, name: impl Into<String>) -> Self {
+        self.app_name = name.into();
+        self
+    }
+
+    /// Expose the event delivery that started this run to events-receive.
+    pub fn with_trigger(mut self, delivery: fractal::app::events_receive::Delivery) -> Self {
+        self.trigger = Some(delivery);
+        self
+    }
+
+    /// Apply the limits of `capabilities` that the linker cannot: guest
+    /// stdio is kept only with the `stdio` grant, SQL is checked against
+    /// the granted tables, and an `ai-inference` token cap lowers the
+    /// per-run token budget. Call after
+    /// [`with_token_budget`](Self::with_token_budget).
+    pub fn with_capabilities(mut self, capabilities: &fractalaw_core::Capabilities) -> Self {
+        self.capabilities = capabilities.clone();
+        let mut wasi = WasiCtxBuilder::new();
+        if capabilities.stdio.is_some() {
+            wasi.inherit_stdout().inherit_stderr();
+        }
+        self.wasi_ctx = wasi.build();
+        #[cfg(feature = "inference")]
+        if let Some(cap) = capabilities
+            .ai_inference
+            .as_ref()
+            .and_then(|g| g.max_tokens)
+        {
+            let per_run = self.token_budget.per_run.map_or(cap, |n| n.min(cap));
+            self.token_budget.per_run = Some(per_run);
+            self.inference_usage.budget = Some(per_run);
+        }
+        self
+    }
+
+    /// Apply the limits of `tier`: the fuel budget, memory cap and per-run
+    /// token budget are lowered to the tier's where they are higher. Call
+    /// after [`with_token_budget`](Self::with_token_budget).
+    pub fn with_tier(mut self, tier: Tier) -> Self {
+        let limits = tier.limits();
+        self.tier = tier;
+        self.limits = limits;
+        self.fuel_limit = self.fuel_limit.min(limits.fuel);
+        self.limits.fuel = self.fuel_limit;
+        self.limiter.max_bytes = limits.memory_bytes;
+        #[cfg(feature = "inference")]
+        {
+            let cap = limits.inference_tokens;
+            let per_run = self.token_budget.per_run.map_or(cap, |n| n.min(cap));
+            self.token_budget.per_run = Some(per_run);
+            self.inference_usage.budget = Some(per_run);
+        }
+        self
+    }
+
+    /// Fuel charged by host functions so far, on top of the guest's own.
+    fn fuel_charged(&self) -> u64 {
+        self.embedding_usage
+            .fuel_charged
+            .saturating_add(self.invoke_usage.fuel_charged)
+    }
+
+    /// Fuel host functions may still charge during the current call.
+    fn fuel_available(&self) -> u64 {
+        match self.fuel_left {
+            Some(left) => left.saturating_sub(self.fuel_pending),
+            None => self.fuel_limit.saturating_sub(self.fuel_charged()),
+        }
+    }
+
+    /// Charge `fuel` to the run; it is taken from the store when the host
+    /// call returns.
+    fn charge_fuel(&mut self, fuel: u64) {
+        self.fuel_pending = self.fuel_pending.saturating_add(fuel);
+    }
+
+    /// Store call hook: note the guest's fuel as a host call begins, and
+    /// take the call's charges from it as the call returns. Charges beyond
+    /// what is left exhaust the fuel and trap the guest as out of fuel.
+    pub(crate) fn call_hook(
+        mut store: wasmtime::StoreContextMut<'_, Self>,
+        hook: wasmtime::CallHook,
+    ) -> anyhow::Result<()> {
+        match hook {
+            wasmtime::CallHook::CallingHost => {
+                let fuel = store.get_fuel()?;
+                let state = store.data_mut();
+                state.fuel_left = Some(fuel);
+                state.fuel_pending = 0;
+            }
+            wasmtime::CallHook::ReturningFromHost => {
+                let charge = std::mem::take(&mut store.data_mut().fuel_pending);
+                if charge > 0 {
+                    let fuel = store.get_fuel()?;
+                    store.set_fuel(fuel.saturating_sub(charge))?;
+                    if charge > fuel {
+                        return Err(wasmtime::Trap::OutOfFuel.into());
+                    }
+                }
+            }
+            _ => {}
         }
+        Ok(())
     }
 
-    /// Attach a DuckDB store for data-query and data-mutate host functions.
+    /// Record that the run hit `limit` of its tier.
+    fn exceed(&mut self, limit: Limit) -> LimitExceeded {
+        let exceeded = LimitExceeded::new(self.tier, limit, &self.limits);
+        tracing::warn!(app = %self.app_name, %exceeded, "tier limit exceeded");
+        self.limit_exceeded = Some(exceeded);
+        exceeded
+    }
+
+    pub fn with_clock(mut self, clock: std::sync::Arc<dyn fractalaw_core::Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Attach a DuckDB store for data-query, data-mutate, events-emit, and
+    /// events-schedule host functions.
     #[cfg(feature = "duckdb")]
     pub fn with_duck(mut self, store: DuckStore) -> Self {
         self.duck = Some(store);
@@ -97,6 +461,28 @@ impl HostState {
         self.inference = Some(config);
         self
     }
+
+    /// Limit tokens generated through ai-inference host functions.
+    #[cfg(feature = "inference")]
+    pub fn with_token_budget(mut self, budget: TokenBudget) -> Self {
+        self.inference_usage.budget = budget.per_run;
+        self.token_budget = budget;
+        self
+    }
+
+    /// Attach a local embedding model for ai-embeddings host functions.
+    #[cfg(feature = "embeddings")]
+    pub fn with_embeddings(mut self, config: EmbeddingsConfig) -> Self {
+        self.embeddings = Some(config);
+        self
+    }
+
+    /// Attach trained family/domain centroids for ai-classify `classify-trained`.
+    #[cfg(feature = "embeddings")]
+    pub fn with_classifier(mut self, classifier: fractalaw_ai::Classifier) -> Self {
+        self.classifier = Some(classifier);
+        self
+    }
 }
 
 impl WasiView for HostState {
@@ -112,8 +498,15 @@ impl WasiView for HostState {
 
 impl fractal::app::audit_log::Host for HostState {
     async fn record_event(&mut self, entry: fractal::app::audit_log::AuditEntry) {
+        // Checkpoints are the node's to record; keep the app's event apart.
+        let event_type = if entry.event_type == fractalaw_core::CHECKPOINT_EVENT {
+            tracing::warn!(app = %self.app_name, "app recorded the reserved checkpoint event");
+            format!("app-{}", entry.event_type)
+        } else {
+            entry.event_type
+        };
         let record = AuditRecord {
-            event_type: entry.event_type,
+            event_type,
             resource: entry.resource,
             detail: entry.detail,
             timestamp: chrono::Utc::now(),
@@ -127,6 +520,218 @@ impl fractal::app::audit_log::Host for HostState {
     }
 }
 
+// ── Event host functions ──
+
+impl fractal::app::events_emit::Host for HostState {
+    async fn emit(
+        &mut self,
+        event: fractal::app::events_emit::DomainEvent,
+    ) -> Result<fractal::app::events_emit::EventId, fractal::app::events_emit::EventError> {
+        self.emit_impl(event)
+    }
+}
+
+impl fractal::app::events_receive::Host for HostState {
+    async fn trigger(&mut self) -> Option<fractal::app::events_receive::Delivery> {
+        self.trigger.clone()
+    }
+}
+
+impl HostState {
+    fn emit_impl(
+        &mut self,
+        event: fractal::app::events_emit::DomainEvent,
+    ) -> Result<u64, fractal::app::events_emit::EventError> {
+        let invalid = |message: String| fractal::app::events_emit::EventError { code: 2, message };
+        if event.event_type.trim().is_empty() {
+            return Err(invalid("event-type must not be empty".into()));
+        }
+        let source_app = match (self.app_name.as_str(), event.source_app.as_str()) {
+            ("", "") => return Err(invalid("source-app must not be empty".into())),
+            ("", guest) => guest.to_string(),
+            (host, guest) if guest.is_empty() || guest == host => host.to_string(),
+            (host, guest) => {
+                return Err(invalid(format!(
+                    "source-app '{guest}' does not match the emitting app '{host}'"
+                )));
+            }
+        };
+
+        #[cfg(feature = "duckdb")]
+        {
+            let duck = self
+                .duck
+                .as_ref()
+                .ok_or(fractal::app::events_emit::EventError {
+                    code: 1,
+                    message: "no DuckDB store attached".into(),
+                })?;
+            let id = duck
+                .append_event(&event.event_type, &event.payload, &source_app)
+                .map_err(|e| fractal::app::events_emit::EventError {
+                    code: 3,
+                    message: e.to_string(),
+                })?;
+            tracing::info!(
+                event_id = id,
+                event_type = %event.event_type,
+                source_app = %source_app,
+                "event emitted"
+            );
+            self.emitted_events.push(id);
+            Ok(id)
+        }
+
+        #[cfg(not(feature = "duckdb"))]
+        {
+            let _ = source_app;
+            Err(fractal::app::events_emit::EventError {
+                code: 1,
+                message: "DuckDB support not compiled in".into(),
+            })
+        }
+    }
+}
+
+impl fractal::app::events_schedule::Host for HostState {
+    async fn schedule(
+        &mut self,
+        task: fractal::app::events_schedule::ScheduledTask,
+    ) -> Result<fractal::app::events_schedule::TaskId, fractal::app::events_emit::EventError> {
+        self.schedule_impl(task)
+    }
+
+    async fn cancel(
+        &mut self,
+        id: fractal::app::events_schedule::TaskId,
+    ) -> Result<(), fractal::app::events_emit::EventError> {
+        self.cancel_impl(id)
+    }
+}
+
+impl HostState {
+    fn schedule_impl(
+        &mut self,
+        task: fractal::app::events_schedule::ScheduledTask,
+    ) -> Result<u64, fractal::app::events_emit::EventError> {
+        let invalid = |message: String| fractal::app::events_emit::EventError { code: 2, message };
+        if task.name.trim().is_empty() {
+            return Err(invalid("task name must not be empty".into()));
+        }
+        let task = fractalaw_core::ScheduledTask {
+            id: 0,
+            name: task.name,
+            cron: task.cron,
+            run_at: task.run_at,
+            target_app: task.target_app,
+            payload: task.payload,
+            owner_app: self.app_name.clone(),
+        };
+
+        #[cfg(feature = "duckdb")]
+        {
+            if !valid_app_name(&task.target_app) {
+                return Err(invalid(format!("invalid target-app '{}'", task.target_app)));
+            }
+            // Scheduling an app runs it on this app's behalf, as invoking does.
+            if task.target_app != self.app_name
+                && !self
+                    .capabilities
+                    .app_invoke
+                    .as_ref()
+                    .is_some_and(|grant| grant.allows(&task.target_app))
+            {
+                tracing::warn!(app = %self.app_name, target_app = %task.target_app, "schedule denied");
+                self.audit_entries.push(AuditRecord {
+                    event_type: "capability-denied".into(),
+                    resource: "events-schedule".into(),
+                    detail: format!("task for '{}', which is not granted", task.target_app),
+                    timestamp: chrono::Utc::now(),
+                });
+                return Err(fractal::app::events_emit::EventError {
+                    code: CAPABILITY_DENIED,
+                    message: format!("target-app '{}' not granted to app-invoke", task.target_app),
+                });
+            }
+            let next_run_at = task.first_run(self.clock.now()).map_err(invalid)?;
+            let duck = self
+                .duck
+                .as_ref()
+                .ok_or(fractal::app::events_emit::EventError {
+                    code: 1,
+                    message: "no DuckDB store attached".into(),
+                })?;
+            let id = duck
+                .insert_scheduled_task(&task, next_run_at)
+                .map_err(|e| fractal::app::events_emit::EventError {
+                    code: 3,
+                    message: e.to_string(),
+                })?;
+            tracing::info!(
+                task_id = id,
+                name = %task.name,
+                target_app = %task.target_app,
+                next_run_at,
+                "task scheduled"
+            );
+            Ok(id)
+        }
+
+        #[cfg(not(feature = "duckdb"))]
+        {
+            let _ = task;
+            Err(fractal::app::events_emit::EventError {
+                code: 1,
+                message: "DuckDB support not compiled in".into(),
+            })
+        }
+    }
+
+    fn cancel_impl(&mut self, id: u64) -> Result<(), fractal::app::events_emit::EventError> {
+        #[cfg(feature = "duckdb")]
+        {
+            let store_err =
+                |e: fractalaw_store::StoreError| fractal::app::events_emit::EventError {
+                    code: 3,
+                    message: e.to_string(),
+                };
+            let invalid =
+                |message: String| fractal::app::events_emit::EventError { code: 2, message };
+            let duck = self
+                .duck
+                .as_ref()
+                .ok_or(fractal::app::events_emit::EventError {
+                    code: 1,
+                    message: "no DuckDB store attached".into(),
+                })?;
+            let task = duck
+                .scheduled_task(id)
+                .map_err(store_err)?
+                .ok_or_else(|| invalid(format!("no scheduled task {id}")))?;
+            if task.owner_app != self.app_name {
+                return Err(invalid(format!(
+                    "task {id} was scheduled by '{}', not '{}'",
+                    task.owner_app, self.app_name
+                )));
+            }
+            if !duck.cancel_scheduled_task(id).map_err(store_err)? {
+                return Err(invalid(format!("task {id} is not active")));
+            }
+            tracing::info!(task_id = id, "task cancelled");
+            Ok(())
+        }
+
+        #[cfg(not(feature = "duckdb"))]
+        {
+            let _ = id;
+            Err(fractal::app::events_emit::EventError {
+                code: 1,
+                message: "DuckDB support not compiled in".into(),
+            })
+        }
+    }
+}
+
 // ── Data query host function ──
 
 impl fractal::app::data_query::Host for HostState {
@@ -139,9 +744,14 @@ impl fractal::app::data_query::Host for HostState {
 }
 
 impl HostState {
-    fn query_impl(&self, sql: &str) -> Result<Vec<u8>, fractal::app::data_query::QueryError> {
+    fn query_impl(&mut self, sql: &str) -> Result<Vec<u8>, fractal::app::data_query::QueryError> {
         #[cfg(feature = "duckdb")]
         {
+            self.authorize_sql("data-query", sql, SqlAccess::Query)
+                .map_err(|(code, message)| fractal::app::data_query::QueryError {
+                    code,
+                    message,
+                })?;
             let duck = self
                 .duck
                 .as_ref()
@@ -193,12 +803,31 @@ impl fractal::app::data_mutate::Host for HostState {
 
 impl HostState {
     fn insert_impl(
-        &self,
+        &mut self,
         table: &str,
         data: &[u8],
     ) -> Result<u64, fractal::app::data_mutate::MutateError> {
         #[cfg(feature = "duckdb")]
         {
+            if sql_policy::is_host_table(table)
+                || !self
+                    .capabilities
+                    .data_mutate
+                    .as_ref()
+                    .is_some_and(|g| g.allows(table))
+            {
+                let reason = format!("writing table {table} is not granted");
+                self.record_data_denial("data-mutate", table, &reason);
+                return Err(fractal::app::data_mutate::MutateError {
+                    code: CAPABILITY_DENIED,
+                    message: reason,
+                });
+            }
+            self.use_tables(vec![table.to_lowercase()])
+                .map_err(|(code, message)| fractal::app::data_mutate::MutateError {
+                    code,
+                    message,
+                })?;
             let duck = self
                 .duck
                 .as_ref()
@@ -233,9 +862,14 @@ impl HostState {
         }
     }
 
-    fn execute_impl(&self, sql: &str) -> Result<u64, fractal::app::data_mutate::MutateError> {
+    fn execute_impl(&mut self, sql: &str) -> Result<u64, fractal::app::data_mutate::MutateError> {
         #[cfg(feature = "duckdb")]
         {
+            self.authorize_sql("data-mutate", sql, SqlAccess::Execute)
+                .map_err(|(code, message)| fractal::app::data_mutate::MutateError {
+                    code,
+                    message,
+                })?;
             let duck = self
                 .duck
                 .as_ref()
@@ -243,12 +877,13 @@ impl HostState {
                     code: 1,
                     message: "no DuckDB store attached".into(),
                 })?;
-            duck.execute(sql)
-                .map_err(|e| fractal::app::data_mutate::MutateError {
-                    code: 2,
-                    message: e.to_string(),
-                })?;
-            Ok(0)
+            let changed =
+                duck.execute_one(sql)
+                    .map_err(|e| fractal::app::data_mutate::MutateError {
+                        code: 2,
+                        message: e.to_string(),
+                    })?;
+            Ok(changed as u64)
         }
 
         #[cfg(not(feature = "duckdb"))]
@@ -262,136 +897,356 @@ impl HostState {
     }
 }
 
-// ── AI embeddings host function (stub) ──
+#[cfg(feature = "duckdb")]
+impl HostState {
+    /// Check guest SQL against the app's table grants (see
+    /// [`sql_policy`]) and its tier's table limit. Unparseable SQL fails
+    /// with code 2, like DuckDB errors; denied SQL with
+    /// [`CAPABILITY_DENIED`], and is audited; SQL touching more tables than
+    /// the tier allows with [`LIMIT_EXCEEDED`].
+    fn authorize_sql(
+        &mut self,
+        capability: &str,
+        sql: &str,
+        access: SqlAccess,
+    ) -> Result<(), (u32, String)> {
+        match sql_policy::authorize(sql, access, &self.capabilities) {
+            Ok(tables) => self.use_tables(tables),
+            Err(SqlRejection::Invalid(message)) => Err((2, message)),
+            Err(SqlRejection::Denied { resource, reason }) => {
+                self.record_data_denial(capability, &resource, &reason);
+                Err((CAPABILITY_DENIED, reason))
+            }
+        }
+    }
+
+    /// Count `tables` against the tier's table limit, refusing the call if
+    /// the run would then have touched too many.
+    fn use_tables(&mut self, tables: Vec<String>) -> Result<(), (u32, String)> {
+        let new: Vec<String> = tables
+            .into_iter()
+            .filter(|t| !self.tables_used.contains(t))
+            .collect();
+        if self.tables_used.len() + new.len() > self.limits.tables {
+            return Err((LIMIT_EXCEEDED, self.exceed(Limit::Tables).to_string()));
+        }
+        self.tables_used.extend(new);
+        Ok(())
+    }
+
+    fn record_data_denial(&mut self, capability: &str, resource: &str, reason: &str) {
+        tracing::warn!(
+            app = %self.app_name,
+            capability,
+            resource,
+            reason,
+            "data access denied"
+        );
+        self.audit_entries.push(AuditRecord {
+            event_type: "data-denied".into(),
+            resource: resource.into(),
+            detail: format!("{capability}: {reason}"),
+            timestamp: chrono::Utc::now(),
+        });
+    }
+}
+
+// ── AI embeddings host function ──
 
 impl fractal::app::ai_embeddings::Host for HostState {
     async fn embed(
         &mut self,
-        _text: String,
+        text: String,
     ) -> Result<Vec<f32>, fractal::app::ai_embeddings::AiError> {
-        Err(fractal::app::ai_embeddings::AiError {
-            code: 1,
-            message: "embeddings not configured — use fractalaw embed CLI instead".into(),
-        })
+        let mut vectors = self.embed_impl(&[text])?;
+        Ok(vectors.pop().unwrap_or_default())
     }
 
     async fn embed_batch(
         &mut self,
-        _texts: Vec<String>,
+        texts: Vec<String>,
     ) -> Result<Vec<Vec<f32>>, fractal::app::ai_embeddings::AiError> {
-        Err(fractal::app::ai_embeddings::AiError {
-            code: 1,
-            message: "embeddings not configured — use fractalaw embed CLI instead".into(),
-        })
-    }
-}
-
-// ── AI inference host function ──
-
-impl fractal::app::ai_inference::Host for HostState {
-    async fn generate(
-        &mut self,
-        request: fractal::app::ai_inference::GenerateRequest,
-    ) -> Result<fractal::app::ai_inference::GenerateResponse, fractal::app::ai_embeddings::AiError>
-    {
-        self.generate_impl(request).await
+        self.embed_impl(&texts)
     }
 }
 
 impl HostState {
-    async fn generate_impl(
+    /// Embed `texts` with the attached model, enforcing the batch limit and fuel budget.
+    ///
+    /// Error codes: 1 = not configured, 2 = model failure, 3 = batch too large,
+    /// 4 = fuel budget exhausted.
+    fn embed_impl(
         &mut self,
-        request: fractal::app::ai_inference::GenerateRequest,
-    ) -> Result<fractal::app::ai_inference::GenerateResponse, fractal::app::ai_embeddings::AiError>
-    {
-        #[cfg(feature = "inference")]
+        texts: &[String],
+    ) -> Result<Vec<Vec<f32>>, fractal::app::ai_embeddings::AiError> {
+        #[cfg(feature = "embeddings")]
         {
             let config = self
-                .inference
-                .as_ref()
+                .embeddings
+                .as_mut()
                 .ok_or(fractal::app::ai_embeddings::AiError {
                     code: 1,
-                    message: "no inference backend configured (set ANTHROPIC_API_KEY)".into(),
+                    message: "embeddings not configured (pass --model-dir to fractalaw run)".into(),
                 })?;
 
-            // Build Claude Messages API request body.
-            let mut body = serde_json::json!({
-                "model": config.model,
-                "max_tokens": request.max_tokens,
-                "messages": [
-                    { "role": "user", "content": request.user_prompt }
-                ],
-            });
-
-            if let Some(system) = &request.system_prompt {
-                body["system"] = serde_json::json!(system);
-            }
-            if request.temperature > 0.0 {
-                body["temperature"] = serde_json::json!(request.temperature);
+            if texts.len() > config.max_batch_size {
+                return Err(fractal::app::ai_embeddings::AiError {
+                    code: 3,
+                    message: format!(
+                        "batch of {} texts exceeds the limit of {}",
+                        texts.len(),
+                        config.max_batch_size
+                    ),
+                });
             }
 
-            let resp = config
-                .client
-                .post("https://api.anthropic.com/v1/messages")
-                .header("x-api-key", &config.api_key)
-                .header("anthropic-version", "2023-06-01")
-                .header("content-type", "application/json")
-                .json(&body)
-                .send()
-                .await
-                .map_err(|e| fractal::app::ai_embeddings::AiError {
-                    code: 2,
-                    message: format!("HTTP request failed: {e}"),
-                })?;
-
-            let status = resp.status();
-            let resp_text =
-                resp.text()
-                    .await
-                    .map_err(|e| fractal::app::ai_embeddings::AiError {
-                        code: 2,
-                        message: format!("failed to read response body: {e}"),
-                    })?;
-
-            if !status.is_success() {
+            let charge = config.fuel_per_text.saturating_mul(texts.len() as u64);
+            let available = self.fuel_available();
+            if charge > available {
                 return Err(fractal::app::ai_embeddings::AiError {
-                    code: 2,
-                    message: format!("Claude API error ({}): {}", status, resp_text),
+                    code: 4,
+                    message: format!(
+                        "embedding {} texts would exceed the fuel budget ({charge} needed, \
+                         {available} of {} left)",
+                        texts.len(),
+                        self.fuel_limit
+                    ),
                 });
             }
 
-            let parsed: serde_json::Value = serde_json::from_str(&resp_text).map_err(|e| {
+            let start = std::time::Instant::now();
+            let refs: Vec<&str> = texts.iter().map(String::as_str).collect();
+            let vectors = config.embedder.embed_batch(&refs).map_err(|e| {
                 fractal::app::ai_embeddings::AiError {
-                    code: 3,
-                    message: format!("failed to parse response JSON: {e}"),
+                    code: 2,
+                    message: format!("embedding failed: {e}"),
                 }
             })?;
+            let elapsed = start.elapsed();
+
+            self.embedding_usage.calls += 1;
+            self.embedding_usage.texts += texts.len() as u64;
+            self.embedding_usage.fuel_charged += charge;
+            self.charge_fuel(charge);
+            self.embedding_usage.elapsed += elapsed;
+
+            tracing::debug!(
+                texts = texts.len(),
+                fuel = charge,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "embedding complete"
+            );
+
+            Ok(vectors)
+        }
+
+        #[cfg(not(feature = "embeddings"))]
+        {
+            let _ = texts;
+            Err(fractal::app::ai_embeddings::AiError {
+                code: 1,
+                message: "embeddings support not compiled in".into(),
+            })
+        }
+    }
+}
+
+// ── AI classify host function ──
+
+impl fractal::app::ai_classify::Host for HostState {
+    async fn classify(
+        &mut self,
+        text: String,
+        categories: Vec<String>,
+    ) -> Result<Vec<fractal::app::ai_classify::Classification>, fractal::app::ai_embeddings::AiError>
+    {
+        self.classify_impl(text, categories)
+    }
+
+    async fn classify_trained(
+        &mut self,
+        text: String,
+        taxonomy: fractal::app::ai_classify::Taxonomy,
+    ) -> Result<Vec<fractal::app::ai_classify::Classification>, fractal::app::ai_embeddings::AiError>
+    {
+        self.classify_trained_impl(text, taxonomy)
+    }
+}
+
+impl HostState {
+    /// Zero-shot classification: embed `text` alongside each category description
+    /// in one batch and rank categories by cosine similarity, best first.
+    fn classify_impl(
+        &mut self,
+        text: String,
+        categories: Vec<String>,
+    ) -> Result<Vec<fractal::app::ai_classify::Classification>, fractal::app::ai_embeddings::AiError>
+    {
+        if categories.is_empty() {
+            return Ok(Vec::new());
+        }
 
-            let text = parsed["content"][0]["text"]
-                .as_str()
+        let mut texts = Vec::with_capacity(categories.len() + 1);
+        texts.push(text);
+        texts.extend(categories.iter().cloned());
+        let vectors = self.embed_impl(&texts)?;
+
+        let (query, category_vectors) =
+            vectors
+                .split_first()
                 .ok_or(fractal::app::ai_embeddings::AiError {
-                    code: 3,
+                    code: 2,
+                    message: "embedding model returned no vectors".into(),
+                })?;
+        let ranked = categories
+            .into_iter()
+            .zip(category_vectors)
+            .map(|(category, v)| (category, dot(query, v)))
+            .collect();
+        Ok(to_classifications(ranked))
+    }
+
+    /// Rank the attached classifier's trained centroids for `taxonomy` against `text`.
+    fn classify_trained_impl(
+        &mut self,
+        text: String,
+        taxonomy: fractal::app::ai_classify::Taxonomy,
+    ) -> Result<Vec<fractal::app::ai_classify::Classification>, fractal::app::ai_embeddings::AiError>
+    {
+        #[cfg(feature = "embeddings")]
+        {
+            use fractal::app::ai_classify::Taxonomy;
+            use fractalaw_ai::LawClassifier;
+
+            if self.classifier.is_none() {
+                return Err(fractal::app::ai_embeddings::AiError {
+                    code: 1,
+                    message: "no trained classifier attached (run `fractalaw classify` first)"
+                        .into(),
+                });
+            }
+            let embedding = self.embed_impl(&[text])?.pop().unwrap_or_default();
+
+            let classifier = self.classifier.as_ref().expect("checked above");
+            if embedding.len() != classifier.dim() {
+                return Err(fractal::app::ai_embeddings::AiError {
+                    code: 2,
                     message: format!(
-                        "unexpected response structure (no content[0].text): {}",
-                        &resp_text[..resp_text.len().min(200)]
+                        "embedding dim {} does not match classifier dim {}",
+                        embedding.len(),
+                        classifier.dim()
                     ),
-                })?
-                .to_string();
-
-            let tokens_used = parsed["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32;
+                });
+            }
 
-            tracing::info!(
-                model = %config.model,
-                tokens_used,
-                "inference complete"
-            );
+            let taxonomy = match taxonomy {
+                Taxonomy::Family => fractalaw_ai::Taxonomy::Family,
+                Taxonomy::Domain => fractalaw_ai::Taxonomy::Domain,
+            };
+            Ok(to_classifications(classifier.rank(taxonomy, &embedding)))
+        }
 
-            Ok(fractal::app::ai_inference::GenerateResponse {
-                text,
-                tokens_used,
-                confidence: 1.0, // API responses don't have intrinsic confidence; guest decides
+        #[cfg(not(feature = "embeddings"))]
+        {
+            let _ = (text, taxonomy);
+            Err(fractal::app::ai_embeddings::AiError {
+                code: 1,
+                message: "embeddings support not compiled in".into(),
             })
         }
+    }
+}
+
+/// Sort `(category, score)` pairs descending and convert to WIT records.
+fn to_classifications(
+    mut ranked: Vec<(String, f32)>,
+) -> Vec<fractal::app::ai_classify::Classification> {
+    ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    ranked
+        .into_iter()
+        .map(|(category, score)| fractal::app::ai_classify::Classification { category, score })
+        .collect()
+}
+
+/// Dot product — equal to cosine similarity for the unit vectors the embedder returns.
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+// ── AI inference host function ──
+
+impl fractal::app::ai_inference::Host for HostState {
+    async fn generate(
+        &mut self,
+        request: fractal::app::ai_inference::GenerateRequest,
+    ) -> Result<fractal::app::ai_inference::GenerateResponse, fractal::app::ai_embeddings::AiError>
+    {
+        self.generate_impl(request).await
+    }
+}
+
+impl HostState {
+    async fn generate_impl(
+        &mut self,
+        request: fractal::app::ai_inference::GenerateRequest,
+    ) -> Result<fractal::app::ai_inference::GenerateResponse, fractal::app::ai_embeddings::AiError>
+    {
+        #[cfg(feature = "inference")]
+        {
+            let config = self
+                .inference
+                .as_ref()
+                .ok_or(fractal::app::ai_embeddings::AiError {
+                    code: 1,
+                    message: "no inference backend configured (set ANTHROPIC_API_KEY or FRACTALAW_INFERENCE_PROVIDER)"
+                        .into(),
+                })?;
+
+            let remaining = [
+                self.token_budget
+                    .per_run
+                    .map(|limit| limit.saturating_sub(self.inference_usage.tokens_used)),
+                self.token_budget.app.as_ref().map(|app| app.remaining()),
+            ]
+            .into_iter()
+            .flatten()
+            .min();
+            if remaining == Some(0) {
+                self.inference_usage.refused += 1;
+                if self.inference_usage.tokens_used >= self.limits.inference_tokens {
+                    self.exceed(Limit::InferenceTokens);
+                }
+                return Err(fractal::app::ai_embeddings::AiError {
+                    code: 5,
+                    message: format!(
+                        "token budget exhausted ({} tokens used this run{})",
+                        self.inference_usage.tokens_used,
+                        match &self.token_budget.app {
+                            Some(app) =>
+                                format!(", {} of {} for {}", app.spent(), app.limit, app.app),
+                            None => String::new(),
+                        }
+                    ),
+                });
+            }
+            // Clamped to `remaining` after the cache lookup, which keys on
+            // the request as the app made it.
+            let (response, cached) = config.generate_cached(request, remaining).await?;
+
+            self.inference_usage.calls += 1;
+            if cached {
+                self.inference_usage.cache_hits += 1;
+                return Ok(response);
+            }
+            let tokens = response.tokens_used as u64;
+            self.inference_usage.tokens_used += tokens;
+            self.inference_usage.input_tokens += response.usage.input_tokens as u64;
+            self.inference_usage.cached_tokens += response.usage.cached_tokens as u64;
+            if let Some(app) = &self.token_budget.app {
+                app.charge(tokens);
+            }
+            Ok(response)
+        }
 
         #[cfg(not(feature = "inference"))]
         {
@@ -404,6 +1259,177 @@ impl HostState {
     }
 }
 
+// ── App invoke host function ──
+
+impl fractal::app::app_invoke::Host for HostState {
+    async fn invoke(
+        &mut self,
+        request: fractal::app::app_invoke::InvokeRequest,
+    ) -> Result<fractal::app::app_invoke::InvokeResponse, fractal::app::app_invoke::InvokeError>
+    {
+        self.invoke_impl(request).await
+    }
+}
+
+impl HostState {
+    /// Run `request.app` on this run's runtime with a slice of its fuel.
+    ///
+    /// The callee runs with the grants of its own manifest; apps without
+    /// one cannot be invoked.
+    ///
+    /// Error codes: 1 = no apps directory, 2 = unknown app or too deeply
+    /// nested, 3 = the callee could not be run, 4 = the callee failed,
+    /// 403 = app not granted or without a manifest, 429 = fuel slice over
+    /// what is left.
+    async fn invoke_impl(
+        &mut self,
+        request: fractal::app::app_invoke::InvokeRequest,
+    ) -> Result<fractal::app::app_invoke::InvokeResponse, fractal::app::app_invoke::InvokeError>
+    {
+        use fractal::app::app_invoke::{InvokeError, InvokeResponse};
+        use fractal::app::events_emit::DomainEvent;
+        use fractal::app::events_receive::Delivery;
+
+        let error = |code: u32, message: String| InvokeError { code, message };
+        let app = request.app;
+        let unavailable = || error(1, "app invocation not available: no apps directory".into());
+        let runtime = self.runtime.clone().ok_or_else(unavailable)?;
+        if !self
+            .capabilities
+            .app_invoke
+            .as_ref()
+            .is_some_and(|grant| grant.allows(&app))
+        {
+            tracing::warn!(app = %self.app_name, callee = %app, "app invocation denied");
+            self.audit_entries.push(AuditRecord {
+                event_type: "capability-denied".into(),
+                resource: "app-invoke".into(),
+                detail: format!("invoke of '{app}', which is not granted"),
+                timestamp: chrono::Utc::now(),
+            });
+            return Err(error(
+                CAPABILITY_DENIED,
+                format!("app '{app}' not granted to app-invoke"),
+            ));
+        }
+        let depth = self.invocation.as_ref().map_or(0, |i| i.depth) + 1;
+        if depth > MAX_INVOKE_DEPTH {
+            return Err(error(
+                2,
+                format!("calling '{app}' would nest app calls deeper than {MAX_INVOKE_DEPTH}"),
+            ));
+        }
+        let remaining = self.fuel_available();
+        let fuel = request.fuel.unwrap_or(remaining);
+        if fuel > remaining || fuel == 0 {
+            return Err(error(
+                LIMIT_EXCEEDED,
+                format!("fuel slice of {fuel} for '{app}' exceeds the {remaining} left"),
+            ));
+        }
+
+        let component = runtime
+            .invoke_component(&app)
+            .ok_or_else(unavailable)?
+            .map_err(|e| error(2, format!("{e:#}")))?;
+        let Some(manifest) = AppManifest::load_for(&component)
+            .map_err(|e| error(3, format!("preparing '{app}': {e}")))?
+        else {
+            tracing::warn!(app = %self.app_name, callee = %app, "invoked app has no manifest");
+            self.audit_entries.push(AuditRecord {
+                event_type: "capability-denied".into(),
+                resource: "app-invoke".into(),
+                detail: format!("invoke of '{app}', which has no manifest"),
+                timestamp: chrono::Utc::now(),
+            });
+            return Err(error(
+                CAPABILITY_DENIED,
+                format!("app '{app}' has no manifest and cannot be invoked"),
+            ));
+        };
+        let mut opts = runtime
+            .invoke_options(&app)
+            .map_err(|e| error(3, format!("preparing '{app}': {e:#}")))?;
+        // The callee runs with its own manifest and tier.
+        opts.manifest = Some(manifest);
+        opts.tier = None;
+        opts.trigger = Some(Delivery {
+            id: 0,
+            event: DomainEvent {
+                event_type: "invoke".into(),
+                payload: request.payload,
+                source_app: self.app_name.clone(),
+            },
+            attempt: 1,
+        });
+        opts.invocation = Some(Invocation {
+            caller: self.app_name.clone(),
+            depth,
+        });
+        #[cfg(feature = "duckdb")]
+        if opts.duck.is_none()
+            && let Some(duck) = &self.duck
+        {
+            opts.duck = Some(
+                duck.try_clone()
+                    .map_err(|e| error(3, format!("connecting '{app}' to the store: {e}")))?,
+            );
+        }
+
+        // Boxed: the callee's run may itself invoke apps.
+        let result = match Box::pin(runtime.run(&component, fuel, opts)).await {
+            Ok(result) => result,
+            Err(e) => {
+                let message = format!("running '{app}' failed: {e:#}");
+                self.audit_entries.push(AuditRecord {
+                    event_type: "app-invoked".into(),
+                    resource: app,
+                    detail: message.clone(),
+                    timestamp: chrono::Utc::now(),
+                });
+                return Err(error(3, message));
+            }
+        };
+
+        self.invoke_usage.calls += 1;
+        self.invoke_usage.fuel_charged = self
+            .invoke_usage
+            .fuel_charged
+            .saturating_add(result.fuel_consumed);
+        self.charge_fuel(result.fuel_consumed);
+        let outcome = match &result.output {
+            Ok(_) => "ok".to_string(),
+            Err(e) => format!("failed: {e}"),
+        };
+        self.audit_entries.push(AuditRecord {
+            event_type: "app-invoked".into(),
+            resource: app.clone(),
+            detail: format!(
+                "depth {depth}, {} of {fuel} fuel, {outcome}",
+                result.fuel_consumed
+            ),
+            timestamp: chrono::Utc::now(),
+        });
+        tracing::info!(
+            app = %self.app_name,
+            callee = %app,
+            fuel = result.fuel_consumed,
+            ok = result.output.is_ok(),
+            "app invoked"
+        );
+        match result.output {
+            Ok(output) => Ok(InvokeResponse {
+                output,
+                fuel_consumed: result.fuel_consumed,
+            }),
+            Err(e) => {
+                self.invoke_usage.failed += 1;
+                Err(error(4, e))
+            }
+        }
+    }
+}
+
 // ── Arrow IPC encoding/decoding ──
 
 /// Encode Arrow RecordBatches into IPC streaming format bytes.
@@ -443,17 +1469,24 @@ fn decode_ipc(
     reader.into_iter().collect()
 }
 
+/// Component instances the pooling allocator pre-allocates; a [`Runtime`]
+/// runs at most this many apps at once.
+pub const MAX_INSTANCES: u32 = 16;
+
 /// Create an [`Engine`] configured for micro-app execution.
 ///
 /// - Pooling allocator with pre-allocated instance slots
 /// - Fuel metering for deterministic execution budgets
 /// - Epoch interruption for wall-clock timeouts
+/// - Memory slots as large as the [`Tier::Heavy`] limit
 /// - Component model + async support
 pub fn create_engine() -> anyhow::Result<Engine> {
     let mut pool = PoolingAllocationConfig::new();
-    pool.total_component_instances(16);
-    pool.total_memories(32);
-    pool.max_memory_size(64 * 1024 * 1024); // 64 MiB per instance
+    pool.total_component_instances(MAX_INSTANCES);
+    pool.total_memories(2 * MAX_INSTANCES);
+    // Slots fit the largest tier; smaller tiers are held to theirs by the
+    // store's limiter.
+    pool.max_memory_size(Tier::Heavy.limits().memory_bytes);
 
     let mut config = Config::new();
     config.async_support(true);
@@ -465,81 +1498,75 @@ pub fn create_engine() -> anyhow::Result<Engine> {
     Engine::new(&config)
 }
 
-/// Load and compile a WASM component from disk.
+/// Load and compile a WASM component from disk. See [`AotCache`] to reuse
+/// the compiled code across processes.
 pub async fn load_component(engine: &Engine, path: &Path) -> anyhow::Result<Component> {
     let bytes = tokio::fs::read(path).await?;
     Component::new(engine, &bytes)
 }
 
-/// Create a [`wasmtime::component::Linker`] with host functions wired up.
+/// Create a [`wasmtime::component::Linker`] with every host function wired
+/// up. See [`create_linker_for`] to link only an app's granted capabilities.
 pub fn create_linker(engine: &Engine) -> anyhow::Result<wasmtime::component::Linker<HostState>> {
-    let mut linker = wasmtime::component::Linker::new(engine);
-    // Wire up our fractal:app host functions
-    MicroApp::add_to_linker::<HostState, HasSelf<HostState>>(&mut linker, |state| state)?;
-    // Wire up WASI p2 interfaces (cli, io, filesystem, clocks) required by the wasip1 adapter
-    wasmtime_wasi::p2::add_to_linker_async(&mut linker)?;
-    Ok(linker)
+    create_linker_for(engine, &AppManifest::unrestricted().capabilities)
 }
 
 /// Optional host resources to attach when running a micro-app.
 #[derive(Default)]
 pub struct RunOptions {
+    /// Event delivery that triggered the run (see [`dispatch_events`]).
+    pub trigger: Option<fractal::app::events_receive::Delivery>,
+    /// Capabilities granted to the run. When `None`, [`Runtime::run`] looks
+    /// for the app's manifest, and grants nothing if there is none.
+    pub manifest: Option<AppManifest>,
+    /// Tier whose limits apply to the run. When `None`, [`Runtime::run`]
+    /// uses the manifest's tier, else [`Tier::Standard`].
+    pub tier: Option<Tier>,
+    /// The app-invoke call that started the run, if any; set by the host.
+    pub invocation: Option<Invocation>,
     #[cfg(feature = "duckdb")]
     pub duck: Option<DuckStore>,
     #[cfg(feature = "inference")]
     pub inference: Option<InferenceConfig>,
+    #[cfg(feature = "inference")]
+    pub token_budget: TokenBudget,
+    #[cfg(feature = "embeddings")]
+    pub embeddings: Option<EmbeddingsConfig>,
+    #[cfg(feature = "embeddings")]
+    pub classifier: Option<fractalaw_ai::Classifier>,
+}
+
+/// An app-invoke call that started a run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invocation {
+    /// The calling app.
+    pub caller: String,
+    /// 1 for an app called from a top-level run, 2 for one it calls, and so
+    /// on up to [`MAX_INVOKE_DEPTH`].
+    pub depth: u32,
 }
 
 /// Load, instantiate, and execute a micro-app component.
 ///
 /// Pass host resources via [`RunOptions`] to enable data and inference host functions.
+/// Each call compiles the component on a fresh engine; use a [`Runtime`] to
+/// run components repeatedly.
 pub async fn run_component(
     wasm_path: &Path,
     fuel: u64,
     opts: RunOptions,
 ) -> anyhow::Result<RunResult> {
-    let engine = create_engine()?;
-    let component = load_component(&engine, wasm_path).await?;
-    let linker = create_linker(&engine)?;
-
-    let mut state = HostState::new();
-    #[cfg(feature = "duckdb")]
-    if let Some(store) = opts.duck {
-        state = state.with_duck(store);
-    }
-    #[cfg(feature = "inference")]
-    if let Some(config) = opts.inference {
-        state = state.with_inference(config);
-    }
-
-    let mut store = Store::new(&engine, state);
-    store.set_fuel(fuel)?;
-    // Allow 100 epoch ticks before interruption (= 100 seconds with 1s ticker).
-    store.set_epoch_deadline(100);
-
-    // Spawn a background task to increment the epoch every second.
-    let epoch_engine = engine.clone();
-    let epoch_handle = tokio::spawn(async move {
-        let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
-        loop {
-            interval.tick().await;
-            epoch_engine.increment_epoch();
-        }
-    });
-
-    let instance = MicroApp::instantiate_async(&mut store, &component, &linker).await?;
-    let output = instance.call_run(&mut store).await?;
-
-    epoch_handle.abort();
-
-    let fuel_consumed = fuel.saturating_sub(store.get_fuel()?);
-    let state = store.into_data();
+    Runtime::new()?.run(wasm_path, fuel, opts).await
+}
 
-    Ok(RunResult {
-        output,
-        audit_entries: state.audit_entries,
-        fuel_consumed,
-    })
+/// App name for a component: its file stem with `_` as `-`, so
+/// `hello_world.wasm` runs as `hello-world`.
+pub fn app_name(wasm_path: &Path) -> String {
+    wasm_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("unknown")
+        .replace('_', "-")
 }
 
 #[cfg(test)]
@@ -552,6 +1579,14 @@ mod tests {
             .join("../../guests/hello-world/target/wasm32-wasip1/release/hello_world.wasm")
     }
 
+    /// The manifest shipped for `app` in the repo's `apps/` directory.
+    fn shipped_manifest(app: &str) -> Option<AppManifest> {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../apps");
+        let manifest = AppManifest::load_for(&dir.join(format!("{app}.wasm"))).unwrap();
+        assert!(manifest.is_some(), "no manifest shipped for {app}");
+        manifest
+    }
+
     /// Helper: run hello-world guest with no host resources attached.
     async fn run_hello_world(fuel: u64) -> RunResult {
         run_component(&hello_world_wasm(), fuel, RunOptions::default())
@@ -568,16 +1603,184 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn apps_cannot_record_checkpoint_events() {
+        use fractal::app::audit_log::{AuditEntry, Host};
+
+        let mut state = HostState::new().with_app_name("forger");
+        state
+            .record_event(AuditEntry {
+                event_type: fractalaw_core::CHECKPOINT_EVENT.into(),
+                resource: "audit_log".into(),
+                detail: "{}".into(),
+            })
+            .await;
+        assert_eq!(state.audit_entries[0].event_type, "app-checkpoint");
+    }
+
+    #[tokio::test]
+    async fn runtime_reuses_compiled_component() {
+        let runtime = Runtime::new().unwrap();
+        runtime.preload(&hello_world_wasm()).await.unwrap();
+        assert_eq!(runtime.loaded(), vec![hello_world_wasm()]);
+
+        for _ in 0..3 {
+            let result = runtime
+                .run(&hello_world_wasm(), 1_000_000_000, RunOptions::default())
+                .await
+                .unwrap();
+            assert!(result.output.is_ok());
+        }
+        assert_eq!(runtime.loaded().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn aot_cache_reuses_and_prunes_artifacts() {
+        use std::collections::HashSet;
+
+        let dir = std::env::temp_dir().join(format!("fractalaw-aot-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = AotCache::open(&dir).unwrap();
+        let engine = create_engine().unwrap();
+
+        let (_, compiled) = cache.load(&engine, &hello_world_wasm()).await.unwrap();
+        assert!(compiled);
+        let (_, compiled) = cache.load(&engine, &hello_world_wasm()).await.unwrap();
+        assert!(!compiled);
+
+        // An unusable artifact is recompiled rather than trusted.
+        let key = AotCache::key(&engine, &std::fs::read(hello_world_wasm()).unwrap());
+        std::fs::write(dir.join(format!("{key}.cwasm")), b"not a cwasm").unwrap();
+        let (_, compiled) = cache.load(&engine, &hello_world_wasm()).await.unwrap();
+        assert!(compiled);
+
+        let runtime = Runtime::new().unwrap().with_aot_cache(cache.clone());
+        let result = runtime
+            .run(&hello_world_wasm(), 1_000_000_000, RunOptions::default())
+            .await
+            .unwrap();
+        assert!(result.output.is_ok());
+
+        assert_eq!(cache.prune(&HashSet::from([key])).unwrap(), 0);
+        assert_eq!(cache.prune(&HashSet::new()).unwrap(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[tokio::test]
     async fn audit_entry_recorded() {
         let result = run_hello_world(1_000_000_000).await;
-        assert_eq!(result.audit_entries.len(), 1);
-        let entry = &result.audit_entries[0];
+        assert_eq!(result.audit_entries.len(), 2);
+        let granted = &result.audit_entries[0];
+        assert_eq!(granted.event_type, "capabilities-granted");
+        assert_eq!(granted.resource, "hello-world");
+        let entry = &result.audit_entries[1];
         assert_eq!(entry.event_type, "app-started");
         assert_eq!(entry.resource, "hello-world");
         assert_eq!(entry.detail, "Bootstrap test — first micro-app execution");
     }
 
+    #[tokio::test]
+    async fn exceeding_tier_fuel_is_reported() {
+        let opts = RunOptions {
+            tier: Some(Tier::Lightweight),
+            ..RunOptions::default()
+        };
+        let result = run_component(&hello_world_wasm(), 10_000, opts)
+            .await
+            .expect("run_component failed");
+        let exceeded = LimitExceeded {
+            tier: Tier::Lightweight,
+            limit: Limit::Fuel,
+            max: 10_000,
+        };
+        assert_eq!(result.tier, Tier::Lightweight);
+        assert_eq!(result.limit_exceeded, Some(exceeded));
+        assert_eq!(result.output, Err(exceeded.to_string()));
+
+        let result = run_hello_world(TIER_FUEL).await;
+        assert_eq!(result.tier, Tier::Standard);
+        assert_eq!(result.limit_exceeded, None);
+    }
+
+    #[tokio::test]
+    async fn invoke_runs_granted_apps_on_a_fuel_slice() {
+        use fractal::app::app_invoke::{Host, InvokeRequest};
+
+        // hello-world has a manifest granting nothing; bare has none.
+        let apps_dir =
+            std::env::temp_dir().join(format!("fractalaw-invoke-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&apps_dir);
+        std::fs::create_dir_all(&apps_dir).unwrap();
+        std::fs::copy(hello_world_wasm(), apps_dir.join("hello_world.wasm")).unwrap();
+        std::fs::write(apps_dir.join("hello_world.manifest.json"), "{}").unwrap();
+        std::fs::copy(hello_world_wasm(), apps_dir.join("bare.wasm")).unwrap();
+        let runtime = Runtime::new()
+            .unwrap()
+            .with_apps(apps_dir.clone(), |_| Ok(RunOptions::default()));
+        let mut capabilities = AppManifest::default().capabilities;
+        capabilities.app_invoke = Some(fractalaw_core::AppGrant {
+            apps: Some(vec!["hello-world".into(), "bare".into()]),
+        });
+        let mut state = HostState::new()
+            .with_app_name("caller")
+            .with_capabilities(&capabilities)
+            .with_tier(Tier::Standard);
+        state.runtime = Some(runtime);
+        let request = |app: &str, fuel: Option<u64>| InvokeRequest {
+            app: app.into(),
+            payload: b"hello".to_vec(),
+            fuel,
+        };
+
+        let response = state
+            .invoke(request("hello-world", Some(1_000_000_000)))
+            .await
+            .unwrap();
+        assert_eq!(response.output, "Hello from the first Fractalaw micro-app!");
+        assert!(response.fuel_consumed > 0);
+        assert_eq!(state.invoke_usage.calls, 1);
+        assert_eq!(state.invoke_usage.fuel_charged, response.fuel_consumed);
+        assert_eq!(state.fuel_pending, response.fuel_consumed);
+        let audited = state.audit_entries.last().unwrap();
+        assert_eq!(audited.event_type, "app-invoked");
+        assert_eq!(audited.resource, "hello-world");
+
+        let err = state
+            .invoke(request("drrp-polisher", None))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, CAPABILITY_DENIED);
+        // A granted app without a manifest would run unrestricted.
+        let err = state.invoke(request("bare", None)).await.unwrap_err();
+        assert_eq!(err.code, CAPABILITY_DENIED);
+        assert_eq!(state.invoke_usage.calls, 1);
+        let err = state
+            .invoke(request("hello-world", Some(u64::MAX)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, LIMIT_EXCEEDED);
+        // The slice comes out of the caller's live fuel, not its budget.
+        state.fuel_left = Some(response.fuel_consumed + 1_000);
+        let err = state
+            .invoke(request("hello-world", Some(1_000_000_000)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, LIMIT_EXCEEDED);
+        state.fuel_left = None;
+
+        state.invocation = Some(Invocation {
+            caller: "outer".into(),
+            depth: MAX_INVOKE_DEPTH,
+        });
+        let err = state
+            .invoke(request("hello-world", None))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, 2);
+        assert_eq!(state.invoke_usage.calls, 1);
+        let _ = std::fs::remove_dir_all(&apps_dir);
+    }
+
     #[tokio::test]
     async fn fuel_consumed() {
         let budget = 1_000_000_000u64;
@@ -604,7 +1807,9 @@ mod tests {
             store
                 .execute("INSERT INTO test_data VALUES (1, 'alpha'), (2, 'beta'), (3, 'gamma')")
                 .unwrap();
-            HostState::new().with_duck(store)
+            HostState::new()
+                .with_duck(store)
+                .with_capabilities(&AppManifest::unrestricted().capabilities)
         }
 
         #[tokio::test]
@@ -666,6 +1871,104 @@ mod tests {
             assert!(!bytes.is_empty());
         }
 
+        #[tokio::test]
+        async fn execute_returns_affected_rows() {
+            use fractal::app::data_mutate::Host;
+
+            let mut state = state_with_duck();
+            let changed = state
+                .execute("UPDATE test_data SET name = upper(name) WHERE id > 1".into())
+                .await
+                .expect("execute failed");
+            assert_eq!(changed, 2);
+        }
+
+        #[tokio::test]
+        async fn sql_outside_grants_is_denied_and_audited() {
+            use fractal::app::data_mutate::Host;
+            use fractal::app::data_query::Host as QHost;
+
+            let manifest = AppManifest::from_json(
+                r#"{"capabilities": {
+                    "data-query": {"tables": ["test_data"]},
+                    "data-mutate": {"tables": ["notes"]}
+                }}"#,
+            )
+            .unwrap();
+            let mut state = state_with_duck().with_capabilities(&manifest.capabilities);
+
+            state
+                .query("SELECT * FROM test_data".into())
+                .await
+                .expect("granted read failed");
+            let err = state
+                .execute("DROP TABLE test_data".into())
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, CAPABILITY_DENIED);
+            let err = state
+                .execute("DELETE FROM test_data".into())
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, CAPABILITY_DENIED);
+            let err = state
+                .insert("test_data".into(), Vec::new())
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, CAPABILITY_DENIED);
+
+            let denials: Vec<&str> = state
+                .audit_entries
+                .iter()
+                .filter(|e| e.event_type == "data-denied")
+                .map(|e| e.resource.as_str())
+                .collect();
+            assert_eq!(denials, ["DROP", "test_data", "test_data"]);
+
+            let bytes = state
+                .query("SELECT count(*)::BIGINT FROM test_data".into())
+                .await
+                .unwrap();
+            let batches = decode_ipc(&bytes).unwrap();
+            let count = batches[0]
+                .column(0)
+                .as_any()
+                .downcast_ref::<arrow::array::Int64Array>()
+                .unwrap()
+                .value(0);
+            assert_eq!(count, 3, "denied statements must not run");
+        }
+
+        #[tokio::test]
+        async fn tables_beyond_the_tier_limit_are_refused() {
+            use fractal::app::data_query::Host;
+
+            let mut state = state_with_duck().with_tier(Tier::Lightweight);
+            for table in ["a", "b", "c"] {
+                state
+                    .duck
+                    .as_ref()
+                    .unwrap()
+                    .execute(&format!("CREATE TABLE {table} (id INTEGER)"))
+                    .unwrap();
+            }
+            state
+                .query("SELECT * FROM test_data, a, b, c".into())
+                .await
+                .expect("four tables are within the lightweight tier");
+            state.query("SELECT * FROM a".into()).await.unwrap();
+
+            state
+                .duck
+                .as_ref()
+                .unwrap()
+                .execute("CREATE TABLE d (id INTEGER)")
+                .unwrap();
+            let err = state.query("SELECT * FROM d".into()).await.unwrap_err();
+            assert_eq!(err.code, LIMIT_EXCEEDED);
+            assert_eq!(state.limit_exceeded.map(|e| e.limit), Some(Limit::Tables));
+        }
+
         #[tokio::test]
         async fn insert_arrow_ipc_roundtrip() {
             use arrow::array::{Int32Array, StringArray};
@@ -719,29 +2022,332 @@ mod tests {
             assert_eq!(col.value(0), 5);
         }
 
+        #[tokio::test]
+        async fn new_state_grants_no_tables() {
+            use fractal::app::data_query::Host;
+
+            let store = DuckStore::open().unwrap();
+            store.execute("CREATE TABLE t (id INTEGER)").unwrap();
+            let mut state = HostState::new().with_duck(store);
+            let err = state.query("SELECT * FROM t".into()).await.unwrap_err();
+            assert_eq!(err.code, CAPABILITY_DENIED);
+            assert!(HostState::new().capabilities.granted().is_empty());
+        }
+
         #[tokio::test]
         async fn insert_without_duck_errors() {
             use fractal::app::data_mutate::Host;
 
-            let mut state = HostState::new();
+            let mut state =
+                HostState::new().with_capabilities(&AppManifest::unrestricted().capabilities);
             let err = state.insert("test".into(), vec![]).await.unwrap_err();
             assert_eq!(err.code, 1);
         }
 
-        // ── Integration test: data-test guest with DuckDB ──
+        // ── Event host function tests ──
 
-        fn data_test_wasm() -> PathBuf {
-            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-                .join("../../guests/data-test/target/wasm32-wasip1/release/data_test.wasm")
+        fn event(event_type: &str, source_app: &str) -> fractal::app::events_emit::DomainEvent {
+            fractal::app::events_emit::DomainEvent {
+                event_type: event_type.into(),
+                payload: b"{\"law\":\"UK_ukpga_1974_37\"}".to_vec(),
+                source_app: source_app.into(),
+            }
         }
 
         #[tokio::test]
-        async fn data_test_guest_end_to_end() {
+        async fn emit_logs_event_and_queues_deliveries() {
+            use fractal::app::events_emit::Host;
+
+            let duck = DuckStore::open().unwrap();
+            duck.subscribe("indexer", "law-updated", "indexer.wasm")
+                .unwrap();
+            let mut state = HostState::new()
+                .with_duck(duck.try_clone().unwrap())
+                .with_app_name("drrp-polisher");
+
+            let first = state.emit(event("law-updated", "")).await.unwrap();
+            let second = state
+                .emit(event("law-updated", "drrp-polisher"))
+                .await
+                .unwrap();
+            assert!(second > first);
+            assert_eq!(state.emitted_events, vec![first, second]);
+
+            let due = duck.due_deliveries(10).unwrap();
+            assert_eq!(due.len(), 2);
+            assert_eq!(due[0].event_id, first);
+            assert_eq!(due[0].source_app, "drrp-polisher");
+            assert_eq!(due[0].component, "indexer.wasm");
+        }
+
+        #[tokio::test]
+        async fn emit_rejects_invalid_events() {
+            use fractal::app::events_emit::Host;
+
+            let mut state = HostState::new()
+                .with_duck(DuckStore::open().unwrap())
+                .with_app_name("drrp-polisher");
+            let err = state
+                .emit(event("law-updated", "someone-else"))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 2);
+            let err = state.emit(event(" ", "")).await.unwrap_err();
+            assert_eq!(err.code, 2);
+            assert!(state.emitted_events.is_empty());
+
+            let mut state = HostState::new().with_app_name("drrp-polisher");
+            let err = state.emit(event("law-updated", "")).await.unwrap_err();
+            assert_eq!(err.code, 1);
+        }
+
+        #[tokio::test]
+        async fn failing_subscriber_is_dead_lettered() {
+            let runtime = Runtime::new().unwrap();
+            let duck = DuckStore::open().unwrap();
+            duck.subscribe("missing", "law-updated", "/nonexistent/missing.wasm")
+                .unwrap();
+            duck.append_event("law-updated", b"{}", "test").unwrap();
+
+            let policy = DeliveryPolicy {
+                max_attempts: 2,
+                retry_delay: std::time::Duration::ZERO,
+                ..DeliveryPolicy::default()
+            };
+            let options = |_: &fractalaw_core::PendingDelivery| -> anyhow::Result<RunOptions> {
+                Ok(RunOptions {
+                    trigger: None,
+                    manifest: None,
+                    tier: None,
+                    invocation: None,
+                    duck: None,
+                    #[cfg(feature = "inference")]
+                    inference: None,
+                    #[cfg(feature = "inference")]
+                    token_budget: TokenBudget::default(),
+                    #[cfg(feature = "embeddings")]
+                    embeddings: None,
+                    #[cfg(feature = "embeddings")]
+                    classifier: None,
+                })
+            };
+
+            let report = dispatch_events(&runtime, &duck, &policy, 1, options)
+                .await
+                .unwrap();
+            assert_eq!(report.retrying, 1);
+            let report = dispatch_events(&runtime, &duck, &policy, 10, options)
+                .await
+                .unwrap();
+            assert_eq!(report.dead_lettered, 1);
+            assert!(duck.due_deliveries(10).unwrap().is_empty());
+
+            let dead: usize = duck
+                .dead_letters(10)
+                .unwrap()
+                .iter()
+                .map(|b| b.num_rows())
+                .sum();
+            assert_eq!(dead, 1);
+        }
+
+        // ── Schedule host function tests ──
+
+        fn scheduled(
+            cron: Option<&str>,
+            run_at: Option<u64>,
+        ) -> fractal::app::events_schedule::ScheduledTask {
+            fractal::app::events_schedule::ScheduledTask {
+                name: "nightly-polish".into(),
+                cron: cron.map(Into::into),
+                run_at,
+                target_app: "drrp-polisher".into(),
+                payload: b"{}".to_vec(),
+            }
+        }
+
+        fn test_clock() -> std::sync::Arc<fractalaw_core::ManualClock> {
+            std::sync::Arc::new(fractalaw_core::ManualClock::new(
+                chrono::DateTime::from_timestamp(1_800_000_000, 0).unwrap(), // 2027-01-15T08:00Z
+            ))
+        }
+
+        #[tokio::test]
+        async fn schedule_and_cancel_own_tasks() {
+            use fractal::app::events_schedule::Host;
+
+            let duck = DuckStore::open().unwrap();
+            let mut state = HostState::new()
+                .with_duck(duck.try_clone().unwrap())
+                .with_clock(test_clock())
+                .with_app_name("planner")
+                .with_capabilities(&AppManifest::unrestricted().capabilities);
+
+            let id = state
+                .schedule(scheduled(Some("@daily"), None))
+                .await
+                .unwrap();
+            assert!(duck.due_tasks(1_800_000_000, 10).unwrap().is_empty());
+            let due = duck.due_tasks(1_800_000_000 + 86_400, 10).unwrap();
+            assert_eq!(due.len(), 1);
+            assert_eq!(due[0].id, id);
+            assert_eq!(due[0].owner_app, "planner");
+
+            let mut other = HostState::new()
+                .with_duck(duck.try_clone().unwrap())
+                .with_app_name("intruder");
+            assert_eq!(other.cancel(id).await.unwrap_err().code, 2);
+            state.cancel(id).await.unwrap();
+            assert_eq!(state.cancel(id).await.unwrap_err().code, 2);
+            assert_eq!(state.cancel(id + 1).await.unwrap_err().code, 2);
+        }
+
+        #[tokio::test]
+        async fn schedule_rejects_invalid_tasks() {
+            use fractal::app::events_schedule::Host;
+
+            let unrestricted = AppManifest::unrestricted().capabilities;
+            let mut state = HostState::new()
+                .with_duck(DuckStore::open().unwrap())
+                .with_app_name("planner")
+                .with_capabilities(&unrestricted);
+            for task in [
+                scheduled(None, None),
+                scheduled(Some("@daily"), Some(1)),
+                scheduled(Some("61 * * * *"), None),
+                fractal::app::events_schedule::ScheduledTask {
+                    target_app: "../escape".into(),
+                    ..scheduled(Some("@daily"), None)
+                },
+            ] {
+                assert_eq!(state.schedule(task).await.unwrap_err().code, 2);
+            }
+
+            let mut state = HostState::new().with_capabilities(&unrestricted);
+            let err = state
+                .schedule(scheduled(Some("@daily"), None))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 1);
+        }
+
+        #[tokio::test]
+        async fn schedule_targets_only_invokable_apps() {
+            use fractal::app::events_schedule::Host;
+
+            let mut capabilities = AppManifest::default().capabilities;
+            capabilities.app_invoke = Some(fractalaw_core::AppGrant {
+                apps: Some(vec!["hello-world".into()]),
+            });
+            let mut state = HostState::new()
+                .with_duck(DuckStore::open().unwrap())
+                .with_clock(test_clock())
+                .with_app_name("planner")
+                .with_capabilities(&capabilities);
+            let err = state
+                .schedule(scheduled(Some("@daily"), None))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, CAPABILITY_DENIED);
+            assert_eq!(
+                state.audit_entries.last().unwrap().event_type,
+                "capability-denied"
+            );
+
+            for target_app in ["hello-world", "planner"] {
+                let task = fractal::app::events_schedule::ScheduledTask {
+                    target_app: target_app.into(),
+                    ..scheduled(Some("@daily"), None)
+                };
+                state.schedule(task).await.unwrap();
+            }
+        }
+
+        #[tokio::test]
+        async fn scheduler_records_failed_runs() {
+            use fractalaw_core::Clock;
+
+            let runtime = Runtime::new().unwrap();
             let duck = DuckStore::open().unwrap();
+            let clock = test_clock();
+            let owner = |cron: Option<&str>, run_at| fractalaw_core::ScheduledTask {
+                id: 0,
+                name: "polish".into(),
+                cron: cron.map(Into::into),
+                run_at,
+                target_app: "missing-app".into(),
+                payload: Vec::new(),
+                owner_app: "cli".into(),
+            };
+            let now = clock.now().timestamp();
+            duck.insert_scheduled_task(&owner(None, Some(now as u64)), now)
+                .unwrap();
+            duck.insert_scheduled_task(&owner(Some("0 * * * *"), None), now)
+                .unwrap();
+
+            let options = |_: &fractalaw_core::ScheduledTask| -> anyhow::Result<RunOptions> {
+                unreachable!("no component to run")
+            };
+            let apps_dir = Path::new("/nonexistent/apps");
+            let report = run_due_tasks(
+                &runtime,
+                &duck,
+                clock.as_ref(),
+                apps_dir,
+                1_000,
+                10,
+                options,
+            )
+            .await
+            .unwrap();
+            assert_eq!(report, ScheduleReport { ran: 0, failed: 2 });
+
+            // The one-shot task is finished; the hourly one waits for 09:00.
+            assert!(duck.due_tasks(now + 3_599, 10).unwrap().is_empty());
+            let due = duck.due_tasks(now + 3_600, 10).unwrap();
+            assert_eq!(due.len(), 1);
+            assert!(due[0].cron.is_some());
+
+            clock.advance(chrono::Duration::hours(1));
+            let report = run_due_tasks(
+                &runtime,
+                &duck,
+                clock.as_ref(),
+                apps_dir,
+                1_000,
+                10,
+                options,
+            )
+            .await
+            .unwrap();
+            assert_eq!(report.failed, 1);
+        }
+
+        // ── Integration test: data-test guest with DuckDB ──
+
+        fn data_test_wasm() -> PathBuf {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("../../guests/data-test/target/wasm32-wasip1/release/data_test.wasm")
+        }
+
+        #[tokio::test]
+        async fn data_test_guest_end_to_end() {
+            let duck = DuckStore::open().unwrap();
+            let host_duck = duck.try_clone().unwrap();
             let opts = RunOptions {
+                trigger: None,
+                manifest: shipped_manifest("data_test"),
+                tier: None,
+                invocation: None,
                 duck: Some(duck),
                 #[cfg(feature = "inference")]
                 inference: None,
+                #[cfg(feature = "inference")]
+                token_budget: TokenBudget::default(),
+                #[cfg(feature = "embeddings")]
+                embeddings: None,
+                #[cfg(feature = "embeddings")]
+                classifier: None,
             };
             let result = run_component(&data_test_wasm(), 1_000_000_000, opts)
                 .await
@@ -758,24 +2364,111 @@ mod tests {
                 "should mention IPC bytes: {output}"
             );
 
-            // Should have 3 audit entries: app-started, ddl-complete, query-complete
+            // Should have 4 audit entries: capabilities-granted, app-started,
+            // ddl-complete, query-complete
             assert_eq!(
                 result.audit_entries.len(),
-                3,
-                "expected 3 audit entries, got: {:?}",
+                4,
+                "expected 4 audit entries, got: {:?}",
                 result
                     .audit_entries
                     .iter()
                     .map(|e| &e.event_type)
                     .collect::<Vec<_>>()
             );
-            assert_eq!(result.audit_entries[0].event_type, "app-started");
-            assert_eq!(result.audit_entries[1].event_type, "ddl-complete");
-            assert_eq!(result.audit_entries[2].event_type, "query-complete");
+            assert_eq!(result.audit_entries[1].event_type, "app-started");
+            assert_eq!(result.audit_entries[2].event_type, "ddl-complete");
+            assert_eq!(result.audit_entries[3].event_type, "query-complete");
+
+            // ...and the same entries, chained, in the store's audit log.
+            let logged = host_duck.audit_log().unwrap();
+            let mut verifier = fractalaw_core::ChainVerifier::new();
+            for entry in &logged {
+                verifier.check(entry).unwrap();
+                assert_eq!(entry.node_id, DEFAULT_NODE_ID);
+                assert_eq!(entry.actor_id, "data-test");
+                assert_eq!(entry.actor_role, APP_ACTOR_ROLE);
+            }
+            let logged: Vec<&str> = logged.iter().map(|e| e.event_type.as_str()).collect();
+            let returned: Vec<&str> = result
+                .audit_entries
+                .iter()
+                .map(|e| e.event_type.as_str())
+                .collect();
+            assert_eq!(logged, returned);
 
             assert!(result.fuel_consumed > 0);
         }
 
+        #[tokio::test]
+        async fn ungranted_capability_is_denied_and_audited() {
+            let manifest = AppManifest::from_json(r#"{"capabilities": {"data-query": {}}}"#)
+                .expect("valid manifest");
+            let opts = RunOptions {
+                manifest: Some(manifest),
+                tier: None,
+                invocation: None,
+                duck: Some(DuckStore::open().unwrap()),
+                ..RunOptions::default()
+            };
+            let result = run_component(&data_test_wasm(), 1_000_000_000, opts)
+                .await
+                .expect("run_component with data-test guest failed");
+
+            let err = result.output.expect_err("DDL should be denied");
+            assert!(err.contains("code 403"), "unexpected error: {err}");
+
+            let granted = &result.audit_entries[0];
+            assert_eq!(granted.event_type, "capabilities-granted");
+            assert_eq!(granted.detail, "data-query");
+            let denied = result
+                .audit_entries
+                .iter()
+                .find(|e| e.event_type == "capability-denied")
+                .expect("denial should be audited");
+            assert_eq!(denied.resource, "data-mutate");
+        }
+
+        #[tokio::test]
+        async fn direct_run_without_manifest_is_granted_nothing() {
+            let opts = RunOptions {
+                duck: Some(DuckStore::open().unwrap()),
+                ..RunOptions::default()
+            };
+            let result = run_component(&data_test_wasm(), 1_000_000_000, opts)
+                .await
+                .expect("run_component with data-test guest failed");
+
+            let err = result.output.expect_err("DDL should be denied");
+            assert!(err.contains("code 403"), "unexpected error: {err}");
+            let granted = &result.audit_entries[0];
+            assert_eq!(granted.event_type, "capabilities-granted");
+            assert_eq!(granted.detail, "none");
+        }
+
+        #[tokio::test]
+        async fn manifest_is_found_in_the_apps_dir() {
+            let apps_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../apps");
+            let runtime = Runtime::new()
+                .unwrap()
+                .with_apps(apps_dir, |_| Ok(RunOptions::default()));
+            let opts = RunOptions {
+                duck: Some(DuckStore::open().unwrap()),
+                ..RunOptions::default()
+            };
+            let result = runtime
+                .run(&data_test_wasm(), 1_000_000_000, opts)
+                .await
+                .expect("run with data-test guest failed");
+
+            let output = result.output.expect("guest returned Err");
+            assert!(output.contains("Data test passed"), "{output}");
+            assert_eq!(
+                result.audit_entries[0].detail,
+                "data-query (test_from_guest), data-mutate (test_from_guest, ddl)"
+            );
+        }
+
         // ── DRRP polisher integration tests ──
 
         fn drrp_polisher_wasm() -> PathBuf {
@@ -787,9 +2480,19 @@ mod tests {
         async fn drrp_polisher_no_annotations() {
             let duck = DuckStore::open().unwrap();
             let opts = RunOptions {
+                trigger: None,
+                manifest: shipped_manifest("drrp_polisher"),
+                tier: None,
+                invocation: None,
                 duck: Some(duck),
                 #[cfg(feature = "inference")]
                 inference: None,
+                #[cfg(feature = "inference")]
+                token_budget: TokenBudget::default(),
+                #[cfg(feature = "embeddings")]
+                embeddings: None,
+                #[cfg(feature = "embeddings")]
+                classifier: None,
             };
             let result = run_component(&drrp_polisher_wasm(), 1_000_000_000, opts)
                 .await
@@ -826,9 +2529,19 @@ mod tests {
             .unwrap();
 
             let opts = RunOptions {
+                trigger: None,
+                manifest: shipped_manifest("drrp_polisher"),
+                tier: None,
+                invocation: None,
                 duck: Some(duck),
                 #[cfg(feature = "inference")]
                 inference: None, // no API key → inference calls will error
+                #[cfg(feature = "inference")]
+                token_budget: TokenBudget::default(),
+                #[cfg(feature = "embeddings")]
+                embeddings: None,
+                #[cfg(feature = "embeddings")]
+                classifier: None,
             };
             let result = run_component(&drrp_polisher_wasm(), 1_000_000_000, opts)
                 .await
@@ -847,6 +2560,219 @@ mod tests {
         }
     }
 
+    // ── Embedding host function tests (require the ONNX model) ──
+
+    #[cfg(feature = "embeddings")]
+    mod embedding_tests {
+        use super::*;
+
+        fn model_dir() -> PathBuf {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../../models/all-MiniLM-L6-v2")
+        }
+
+        fn embeddings_config() -> EmbeddingsConfig {
+            let dir = model_dir();
+            if !dir.join("model.onnx").exists() {
+                panic!("Model not found at {dir:?} — see fractalaw-ai embedder tests");
+            }
+            EmbeddingsConfig::new(fractalaw_ai::Embedder::load(&dir).unwrap())
+        }
+
+        #[tokio::test]
+        async fn embed_returns_unit_vector() {
+            use fractal::app::ai_embeddings::Host;
+
+            let mut state = HostState::new().with_embeddings(embeddings_config());
+            let vec = state
+                .embed("Health and safety at work".into())
+                .await
+                .unwrap();
+            assert_eq!(vec.len(), 384);
+            let norm: f32 = vec.iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!((norm - 1.0).abs() < 1e-4, "expected unit norm, got {norm}");
+        }
+
+        #[tokio::test]
+        async fn embed_batch_tracks_usage() {
+            use fractal::app::ai_embeddings::Host;
+
+            let mut state = HostState::new().with_embeddings(embeddings_config());
+            let vecs = state
+                .embed_batch(vec!["fire safety".into(), "waste disposal".into()])
+                .await
+                .unwrap();
+            assert_eq!(vecs.len(), 2);
+            assert_eq!(state.embedding_usage.calls, 1);
+            assert_eq!(state.embedding_usage.texts, 2);
+            assert_eq!(
+                state.embedding_usage.fuel_charged,
+                2 * DEFAULT_EMBED_FUEL_PER_TEXT
+            );
+        }
+
+        #[tokio::test]
+        async fn embed_batch_over_limit_errors() {
+            use fractal::app::ai_embeddings::Host;
+
+            let mut config = embeddings_config();
+            config.max_batch_size = 2;
+            let mut state = HostState::new().with_embeddings(config);
+            let err = state
+                .embed_batch(vec!["a".into(), "b".into(), "c".into()])
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 3);
+            assert_eq!(state.embedding_usage.calls, 0);
+        }
+
+        #[tokio::test]
+        async fn embed_over_fuel_budget_errors() {
+            use fractal::app::ai_embeddings::Host;
+
+            let mut state = HostState::new().with_embeddings(embeddings_config());
+            state.fuel_limit = DEFAULT_EMBED_FUEL_PER_TEXT;
+            state.embed("first".into()).await.expect("first text fits");
+            let err = state.embed("second".into()).await.unwrap_err();
+            assert_eq!(err.code, 4);
+            assert_eq!(state.embedding_usage.texts, 1);
+        }
+
+        #[tokio::test]
+        async fn embed_charges_count_the_guests_own_fuel() {
+            use fractal::app::ai_embeddings::Host;
+
+            // A guest that has burned most of its budget cannot embed as if
+            // the whole budget were left.
+            let mut state = HostState::new().with_embeddings(embeddings_config());
+            state.fuel_limit = 10 * DEFAULT_EMBED_FUEL_PER_TEXT;
+            state.fuel_left = Some(DEFAULT_EMBED_FUEL_PER_TEXT * 3 / 2);
+            state.embed("first".into()).await.expect("first text fits");
+            assert_eq!(state.fuel_pending, DEFAULT_EMBED_FUEL_PER_TEXT);
+            let err = state.embed("second".into()).await.unwrap_err();
+            assert_eq!(err.code, 4);
+        }
+
+        #[tokio::test]
+        async fn classify_ranks_categories_zero_shot() {
+            use fractal::app::ai_classify::Host;
+
+            let mut state = HostState::new().with_embeddings(embeddings_config());
+            let results = state
+                .classify(
+                    "Fire exits must be kept clear and clearly signed".into(),
+                    vec![
+                        "income tax".into(),
+                        "fire safety".into(),
+                        "water pollution".into(),
+                    ],
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(results.len(), 3);
+            assert_eq!(results[0].category, "fire safety");
+            assert!(results[0].score >= results[1].score);
+            assert!(results[1].score >= results[2].score);
+            // One batch: the text plus three category descriptions.
+            assert_eq!(state.embedding_usage.texts, 4);
+        }
+
+        #[tokio::test]
+        async fn classify_trained_without_classifier_errors() {
+            use fractal::app::ai_classify::{Host, Taxonomy};
+
+            let mut state = HostState::new().with_embeddings(embeddings_config());
+            let err = state
+                .classify_trained("asbestos removal".into(), Taxonomy::Family)
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 1);
+            assert!(err.message.contains("classifier"));
+        }
+
+        #[tokio::test]
+        async fn classify_trained_ranks_family_centroids() {
+            use fractal::app::ai_classify::{Host, Taxonomy};
+            use std::collections::HashMap;
+
+            let mut config = embeddings_config();
+            let vecs = config
+                .embedder
+                .embed_batch(&[
+                    "fire precautions in workplaces",
+                    "disposal of controlled waste",
+                ])
+                .unwrap();
+            let mut law_embeddings = HashMap::new();
+            law_embeddings.insert("fire_law".to_string(), vecs[0].clone());
+            law_embeddings.insert("waste_law".to_string(), vecs[1].clone());
+            let labels = fractalaw_ai::LabelSet {
+                law_family: HashMap::from([
+                    ("fire_law".to_string(), "FIRE".to_string()),
+                    ("waste_law".to_string(), "WASTE".to_string()),
+                ]),
+                law_sub_family: HashMap::new(),
+                law_domain: HashMap::new(),
+                law_subjects: HashMap::new(),
+            };
+            let classifier = fractalaw_ai::Classifier::build(&law_embeddings, &labels);
+
+            let mut state = HostState::new()
+                .with_embeddings(config)
+                .with_classifier(classifier);
+            let results = state
+                .classify_trained("smoke alarms and escape routes".into(), Taxonomy::Family)
+                .await
+                .unwrap();
+            assert_eq!(results.len(), 2);
+            assert_eq!(results[0].category, "FIRE");
+        }
+
+        fn inference_test_wasm() -> PathBuf {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(
+                "../../guests/inference-test/target/wasm32-wasip1/release/inference_test.wasm",
+            )
+        }
+
+        #[tokio::test]
+        async fn inference_test_guest_embeds() {
+            let opts = RunOptions {
+                trigger: None,
+                manifest: shipped_manifest("inference_test"),
+                tier: None,
+                invocation: None,
+                #[cfg(feature = "duckdb")]
+                duck: None,
+                #[cfg(feature = "inference")]
+                inference: None, // embeddings succeed, then generate errors
+                #[cfg(feature = "inference")]
+                token_budget: TokenBudget::default(),
+                embeddings: Some(embeddings_config()),
+                classifier: None,
+            };
+            let result = run_component(&inference_test_wasm(), 1_000_000_000, opts)
+                .await
+                .expect("run_component failed");
+
+            let err = result
+                .output
+                .expect_err("generate should fail without a backend");
+            assert!(err.contains("Inference failed"), "unexpected error: {err}");
+
+            assert_eq!(result.audit_entries[1].event_type, "app-started");
+            assert_eq!(result.audit_entries[2].event_type, "embed-complete");
+            assert!(
+                result.audit_entries[2].detail.contains("dim=384"),
+                "unexpected detail: {}",
+                result.audit_entries[2].detail
+            );
+
+            assert_eq!(result.embedding_usage.calls, 2);
+            assert_eq!(result.embedding_usage.texts, 4);
+            assert!(result.fuel_consumed > result.embedding_usage.fuel_charged);
+        }
+    }
+
     // ── AI host function unit tests ──
 
     mod ai_tests {
@@ -874,6 +2800,27 @@ mod tests {
             assert_eq!(err.code, 1);
         }
 
+        #[tokio::test]
+        async fn classify_without_embeddings_errors() {
+            use fractal::app::ai_classify::Host;
+
+            let mut state = HostState::new();
+            let err = state
+                .classify("fire exits".into(), vec!["fire safety".into()])
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 1);
+        }
+
+        #[tokio::test]
+        async fn classify_without_categories_is_empty() {
+            use fractal::app::ai_classify::Host;
+
+            let mut state = HostState::new();
+            let results = state.classify("fire exits".into(), vec![]).await.unwrap();
+            assert!(results.is_empty());
+        }
+
         #[tokio::test]
         async fn generate_without_config_errors() {
             use fractal::app::ai_inference::Host;
@@ -884,10 +2831,518 @@ mod tests {
                 user_prompt: "Hello".into(),
                 max_tokens: 100,
                 temperature: 0.0,
+                json_schema: None,
             };
             let err = state.generate(request).await.unwrap_err();
             assert_eq!(err.code, 1);
             assert!(err.message.contains("ANTHROPIC_API_KEY"));
         }
+
+        /// Serve canned `(status, JSON body)` responses, one per connection, on a local port.
+        ///
+        /// Returns the base URL and a receiver for the raw HTTP requests.
+        #[cfg(feature = "inference")]
+        async fn mock_inference_server(
+            responses: &[(u16, &'static str)],
+        ) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+            use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+            let responses = responses.to_vec();
+            tokio::spawn(async move {
+                for (status, response) in responses {
+                    let (mut socket, _) = listener.accept().await.unwrap();
+                    let mut request = Vec::new();
+                    let mut buf = [0u8; 4096];
+                    // Read headers, then as much body as Content-Length announces.
+                    loop {
+                        let n = socket.read(&mut buf).await.unwrap();
+                        request.extend_from_slice(&buf[..n]);
+                        let text = String::from_utf8_lossy(&request);
+                        if let Some(end) = text.find("\r\n\r\n") {
+                            let length = text[..end]
+                                .lines()
+                                .find_map(|l| {
+                                    l.to_ascii_lowercase()
+                                        .strip_prefix("content-length:")
+                                        .map(|v| v.trim().parse::<usize>().unwrap())
+                                })
+                                .unwrap_or(0);
+                            if request.len() >= end + 4 + length || n == 0 {
+                                break;
+                            }
+                        }
+                    }
+                    let reply = format!(
+                        "HTTP/1.1 {status} Mock\r\ncontent-type: application/json\r\n\
+                         content-length: {}\r\nconnection: close\r\n\r\n{response}",
+                        response.len()
+                    );
+                    socket.write_all(reply.as_bytes()).await.unwrap();
+                    let _ = tx.send(String::from_utf8_lossy(&request).into_owned());
+                }
+            });
+            (url, rx)
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn generate_against_mock_server() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, mut request_rx) = mock_inference_server(&[
+                (200, r#"{"content":[{"type":"text","text":"Employers must ensure safety [1]."}],"usage":{"input_tokens":40,"cache_read_input_tokens":100,"output_tokens":9}}"#),
+            ])
+            .await;
+            let config = InferenceConfig::new(
+                AnthropicProvider::new("test-key".into(), "mock-model".into())
+                    .with_base_url(format!("{url}/")),
+            );
+            let mut state = HostState::new().with_inference(config);
+
+            let response = state
+                .generate(fractal::app::ai_inference::GenerateRequest {
+                    system_prompt: Some("Cite sources.".into()),
+                    user_prompt: "What must employers do?".into(),
+                    max_tokens: 100,
+                    temperature: 0.0,
+                    json_schema: None,
+                })
+                .await
+                .unwrap();
+            assert_eq!(response.text, "Employers must ensure safety [1].");
+            assert_eq!(response.tokens_used, 9);
+            assert_eq!(response.usage.input_tokens, 140);
+            assert_eq!(response.usage.output_tokens, 9);
+            assert_eq!(response.usage.cached_tokens, 100);
+            assert_eq!(response.confidence, None);
+            assert_eq!(response.provider, "anthropic");
+            assert_eq!(response.model, "mock-model");
+            assert_eq!(state.inference_usage.input_tokens, 140);
+
+            let request = request_rx.recv().await.unwrap();
+            assert!(request.starts_with("POST /v1/messages "), "{request}");
+            assert!(request.contains("x-api-key: test-key"));
+            assert!(request.contains(r#""model":"mock-model""#));
+            assert!(request.contains(r#""system":"Cite sources.""#));
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn openai_provider_against_mock_server() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, mut request_rx) = mock_inference_server(&[
+                (200, r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"Report it [2]."},"logprobs":{"content":[{"token":"Report","logprob":-0.1},{"token":" it","logprob":-0.3}]}}],"usage":{"prompt_tokens":30,"completion_tokens":5,"prompt_tokens_details":{"cached_tokens":16}}}"#),
+            ])
+            .await;
+            let config =
+                InferenceConfig::new(OpenAiProvider::new(format!("{url}/v1"), "llama3".into()));
+            let mut state = HostState::new().with_inference(config);
+
+            let response = state
+                .generate(fractal::app::ai_inference::GenerateRequest {
+                    system_prompt: Some("Cite sources.".into()),
+                    user_prompt: "What must be reported?".into(),
+                    max_tokens: 100,
+                    temperature: 0.0,
+                    json_schema: None,
+                })
+                .await
+                .unwrap();
+            assert_eq!(response.text, "Report it [2].");
+            assert_eq!(response.tokens_used, 5);
+            assert_eq!(response.usage.input_tokens, 30);
+            assert_eq!(response.usage.cached_tokens, 16);
+            let confidence = response.confidence.unwrap();
+            assert!((confidence - (-0.2f32).exp()).abs() < 1e-6, "{confidence}");
+
+            let request = request_rx.recv().await.unwrap();
+            assert!(
+                request.starts_with("POST /v1/chat/completions "),
+                "{request}"
+            );
+            assert!(!request.to_ascii_lowercase().contains("authorization:"));
+            assert!(request.contains(r#""role":"system""#));
+            assert!(request.contains(r#""content":"Cite sources.""#));
+            assert!(request.contains(r#""temperature":0.0"#));
+            assert!(request.contains(r#""logprobs":true"#));
+        }
+
+        #[cfg(feature = "inference")]
+        const HOLDER_SCHEMA: &str = r#"{"type":"object","properties":{"holder":{"type":"string"},"qualifier":{"type":["string","null"]}},"required":["holder","qualifier"],"additionalProperties":false}"#;
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn json_schema_uses_forced_tool_call() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, mut request_rx) = mock_inference_server(&[
+                (200, r#"{"content":[{"type":"tool_use","id":"t1","name":"respond","input":{"holder":"employer","qualifier":null}}],"usage":{"output_tokens":12}}"#),
+            ])
+            .await;
+            let config = InferenceConfig::new(
+                AnthropicProvider::new("test-key".into(), "mock-model".into()).with_base_url(url),
+            );
+            let mut state = HostState::new().with_inference(config);
+
+            let response = state
+                .generate(fractal::app::ai_inference::GenerateRequest {
+                    system_prompt: None,
+                    user_prompt: "Who holds the duty?".into(),
+                    max_tokens: 100,
+                    temperature: 0.0,
+                    json_schema: Some(HOLDER_SCHEMA.into()),
+                })
+                .await
+                .unwrap();
+            let value: serde_json::Value = serde_json::from_str(&response.text).unwrap();
+            assert_eq!(
+                value,
+                serde_json::json!({ "holder": "employer", "qualifier": null })
+            );
+
+            let request = request_rx.recv().await.unwrap();
+            assert!(request.contains(r#""tool_choice":{"name":"respond","type":"tool"}"#));
+            assert!(request.contains(r#""input_schema":{"#));
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn json_schema_repairs_invalid_reply() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, mut request_rx) = mock_inference_server(&[
+                (200, r#"{"choices":[{"message":{"content":"Sure! {\"holder\": 42}"}}],"usage":{"completion_tokens":6}}"#),
+                (200, r#"{"choices":[{"message":{"content":"{\"holder\": \"employer\", \"qualifier\": \"so far as is reasonably practicable\"}"}}],"usage":{"completion_tokens":14}}"#),
+            ])
+            .await;
+            let config = InferenceConfig::new(OpenAiProvider::new(url, "llama3".into()));
+            let mut state = HostState::new().with_inference(config);
+
+            let response = state
+                .generate(fractal::app::ai_inference::GenerateRequest {
+                    system_prompt: None,
+                    user_prompt: "Who holds the duty?".into(),
+                    max_tokens: 100,
+                    temperature: 0.0,
+                    json_schema: Some(HOLDER_SCHEMA.into()),
+                })
+                .await
+                .unwrap();
+            let value: serde_json::Value = serde_json::from_str(&response.text).unwrap();
+            assert_eq!(value["qualifier"], "so far as is reasonably practicable");
+            assert_eq!(response.tokens_used, 20);
+            assert_eq!(response.usage.output_tokens, 20);
+
+            let first = request_rx.recv().await.unwrap();
+            assert!(first.contains(r#""response_format":{"#));
+            let repair = request_rx.recv().await.unwrap();
+            assert!(
+                repair.contains("/holder: expected string, got number"),
+                "{repair}"
+            );
+            assert!(repair.contains("missing required property 'qualifier'"));
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn json_schema_gives_up_after_repairs() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, _request_rx) = mock_inference_server(&[
+                (200, r#"{"choices":[{"message":{"content":"not json"}}]}"#),
+                (
+                    200,
+                    r#"{"choices":[{"message":{"content":"still not json"}}]}"#,
+                ),
+            ])
+            .await;
+            let config =
+                InferenceConfig::new(OpenAiProvider::new(url, "llama3".into())).with_max_repairs(1);
+            let mut state = HostState::new().with_inference(config);
+
+            let err = state
+                .generate(fractal::app::ai_inference::GenerateRequest {
+                    system_prompt: None,
+                    user_prompt: "Who holds the duty?".into(),
+                    max_tokens: 100,
+                    temperature: 0.0,
+                    json_schema: Some(HOLDER_SCHEMA.into()),
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 3);
+            assert!(
+                err.message.contains("after 2 attempt(s)"),
+                "{}",
+                err.message
+            );
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn json_schema_repairs_share_the_token_budget() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, mut request_rx) = mock_inference_server(&[
+                (200, r#"{"choices":[{"message":{"content":"not json"}}],"usage":{"completion_tokens":30}}"#),
+                (200, r#"{"choices":[{"message":{"content":"still not json"}}],"usage":{"completion_tokens":20}}"#),
+            ])
+            .await;
+            let config =
+                InferenceConfig::new(OpenAiProvider::new(url, "llama3".into())).with_max_repairs(3);
+            let mut state =
+                HostState::new()
+                    .with_inference(config)
+                    .with_token_budget(TokenBudget {
+                        per_run: Some(50),
+                        app: None,
+                    });
+
+            let err = state
+                .generate(fractal::app::ai_inference::GenerateRequest {
+                    system_prompt: None,
+                    user_prompt: "Who holds the duty?".into(),
+                    max_tokens: 40,
+                    temperature: 0.0,
+                    json_schema: Some(HOLDER_SCHEMA.into()),
+                })
+                .await
+                .unwrap_err();
+            assert_eq!(err.code, 5, "{}", err.message);
+            let first = request_rx.recv().await.unwrap();
+            assert!(first.contains(r#""max_tokens":40"#), "{first}");
+            let repair = request_rx.recv().await.unwrap();
+            assert!(repair.contains(r#""max_tokens":20"#), "{repair}");
+            assert!(request_rx.try_recv().is_err());
+        }
+
+        #[cfg(feature = "inference")]
+        fn quick_retry() -> RetryPolicy {
+            RetryPolicy {
+                max_retries: 2,
+                base_delay: std::time::Duration::from_millis(1),
+                max_delay: std::time::Duration::from_millis(10),
+            }
+        }
+
+        #[cfg(feature = "inference")]
+        fn short_request(max_tokens: u32) -> fractal::app::ai_inference::GenerateRequest {
+            fractal::app::ai_inference::GenerateRequest {
+                system_prompt: None,
+                user_prompt: "Who holds the duty?".into(),
+                max_tokens,
+                temperature: 0.0,
+                json_schema: None,
+            }
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn generate_retries_rate_limited_and_overloaded() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, mut request_rx) = mock_inference_server(&[
+                (429, r#"{"type":"error","error":{"type":"rate_limit_error"}}"#),
+                (529, r#"{"type":"error","error":{"type":"overloaded_error"}}"#),
+                (
+                    200,
+                    r#"{"content":[{"type":"text","text":"The employer."}],"usage":{"output_tokens":3}}"#,
+                ),
+            ])
+            .await;
+            let config = InferenceConfig::new(
+                AnthropicProvider::new("test-key".into(), "mock-model".into()).with_base_url(url),
+            )
+            .with_retry(quick_retry());
+            let mut state = HostState::new().with_inference(config);
+
+            let response = state.generate(short_request(100)).await.unwrap();
+            assert_eq!(response.text, "The employer.");
+            for _ in 0..3 {
+                request_rx.recv().await.unwrap();
+            }
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn generate_does_not_retry_client_errors() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, _request_rx) = mock_inference_server(&[(
+                400,
+                r#"{"type":"error","error":{"type":"invalid_request_error"}}"#,
+            )])
+            .await;
+            let config = InferenceConfig::new(
+                AnthropicProvider::new("test-key".into(), "mock-model".into()).with_base_url(url),
+            )
+            .with_retry(quick_retry());
+            let mut state = HostState::new().with_inference(config);
+
+            let err = state.generate(short_request(100)).await.unwrap_err();
+            assert_eq!(err.code, 2);
+            assert!(err.message.contains("400"), "{}", err.message);
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn token_budget_clamps_then_refuses() {
+            use fractal::app::ai_inference::Host;
+
+            let (url, mut request_rx) = mock_inference_server(&[(
+                200,
+                r#"{"choices":[{"message":{"content":"The employer."}}],"usage":{"completion_tokens":40}}"#,
+            )])
+            .await;
+            let app = AppTokenBudget::new("drrp-polisher", 1_000);
+            let budget = TokenBudget {
+                per_run: Some(40),
+                app: Some(app.clone()),
+            };
+            let config = InferenceConfig::new(OpenAiProvider::new(url, "llama3".into()));
+            let mut state = HostState::new()
+                .with_inference(config)
+                .with_token_budget(budget);
+
+            state.generate(short_request(100)).await.unwrap();
+            let request = request_rx.recv().await.unwrap();
+            assert!(request.contains(r#""max_tokens":40"#), "{request}");
+
+            let err = state.generate(short_request(100)).await.unwrap_err();
+            assert_eq!(err.code, 5);
+            assert_eq!(state.inference_usage.calls, 1);
+            assert_eq!(state.inference_usage.tokens_used, 40);
+            assert_eq!(state.inference_usage.refused, 1);
+            assert_eq!(state.inference_usage.budget, Some(40));
+            assert_eq!(app.spent(), 40);
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn cache_records_then_replays_offline() {
+            use fractal::app::ai_inference::Host;
+
+            let dir = std::env::temp_dir()
+                .join(format!("fractalaw-inference-cache-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            let cache = fractalaw_ai::ResponseCache::open(&dir).unwrap();
+
+            let (url, mut request_rx) = mock_inference_server(&[(
+                200,
+                r#"{"choices":[{"message":{"content":"The employer."}}],"usage":{"completion_tokens":3}}"#,
+            )])
+            .await;
+            let provider = OpenAiProvider::new(url, "llama3".into());
+            let id = provider.id();
+            let config =
+                InferenceConfig::new(provider).with_cache(cache.clone(), CacheMode::Record);
+            let mut state = HostState::new().with_inference(config);
+
+            // The second identical call is a hit: one request reaches the server.
+            for _ in 0..2 {
+                let response = state.generate(short_request(100)).await.unwrap();
+                assert_eq!(response.text, "The employer.");
+            }
+            request_rx.recv().await.unwrap();
+            assert!(request_rx.try_recv().is_err());
+            assert_eq!(state.inference_usage.calls, 2);
+            assert_eq!(state.inference_usage.cache_hits, 1);
+            assert_eq!(state.inference_usage.tokens_used, 3);
+
+            // Replay needs no server, and misses instead of calling out.
+            let mut state =
+                HostState::new().with_inference(InferenceConfig::replay(cache.clone(), id.clone()));
+            let response = state.generate(short_request(100)).await.unwrap();
+            assert_eq!(response.text, "The employer.");
+            assert_eq!(response.tokens_used, 3);
+            assert_eq!(response.provider, "openai");
+            let err = state.generate(short_request(99)).await.unwrap_err();
+            assert_eq!(err.code, 2);
+            assert!(err.message.contains("replay mode"), "{}", err.message);
+
+            // Another server with the same model name has its own entries.
+            let elsewhere = ProviderId {
+                endpoint: "http://gpu-box:8080".into(),
+                ..id
+            };
+            let mut state =
+                HostState::new().with_inference(InferenceConfig::replay(cache, elsewhere));
+            let err = state.generate(short_request(100)).await.unwrap_err();
+            assert_eq!(err.code, 2);
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn cache_keys_ignore_the_token_budget() {
+            use fractal::app::ai_inference::Host;
+
+            let dir = std::env::temp_dir().join(format!(
+                "fractalaw-inference-budget-cache-{}",
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            let cache = fractalaw_ai::ResponseCache::open(&dir).unwrap();
+
+            // Recorded while the budget clamps the request to 40 tokens...
+            let (url, mut request_rx) = mock_inference_server(&[(
+                200,
+                r#"{"choices":[{"message":{"content":"The employer."}}],"usage":{"completion_tokens":3}}"#,
+            )])
+            .await;
+            let provider = OpenAiProvider::new(url, "llama3".into());
+            let id = provider.id();
+            let config =
+                InferenceConfig::new(provider).with_cache(cache.clone(), CacheMode::Record);
+            let mut state =
+                HostState::new()
+                    .with_inference(config)
+                    .with_token_budget(TokenBudget {
+                        per_run: Some(40),
+                        app: None,
+                    });
+            state.generate(short_request(100)).await.unwrap();
+            let request = request_rx.recv().await.unwrap();
+            assert!(request.contains(r#""max_tokens":40"#), "{request}");
+
+            // ...and replayed for the same request under any other budget.
+            let mut state = HostState::new().with_inference(InferenceConfig::replay(cache, id));
+            let response = state.generate(short_request(100)).await.unwrap();
+            assert_eq!(response.text, "The employer.");
+
+            std::fs::remove_dir_all(&dir).unwrap();
+        }
+
+        #[cfg(feature = "inference")]
+        #[tokio::test]
+        async fn app_budget_carries_across_runs() {
+            use fractal::app::ai_inference::Host;
+
+            let app = AppTokenBudget::new("drrp-polisher", 100);
+            app.charge(100);
+            let config =
+                InferenceConfig::new(OpenAiProvider::new("http://127.0.0.1:9", "llama3".into()));
+            let mut state =
+                HostState::new()
+                    .with_inference(config)
+                    .with_token_budget(TokenBudget {
+                        per_run: None,
+                        app: Some(app),
+                    });
+
+            // Refused before any request is sent.
+            let err = state.generate(short_request(10)).await.unwrap_err();
+            assert_eq!(err.code, 5);
+            assert!(
+                err.message.contains("100 of 100 for drrp-polisher"),
+                "{}",
+                err.message
+            );
+        }
     }
 }
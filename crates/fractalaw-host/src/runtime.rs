@@ -0,0 +1,406 @@
+//! A long-lived micro-app runtime.
+//!
+//! [`Runtime`] keeps one [`Engine`] and one epoch ticker, caches a linker
+//! per set of granted capabilities, and caches compiled components by path,
+//! so repeated runs skip compilation and reuse the engine's pooled instance
+//! slots. With an [`AotCache`], components compiled by earlier processes
+//! are loaded from disk instead. [`run_component`] builds a throwaway
+//! runtime per call; `fractalaw serve` keeps one for its lifetime.
+//!
+//! With an apps directory (see [`Runtime::with_apps`]), runs may call the
+//! apps in it through `app-invoke`. Each callee is a run of its own on the
+//! same runtime, with its own manifest, tier and fuel slice.
+//!
+//! A run's audit entries are appended to the `audit_log` table of the store
+//! attached to it, as acts of the app on the runtime's node.
+//!
+//! [`run_component`]: crate::run_component
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use wasmtime::component::{Component, Linker};
+use wasmtime::{Engine, Store};
+
+#[cfg(feature = "duckdb")]
+use fractalaw_core::AuditEntry;
+use fractalaw_core::{AppManifest, Capabilities, Limit};
+
+use crate::{
+    AotCache, AuditRecord, HostState, MAX_INSTANCES, MicroApp, RunOptions, RunResult, app_name,
+    create_engine, create_linker_for, load_component,
+};
+
+/// Node id recorded in audit entries unless set with
+/// [`Runtime::with_node_id`].
+pub const DEFAULT_NODE_ID: &str = "local";
+
+/// `actor_role` of audit entries recorded by micro-apps and on their behalf.
+pub const APP_ACTOR_ROLE: &str = "micro-app";
+
+/// Most `app-invoke` calls nested below a top-level run.
+pub const MAX_INVOKE_DEPTH: u32 = 4;
+
+/// Host resources for a run of the named app started through `app-invoke`.
+type InvokeOptions = Arc<dyn Fn(&str) -> anyhow::Result<RunOptions> + Send + Sync>;
+
+/// Shared engine, linkers, and compiled-component cache for micro-app runs.
+///
+/// Clones are cheap and share the engine, caches and instance slots.
+#[derive(Clone)]
+pub struct Runtime {
+    engine: Engine,
+    /// Linkers keyed by the names of the capabilities they grant.
+    linkers: Arc<RwLock<HashMap<Vec<&'static str>, Linker<HostState>>>>,
+    components: Arc<RwLock<HashMap<PathBuf, (SystemTime, Component)>>>,
+    /// One permit per pooled instance slot, so excess runs wait instead of
+    /// failing to allocate.
+    slots: Arc<Semaphore>,
+    ticker: Arc<Ticker>,
+    aot: Option<AotCache>,
+    node_id: String,
+    /// Where `app-invoke` finds the apps it calls, and their host resources.
+    apps: Option<(PathBuf, InvokeOptions)>,
+}
+
+/// The epoch ticker, stopped when the last clone of its runtime is dropped.
+struct Ticker(JoinHandle<()>);
+
+impl Drop for Ticker {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl Runtime {
+    /// Create the engine and start its epoch ticker. Must be called from
+    /// within a Tokio runtime.
+    pub fn new() -> anyhow::Result<Self> {
+        let engine = create_engine()?;
+        let epoch_engine = engine.clone();
+        let ticker = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(std::time::Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                epoch_engine.increment_epoch();
+            }
+        });
+        Ok(Self {
+            engine,
+            linkers: Arc::new(RwLock::new(HashMap::new())),
+            components: Arc::new(RwLock::new(HashMap::new())),
+            slots: Arc::new(Semaphore::new(MAX_INSTANCES as usize)),
+            ticker: Arc::new(Ticker(ticker)),
+            aot: None,
+            node_id: DEFAULT_NODE_ID.into(),
+            apps: None,
+        })
+    }
+
+    /// Load components through `cache`, compiling only those not already
+    /// precompiled for this engine.
+    pub fn with_aot_cache(mut self, cache: AotCache) -> Self {
+        self.aot = Some(cache);
+        self
+    }
+
+    /// Record audit entries as made on node `id`.
+    pub fn with_node_id(mut self, id: impl Into<String>) -> Self {
+        self.node_id = id.into();
+        self
+    }
+
+    /// Let runs call the apps in `apps_dir` through `app-invoke`. `options`
+    /// supplies each callee's host resources, as it does for
+    /// `dispatch_events`; a callee given no store gets a connection to its
+    /// caller's. Components run from elsewhere without a manifest beside
+    /// them take the one named after them in `apps_dir`.
+    pub fn with_apps<F>(mut self, apps_dir: impl Into<PathBuf>, options: F) -> Self
+    where
+        F: Fn(&str) -> anyhow::Result<RunOptions> + Send + Sync + 'static,
+    {
+        self.apps = Some((apps_dir.into(), Arc::new(options)));
+        self
+    }
+
+    /// Component of `app` for a run through `app-invoke`, or `None`
+    /// without an apps directory.
+    pub(crate) fn invoke_component(&self, app: &str) -> Option<anyhow::Result<PathBuf>> {
+        let (apps_dir, _) = self.apps.as_ref()?;
+        Some(app_component(apps_dir, app))
+    }
+
+    /// Manifest for the component at `wasm_path`: the one beside it, or
+    /// else `<apps_dir>/<stem>.manifest.json`.
+    fn find_manifest(&self, wasm_path: &Path) -> anyhow::Result<Option<AppManifest>> {
+        if let Some(manifest) = AppManifest::load_for(wasm_path).map_err(anyhow::Error::msg)? {
+            return Ok(Some(manifest));
+        }
+        match (&self.apps, wasm_path.file_name()) {
+            (Some((apps_dir, _)), Some(file)) => {
+                AppManifest::load_for(&apps_dir.join(file)).map_err(anyhow::Error::msg)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Host resources for a run of `app` through `app-invoke`.
+    pub(crate) fn invoke_options(&self, app: &str) -> anyhow::Result<RunOptions> {
+        match &self.apps {
+            Some((_, options)) => options(app),
+            None => Ok(RunOptions::default()),
+        }
+    }
+
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    /// Compile `wasm_path` into the cache, unless it is cached and unchanged
+    /// on disk since.
+    pub async fn preload(&self, wasm_path: &Path) -> anyhow::Result<()> {
+        self.component(wasm_path).await.map(drop)
+    }
+
+    /// Paths of the cached components.
+    pub fn loaded(&self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.components.read().unwrap().keys().cloned().collect();
+        paths.sort();
+        paths
+    }
+
+    async fn component(&self, wasm_path: &Path) -> anyhow::Result<Component> {
+        let modified = tokio::fs::metadata(wasm_path).await?.modified()?;
+        if let Some((at, component)) = self.components.read().unwrap().get(wasm_path)
+            && *at == modified
+        {
+            return Ok(component.clone());
+        }
+        let component = match &self.aot {
+            Some(cache) => cache.load(&self.engine, wasm_path).await?.0,
+            None => load_component(&self.engine, wasm_path).await?,
+        };
+        tracing::debug!(path = %wasm_path.display(), "loaded component");
+        self.components
+            .write()
+            .unwrap()
+            .insert(wasm_path.to_path_buf(), (modified, component.clone()));
+        Ok(component)
+    }
+
+    fn linker(&self, capabilities: &Capabilities) -> anyhow::Result<Linker<HostState>> {
+        let key = capabilities.granted();
+        if let Some(linker) = self.linkers.read().unwrap().get(&key) {
+            return Ok(linker.clone());
+        }
+        let linker = create_linker_for(&self.engine, capabilities)?;
+        self.linkers.write().unwrap().insert(key, linker.clone());
+        Ok(linker)
+    }
+
+    /// Instantiate and execute a micro-app component, compiling it first if
+    /// it is not cached.
+    ///
+    /// The run gets the capabilities of `opts.manifest`, or else of the
+    /// manifest beside the component (see [`AppManifest::load_for`]), or
+    /// else of the manifest named after it in the apps directory (see
+    /// [`with_apps`](Self::with_apps)). A component without any is granted
+    /// nothing, with a warning; one invoked by another app is refused. Pass
+    /// [`AppManifest::unrestricted`] to grant everything.
+    ///
+    /// It is held to the limits of `opts.tier`, or else the manifest's tier,
+    /// or else [`Tier::Standard`](fractalaw_core::Tier::Standard), with
+    /// `fuel` lowered to the tier's (pass [`TIER_FUEL`](crate::TIER_FUEL)
+    /// for exactly the tier's). Running out of fuel or time, or growing
+    /// memory past the limit, ends the run with an `Err` output and
+    /// [`RunResult::limit_exceeded`] set.
+    ///
+    /// A run started through `app-invoke` ([`RunOptions::invocation`])
+    /// fails at once if no instance slot is free: its callers hold slots
+    /// while they wait for it, so waiting could deadlock.
+    pub async fn run(
+        &self,
+        wasm_path: &Path,
+        fuel: u64,
+        mut opts: RunOptions,
+    ) -> anyhow::Result<RunResult> {
+        let app = app_name(wasm_path);
+        let manifest = match opts.manifest.take() {
+            Some(manifest) => manifest,
+            None => match self.find_manifest(wasm_path)? {
+                Some(manifest) => manifest,
+                None if opts.invocation.is_some() => {
+                    anyhow::bail!("app '{app}' has no manifest and cannot be invoked")
+                }
+                None => {
+                    tracing::warn!(%app, "no app manifest; granting no capabilities");
+                    AppManifest::default()
+                }
+            },
+        };
+        let linker = self.linker(&manifest.capabilities)?;
+        let granted = AuditRecord {
+            event_type: "capabilities-granted".into(),
+            resource: app.clone(),
+            detail: manifest.capabilities.summary(),
+            timestamp: chrono::Utc::now(),
+        };
+        opts.tier = opts.tier.or(manifest.tier);
+        opts.manifest = Some(manifest);
+
+        let component = self.component(wasm_path).await?;
+        let _slot = match &opts.invocation {
+            Some(_) => self
+                .slots
+                .try_acquire()
+                .map_err(|_| anyhow::anyhow!("no free instance slot to run '{app}'"))?,
+            None => self.slots.acquire().await?,
+        };
+
+        let mut state = HostState::from_options(app, fuel, opts);
+        state.audit_entries.push(granted);
+        if let Some(invocation) = &state.invocation {
+            state.audit_entries.push(AuditRecord {
+                event_type: "invoked".into(),
+                resource: invocation.caller.clone(),
+                detail: format!(
+                    "called by '{}' at depth {}",
+                    invocation.caller, invocation.depth
+                ),
+                timestamp: chrono::Utc::now(),
+            });
+        }
+        if self.apps.is_some() {
+            state.runtime = Some(self.clone());
+        }
+        let fuel = state.fuel_limit;
+        // The ticker advances the epoch once a second.
+        let deadline = state.limits.deadline.as_secs();
+        let mut store = Store::new(&self.engine, state);
+        store.limiter(|state| &mut state.limiter);
+        store.call_hook(HostState::call_hook);
+        store.set_fuel(fuel)?;
+        store.set_epoch_deadline(deadline);
+
+        let output = match Self::call(&mut store, &component, &linker).await {
+            Ok(output) => Ok(output),
+            Err(e) => {
+                let limit = match e.downcast_ref::<wasmtime::Trap>() {
+                    Some(wasmtime::Trap::OutOfFuel) => Some(Limit::Fuel),
+                    Some(wasmtime::Trap::Interrupt) => Some(Limit::Deadline),
+                    _ if store.data().limiter.exceeded => Some(Limit::Memory),
+                    _ => None,
+                };
+                match limit {
+                    Some(limit) => Ok(Err(store.data_mut().exceed(limit).to_string())),
+                    None => Err(e),
+                }
+            }
+        };
+        if store.data().limiter.exceeded && store.data().limit_exceeded.is_none() {
+            store.data_mut().exceed(Limit::Memory);
+        }
+
+        // The audit trail is kept even for runs that fail.
+        let persisted = self.persist_audit(store.data());
+        if let (Err(e), Err(_)) = (&persisted, &output) {
+            tracing::error!(error = %e, "failed to persist audit entries of a failed run");
+        }
+        let output = output?;
+        persisted?;
+
+        // Host charges were taken from the store's fuel as they were made.
+        let fuel_consumed = fuel.saturating_sub(store.get_fuel()?);
+        let state = store.into_data();
+
+        Ok(RunResult {
+            output,
+            audit_entries: state.audit_entries,
+            fuel_consumed,
+            embedding_usage: state.embedding_usage,
+            inference_usage: state.inference_usage,
+            invoke_usage: state.invoke_usage,
+            emitted_events: state.emitted_events,
+            tier: state.tier,
+            limit_exceeded: state.limit_exceeded,
+        })
+    }
+
+    /// Append the run's audit entries to its store's `audit_log`, if a store
+    /// is attached.
+    fn persist_audit(&self, state: &HostState) -> anyhow::Result<()> {
+        #[cfg(feature = "duckdb")]
+        if let Some(duck) = &state.duck {
+            let entries = state
+                .audit_entries
+                .iter()
+                .map(|record| {
+                    let mut entry = AuditEntry::new(
+                        &self.node_id,
+                        &state.app_name,
+                        APP_ACTOR_ROLE,
+                        &record.event_type,
+                    );
+                    entry.timestamp = record.timestamp;
+                    entry.resource = Some(record.resource.clone());
+                    entry.detail = Some(record.detail.clone());
+                    entry
+                })
+                .collect();
+            duck.append_audit(entries)?;
+        }
+        #[cfg(not(feature = "duckdb"))]
+        let _ = state;
+        Ok(())
+    }
+
+    async fn call(
+        store: &mut Store<HostState>,
+        component: &Component,
+        linker: &Linker<HostState>,
+    ) -> anyhow::Result<Result<String, String>> {
+        let instance = MicroApp::instantiate_async(&mut *store, component, linker).await?;
+        instance.call_run(&mut *store).await
+    }
+}
+
+/// Whether `app` is usable as an app name: non-empty ASCII letters, digits,
+/// `-` and `_`, so it cannot escape the apps directory.
+pub fn valid_app_name(app: &str) -> bool {
+    !app.is_empty()
+        && app
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+/// Component for `app` in `apps_dir`: `<apps_dir>/<app with - as _>.wasm`,
+/// the inverse of [`app_name`](crate::app_name).
+pub fn app_component(apps_dir: &Path, app: &str) -> anyhow::Result<PathBuf> {
+    anyhow::ensure!(valid_app_name(app), "invalid app name '{app}'");
+    let path = apps_dir.join(format!("{}.wasm", app.replace('-', "_")));
+    anyhow::ensure!(
+        path.is_file(),
+        "no component for app '{app}' at {}",
+        path.display()
+    );
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_names_stay_inside_apps_dir() {
+        assert!(valid_app_name("drrp-polisher"));
+        assert!(valid_app_name("hello_world"));
+        assert!(!valid_app_name(""));
+        assert!(!valid_app_name("../secrets"));
+        assert!(!valid_app_name("a/b"));
+        assert!(app_component(Path::new("/nonexistent"), "../x").is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Scheduled micro-app runs.
+//!
+//! `events-schedule` (and `fractalaw schedule add`) store tasks in DuckDB
+//! with their next run time. [`run_due_tasks`] runs the target app of every
+//! task that has come due and records when it should run next, so schedules
+//! survive host restarts. A recurring task found overdue after downtime runs
+//! once, not once per missed slot. Time comes from a [`Clock`], so tests can
+//! drive the scheduler without waiting.
+
+use std::path::Path;
+
+use fractalaw_core::{Clock, ScheduledTask};
+use fractalaw_store::DuckStore;
+
+use crate::fractal::app::events_emit::DomainEvent;
+use crate::fractal::app::events_receive::Delivery;
+use crate::{RunOptions, Runtime, app_component};
+
+/// Outcome of a [`run_due_tasks`] pass.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScheduleReport {
+    /// Runs that succeeded.
+    pub ran: usize,
+    pub failed: usize,
+}
+
+/// Run the target app of each task due at `clock.now()` on `runtime`, up to
+/// `limit` runs.
+///
+/// `options` supplies the host resources for each run; the scheduler sets
+/// the trigger, and attaches a connection to `duck` when no store is given.
+/// A failed run is logged and recorded on the task; recurring tasks still
+/// run again at their next time.
+pub async fn run_due_tasks<F>(
+    runtime: &Runtime,
+    duck: &DuckStore,
+    clock: &dyn Clock,
+    apps_dir: &Path,
+    fuel: u64,
+    limit: usize,
+    mut options: F,
+) -> anyhow::Result<ScheduleReport>
+where
+    F: FnMut(&ScheduledTask) -> anyhow::Result<RunOptions>,
+{
+    let mut report = ScheduleReport::default();
+    for task in duck.due_tasks(clock.now().timestamp(), limit)? {
+        let outcome = match app_component(apps_dir, &task.target_app) {
+            Ok(component) => {
+                let mut opts = options(&task)?;
+                if opts.duck.is_none() {
+                    opts.duck = Some(duck.try_clone()?);
+                }
+                opts.trigger = Some(trigger(&task));
+                match runtime.run(&component, fuel, opts).await {
+                    Ok(result) => result.output.map(drop),
+                    Err(e) => Err(format!("{e:#}")),
+                }
+            }
+            Err(e) => Err(format!("{e:#}")),
+        };
+
+        let now = clock.now();
+        let next = task.next_run_after(now);
+        let error = outcome.err();
+        duck.record_task_run(task.id, now.timestamp(), next, error.as_deref())?;
+        match error {
+            None => report.ran += 1,
+            Some(error) => {
+                tracing::warn!(
+                    task_id = task.id,
+                    name = %task.name,
+                    app = %task.target_app,
+                    %error,
+                    "scheduled run failed"
+                );
+                report.failed += 1;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// How a scheduled run sees its task through `events-receive`.
+fn trigger(task: &ScheduledTask) -> Delivery {
+    Delivery {
+        id: task.id,
+        event: DomainEvent {
+            event_type: format!("schedule:{}", task.name),
+            payload: task.payload.clone(),
+            source_app: task.owner_app.clone(),
+        },
+        attempt: 1,
+    }
+}
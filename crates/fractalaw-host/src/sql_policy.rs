@@ -0,0 +1,634 @@
+//! Table-level authorization of guest SQL.
+//!
+//! `data-query` and `data-mutate` parse each statement before DuckDB sees
+//! it, classify it, and check every table it reads or writes against the
+//! app's [`Capabilities`]:
+//!
+//! - `data-query.query` runs only queries, over tables its grant lists.
+//! - `data-mutate.execute` also runs `INSERT`, `UPDATE` and `DELETE` on the
+//!   tables its grant lists, and `CREATE TABLE`, `CREATE INDEX`,
+//!   `ALTER TABLE`, `DROP TABLE`, `DROP INDEX` and `TRUNCATE` on them when
+//!   the grant allows DDL.
+//!   Tables it only reads may come from either grant.
+//!
+//! Tables are named bare or in the `main` schema; other catalogs and
+//! schemas are refused. So is everything else: more than one statement per
+//! call, table functions such as `read_csv`, statements like `ATTACH`,
+//! `COPY`, `PRAGMA`, `SET` or `INSTALL` that reach beyond the app's tables,
+//! writes to tables only the host writes, such as `audit_log` or
+//! `scheduled_tasks`, and renaming a table to any of those names.
+
+use std::ops::ControlFlow;
+
+use fractalaw_core::Capabilities;
+use sqlparser::ast::{ObjectName, ObjectType, Query, Statement, TableFactor, Visit, Visitor};
+use sqlparser::dialect::DuckDbDialect;
+use sqlparser::parser::Parser;
+
+/// Tables guests may read when granted but never write, whatever their
+/// grants: the audit log, the event bus and scheduler, and classifier
+/// reviews and evaluations.
+const HOST_TABLES: &[&str] = &[
+    "audit_log",
+    "events",
+    "event_subscriptions",
+    "event_deliveries",
+    "scheduled_tasks",
+    "classification_reviews",
+    "classifier_eval_runs",
+    "classifier_eval_families",
+    "classifier_eval_confusion",
+    "classifier_eval_calibration",
+];
+
+/// Whether `table` is written only by the host.
+pub fn is_host_table(table: &str) -> bool {
+    HOST_TABLES.iter().any(|t| t.eq_ignore_ascii_case(table))
+}
+
+/// The host function a statement was passed to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlAccess {
+    Query,
+    Execute,
+}
+
+/// Why a statement may not run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlRejection {
+    /// The statement does not parse.
+    Invalid(String),
+    /// The statement is outside the app's grants. `resource` is the table or
+    /// statement kind at fault.
+    Denied { resource: String, reason: String },
+}
+
+impl SqlRejection {
+    fn denied(resource: impl Into<String>, reason: impl Into<String>) -> Self {
+        Self::Denied {
+            resource: resource.into(),
+            reason: reason.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Read,
+    Write,
+    Ddl,
+}
+
+/// Check that `sql`, passed to `access`, stays within `capabilities`, and
+/// return the distinct tables it touches.
+pub fn authorize(
+    sql: &str,
+    access: SqlAccess,
+    capabilities: &Capabilities,
+) -> Result<Vec<String>, SqlRejection> {
+    let statements = Parser::parse_sql(&DuckDbDialect {}, sql)
+        .map_err(|e| SqlRejection::Invalid(e.to_string()))?;
+    let [statement] = statements.as_slice() else {
+        return Err(SqlRejection::denied(
+            "sql",
+            format!("expected one statement, got {}", statements.len()),
+        ));
+    };
+
+    let kind = match statement {
+        Statement::Query { .. } => Kind::Read,
+        Statement::Insert { .. } | Statement::Update { .. } | Statement::Delete { .. } => {
+            Kind::Write
+        }
+        // `DROP SCHEMA main CASCADE` names a schema, not a table, and would
+        // take the host tables with it.
+        Statement::Drop { object_type, .. }
+            if !matches!(object_type, ObjectType::Table | ObjectType::Index) =>
+        {
+            return Err(SqlRejection::denied(
+                "DROP",
+                format!("DROP {object_type} statements are not allowed"),
+            ));
+        }
+        Statement::CreateTable { .. }
+        | Statement::CreateIndex { .. }
+        | Statement::AlterTable { .. }
+        | Statement::Drop { .. }
+        | Statement::Truncate { .. } => Kind::Ddl,
+        _ => {
+            let keyword = statement_keyword(statement);
+            return Err(SqlRejection::denied(
+                keyword.clone(),
+                format!("{keyword} statements are not allowed"),
+            ));
+        }
+    };
+
+    let tables = Tables::of(statement)?;
+    if let Some(function) = tables.functions.first() {
+        return Err(SqlRejection::denied(
+            function.clone(),
+            format!("table function {function} is not allowed"),
+        ));
+    }
+
+    let query = capabilities.data_query.as_ref();
+    let mutate = capabilities.data_mutate.as_ref();
+    match (access, kind) {
+        (SqlAccess::Query, Kind::Read) => {}
+        (SqlAccess::Query, _) => {
+            return Err(SqlRejection::denied(
+                statement_keyword(statement),
+                "data-query only runs queries; use data-mutate",
+            ));
+        }
+        (SqlAccess::Execute, Kind::Ddl) if !mutate.is_some_and(|g| g.ddl) => {
+            return Err(SqlRejection::denied(
+                statement_keyword(statement),
+                "DDL is not granted",
+            ));
+        }
+        (SqlAccess::Execute, _) => {}
+    }
+
+    for table in &tables.targets {
+        if is_host_table(table) {
+            return Err(SqlRejection::denied(
+                table.clone(),
+                format!("table {table} is written only by the host"),
+            ));
+        }
+        if !mutate.is_some_and(|g| g.allows(table)) {
+            return Err(SqlRejection::denied(
+                table.clone(),
+                format!("writing table {table} is not granted"),
+            ));
+        }
+    }
+    for table in &tables.reads {
+        let readable = query.is_some_and(|g| g.allows(table))
+            || (access == SqlAccess::Execute && mutate.is_some_and(|g| g.allows(table)));
+        if !readable {
+            return Err(SqlRejection::denied(
+                table.clone(),
+                format!("reading table {table} is not granted"),
+            ));
+        }
+    }
+
+    let mut touched = [tables.targets, tables.reads].concat();
+    touched.sort();
+    touched.dedup();
+    Ok(touched)
+}
+
+/// Leading keywords of `statement`, e.g. `DROP` or `CREATE TABLE`, for
+/// messages and the audit log.
+fn statement_keyword(statement: &Statement) -> String {
+    let sql = statement.to_string();
+    let words: Vec<&str> = sql.split_whitespace().take(2).collect();
+    match words.as_slice() {
+        [first, second] if first.eq_ignore_ascii_case("create") => {
+            format!("{first} {second}").to_uppercase()
+        }
+        [first, ..] => first.to_uppercase(),
+        [] => "empty".into(),
+    }
+}
+
+/// Tables a statement touches. Relations outside any query are the ones it
+/// writes (or defines); those inside a query are read, unless they are
+/// unqualified names of a CTE in scope.
+#[derive(Debug, Default)]
+struct Tables {
+    /// One scope per query being visited, innermost last.
+    scopes: Vec<CteScope>,
+    targets: Vec<String>,
+    reads: Vec<String>,
+    functions: Vec<String>,
+    invalid: Option<String>,
+}
+
+/// CTEs a query's `WITH` defines. Each CTE body sees only the CTEs before
+/// it (all of them under `WITH RECURSIVE`); the query body sees them all.
+#[derive(Debug)]
+struct CteScope {
+    ctes: Vec<(String, *const Query)>,
+    visible: usize,
+    recursive: bool,
+}
+
+impl Tables {
+    fn of(statement: &Statement) -> Result<Self, SqlRejection> {
+        let mut tables = Self::default();
+        let _ = statement.visit(&mut tables);
+        // `DROP` names are not visited as relations.
+        if let Statement::Drop { names, .. } = statement {
+            for name in names {
+                tables.add(name.to_string(), false);
+            }
+        }
+        // Nor is the new name of `ALTER TABLE … RENAME TO`, which claims
+        // a table as surely as creating it.
+        if matches!(statement, Statement::AlterTable { .. }) {
+            let sql = statement.to_string();
+            for marker in [" RENAME TO ", " RENAME AS "] {
+                for rest in sql.split(marker).skip(1) {
+                    let new_name = rest.split(',').next().unwrap_or_default().trim();
+                    tables.add(new_name.to_string(), false);
+                }
+            }
+        }
+        if let Some(name) = tables.invalid {
+            return Err(SqlRejection::denied(
+                name.clone(),
+                format!("unsupported table reference {name}"),
+            ));
+        }
+        Ok(tables)
+    }
+
+    fn add(&mut self, raw: String, read: bool) {
+        match table_name(&raw) {
+            Some((table, qualified)) if read => {
+                if qualified || !self.is_cte(&table) {
+                    self.reads.push(table);
+                }
+            }
+            Some((table, _)) => self.targets.push(table),
+            None => {
+                self.invalid.get_or_insert(raw);
+            }
+        }
+    }
+
+    /// Whether `name` resolves to a CTE visible from the current query.
+    fn is_cte(&self, name: &str) -> bool {
+        self.scopes.iter().any(|scope| {
+            scope.ctes[..scope.visible]
+                .iter()
+                .any(|(cte, _)| cte == name)
+        })
+    }
+}
+
+impl Visitor for Tables {
+    type Break = ();
+
+    fn pre_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        // Entering the body of the enclosing query's i-th CTE.
+        if let Some(scope) = self.scopes.last_mut()
+            && let Some(i) = scope.position(query)
+            && !scope.recursive
+        {
+            scope.visible = i;
+        }
+        let (ctes, recursive) = match &query.with {
+            Some(with) => (
+                with.cte_tables
+                    .iter()
+                    .map(|cte| {
+                        let body: *const Query = &*cte.query;
+                        (cte.alias.name.value.to_lowercase(), body)
+                    })
+                    .collect(),
+                with.recursive,
+            ),
+            None => (Vec::new(), false),
+        };
+        self.scopes.push(CteScope {
+            visible: if recursive { ctes.len() } else { 0 },
+            ctes,
+            recursive,
+        });
+        ControlFlow::Continue(())
+    }
+
+    fn post_visit_query(&mut self, query: &Query) -> ControlFlow<()> {
+        self.scopes.pop();
+        // Leaving the i-th CTE body: later CTEs and the body may use it.
+        if let Some(scope) = self.scopes.last_mut()
+            && let Some(i) = scope.position(query)
+            && !scope.recursive
+        {
+            scope.visible = i + 1;
+        }
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_relation(&mut self, relation: &ObjectName) -> ControlFlow<()> {
+        self.add(relation.to_string(), !self.scopes.is_empty());
+        ControlFlow::Continue(())
+    }
+
+    fn pre_visit_table_factor(&mut self, factor: &TableFactor) -> ControlFlow<()> {
+        match factor {
+            TableFactor::Table {
+                name,
+                args: Some(_),
+                ..
+            }
+            | TableFactor::Function { name, .. } => self.functions.push(name.to_string()),
+            TableFactor::TableFunction { expr, .. } => self.functions.push(expr.to_string()),
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl CteScope {
+    /// Index of the CTE whose body is `query`.
+    fn position(&self, query: &Query) -> Option<usize> {
+        self.ctes
+            .iter()
+            .position(|(_, body)| std::ptr::eq(*body, query))
+    }
+}
+
+/// Lower-cased table name of a reference such as `legislation`,
+/// `"Legislation"` or `main.legislation`, and whether it named the `main`
+/// schema. Other catalogs and schemas, and anything that is not a plain
+/// identifier path, such as a file name, are `None`: a name like
+/// `memory.main.audit_log` would otherwise slip past the host tables.
+fn table_name(reference: &str) -> Option<(String, bool)> {
+    let parts: Vec<String> = reference
+        .split('.')
+        .map(|p| p.trim_matches('"').to_lowercase())
+        .collect();
+    let plain =
+        |p: &String| !p.is_empty() && p.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_');
+    if !parts.iter().all(plain) {
+        return None;
+    }
+    match parts.as_slice() {
+        [table] => Some((table.clone(), false)),
+        [schema, table] if schema == "main" => Some((table.clone(), true)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fractalaw_core::AppManifest;
+
+    fn caps(json: &str) -> Capabilities {
+        AppManifest::from_json(json).unwrap().capabilities
+    }
+
+    fn denied(result: Result<Vec<String>, SqlRejection>) -> String {
+        match result {
+            Err(SqlRejection::Denied { resource, .. }) => resource,
+            other => panic!("expected a denial, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn queries_read_only_granted_tables() {
+        let caps = caps(r#"{"capabilities": {"data-query": {"tables": ["legislation"]}}}"#);
+        let query = |sql| authorize(sql, SqlAccess::Query, &caps);
+
+        assert_eq!(
+            query("SELECT * FROM Legislation WHERE year > 2000"),
+            Ok(vec!["legislation".into()])
+        );
+        assert_eq!(
+            query("SELECT * FROM main.\"legislation\""),
+            Ok(vec!["legislation".into()])
+        );
+        assert_eq!(
+            query("WITH recent AS (SELECT * FROM legislation) SELECT * FROM recent"),
+            Ok(vec!["legislation".into()])
+        );
+        assert_eq!(
+            denied(query(
+                "SELECT * FROM legislation WHERE id IN (SELECT id FROM secrets)"
+            )),
+            "secrets"
+        );
+        assert_eq!(
+            denied(query("SELECT * FROM read_csv('/etc/passwd')")),
+            "read_csv"
+        );
+        assert_eq!(denied(query("DROP TABLE legislation")), "DROP");
+        assert_eq!(denied(query("SELECT 1; DROP TABLE legislation")), "sql");
+        assert!(matches!(query("SELEC 1"), Err(SqlRejection::Invalid(_))));
+    }
+
+    #[test]
+    fn writes_and_ddl_follow_the_mutate_grant() {
+        let caps = caps(
+            r#"{"capabilities": {
+                "data-query": {"tables": ["annotations"]},
+                "data-mutate": {"tables": ["polished"]}
+            }}"#,
+        );
+        let execute = |sql| authorize(sql, SqlAccess::Execute, &caps);
+
+        assert_eq!(
+            execute("INSERT INTO polished VALUES (1, 'x')"),
+            Ok(vec!["polished".into()])
+        );
+        assert_eq!(
+            execute("INSERT INTO polished SELECT * FROM annotations"),
+            Ok(vec!["annotations".into(), "polished".into()])
+        );
+        assert_eq!(
+            execute("UPDATE polished SET x = 1 WHERE id IN (SELECT id FROM annotations)"),
+            Ok(vec!["annotations".into(), "polished".into()])
+        );
+        assert_eq!(
+            denied(execute("UPDATE annotations SET polished = true")),
+            "annotations"
+        );
+        assert_eq!(denied(execute("DELETE FROM legislation")), "legislation");
+        assert_eq!(
+            denied(execute("CREATE TABLE IF NOT EXISTS polished (id INTEGER)")),
+            "CREATE TABLE"
+        );
+        assert_eq!(denied(execute("DROP TABLE polished")), "DROP");
+        assert_eq!(denied(execute("ATTACH 'other.db' AS other")), "ATTACH");
+        assert_eq!(denied(execute("COPY polished TO 'out.csv'")), "COPY");
+
+        let ddl = caps_with_ddl();
+        let execute = |sql| authorize(sql, SqlAccess::Execute, &ddl);
+        assert_eq!(
+            execute("CREATE TABLE IF NOT EXISTS polished (id INTEGER)"),
+            Ok(vec!["polished".into()])
+        );
+        assert_eq!(execute("DROP TABLE polished"), Ok(vec!["polished".into()]));
+        assert_eq!(denied(execute("DROP TABLE legislation")), "legislation");
+        assert_eq!(
+            denied(execute("CREATE TABLE copy AS SELECT * FROM polished")),
+            "copy"
+        );
+    }
+
+    fn caps_with_ddl() -> Capabilities {
+        caps(r#"{"capabilities": {"data-mutate": {"tables": ["polished"], "ddl": true}}}"#)
+    }
+
+    #[test]
+    fn unrestricted_apps_keep_table_ddl() {
+        let caps = AppManifest::unrestricted().capabilities;
+        for sql in [
+            "CREATE TABLE t (id INTEGER)",
+            "INSERT INTO t VALUES (1)",
+            "SELECT count(*) FROM t",
+            "DROP TABLE t",
+        ] {
+            assert!(authorize(sql, SqlAccess::Execute, &caps).is_ok(), "{sql}");
+        }
+        assert_eq!(
+            denied(authorize(
+                "SELECT * FROM 'data.csv'",
+                SqlAccess::Query,
+                &caps
+            )),
+            "'data.csv'"
+        );
+        assert_eq!(
+            denied(authorize("INSTALL httpfs", SqlAccess::Execute, &caps)),
+            "INSTALL"
+        );
+        for sql in ["DELETE FROM audit_log", "DROP TABLE main.Audit_Log"] {
+            assert_eq!(
+                denied(authorize(sql, SqlAccess::Execute, &caps)),
+                "audit_log"
+            );
+        }
+        assert!(authorize("SELECT * FROM audit_log", SqlAccess::Query, &caps).is_ok());
+    }
+
+    #[test]
+    fn drop_is_limited_to_tables_and_indexes() {
+        let unrestricted = AppManifest::unrestricted().capabilities;
+        let execute = |sql| authorize(sql, SqlAccess::Execute, &unrestricted);
+        for sql in [
+            "DROP SCHEMA main CASCADE",
+            "DROP SCHEMA IF EXISTS polished",
+            "DROP VIEW polished",
+            "DROP SEQUENCE polished",
+        ] {
+            assert_eq!(denied(execute(sql)), "DROP", "{sql}");
+        }
+        // Even a grant naming the schema like a table does not reach it.
+        let caps = caps(r#"{"capabilities": {"data-mutate": {"tables": ["main"], "ddl": true}}}"#);
+        assert_eq!(
+            denied(authorize(
+                "DROP SCHEMA main CASCADE",
+                SqlAccess::Execute,
+                &caps
+            )),
+            "DROP"
+        );
+        assert_eq!(execute("DROP INDEX idx"), Ok(vec!["idx".into()]));
+    }
+
+    #[test]
+    fn ctes_hide_only_unqualified_names_in_scope() {
+        let caps = caps(r#"{"capabilities": {"data-query": {"tables": ["legislation"]}}}"#);
+        let query = |sql| authorize(sql, SqlAccess::Query, &caps);
+
+        assert_eq!(
+            query("WITH a AS (SELECT * FROM legislation), b AS (SELECT * FROM a) SELECT * FROM b"),
+            Ok(vec!["legislation".into()])
+        );
+        for sql in [
+            // A schema-qualified name is the real table, not the CTE.
+            "WITH audit_log AS (SELECT 1) SELECT * FROM main.audit_log",
+            // A CTE in a subquery does not reach the outer query.
+            "SELECT * FROM audit_log WHERE id IN \
+             (WITH audit_log AS (SELECT 1 AS id) SELECT id FROM audit_log)",
+            // Nor does a CTE reach into its own body.
+            "WITH audit_log AS (SELECT * FROM audit_log) SELECT * FROM audit_log",
+        ] {
+            assert_eq!(denied(query(sql)), "audit_log", "{sql}");
+        }
+    }
+
+    #[test]
+    fn tables_outside_main_are_refused() {
+        let unrestricted = AppManifest::unrestricted().capabilities;
+        let execute = |sql| authorize(sql, SqlAccess::Execute, &unrestricted);
+
+        assert_eq!(
+            denied(execute("DELETE FROM memory.main.audit_log")),
+            "memory.main.audit_log"
+        );
+        assert_eq!(
+            denied(execute("SELECT * FROM other.events")),
+            "other.events"
+        );
+        assert_eq!(
+            denied(execute("INSERT INTO \"MAIN\".\"Events\" VALUES (1)")),
+            "events"
+        );
+    }
+
+    #[test]
+    fn renames_claim_the_new_table() {
+        let ddl = caps_with_ddl();
+        let execute = |sql| authorize(sql, SqlAccess::Execute, &ddl);
+        assert_eq!(
+            denied(execute("ALTER TABLE polished RENAME TO events")),
+            "events"
+        );
+        assert_eq!(
+            denied(execute("ALTER TABLE polished RENAME TO other")),
+            "other"
+        );
+        assert_eq!(
+            execute("ALTER TABLE polished RENAME COLUMN a TO b"),
+            Ok(vec!["polished".into()])
+        );
+
+        let unrestricted = AppManifest::unrestricted().capabilities;
+        assert_eq!(
+            denied(authorize(
+                "ALTER TABLE t RENAME TO main.scheduled_tasks",
+                SqlAccess::Execute,
+                &unrestricted
+            )),
+            "scheduled_tasks"
+        );
+    }
+
+    #[test]
+    fn host_tables_stay_read_only() {
+        // Even an app granted the table by name cannot forge scheduled
+        // runs or event deliveries.
+        let caps = caps(
+            r#"{"capabilities": {"data-mutate": {"tables": ["scheduled_tasks", "event_deliveries"], "ddl": true}}}"#,
+        );
+        for sql in [
+            "INSERT INTO scheduled_tasks (name, target_app) VALUES ('x', 'y')",
+            "UPDATE scheduled_tasks SET next_run_at = 0",
+            "DELETE FROM main.Scheduled_Tasks",
+            "DROP TABLE scheduled_tasks",
+        ] {
+            assert_eq!(
+                denied(authorize(sql, SqlAccess::Execute, &caps)),
+                "scheduled_tasks",
+                "{sql}"
+            );
+        }
+        assert_eq!(
+            denied(authorize(
+                "UPDATE event_deliveries SET status = 'delivered'",
+                SqlAccess::Execute,
+                &caps
+            )),
+            "event_deliveries"
+        );
+        let unrestricted = AppManifest::unrestricted().capabilities;
+        assert_eq!(
+            denied(authorize(
+                "DELETE FROM classifier_eval_runs",
+                SqlAccess::Execute,
+                &unrestricted
+            )),
+            "classifier_eval_runs"
+        );
+    }
+}
@@ -1,14 +1,20 @@
 //! DuckDB storage layer for legislation hot path and analytical path.
 
 use std::path::Path;
+use std::sync::Mutex;
 
 use arrow::array::Array;
 use arrow::record_batch::RecordBatch;
 use duckdb::Connection;
+use fractalaw_core::{AuditEntry, GENESIS_HASH, NodeKey};
 use tracing::info;
 
 use crate::StoreError;
 
+/// Held while appending to `audit_log`, so appends through different
+/// connections in one process never chain to the same head.
+static AUDIT_APPEND: Mutex<()> = Mutex::new(());
+
 /// DuckDB store for legislation hot path and analytical path.
 ///
 /// The hot path (`legislation` table) stores one row per law with 78 columns
@@ -220,10 +226,12 @@ impl DuckStore {
 
     // ── DRRP tables ──
 
-    /// Create the `drrp_annotations` and `polished_drrp` tables if they don't exist.
+    /// Create the `drrp_annotations`, `drrp_candidates`, and `polished_drrp`
+    /// tables if they don't exist.
     ///
     /// Unlike legislation/law_edges (loaded from Parquet), these are empty tables
-    /// populated by `fractalaw sync pull` and the drrp-polisher micro-app.
+    /// populated by `fractalaw sync pull`, `fractalaw classify-sections`, and the
+    /// drrp-polisher micro-app.
     pub fn create_drrp_tables(&self) -> Result<(), StoreError> {
         self.conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS drrp_annotations (
@@ -236,6 +244,16 @@ impl DuckStore {
                 polished       BOOLEAN NOT NULL DEFAULT false,
                 synced_at      TIMESTAMPTZ NOT NULL
             );
+            CREATE TABLE IF NOT EXISTS drrp_candidates (
+                law_name       VARCHAR NOT NULL,
+                provision      VARCHAR NOT NULL,
+                drrp_type      VARCHAR NOT NULL,
+                source_text    VARCHAR NOT NULL,
+                confidence     FLOAT   NOT NULL,
+                scraped_at     TIMESTAMPTZ NOT NULL,
+                polished       BOOLEAN NOT NULL DEFAULT false,
+                queued_at      TIMESTAMPTZ NOT NULL
+            );
             CREATE TABLE IF NOT EXISTS polished_drrp (
                 law_name       VARCHAR NOT NULL,
                 provision      VARCHAR NOT NULL,
@@ -247,10 +265,14 @@ impl DuckStore {
                 confidence     FLOAT   NOT NULL,
                 polished_at    TIMESTAMPTZ NOT NULL,
                 model          VARCHAR NOT NULL,
-                pushed         BOOLEAN NOT NULL DEFAULT false
-            );",
+                pushed         BOOLEAN NOT NULL DEFAULT false,
+                model_confidence FLOAT,
+                provider       VARCHAR
+            );
+            ALTER TABLE polished_drrp ADD COLUMN IF NOT EXISTS model_confidence FLOAT;
+            ALTER TABLE polished_drrp ADD COLUMN IF NOT EXISTS provider VARCHAR;",
         )?;
-        info!("ensured drrp_annotations and polished_drrp tables exist");
+        info!("ensured drrp_annotations, drrp_candidates and polished_drrp tables exist");
         Ok(())
     }
 
@@ -259,11 +281,314 @@ impl DuckStore {
         self.count_table("drrp_annotations")
     }
 
+    /// Number of rows in the `drrp_candidates` table.
+    pub fn drrp_candidates_count(&self) -> Result<usize, StoreError> {
+        self.count_table("drrp_candidates")
+    }
+
     /// Number of rows in the `polished_drrp` table.
     pub fn polished_drrp_count(&self) -> Result<usize, StoreError> {
         self.count_table("polished_drrp")
     }
 
+    // ── Classifier evaluation tables ──
+
+    /// Create the classifier evaluation tables if they don't already exist.
+    ///
+    /// One row per run in `classifier_eval_runs`, with the version of the
+    /// model trained on every labelled law and the method's parameters as
+    /// JSON; per-family metrics, the confusion matrix, and confidence
+    /// calibration bins keyed by `run_id`.
+    pub fn create_classifier_eval_tables(&self) -> Result<(), StoreError> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS classifier_eval_runs (
+                run_id          VARCHAR NOT NULL,
+                evaluated_at    TIMESTAMPTZ NOT NULL,
+                method          VARCHAR NOT NULL,
+                embedding_model VARCHAR NOT NULL,
+                split           VARCHAR NOT NULL,
+                laws            INTEGER NOT NULL,
+                accuracy        FLOAT   NOT NULL,
+                macro_f1        FLOAT   NOT NULL,
+                ece             FLOAT   NOT NULL,
+                model_version   VARCHAR,
+                parameters      VARCHAR
+            );
+            -- Tables created before these columns existed gain them, NULL for old runs.
+            ALTER TABLE classifier_eval_runs ADD COLUMN IF NOT EXISTS model_version VARCHAR;
+            ALTER TABLE classifier_eval_runs ADD COLUMN IF NOT EXISTS parameters VARCHAR;
+            CREATE TABLE IF NOT EXISTS classifier_eval_families (
+                run_id          VARCHAR NOT NULL,
+                family          VARCHAR NOT NULL,
+                support         INTEGER NOT NULL,
+                predicted       INTEGER NOT NULL,
+                precision       FLOAT   NOT NULL,
+                recall          FLOAT   NOT NULL,
+                f1              FLOAT   NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS classifier_eval_confusion (
+                run_id           VARCHAR NOT NULL,
+                true_family      VARCHAR NOT NULL,
+                predicted_family VARCHAR NOT NULL,
+                count            INTEGER NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS classifier_eval_calibration (
+                run_id          VARCHAR NOT NULL,
+                bin_lower       FLOAT   NOT NULL,
+                bin_upper       FLOAT   NOT NULL,
+                count           INTEGER NOT NULL,
+                mean_confidence FLOAT   NOT NULL,
+                accuracy        FLOAT   NOT NULL
+            );",
+        )?;
+        info!("ensured classifier_eval_* tables exist");
+        Ok(())
+    }
+
+    /// Number of rows in the `classifier_eval_runs` table.
+    pub fn classifier_eval_runs_count(&self) -> Result<usize, StoreError> {
+        self.count_table("classifier_eval_runs")
+    }
+
+    // ── Classification review ──
+
+    /// Create the `classification_reviews` table if it doesn't already exist.
+    ///
+    /// Each row snapshots the classification a reviewer saw, their decision,
+    /// who made it, and when. Rows are append-only; the latest per law wins.
+    pub fn create_review_tables(&self) -> Result<(), StoreError> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS classification_reviews (
+                law_name            VARCHAR NOT NULL,
+                decision            VARCHAR NOT NULL,
+                ground_truth_family VARCHAR,
+                ai_family           VARCHAR NOT NULL,
+                confidence          FLOAT,
+                model               VARCHAR,
+                reviewer            VARCHAR NOT NULL,
+                reviewed_at         TIMESTAMPTZ NOT NULL
+            );",
+        )?;
+        info!("ensured classification_reviews table exists");
+        Ok(())
+    }
+
+    /// Number of rows in the `classification_reviews` table.
+    pub fn classification_reviews_count(&self) -> Result<usize, StoreError> {
+        self.count_table("classification_reviews")
+    }
+
+    /// Conflicts and low-confidence predictions awaiting review, most uncertain first.
+    ///
+    /// Includes every `conflict` row plus `predicted` rows below `max_confidence`.
+    /// Laws already reviewed for their current AI label are skipped.
+    pub fn review_queue(
+        &self,
+        max_confidence: f32,
+        limit: usize,
+    ) -> Result<Vec<RecordBatch>, StoreError> {
+        self.require_classifications()?;
+        self.create_review_tables()?;
+        self.query_arrow(&format!(
+            "SELECT l.name, l.family, l.classified_family, \
+                    round(l.classification_confidence, 3) AS confidence, \
+                    l.classification_status AS status, \
+                    l.classification_model AS model \
+             FROM legislation l \
+             WHERE (l.classification_status = 'conflict' \
+                    OR (l.classification_status = 'predicted' \
+                        AND l.classification_confidence < {max_confidence})) \
+               AND NOT EXISTS ( \
+                    SELECT 1 FROM classification_reviews r \
+                    WHERE r.law_name = l.name AND r.ai_family = l.classified_family) \
+             ORDER BY l.classification_confidence ASC, l.name \
+             LIMIT {limit}"
+        ))
+    }
+
+    /// Record a reviewer's decision on a law's current AI classification.
+    ///
+    /// `keep_truth` needs a ground-truth family to keep; laws without one
+    /// can only have their AI label accepted.
+    pub fn record_review(
+        &self,
+        law_name: &str,
+        decision: fractalaw_core::ReviewDecision,
+        reviewer: &str,
+    ) -> Result<(), StoreError> {
+        use fractalaw_core::ReviewDecision;
+
+        self.require_classifications()?;
+        self.create_review_tables()?;
+        let needs_truth = decision == ReviewDecision::KeepTruth;
+        let inserted = self.conn.execute(
+            &format!(
+                "INSERT INTO classification_reviews \
+                 SELECT name, '{}', family, classified_family, classification_confidence, \
+                        classification_model, '{}', CURRENT_TIMESTAMP \
+                 FROM legislation \
+                 WHERE name = '{}' AND classified_family IS NOT NULL{}",
+                decision.as_str(),
+                sql_escape(reviewer),
+                sql_escape(law_name),
+                if needs_truth {
+                    " AND family IS NOT NULL"
+                } else {
+                    ""
+                },
+            ),
+            [],
+        )?;
+        if inserted > 0 {
+            return Ok(());
+        }
+        let classified = self
+            .query_arrow(&format!(
+                "SELECT name FROM legislation \
+                 WHERE name = '{}' AND classified_family IS NOT NULL",
+                sql_escape(law_name),
+            ))?
+            .iter()
+            .any(|b| b.num_rows() > 0);
+        Err(StoreError::Other(if needs_truth && classified {
+            format!("law '{law_name}' has no ground-truth family to keep")
+        } else {
+            format!("law '{law_name}' has no AI classification to review")
+        }))
+    }
+
+    /// `(law_name, family)` for laws whose latest review accepted the AI label.
+    pub fn accepted_family_overrides(&self) -> Result<Vec<(String, String)>, StoreError> {
+        self.create_review_tables()?;
+        let batches = self.query_arrow(
+            "SELECT law_name, ai_family FROM ( \
+                SELECT law_name, decision, ai_family, \
+                       row_number() OVER ( \
+                           PARTITION BY law_name ORDER BY reviewed_at DESC, rowid DESC) AS rn \
+                FROM classification_reviews) \
+             WHERE rn = 1 AND decision = 'accept_ai' \
+             ORDER BY law_name",
+        )?;
+        let mut overrides = Vec::new();
+        for batch in &batches {
+            let names = string_col(batch, "law_name");
+            let families = string_col(batch, "ai_family");
+            overrides.extend(names.into_iter().zip(families));
+        }
+        Ok(overrides)
+    }
+
+    /// Fail with a hint unless `fractalaw classify` has added its columns.
+    fn require_classifications(&self) -> Result<(), StoreError> {
+        let batches = self.query_arrow(
+            "SELECT count(*)::BIGINT AS cnt FROM information_schema.columns \
+             WHERE table_name = 'legislation' AND column_name = 'classification_status'",
+        )?;
+        let present = batches
+            .first()
+            .and_then(|b| {
+                b.column(0)
+                    .as_any()
+                    .downcast_ref::<arrow::array::Int64Array>()
+                    .map(|a| a.value(0) > 0)
+            })
+            .unwrap_or(false);
+        if !present {
+            return Err(StoreError::Other(
+                "no classifications yet (run `fractalaw classify` first)".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    // ── Section classification ──
+
+    /// Create the `section_classifications` table if it doesn't exist.
+    ///
+    /// Long format: one row per `(section_id, taxonomy, label)`, where
+    /// `taxonomy` is `drrp` or `subject`. Each `fractalaw classify-sections`
+    /// run replaces the table contents.
+    pub fn create_section_classification_tables(&self) -> Result<(), StoreError> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS section_classifications (
+                section_id     VARCHAR NOT NULL,
+                law_name       VARCHAR NOT NULL,
+                provision      VARCHAR,
+                taxonomy       VARCHAR NOT NULL,
+                label          VARCHAR NOT NULL,
+                confidence     FLOAT   NOT NULL,
+                model          VARCHAR NOT NULL,
+                classified_at  TIMESTAMPTZ NOT NULL
+            );",
+        )?;
+        info!("ensured section_classifications table exists");
+        Ok(())
+    }
+
+    /// Number of rows in the `section_classifications` table.
+    pub fn section_classifications_count(&self) -> Result<usize, StoreError> {
+        self.count_table("section_classifications")
+    }
+
+    /// Provision-level DRRP ground truth as `(law_name, provision, drrp_type)`.
+    ///
+    /// Combines sertantai's `drrp_annotations` with the `article` references of
+    /// the legislation `duties`, `rights`, `responsibilities`, and `powers`
+    /// entries. Locally queued `drrp_candidates` are model output, not ground
+    /// truth, and are never included. Provision formats differ between the two sources; callers
+    /// normalise them (see `fractalaw_ai::sections::provision_key`).
+    pub fn section_drrp_labels(&self) -> Result<Vec<RecordBatch>, StoreError> {
+        self.create_drrp_tables()?;
+        self.query_arrow(
+            "SELECT law_name, provision, drrp_type FROM drrp_annotations \
+             UNION ALL \
+             SELECT law_name, entry.article AS provision, drrp_type FROM ( \
+                SELECT name AS law_name, unnest(duties) AS entry, 'duty' AS drrp_type FROM legislation \
+                UNION ALL SELECT name, unnest(rights), 'right' FROM legislation \
+                UNION ALL SELECT name, unnest(responsibilities), 'responsibility' FROM legislation \
+                UNION ALL SELECT name, unnest(powers), 'power' FROM legislation \
+             ) WHERE entry.article IS NOT NULL",
+        )
+    }
+
+    /// Queue locally generated DRRP candidates for the drrp-polisher.
+    ///
+    /// Candidates go to `drrp_candidates`, never `drrp_annotations`, so they
+    /// stay out of the synced annotation set and out of
+    /// [`section_drrp_labels`](Self::section_drrp_labels). Candidates already
+    /// present in either table (same law, provision, and type) are skipped.
+    /// Returns the number of rows inserted.
+    pub fn insert_drrp_candidates(
+        &self,
+        candidates: &[fractalaw_core::Annotation],
+    ) -> Result<usize, StoreError> {
+        self.create_drrp_tables()?;
+        let mut inserted = 0;
+        for ann in candidates {
+            let law_name = sql_escape(&ann.law_name);
+            let provision = sql_escape(&ann.provision);
+            let drrp_type = sql_escape(&ann.drrp_type);
+            let matches = format!(
+                "law_name = '{law_name}' AND provision = '{provision}' \
+                 AND drrp_type = '{drrp_type}'"
+            );
+            inserted += self.conn.execute(
+                &format!(
+                    "INSERT INTO drrp_candidates \
+                     SELECT '{law_name}', '{provision}', '{drrp_type}', '{}', {}, '{}', false, \
+                            CURRENT_TIMESTAMP \
+                     WHERE NOT EXISTS (SELECT 1 FROM drrp_annotations WHERE {matches}) \
+                       AND NOT EXISTS (SELECT 1 FROM drrp_candidates WHERE {matches})",
+                    sql_escape(&ann.source_text),
+                    ann.confidence,
+                    sql_escape(&ann.scraped_at),
+                ),
+                [],
+            )?;
+        }
+        Ok(inserted)
+    }
+
     // ── Sync helpers ──
 
     /// Insert a batch of annotations pulled from sertantai.
@@ -296,7 +621,8 @@ impl DuckStore {
     pub fn get_unpushed_polished(&self) -> Result<Vec<fractalaw_core::PolishedEntry>, StoreError> {
         let batches = self.query_arrow(
             "SELECT law_name, provision, drrp_type, holder, text, qualifier, \
-             clause_ref, confidence, polished_at::VARCHAR AS polished_at, model \
+             clause_ref, confidence, model_confidence, polished_at::VARCHAR AS polished_at, \
+             provider, model \
              FROM polished_drrp WHERE pushed = false",
         )?;
         let mut entries = Vec::new();
@@ -309,7 +635,9 @@ impl DuckStore {
             let qualifier = string_col_nullable(batch, "qualifier");
             let clause_ref = string_col(batch, "clause_ref");
             let confidence = float_col(batch, "confidence");
+            let model_confidence = float_col_nullable(batch, "model_confidence");
             let polished_at = string_col(batch, "polished_at");
+            let provider = string_col_nullable(batch, "provider");
             let model = string_col(batch, "model");
 
             for i in 0..batch.num_rows() {
@@ -322,7 +650,9 @@ impl DuckStore {
                     qualifier: qualifier[i].clone(),
                     clause_ref: clause_ref[i].clone(),
                     confidence: confidence[i],
+                    model_confidence: model_confidence[i],
                     polished_at: polished_at[i].clone(),
+                    provider: provider[i].clone(),
                     model: model[i].clone(),
                 });
             }
@@ -374,6 +704,626 @@ impl DuckStore {
         Ok(None)
     }
 
+    // ── Event log ──
+
+    /// Create the event log tables if they don't already exist.
+    ///
+    /// `events` is append-only; `event_id` comes from a sequence, so ids are
+    /// monotonic. `event_deliveries` holds one row per event and subscriber,
+    /// written when the event is emitted and updated as delivery proceeds.
+    pub fn create_event_tables(&self) -> Result<(), StoreError> {
+        self.conn.execute_batch(
+            "CREATE SEQUENCE IF NOT EXISTS event_id_seq START 1;
+            CREATE TABLE IF NOT EXISTS events (
+                event_id       BIGINT  NOT NULL,
+                event_type     VARCHAR NOT NULL,
+                payload        BLOB    NOT NULL,
+                source_app     VARCHAR NOT NULL,
+                emitted_at     TIMESTAMPTZ NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS event_subscriptions (
+                subscriber     VARCHAR NOT NULL,
+                event_type     VARCHAR NOT NULL,
+                component      VARCHAR NOT NULL,
+                created_at     TIMESTAMPTZ NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS event_deliveries (
+                event_id        BIGINT  NOT NULL,
+                subscriber      VARCHAR NOT NULL,
+                status          VARCHAR NOT NULL,
+                attempts        INTEGER NOT NULL,
+                last_error      VARCHAR,
+                next_attempt_at TIMESTAMPTZ NOT NULL,
+                updated_at      TIMESTAMPTZ NOT NULL
+            );",
+        )?;
+        info!("ensured events, event_subscriptions, and event_deliveries tables exist");
+        Ok(())
+    }
+
+    /// Append an event to the log and queue a delivery for each subscriber
+    /// to its type, in one transaction. Returns the new `event_id`.
+    pub fn append_event(
+        &self,
+        event_type: &str,
+        payload: &[u8],
+        source_app: &str,
+    ) -> Result<u64, StoreError> {
+        self.create_event_tables()?;
+        let batches = self.query_arrow("SELECT nextval('event_id_seq')::BIGINT AS id")?;
+        let id = i64_col(batches.first().ok_or(StoreError::NoResults)?, "id")
+            .first()
+            .copied()
+            .ok_or(StoreError::NoResults)?;
+        let event_type = sql_escape(event_type);
+        let payload = blob_literal(payload);
+        let result = self.conn.execute_batch(&format!(
+            "BEGIN TRANSACTION;
+            INSERT INTO events VALUES
+                ({id}, '{event_type}', {payload}, '{}', CURRENT_TIMESTAMP);
+            INSERT INTO event_deliveries
+                SELECT {id}, subscriber, 'pending', 0, NULL, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP
+                FROM event_subscriptions WHERE event_type = '{event_type}';
+            COMMIT;",
+            sql_escape(source_app),
+        ));
+        if let Err(e) = result {
+            let _ = self.conn.execute_batch("ROLLBACK");
+            return Err(e.into());
+        }
+        Ok(id as u64)
+    }
+
+    /// Register `component` to run as `subscriber` for every later event of
+    /// `event_type`, replacing any earlier registration of the pair.
+    pub fn subscribe(
+        &self,
+        subscriber: &str,
+        event_type: &str,
+        component: &str,
+    ) -> Result<(), StoreError> {
+        self.create_event_tables()?;
+        let subscriber = sql_escape(subscriber);
+        let event_type = sql_escape(event_type);
+        self.conn.execute_batch(&format!(
+            "DELETE FROM event_subscriptions
+                WHERE subscriber = '{subscriber}' AND event_type = '{event_type}';
+            INSERT INTO event_subscriptions
+                VALUES ('{subscriber}', '{event_type}', '{}', CURRENT_TIMESTAMP);",
+            sql_escape(component),
+        ))?;
+        Ok(())
+    }
+
+    /// Remove a subscription and its undelivered events. Returns whether it existed.
+    pub fn unsubscribe(&self, subscriber: &str, event_type: &str) -> Result<bool, StoreError> {
+        self.create_event_tables()?;
+        let subscriber = sql_escape(subscriber);
+        let event_type = sql_escape(event_type);
+        let removed = self.conn.execute(
+            &format!(
+                "DELETE FROM event_subscriptions \
+                 WHERE subscriber = '{subscriber}' AND event_type = '{event_type}'"
+            ),
+            [],
+        )?;
+        self.conn.execute_batch(&format!(
+            "DELETE FROM event_deliveries
+                WHERE subscriber = '{subscriber}' AND status = 'pending'
+                  AND event_id IN (SELECT event_id FROM events WHERE event_type = '{event_type}')"
+        ))?;
+        Ok(removed > 0)
+    }
+
+    /// All subscriptions, by event type then subscriber.
+    pub fn subscriptions(&self) -> Result<Vec<fractalaw_core::Subscription>, StoreError> {
+        self.create_event_tables()?;
+        let batches = self.query_arrow(
+            "SELECT subscriber, event_type, component FROM event_subscriptions \
+             ORDER BY event_type, subscriber",
+        )?;
+        let mut subscriptions = Vec::new();
+        for batch in &batches {
+            let subscriber = string_col(batch, "subscriber");
+            let event_type = string_col(batch, "event_type");
+            let component = string_col(batch, "component");
+            for i in 0..batch.num_rows() {
+                subscriptions.push(fractalaw_core::Subscription {
+                    subscriber: subscriber[i].clone(),
+                    event_type: event_type[i].clone(),
+                    component: component[i].clone(),
+                });
+            }
+        }
+        Ok(subscriptions)
+    }
+
+    /// Pending deliveries whose retry time has come, oldest event first.
+    pub fn due_deliveries(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<fractalaw_core::PendingDelivery>, StoreError> {
+        self.create_event_tables()?;
+        let batches = self.query_arrow(&format!(
+            "SELECT d.event_id, e.event_type, e.payload, e.source_app, \
+                    d.subscriber, s.component, d.attempts::BIGINT AS attempts \
+             FROM event_deliveries d \
+             JOIN events e ON e.event_id = d.event_id \
+             JOIN event_subscriptions s \
+               ON s.subscriber = d.subscriber AND s.event_type = e.event_type \
+             WHERE d.status = 'pending' AND d.next_attempt_at <= CURRENT_TIMESTAMP \
+             ORDER BY d.event_id, d.subscriber \
+             LIMIT {limit}"
+        ))?;
+        let mut deliveries = Vec::new();
+        for batch in &batches {
+            let event_id = i64_col(batch, "event_id");
+            let event_type = string_col(batch, "event_type");
+            let payload = blob_col(batch, "payload");
+            let source_app = string_col(batch, "source_app");
+            let subscriber = string_col(batch, "subscriber");
+            let component = string_col(batch, "component");
+            let attempts = i64_col(batch, "attempts");
+            for i in 0..batch.num_rows() {
+                deliveries.push(fractalaw_core::PendingDelivery {
+                    event_id: event_id[i] as u64,
+                    event_type: event_type[i].clone(),
+                    payload: payload[i].clone(),
+                    source_app: source_app[i].clone(),
+                    subscriber: subscriber[i].clone(),
+                    component: component[i].clone(),
+                    attempts: attempts[i] as u32,
+                });
+            }
+        }
+        Ok(deliveries)
+    }
+
+    /// Record that `subscriber` processed `event_id`.
+    pub fn mark_delivered(&self, event_id: u64, subscriber: &str) -> Result<(), StoreError> {
+        self.set_delivery_status(
+            event_id,
+            subscriber,
+            fractalaw_core::DeliveryStatus::Delivered,
+            None,
+            0,
+        )
+    }
+
+    /// Record a failed delivery attempt. The delivery is retried after
+    /// `retry_after_secs`, unless `dead_letter` is set, which gives up on it.
+    pub fn record_delivery_failure(
+        &self,
+        event_id: u64,
+        subscriber: &str,
+        error: &str,
+        retry_after_secs: u64,
+        dead_letter: bool,
+    ) -> Result<(), StoreError> {
+        let status = if dead_letter {
+            fractalaw_core::DeliveryStatus::Dead
+        } else {
+            fractalaw_core::DeliveryStatus::Pending
+        };
+        self.set_delivery_status(event_id, subscriber, status, Some(error), retry_after_secs)
+    }
+
+    fn set_delivery_status(
+        &self,
+        event_id: u64,
+        subscriber: &str,
+        status: fractalaw_core::DeliveryStatus,
+        error: Option<&str>,
+        retry_after_secs: u64,
+    ) -> Result<(), StoreError> {
+        let (attempts, last_error) = match error {
+            Some(e) => ("attempts + 1", format!("'{}'", sql_escape(e))),
+            None => ("attempts", "last_error".to_string()),
+        };
+        let updated = self.conn.execute(
+            &format!(
+                "UPDATE event_deliveries SET status = '{}', attempts = {attempts}, \
+                    last_error = {last_error}, \
+                    next_attempt_at = CURRENT_TIMESTAMP + to_seconds({retry_after_secs}), \
+                    updated_at = CURRENT_TIMESTAMP \
+                 WHERE event_id = {event_id} AND subscriber = '{}'",
+                status.as_str(),
+                sql_escape(subscriber),
+            ),
+            [],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::Other(format!(
+                "no delivery of event {event_id} to '{subscriber}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Dead-lettered deliveries, most recent failure first.
+    pub fn dead_letters(&self, limit: usize) -> Result<Vec<RecordBatch>, StoreError> {
+        self.create_event_tables()?;
+        self.query_arrow(&format!(
+            "SELECT d.event_id, e.event_type, e.source_app, d.subscriber, d.attempts, \
+                    d.last_error, d.updated_at \
+             FROM event_deliveries d JOIN events e ON e.event_id = d.event_id \
+             WHERE d.status = 'dead' \
+             ORDER BY d.updated_at DESC, d.event_id DESC \
+             LIMIT {limit}"
+        ))
+    }
+
+    /// Queue a dead-lettered delivery again with a fresh attempt count.
+    pub fn requeue_dead_letter(&self, event_id: u64, subscriber: &str) -> Result<(), StoreError> {
+        self.create_event_tables()?;
+        let updated = self.conn.execute(
+            &format!(
+                "UPDATE event_deliveries SET status = 'pending', attempts = 0, \
+                    next_attempt_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP \
+                 WHERE event_id = {event_id} AND subscriber = '{}' AND status = 'dead'",
+                sql_escape(subscriber),
+            ),
+            [],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::Other(format!(
+                "event {event_id} is not dead-lettered for '{subscriber}'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// The most recent events with a per-status count of their deliveries.
+    pub fn recent_events(&self, limit: usize) -> Result<Vec<RecordBatch>, StoreError> {
+        self.create_event_tables()?;
+        self.query_arrow(&format!(
+            "SELECT e.event_id, e.event_type, e.source_app, octet_length(e.payload) AS bytes, \
+                    e.emitted_at, \
+                    count(d.subscriber) FILTER (WHERE d.status = 'pending') AS pending, \
+                    count(d.subscriber) FILTER (WHERE d.status = 'delivered') AS delivered, \
+                    count(d.subscriber) FILTER (WHERE d.status = 'dead') AS dead \
+             FROM events e LEFT JOIN event_deliveries d ON d.event_id = e.event_id \
+             GROUP BY ALL \
+             ORDER BY e.event_id DESC \
+             LIMIT {limit}"
+        ))
+    }
+
+    // ── Scheduled tasks ──
+
+    /// Create the `scheduled_tasks` table. Run times are Unix seconds so the
+    /// scheduler can be driven by a test clock.
+    pub fn create_schedule_tables(&self) -> Result<(), StoreError> {
+        self.conn.execute_batch(
+            "CREATE SEQUENCE IF NOT EXISTS task_id_seq START 1;
+            CREATE TABLE IF NOT EXISTS scheduled_tasks (
+                task_id      BIGINT  NOT NULL,
+                name         VARCHAR NOT NULL,
+                cron         VARCHAR,
+                run_at       BIGINT,
+                target_app   VARCHAR NOT NULL,
+                payload      BLOB    NOT NULL,
+                owner_app    VARCHAR NOT NULL,
+                status       VARCHAR NOT NULL,
+                next_run_at  BIGINT,
+                last_run_at  BIGINT,
+                last_error   VARCHAR,
+                runs         INTEGER NOT NULL,
+                created_at   TIMESTAMPTZ NOT NULL
+            );",
+        )?;
+        info!("ensured scheduled_tasks table exists");
+        Ok(())
+    }
+
+    /// Store an active task first due at `next_run_at`. `task.id` is ignored;
+    /// the new `task_id` is returned.
+    pub fn insert_scheduled_task(
+        &self,
+        task: &fractalaw_core::ScheduledTask,
+        next_run_at: i64,
+    ) -> Result<u64, StoreError> {
+        self.create_schedule_tables()?;
+        let batches = self.query_arrow("SELECT nextval('task_id_seq')::BIGINT AS id")?;
+        let id = i64_col(batches.first().ok_or(StoreError::NoResults)?, "id")
+            .first()
+            .copied()
+            .ok_or(StoreError::NoResults)?;
+        let cron = match &task.cron {
+            Some(c) => format!("'{}'", sql_escape(c)),
+            None => "NULL".to_string(),
+        };
+        let run_at = match task.run_at {
+            Some(t) => t.to_string(),
+            None => "NULL".to_string(),
+        };
+        self.conn.execute_batch(&format!(
+            "INSERT INTO scheduled_tasks VALUES
+                ({id}, '{}', {cron}, {run_at}, '{}', {}, '{}', 'active', {next_run_at},
+                 NULL, NULL, 0, CURRENT_TIMESTAMP);",
+            sql_escape(&task.name),
+            sql_escape(&task.target_app),
+            blob_literal(&task.payload),
+            sql_escape(&task.owner_app),
+        ))?;
+        Ok(id as u64)
+    }
+
+    /// Look up a task by id, whatever its status.
+    pub fn scheduled_task(
+        &self,
+        id: u64,
+    ) -> Result<Option<fractalaw_core::ScheduledTask>, StoreError> {
+        Ok(self
+            .read_tasks(&format!("WHERE task_id = {id}"))?
+            .into_iter()
+            .next())
+    }
+
+    /// Active tasks due at or before `now` (Unix seconds), earliest first.
+    pub fn due_tasks(
+        &self,
+        now: i64,
+        limit: usize,
+    ) -> Result<Vec<fractalaw_core::ScheduledTask>, StoreError> {
+        self.read_tasks(&format!(
+            "WHERE status = 'active' AND next_run_at <= {now} \
+             ORDER BY next_run_at, task_id LIMIT {limit}"
+        ))
+    }
+
+    fn read_tasks(&self, filter: &str) -> Result<Vec<fractalaw_core::ScheduledTask>, StoreError> {
+        self.create_schedule_tables()?;
+        let batches = self.query_arrow(&format!(
+            "SELECT task_id, name, cron, run_at, target_app, payload, owner_app \
+             FROM scheduled_tasks {filter}"
+        ))?;
+        let mut tasks = Vec::new();
+        for batch in &batches {
+            let id = i64_col(batch, "task_id");
+            let name = string_col(batch, "name");
+            let cron = string_col_nullable(batch, "cron");
+            let run_at = i64_col_nullable(batch, "run_at");
+            let target_app = string_col(batch, "target_app");
+            let payload = blob_col(batch, "payload");
+            let owner_app = string_col(batch, "owner_app");
+            for i in 0..batch.num_rows() {
+                tasks.push(fractalaw_core::ScheduledTask {
+                    id: id[i] as u64,
+                    name: name[i].clone(),
+                    cron: cron[i].clone(),
+                    run_at: run_at[i].map(|t| t as u64),
+                    target_app: target_app[i].clone(),
+                    payload: payload[i].clone(),
+                    owner_app: owner_app[i].clone(),
+                });
+            }
+        }
+        Ok(tasks)
+    }
+
+    /// Record a run of task `id` at `ran_at`. With a `next_run_at` the task
+    /// stays active; without one it is finished, as `failed` if `error` is set.
+    pub fn record_task_run(
+        &self,
+        id: u64,
+        ran_at: i64,
+        next_run_at: Option<i64>,
+        error: Option<&str>,
+    ) -> Result<(), StoreError> {
+        self.create_schedule_tables()?;
+        let status = match (next_run_at, error) {
+            (Some(_), _) => fractalaw_core::TaskStatus::Active,
+            (None, None) => fractalaw_core::TaskStatus::Done,
+            (None, Some(_)) => fractalaw_core::TaskStatus::Failed,
+        };
+        let next_run_at = next_run_at.map_or("NULL".to_string(), |t| t.to_string());
+        let last_error = error.map_or("NULL".to_string(), |e| format!("'{}'", sql_escape(e)));
+        let updated = self.conn.execute(
+            &format!(
+                "UPDATE scheduled_tasks SET status = '{}', next_run_at = {next_run_at}, \
+                    last_run_at = {ran_at}, last_error = {last_error}, runs = runs + 1 \
+                 WHERE task_id = {id}",
+                status.as_str(),
+            ),
+            [],
+        )?;
+        if updated == 0 {
+            return Err(StoreError::Other(format!("no scheduled task {id}")));
+        }
+        Ok(())
+    }
+
+    /// Cancel an active task. Returns `false` if it is not active.
+    pub fn cancel_scheduled_task(&self, id: u64) -> Result<bool, StoreError> {
+        self.create_schedule_tables()?;
+        let updated = self.conn.execute(
+            &format!(
+                "UPDATE scheduled_tasks SET status = 'cancelled', next_run_at = NULL \
+                 WHERE task_id = {id} AND status = 'active'"
+            ),
+            [],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// Scheduled tasks for display, next due first; finished and cancelled
+    /// tasks only if `all` is set.
+    pub fn scheduled_tasks(&self, all: bool) -> Result<Vec<RecordBatch>, StoreError> {
+        self.create_schedule_tables()?;
+        let filter = if all { "" } else { "WHERE status = 'active'" };
+        self.query_arrow(&format!(
+            "SELECT task_id, name, coalesce(cron, 'once') AS schedule, target_app, \
+                    owner_app, status, to_timestamp(next_run_at) AS next_run_at, \
+                    to_timestamp(last_run_at) AS last_run_at, runs, last_error \
+             FROM scheduled_tasks {filter} \
+             ORDER BY next_run_at NULLS LAST, task_id"
+        ))
+    }
+
+    // ── Audit log ──
+
+    /// Create the `audit_log` table (see
+    /// [`esh::audit_log_schema`](fractalaw_core::esh::audit_log_schema)).
+    ///
+    /// The table is append-only by convention: nothing here updates or
+    /// deletes entries, and the hash chain exposes anything that does.
+    pub fn create_audit_tables(&self) -> Result<(), StoreError> {
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS audit_log (
+                entry_id    UBIGINT NOT NULL,
+                timestamp   TIMESTAMPTZ NOT NULL,
+                node_id     VARCHAR NOT NULL,
+                actor_id    VARCHAR NOT NULL,
+                actor_role  VARCHAR NOT NULL,
+                event_type  VARCHAR NOT NULL,
+                resource    VARCHAR,
+                detail      VARCHAR,
+                prev_hash   BLOB    NOT NULL,
+                signature   BLOB
+            );",
+        )?;
+        info!("ensured audit_log table exists");
+        Ok(())
+    }
+
+    /// Append `entries` to the audit log in one transaction, each chained
+    /// to the one before. Their `entry_id` and `prev_hash` are ignored and
+    /// assigned here; the entries are returned as stored.
+    pub fn append_audit(
+        &self,
+        mut entries: Vec<AuditEntry>,
+    ) -> Result<Vec<AuditEntry>, StoreError> {
+        if entries.is_empty() {
+            return Ok(entries);
+        }
+        self.create_audit_tables()?;
+        let _guard = AUDIT_APPEND.lock().unwrap_or_else(|e| e.into_inner());
+        self.append_audit_locked(entries, None)
+    }
+
+    /// Append a checkpoint signed with `key` over the current head of the
+    /// audit log. Returns `None`, appending nothing, when the log is empty
+    /// or its head is already a checkpoint.
+    pub fn append_checkpoint(&self, key: &NodeKey) -> Result<Option<AuditEntry>, StoreError> {
+        self.create_audit_tables()?;
+        let _guard = AUDIT_APPEND.lock().unwrap_or_else(|e| e.into_inner());
+        match self.audit_head()? {
+            Some(head) if !head.is_checkpoint() => {}
+            _ => return Ok(None),
+        }
+        let stored = self.append_audit_locked(vec![AuditEntry::checkpoint(key)], Some(key))?;
+        Ok(stored.into_iter().next())
+    }
+
+    /// [`append_audit`](Self::append_audit) with `AUDIT_APPEND` held,
+    /// signing each entry with `key` once it is chained.
+    fn append_audit_locked(
+        &self,
+        mut entries: Vec<AuditEntry>,
+        key: Option<&NodeKey>,
+    ) -> Result<Vec<AuditEntry>, StoreError> {
+        let (mut entry_id, mut prev_hash) = match self.audit_head()? {
+            Some(head) => (head.entry_id, head.hash()),
+            None => (0, GENESIS_HASH),
+        };
+        let mut rows = Vec::with_capacity(entries.len());
+        for entry in &mut entries {
+            entry_id += 1;
+            entry.entry_id = entry_id;
+            entry.prev_hash = prev_hash;
+            // TIMESTAMPTZ keeps microseconds.
+            if let Some(timestamp) =
+                chrono::DateTime::from_timestamp_micros(entry.timestamp.timestamp_micros())
+            {
+                entry.timestamp = timestamp;
+            }
+            if let Some(key) = key {
+                entry.sign(key);
+            }
+            prev_hash = entry.hash();
+            rows.push(audit_row(entry));
+        }
+        let result = self.conn.execute_batch(&format!(
+            "BEGIN TRANSACTION;
+            INSERT INTO audit_log VALUES {};
+            COMMIT;",
+            rows.join(",\n")
+        ));
+        if let Err(e) = result {
+            let _ = self.conn.execute_batch("ROLLBACK");
+            return Err(e.into());
+        }
+        Ok(entries)
+    }
+
+    /// The last audit entry, if any.
+    pub fn audit_head(&self) -> Result<Option<AuditEntry>, StoreError> {
+        Ok(self
+            .read_audit("ORDER BY entry_id DESC LIMIT 1")?
+            .into_iter()
+            .next())
+    }
+
+    /// The whole audit log, in `entry_id` order.
+    pub fn audit_log(&self) -> Result<Vec<AuditEntry>, StoreError> {
+        self.read_audit("ORDER BY entry_id")
+    }
+
+    fn read_audit(&self, order: &str) -> Result<Vec<AuditEntry>, StoreError> {
+        self.create_audit_tables()?;
+        let batches = self.query_arrow(&format!(
+            "SELECT entry_id::BIGINT AS entry_id, epoch_us(timestamp)::BIGINT AS timestamp_us, \
+                    node_id, actor_id, actor_role, event_type, resource, detail, \
+                    prev_hash, signature \
+             FROM audit_log {order}"
+        ))?;
+        let mut entries = Vec::new();
+        for batch in &batches {
+            let entry_id = i64_col(batch, "entry_id");
+            let timestamp = i64_col(batch, "timestamp_us");
+            let node_id = string_col(batch, "node_id");
+            let actor_id = string_col(batch, "actor_id");
+            let actor_role = string_col(batch, "actor_role");
+            let event_type = string_col(batch, "event_type");
+            let resource = string_col_nullable(batch, "resource");
+            let detail = string_col_nullable(batch, "detail");
+            let prev_hash = blob_col(batch, "prev_hash");
+            let signature = blob_col_nullable(batch, "signature");
+            for i in 0..batch.num_rows() {
+                let id = entry_id[i] as u64;
+                let invalid = |column: &str| {
+                    StoreError::Other(format!("audit entry {id}: malformed {column}"))
+                };
+                entries.push(AuditEntry {
+                    entry_id: id,
+                    timestamp: chrono::DateTime::from_timestamp_micros(timestamp[i])
+                        .ok_or_else(|| invalid("timestamp"))?,
+                    node_id: node_id[i].clone(),
+                    actor_id: actor_id[i].clone(),
+                    actor_role: actor_role[i].clone(),
+                    event_type: event_type[i].clone(),
+                    resource: resource[i].clone(),
+                    detail: detail[i].clone(),
+                    prev_hash: prev_hash[i]
+                        .as_slice()
+                        .try_into()
+                        .map_err(|_| invalid("prev_hash"))?,
+                    signature: match &signature[i] {
+                        Some(bytes) => Some(
+                            bytes
+                                .as_slice()
+                                .try_into()
+                                .map_err(|_| invalid("signature"))?,
+                        ),
+                        None => None,
+                    },
+                });
+            }
+        }
+        Ok(entries)
+    }
+
     // ── Escape hatch ──
 
     /// Execute a DDL/DML statement that returns no result set.
@@ -384,6 +1334,12 @@ impl DuckStore {
         Ok(())
     }
 
+    /// Execute a single statement and return the number of rows it changed
+    /// (0 for DDL).
+    pub fn execute_one(&self, sql: &str) -> Result<usize, StoreError> {
+        Ok(self.conn.execute(sql, [])?)
+    }
+
     /// Execute arbitrary SQL and return Arrow RecordBatches.
     pub fn query_arrow(&self, sql: &str) -> Result<Vec<RecordBatch>, StoreError> {
         let mut stmt = self.conn.prepare(sql)?;
@@ -391,6 +1347,14 @@ impl DuckStore {
         Ok(batches)
     }
 
+    /// Open another connection to the same database, e.g. for a micro-app
+    /// run while this one keeps serving the host.
+    pub fn try_clone(&self) -> Result<Self, StoreError> {
+        Ok(Self {
+            conn: self.conn.try_clone()?,
+        })
+    }
+
     /// Access the underlying DuckDB connection (for DataFusion TableProvider registration).
     pub fn connection(&self) -> &Connection {
         &self.conn
@@ -402,6 +1366,38 @@ fn sql_escape(s: &str) -> String {
     s.replace('\'', "''")
 }
 
+/// A BLOB literal for `bytes`.
+fn blob_literal(bytes: &[u8]) -> String {
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!("from_hex('{hex}')")
+}
+
+/// A quoted string literal, or `NULL`.
+fn opt_literal(value: Option<&str>) -> String {
+    value.map_or("NULL".to_string(), |v| format!("'{}'", sql_escape(v)))
+}
+
+/// The `VALUES` tuple for an `audit_log` row.
+fn audit_row(entry: &AuditEntry) -> String {
+    format!(
+        "({}, '{}'::TIMESTAMPTZ, '{}', '{}', '{}', '{}', {}, {}, {}, {})",
+        entry.entry_id,
+        entry
+            .timestamp
+            .to_rfc3339_opts(chrono::SecondsFormat::Micros, true),
+        sql_escape(&entry.node_id),
+        sql_escape(&entry.actor_id),
+        sql_escape(&entry.actor_role),
+        sql_escape(&entry.event_type),
+        opt_literal(entry.resource.as_deref()),
+        opt_literal(entry.detail.as_deref()),
+        blob_literal(&entry.prev_hash),
+        entry
+            .signature
+            .map_or("NULL".to_string(), |s| blob_literal(&s)),
+    )
+}
+
 /// Extract a non-nullable VARCHAR column as a Vec of Strings.
 fn string_col(batch: &RecordBatch, name: &str) -> Vec<String> {
     let col = batch.column_by_name(name).expect(name);
@@ -448,6 +1444,61 @@ fn string_col_nullable(batch: &RecordBatch, name: &str) -> Vec<Option<String>> {
     }
 }
 
+/// Extract a BIGINT column as a Vec of i64.
+fn i64_col(batch: &RecordBatch, name: &str) -> Vec<i64> {
+    let col = batch.column_by_name(name).expect(name);
+    let arr = col
+        .as_any()
+        .downcast_ref::<arrow::array::Int64Array>()
+        .unwrap_or_else(|| panic!("column {name} is not Int64"));
+    (0..arr.len()).map(|i| arr.value(i)).collect()
+}
+
+/// Extract a nullable BIGINT column as a Vec of Option<i64>.
+fn i64_col_nullable(batch: &RecordBatch, name: &str) -> Vec<Option<i64>> {
+    let col = batch.column_by_name(name).expect(name);
+    let arr = col
+        .as_any()
+        .downcast_ref::<arrow::array::Int64Array>()
+        .unwrap_or_else(|| panic!("column {name} is not Int64"));
+    (0..arr.len())
+        .map(|i| {
+            if arr.is_null(i) {
+                None
+            } else {
+                Some(arr.value(i))
+            }
+        })
+        .collect()
+}
+
+/// Extract a non-nullable BLOB column as a Vec of byte vectors.
+fn blob_col(batch: &RecordBatch, name: &str) -> Vec<Vec<u8>> {
+    let col = batch.column_by_name(name).expect(name);
+    if let Some(arr) = col.as_any().downcast_ref::<arrow::array::BinaryArray>() {
+        (0..arr.len()).map(|i| arr.value(i).to_vec()).collect()
+    } else if let Some(arr) = col
+        .as_any()
+        .downcast_ref::<arrow::array::LargeBinaryArray>()
+    {
+        (0..arr.len()).map(|i| arr.value(i).to_vec()).collect()
+    } else {
+        panic!("column {name} is not a binary type");
+    }
+}
+
+/// Extract a nullable BLOB column as a Vec of optional byte vectors.
+fn blob_col_nullable(batch: &RecordBatch, name: &str) -> Vec<Option<Vec<u8>>> {
+    let col = batch.column_by_name(name).expect(name);
+    let arr = col
+        .as_any()
+        .downcast_ref::<arrow::array::BinaryArray>()
+        .unwrap_or_else(|| panic!("column {name} is not Binary"));
+    (0..arr.len())
+        .map(|i| (!arr.is_null(i)).then(|| arr.value(i).to_vec()))
+        .collect()
+}
+
 /// Extract a FLOAT column as a Vec of f32.
 fn float_col(batch: &RecordBatch, name: &str) -> Vec<f32> {
     let col = batch.column_by_name(name).expect(name);
@@ -458,6 +1509,24 @@ fn float_col(batch: &RecordBatch, name: &str) -> Vec<f32> {
     (0..arr.len()).map(|i| arr.value(i)).collect()
 }
 
+/// Extract a nullable FLOAT column as a Vec of Option<f32>.
+fn float_col_nullable(batch: &RecordBatch, name: &str) -> Vec<Option<f32>> {
+    let col = batch.column_by_name(name).expect(name);
+    let arr = col
+        .as_any()
+        .downcast_ref::<arrow::array::Float32Array>()
+        .unwrap_or_else(|| panic!("column {name} is not Float32"));
+    (0..arr.len())
+        .map(|i| {
+            if arr.is_null(i) {
+                None
+            } else {
+                Some(arr.value(i))
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -680,6 +1749,7 @@ mod tests {
         let store = DuckStore::open().unwrap();
         store.create_drrp_tables().unwrap();
         assert_eq!(store.drrp_annotations_count().unwrap(), 0);
+        assert_eq!(store.drrp_candidates_count().unwrap(), 0);
         assert_eq!(store.polished_drrp_count().unwrap(), 0);
     }
 
@@ -691,6 +1761,298 @@ mod tests {
         assert_eq!(store.drrp_annotations_count().unwrap(), 0);
     }
 
+    #[test]
+    fn create_classifier_eval_tables_idempotent() {
+        let store = DuckStore::open().unwrap();
+        store.create_classifier_eval_tables().unwrap();
+        store.create_classifier_eval_tables().unwrap();
+        assert_eq!(store.classifier_eval_runs_count().unwrap(), 0);
+        store
+            .execute(
+                "INSERT INTO classifier_eval_runs VALUES (
+                    'eval-1', '2026-02-21T10:00:00Z', 'knn:5', 'all-MiniLM-L6-v2',
+                    '5-fold', 120, 0.82, 0.74, 0.06, 'knn-k5-3f2a9c01b7de', '{\"k\":5}'
+                )",
+            )
+            .unwrap();
+        assert_eq!(store.classifier_eval_runs_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn classifier_eval_runs_gain_model_columns() {
+        let store = DuckStore::open().unwrap();
+        store
+            .execute(
+                "CREATE TABLE classifier_eval_runs (
+                    run_id VARCHAR NOT NULL, evaluated_at TIMESTAMPTZ NOT NULL,
+                    method VARCHAR NOT NULL, embedding_model VARCHAR NOT NULL,
+                    split VARCHAR NOT NULL, laws INTEGER NOT NULL, accuracy FLOAT NOT NULL,
+                    macro_f1 FLOAT NOT NULL, ece FLOAT NOT NULL
+                );
+                INSERT INTO classifier_eval_runs VALUES (
+                    'eval-0', '2026-02-20T10:00:00Z', 'centroid', 'all-MiniLM-L6-v2',
+                    '5-fold', 120, 0.80, 0.70, 0.07
+                )",
+            )
+            .unwrap();
+        store.create_classifier_eval_tables().unwrap();
+        let batches = store
+            .query_arrow(
+                "SELECT count(*)::BIGINT AS n FROM classifier_eval_runs \
+                 WHERE model_version IS NULL AND parameters IS NULL",
+            )
+            .unwrap();
+        assert_eq!(i64_col(&batches[0], "n"), [1]);
+    }
+
+    // ── Section classification ──
+
+    #[test]
+    fn create_section_classification_tables_idempotent() {
+        let store = DuckStore::open().unwrap();
+        store.create_section_classification_tables().unwrap();
+        store.create_section_classification_tables().unwrap();
+        assert_eq!(store.section_classifications_count().unwrap(), 0);
+    }
+
+    #[test]
+    fn section_drrp_labels_merge_annotations_and_entries() {
+        let store = DuckStore::open().unwrap();
+        store
+            .execute(
+                "CREATE TABLE legislation (
+                    name VARCHAR,
+                    duties STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[],
+                    rights STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[],
+                    responsibilities STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[],
+                    powers STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[]
+                );
+                INSERT INTO legislation VALUES (
+                    'UK_ukpga_1974_37',
+                    [{'holder': 'Employer', 'duty_type': 'Duty', 'clause': 'shall ensure', 'article': 'section/2'},
+                     {'holder': 'Employer', 'duty_type': 'Duty', 'clause': 'no article', 'article': NULL}],
+                    NULL,
+                    NULL,
+                    [{'holder': 'Inspector', 'duty_type': 'Power', 'clause': 'may enter', 'article': 'section/20'}]
+                );",
+            )
+            .unwrap();
+        store.create_drrp_tables().unwrap();
+        store
+            .insert_annotations(&[fractalaw_core::Annotation {
+                law_name: "UK_ukpga_1974_37".into(),
+                provision: "s.7(a)".into(),
+                drrp_type: "duty".into(),
+                source_text: "It shall be the duty of every employee...".into(),
+                confidence: 0.8,
+                scraped_at: "2026-02-21T10:00:00Z".into(),
+            }])
+            .unwrap();
+
+        let batches = store.section_drrp_labels().unwrap();
+        let mut rows: Vec<(String, String)> = batches
+            .iter()
+            .flat_map(|b| {
+                string_col(b, "provision")
+                    .into_iter()
+                    .zip(string_col(b, "drrp_type"))
+            })
+            .collect();
+        rows.sort();
+        assert_eq!(
+            rows,
+            vec![
+                ("s.7(a)".to_string(), "duty".to_string()),
+                ("section/2".to_string(), "duty".to_string()),
+                ("section/20".to_string(), "power".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn drrp_candidates_skip_existing_and_stay_out_of_annotations() {
+        let store = DuckStore::open().unwrap();
+        store.create_drrp_tables().unwrap();
+        store
+            .execute(
+                "INSERT INTO drrp_annotations VALUES (
+                    'UK_ukpga_1974_37', '2', 'duty', 'existing', 0.9,
+                    '2026-02-20T10:00:00Z', false, '2026-02-21T10:00:00Z'
+                )",
+            )
+            .unwrap();
+        let before = store.get_last_sync_at().unwrap();
+
+        let inserted = store
+            .insert_drrp_candidates(&[drrp_candidate("2"), drrp_candidate("3")])
+            .unwrap();
+        assert_eq!(inserted, 1);
+        // Re-queueing the same candidate is a no-op.
+        assert_eq!(
+            store
+                .insert_drrp_candidates(&[drrp_candidate("3")])
+                .unwrap(),
+            0
+        );
+
+        assert_eq!(store.drrp_annotations_count().unwrap(), 1);
+        assert_eq!(store.drrp_candidates_count().unwrap(), 1);
+        assert_eq!(store.get_last_sync_at().unwrap(), before);
+    }
+
+    #[test]
+    fn queued_drrp_candidates_are_not_section_labels() {
+        let store = DuckStore::open().unwrap();
+        store
+            .execute(
+                "CREATE TABLE legislation (
+                    name VARCHAR,
+                    duties STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[],
+                    rights STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[],
+                    responsibilities STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[],
+                    powers STRUCT(holder VARCHAR, duty_type VARCHAR, clause VARCHAR, article VARCHAR)[]
+                );",
+            )
+            .unwrap();
+        store
+            .insert_drrp_candidates(&[drrp_candidate("3")])
+            .unwrap();
+
+        let batches = store.section_drrp_labels().unwrap();
+        let rows: usize = batches.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 0);
+    }
+
+    fn drrp_candidate(provision: &str) -> fractalaw_core::Annotation {
+        fractalaw_core::Annotation {
+            law_name: "UK_ukpga_1974_37".into(),
+            provision: provision.into(),
+            drrp_type: "duty".into(),
+            source_text: "Every employer's duty...".into(),
+            confidence: 0.6,
+            scraped_at: "2026-03-01T10:00:00Z".into(),
+        }
+    }
+
+    // ── Classification review ──
+
+    /// Minimal classified legislation table.
+    fn classified_store() -> DuckStore {
+        let store = DuckStore::open().unwrap();
+        store
+            .execute(
+                "CREATE TABLE legislation (
+                    name VARCHAR, family VARCHAR, classified_family VARCHAR,
+                    classification_confidence FLOAT, classification_status VARCHAR,
+                    classification_model VARCHAR
+                );
+                INSERT INTO legislation VALUES
+                    ('law_conflict', 'ENERGY', 'WASTE', 0.62, 'conflict', 'centroid-v1-abc'),
+                    ('law_unsure', NULL, 'FIRE', 0.31, 'predicted', 'centroid-v1-abc'),
+                    ('law_sure', NULL, 'FIRE', 0.91, 'predicted', 'centroid-v1-abc'),
+                    ('law_ok', 'WASTE', 'WASTE', 0.88, 'confirmed', 'centroid-v1-abc'),
+                    ('law_unclassified', 'WASTE', NULL, NULL, NULL, NULL);",
+            )
+            .unwrap();
+        store
+    }
+
+    fn queue_names(store: &DuckStore) -> Vec<String> {
+        store
+            .review_queue(0.5, 10)
+            .unwrap()
+            .iter()
+            .flat_map(|b| string_col(b, "name"))
+            .collect()
+    }
+
+    #[test]
+    fn review_queue_orders_by_uncertainty() {
+        let store = classified_store();
+        assert_eq!(queue_names(&store), vec!["law_unsure", "law_conflict"]);
+    }
+
+    #[test]
+    fn review_queue_requires_classifications() {
+        let store = DuckStore::open().unwrap();
+        store
+            .execute("CREATE TABLE legislation (name VARCHAR, family VARCHAR)")
+            .unwrap();
+        assert!(matches!(
+            store.review_queue(0.5, 10),
+            Err(StoreError::Other(_))
+        ));
+    }
+
+    #[test]
+    fn reviewed_laws_leave_queue_and_feed_overrides() {
+        use fractalaw_core::ReviewDecision;
+
+        let store = classified_store();
+        store
+            .record_review("law_conflict", ReviewDecision::AcceptAi, "alice")
+            .unwrap();
+        store
+            .record_review("law_unsure", ReviewDecision::AcceptAi, "bob")
+            .unwrap();
+        assert_eq!(store.classification_reviews_count().unwrap(), 2);
+        assert!(queue_names(&store).is_empty());
+
+        assert_eq!(
+            store.accepted_family_overrides().unwrap(),
+            vec![
+                ("law_conflict".to_string(), "WASTE".to_string()),
+                ("law_unsure".to_string(), "FIRE".to_string()),
+            ]
+        );
+
+        // A later decision on the same law supersedes the earlier one.
+        store
+            .record_review("law_conflict", ReviewDecision::KeepTruth, "alice")
+            .unwrap();
+        assert_eq!(
+            store.accepted_family_overrides().unwrap(),
+            vec![("law_unsure".to_string(), "FIRE".to_string())]
+        );
+
+        let who = store
+            .query_arrow(
+                "SELECT reviewer FROM classification_reviews \
+                 WHERE law_name = 'law_conflict' AND decision = 'keep_truth'",
+            )
+            .unwrap();
+        assert_eq!(string_col(&who[0], "reviewer"), vec!["alice"]);
+    }
+
+    #[test]
+    fn keep_truth_requires_ground_truth() {
+        use fractalaw_core::ReviewDecision;
+
+        let store = classified_store();
+        let err = store
+            .record_review("law_unsure", ReviewDecision::KeepTruth, "bob")
+            .unwrap_err();
+        assert!(err.to_string().contains("no ground-truth family"));
+        assert_eq!(store.classification_reviews_count().unwrap(), 0);
+        assert_eq!(queue_names(&store), vec!["law_unsure", "law_conflict"]);
+    }
+
+    #[test]
+    fn review_of_unclassified_law_errors() {
+        use fractalaw_core::ReviewDecision;
+
+        let store = classified_store();
+        let err = store
+            .record_review("law_unclassified", ReviewDecision::AcceptAi, "alice")
+            .unwrap_err();
+        assert!(err.to_string().contains("no AI classification"));
+        assert!(
+            store
+                .record_review("no_such_law", ReviewDecision::AcceptAi, "alice")
+                .is_err()
+        );
+    }
+
     #[test]
     fn drrp_annotations_insert_and_count() {
         let store = DuckStore::open().unwrap();
@@ -717,7 +2079,8 @@ mod tests {
                     'UK_ukpga_1974_37', 's.2(1)', 'duty', 'every employer',
                     'ensure health safety and welfare of employees',
                     'so far as is reasonably practicable', 's.2(1)',
-                    0.95, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false
+                    0.95, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false,
+                    NULL, NULL
                 )",
             )
             .unwrap();
@@ -734,7 +2097,8 @@ mod tests {
                     'UK_ukpga_1974_37', 's.3', 'duty', 'every employer',
                     'conduct undertaking without risk to persons',
                     NULL, 's.3',
-                    0.90, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false
+                    0.90, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false,
+                    NULL, NULL
                 )",
             )
             .unwrap();
@@ -768,6 +2132,12 @@ mod tests {
         .unwrap();
 
         store.insert_batch("test_insert", &batch).unwrap();
+        assert_eq!(
+            store
+                .execute_one("UPDATE test_insert SET score = 1.0 WHERE name = 'bob'")
+                .unwrap(),
+            1
+        );
 
         let result = store
             .query_arrow("SELECT name, score FROM test_insert ORDER BY name")
@@ -861,13 +2231,15 @@ mod tests {
                 "INSERT INTO polished_drrp VALUES
                     ('UK_ukpga_1974_37', 's.2(1)', 'duty', 'every employer',
                      'ensure health safety', 'so far as is reasonably practicable', 's.2(1)',
-                     0.95, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false),
+                     0.95, '2026-02-21T13:00:00Z', 'llama3', false, 0.5, 'openai'),
                     ('UK_ukpga_1974_37', 's.7(a)', 'duty', 'every employee',
                      'take reasonable care', NULL, 's.7(a)',
-                     0.90, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false),
+                     0.90, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false,
+                     NULL, 'anthropic'),
                     ('UK_ukpga_1974_37', 's.3', 'duty', 'every employer',
                      'conduct undertaking', NULL, 's.3',
-                     0.88, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', true)",
+                     0.88, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', true,
+                     NULL, NULL)",
             )
             .unwrap();
 
@@ -879,6 +2251,11 @@ mod tests {
             Some("so far as is reasonably practicable")
         );
         assert!(entries[1].qualifier.is_none());
+        // The scraper's confidence stays apart from the model's.
+        assert_eq!(entries[0].confidence, 0.95);
+        assert_eq!(entries[0].model_confidence, Some(0.5));
+        assert_eq!(entries[0].provider.as_deref(), Some("openai"));
+        assert_eq!(entries[1].model_confidence, None);
     }
 
     #[test]
@@ -891,7 +2268,8 @@ mod tests {
                 "INSERT INTO polished_drrp VALUES
                     ('UK_ukpga_1974_37', 's.2(1)', 'duty', 'every employer',
                      'ensure health safety', NULL, 's.2(1)',
-                     0.95, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false)",
+                     0.95, '2026-02-21T13:00:00Z', 'claude-sonnet-4-5-20250929', false,
+                     NULL, NULL)",
             )
             .unwrap();
 
@@ -930,4 +2308,216 @@ mod tests {
             "expected latest timestamp, got {ts}"
         );
     }
+
+    #[test]
+    fn events_fan_out_to_subscribers() {
+        let store = DuckStore::open().unwrap();
+        store
+            .subscribe("site-notifier", "law-amended", "apps/site_notifier.wasm")
+            .unwrap();
+        store
+            .subscribe("digest", "law-amended", "apps/digest.wasm")
+            .unwrap();
+        store
+            .subscribe("digest", "law-revoked", "apps/digest.wasm")
+            .unwrap();
+
+        let first = store
+            .append_event(
+                "law-amended",
+                b"{\"law\":\"UK_ukpga_1974_37\"}",
+                "regulatory-monitor",
+            )
+            .unwrap();
+        let second = store
+            .append_event("law-revoked", &[], "regulatory-monitor")
+            .unwrap();
+        assert!(second > first);
+
+        let due = store.due_deliveries(10).unwrap();
+        let pairs: Vec<(u64, &str)> = due
+            .iter()
+            .map(|d| (d.event_id, d.subscriber.as_str()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (first, "digest"),
+                (first, "site-notifier"),
+                (second, "digest")
+            ]
+        );
+        assert_eq!(due[0].payload, b"{\"law\":\"UK_ukpga_1974_37\"}");
+        assert_eq!(due[0].component, "apps/digest.wasm");
+        assert!(due[2].payload.is_empty());
+
+        store.mark_delivered(first, "digest").unwrap();
+        assert_eq!(store.due_deliveries(10).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn failed_deliveries_retry_then_dead_letter() {
+        let store = DuckStore::open().unwrap();
+        store
+            .subscribe("digest", "law-amended", "digest.wasm")
+            .unwrap();
+        let id = store.append_event("law-amended", b"x", "monitor").unwrap();
+
+        // A retry scheduled in the future isn't due yet.
+        store
+            .record_delivery_failure(id, "digest", "trap: it's broken", 3600, false)
+            .unwrap();
+        assert!(store.due_deliveries(10).unwrap().is_empty());
+
+        store
+            .record_delivery_failure(id, "digest", "trap again", 0, true)
+            .unwrap();
+        let dead = store.dead_letters(10).unwrap();
+        assert_eq!(dead.iter().map(|b| b.num_rows()).sum::<usize>(), 1);
+        assert_eq!(string_col(&dead[0], "last_error"), vec!["trap again"]);
+
+        store.requeue_dead_letter(id, "digest").unwrap();
+        let due = store.due_deliveries(10).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].attempts, 0);
+        assert!(store.requeue_dead_letter(id, "digest").is_err());
+    }
+
+    #[test]
+    fn unsubscribe_drops_pending_deliveries() {
+        let store = DuckStore::open().unwrap();
+        store
+            .subscribe("digest", "law-amended", "digest.wasm")
+            .unwrap();
+        store.append_event("law-amended", b"x", "monitor").unwrap();
+
+        assert!(store.unsubscribe("digest", "law-amended").unwrap());
+        assert!(!store.unsubscribe("digest", "law-amended").unwrap());
+        assert!(store.due_deliveries(10).unwrap().is_empty());
+        assert!(store.subscriptions().unwrap().is_empty());
+    }
+
+    fn task(name: &str, cron: Option<&str>, run_at: Option<u64>) -> fractalaw_core::ScheduledTask {
+        fractalaw_core::ScheduledTask {
+            id: 0,
+            name: name.into(),
+            cron: cron.map(Into::into),
+            run_at,
+            target_app: "drrp-polisher".into(),
+            payload: b"{\"limit\":50}".to_vec(),
+            owner_app: "cli".into(),
+        }
+    }
+
+    #[test]
+    fn scheduled_tasks_come_due_and_finish() {
+        let store = DuckStore::open().unwrap();
+        let nightly = store
+            .insert_scheduled_task(&task("nightly", Some("0 2 * * *"), None), 1_000)
+            .unwrap();
+        let once = store
+            .insert_scheduled_task(&task("once", None, Some(500)), 500)
+            .unwrap();
+
+        assert!(store.due_tasks(499, 10).unwrap().is_empty());
+        let due = store.due_tasks(1_000, 10).unwrap();
+        let ids: Vec<u64> = due.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![once, nightly]);
+        assert_eq!(due[0].run_at, Some(500));
+        assert_eq!(due[1].cron.as_deref(), Some("0 2 * * *"));
+        assert_eq!(due[1].payload, b"{\"limit\":50}");
+
+        store
+            .record_task_run(once, 600, None, Some("trap"))
+            .unwrap();
+        store
+            .record_task_run(nightly, 1_000, Some(2_000), None)
+            .unwrap();
+        assert!(store.due_tasks(1_999, 10).unwrap().is_empty());
+        assert_eq!(store.due_tasks(2_000, 10).unwrap()[0].id, nightly);
+
+        let rows: usize = store
+            .scheduled_tasks(true)
+            .unwrap()
+            .iter()
+            .map(|b| b.num_rows())
+            .sum();
+        assert_eq!(rows, 2);
+        let active: usize = store
+            .scheduled_tasks(false)
+            .unwrap()
+            .iter()
+            .map(|b| b.num_rows())
+            .sum();
+        assert_eq!(active, 1);
+    }
+
+    #[test]
+    fn cancelled_tasks_never_run() {
+        let store = DuckStore::open().unwrap();
+        let id = store
+            .insert_scheduled_task(&task("once", None, Some(10)), 10)
+            .unwrap();
+        assert!(store.cancel_scheduled_task(id).unwrap());
+        assert!(!store.cancel_scheduled_task(id).unwrap());
+        assert!(store.due_tasks(i64::MAX, 10).unwrap().is_empty());
+        assert_eq!(store.scheduled_task(id).unwrap().unwrap().name, "once");
+        assert!(store.scheduled_task(id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn audit_log_chains_and_exposes_tampering() {
+        use fractalaw_core::ChainVerifier;
+
+        let store = DuckStore::open().unwrap();
+        let entry = |event_type: &str| {
+            let mut entry = AuditEntry::new("node-a", "hello-world", "micro-app", event_type);
+            entry.resource = Some("hello-world".into());
+            entry
+        };
+        store
+            .append_audit(vec![entry("capabilities-granted"), entry("app-started")])
+            .unwrap();
+        let stored = store.append_audit(vec![entry("app-finished")]).unwrap();
+        assert_eq!(stored[0].entry_id, 3);
+
+        let verify = |store: &DuckStore| {
+            let mut verifier = ChainVerifier::new();
+            for entry in store.audit_log().unwrap() {
+                verifier.check(&entry)?;
+            }
+            Ok::<u64, fractalaw_core::ChainBreak>(verifier.verified())
+        };
+        assert_eq!(verify(&store), Ok(3));
+        assert_eq!(store.audit_head().unwrap(), Some(stored[0].clone()));
+
+        store
+            .execute("UPDATE audit_log SET detail = 'nothing to see' WHERE entry_id = 2")
+            .unwrap();
+        assert_eq!(verify(&store).unwrap_err().entry_id, 3);
+    }
+
+    #[test]
+    fn checkpoints_sign_the_audit_head() {
+        use fractalaw_core::ChainVerifier;
+
+        let store = DuckStore::open().unwrap();
+        let (key, _) = NodeKey::generate().unwrap();
+        assert!(store.append_checkpoint(&key).unwrap().is_none());
+
+        let entry = AuditEntry::new(key.node_id(), "hello-world", "micro-app", "app-started");
+        store.append_audit(vec![entry.clone(), entry]).unwrap();
+        let checkpoint = store.append_checkpoint(&key).unwrap().unwrap();
+        assert_eq!(checkpoint.entry_id, 3);
+        assert!(checkpoint.signed_by(&key.public_key()));
+        // Nothing new to cover yet.
+        assert!(store.append_checkpoint(&key).unwrap().is_none());
+
+        let mut verifier = ChainVerifier::new().trust(key.public_key());
+        for entry in store.audit_log().unwrap() {
+            verifier.check(&entry).unwrap();
+        }
+        assert_eq!(verifier.checkpoints(), 1);
+        assert_eq!(verifier.last_checkpoint(), Some(3));
+    }
 }
@@ -8,6 +8,9 @@ pub enum StoreError {
     #[error("no results for query")]
     NoResults,
 
+    #[error("embedding model mismatch: expected '{query}', legislation_text holds {stored:?}")]
+    EmbeddingModelMismatch { query: String, stored: Vec<String> },
+
     #[cfg(feature = "duckdb")]
     #[error("duckdb error: {0}")]
     DuckDb(#[from] ::duckdb::Error),
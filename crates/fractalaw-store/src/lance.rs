@@ -6,10 +6,19 @@
 
 use std::path::Path;
 
-use arrow::array::RecordBatchIterator;
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+
+use arrow::array::{
+    Array, ArrayRef, FixedSizeListArray, Float32Array, LargeStringArray, RecordBatchIterator,
+    StringArray, new_null_array,
+};
+use arrow::datatypes::{Field, Schema};
 use arrow::record_batch::RecordBatch;
 use futures::TryStreamExt;
-use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::database::CreateTableMode;
+use lancedb::index::{Index, IndexType};
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
 use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
 use tracing::info;
 
@@ -18,6 +27,23 @@ use crate::StoreError;
 const LEGISLATION_TEXT_TABLE: &str = "legislation_text";
 const AMENDMENT_ANNOTATIONS_TABLE: &str = "amendment_annotations";
 
+/// Shadow column holding re-embedded vectors during a model migration.
+pub const SHADOW_EMBEDDING: &str = "embedding_next";
+/// Model that produced the [`SHADOW_EMBEDDING`] vectors.
+pub const SHADOW_EMBEDDING_MODEL: &str = "embedding_model_next";
+/// Shadow column holding token IDs from the new model's tokenizer.
+pub const SHADOW_TOKEN_IDS: &str = "token_ids_next";
+/// Tokenizer that produced the [`SHADOW_TOKEN_IDS`].
+pub const SHADOW_TOKENIZER_MODEL: &str = "tokenizer_model_next";
+
+/// Live columns and the shadow columns that replace them on a swap.
+const SHADOW_COLUMNS: [(&str, &str); 4] = [
+    ("embedding", SHADOW_EMBEDDING),
+    ("embedding_model", SHADOW_EMBEDDING_MODEL),
+    ("token_ids", SHADOW_TOKEN_IDS),
+    ("tokenizer_model", SHADOW_TOKENIZER_MODEL),
+];
+
 /// LanceDB store for the semantic path (legislation text + annotations).
 ///
 /// Manages two Lance tables:
@@ -25,6 +51,9 @@ const AMENDMENT_ANNOTATIONS_TABLE: &str = "amendment_annotations";
 /// - `amendment_annotations`: 19K amendment footnotes linked to text sections
 pub struct LanceStore {
     db: lancedb::Connection,
+    /// `embedding_model` values of `legislation_text` and the table version
+    /// they were read at.
+    models: Mutex<Option<(u64, Vec<String>)>>,
 }
 
 impl LanceStore {
@@ -36,7 +65,10 @@ impl LanceStore {
             .to_str()
             .ok_or_else(|| StoreError::Other("non-UTF8 database path".into()))?;
         let db = lancedb::connect(uri).execute().await?;
-        Ok(Self { db })
+        Ok(Self {
+            db,
+            models: Mutex::new(None),
+        })
     }
 
     /// Create (or replace) the `legislation_text` table from a Parquet file.
@@ -93,15 +125,65 @@ impl LanceStore {
         Ok(count)
     }
 
+    /// Distinct `embedding_model` values in `legislation_text`, sorted.
+    ///
+    /// Rows that have not been embedded (NULL model) are ignored. The column
+    /// is scanned once per table version; later calls reuse the result until
+    /// the table changes.
+    pub async fn embedding_models(&self) -> Result<Vec<String>, StoreError> {
+        let table = self.legislation_text().await?;
+        let version = table.version().await?;
+        if let Some((read_at, models)) = &*self.models.lock().unwrap()
+            && *read_at == version
+        {
+            return Ok(models.clone());
+        }
+
+        let batches: Vec<RecordBatch> = table
+            .query()
+            .select(Select::Columns(vec!["embedding_model".to_string()]))
+            .execute()
+            .await?
+            .try_collect()
+            .await?;
+
+        let mut models = BTreeSet::new();
+        for batch in &batches {
+            let col = batch
+                .column_by_name("embedding_model")
+                .ok_or_else(|| StoreError::Other("missing 'embedding_model' column".into()))?;
+            for row in 0..batch.num_rows() {
+                if let Some(model) = string_value(col.as_ref(), row) {
+                    models.insert(model.to_string());
+                }
+            }
+        }
+        let models: Vec<String> = models.into_iter().collect();
+        *self.models.lock().unwrap() = Some((version, models.clone()));
+        Ok(models)
+    }
+
     /// Vector similarity search on the `legislation_text` embedding column.
     ///
     /// Returns the nearest `limit` rows to the query vector, ordered by distance.
-    /// Requires embeddings to have been populated (Task 4).
+    /// `model` names the embedding model that produced `query_vector`; the search
+    /// is refused with [`StoreError::EmbeddingModelMismatch`] unless every
+    /// embedded row comes from that model, since distances between vectors of
+    /// different models are meaningless.
     pub async fn search_text(
         &self,
         query_vector: &[f32],
+        model: &str,
         limit: usize,
     ) -> Result<Vec<RecordBatch>, StoreError> {
+        let stored = self.embedding_models().await?;
+        if stored.iter().any(|m| m != model) {
+            return Err(StoreError::EmbeddingModelMismatch {
+                query: model.to_string(),
+                stored,
+            });
+        }
+
         let table = self.legislation_text().await?;
         let results: Vec<RecordBatch> = table
             .vector_search(query_vector)?
@@ -157,6 +239,8 @@ impl LanceStore {
         if existing.contains(&table_name.to_string()) {
             self.db.drop_table(table_name, &[]).await?;
         }
+        // A recreated table starts again at version 1.
+        self.models.lock().unwrap().take();
 
         self.db
             .create_table(table_name, Box::new(reader))
@@ -171,8 +255,116 @@ impl LanceStore {
         Ok(())
     }
 
+    // ── Embedding model migration ──
+
+    /// Read every row of the `legislation_text` table.
+    pub async fn read_legislation_text(&self) -> Result<Vec<RecordBatch>, StoreError> {
+        let table = self.legislation_text().await?;
+        let batches: Vec<RecordBatch> = table.query().execute().await?.try_collect().await?;
+        Ok(batches)
+    }
+
+    /// Stage re-embedded vectors in the [`SHADOW_EMBEDDING`] and
+    /// [`SHADOW_EMBEDDING_MODEL`] columns of `legislation_text`, with the
+    /// new tokenizer's output in [`SHADOW_TOKEN_IDS`] and
+    /// [`SHADOW_TOKENIZER_MODEL`].
+    ///
+    /// `batches` are full table rows with the shadow columns appended. Live
+    /// `embedding` values are untouched, so searches keep using the old model
+    /// until [`swap_shadow_embeddings`](Self::swap_shadow_embeddings).
+    pub async fn stage_shadow_embeddings(
+        &self,
+        batches: Vec<RecordBatch>,
+    ) -> Result<(), StoreError> {
+        for batch in &batches {
+            for col in [SHADOW_EMBEDDING, SHADOW_EMBEDDING_MODEL] {
+                if batch.column_by_name(col).is_none() {
+                    return Err(StoreError::Other(format!(
+                        "staged batch is missing shadow column '{col}'"
+                    )));
+                }
+            }
+        }
+        self.overwrite_table(LEGISLATION_TEXT_TABLE, batches).await
+    }
+
+    /// Validate staged shadow embeddings and promote them to the live columns.
+    ///
+    /// Fails without changing anything unless every row carries a finite,
+    /// non-zero shadow vector from `expected_model` (see
+    /// [`validate_shadow_embeddings`]). The swap is a single table overwrite,
+    /// so readers see either the old model or the new one, never a mix.
+    /// `token_ids` and `tokenizer_model` are swapped along with the vectors,
+    /// or cleared if nothing was staged for them. Returns the number of rows
+    /// swapped.
+    pub async fn swap_shadow_embeddings(&self, expected_model: &str) -> Result<usize, StoreError> {
+        let batches = self.read_legislation_text().await?;
+        let rows = validate_shadow_embeddings(&batches, expected_model)?;
+        let promoted = batches
+            .iter()
+            .map(promote_shadow_embeddings)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.overwrite_table(LEGISLATION_TEXT_TABLE, promoted)
+            .await?;
+        info!(rows, model = expected_model, "swapped shadow embeddings");
+        Ok(rows)
+    }
+
     // ── Internal ──
 
+    /// Replace a table's contents in one commit (unlike drop + create, which
+    /// leaves a window with no table at all).
+    ///
+    /// An overwrite discards the table's indices, so each is rebuilt on its
+    /// columns afterwards. If a rebuild fails the new contents stay, and the
+    /// error names the index to recreate.
+    async fn overwrite_table(
+        &self,
+        table_name: &str,
+        batches: Vec<RecordBatch>,
+    ) -> Result<(), StoreError> {
+        if batches.is_empty() {
+            return Err(StoreError::Other("no record batches provided".into()));
+        }
+        let existing = self.db.table_names().execute().await?;
+        let indices = if existing.contains(&table_name.to_string()) {
+            let table = self.db.open_table(table_name).execute().await?;
+            table.list_indices().await?
+        } else {
+            Vec::new()
+        };
+
+        let schema = batches[0].schema();
+        let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        let table = self
+            .db
+            .create_table(table_name, Box::new(reader))
+            .mode(CreateTableMode::Overwrite)
+            .execute()
+            .await?;
+
+        for index in indices {
+            let kind = match index.index_type {
+                IndexType::FTS => Index::FTS(Default::default()),
+                _ => Index::Auto,
+            };
+            table
+                .create_index(index.columns.as_slice(), kind)
+                .name(index.name.clone())
+                .execute()
+                .await
+                .map_err(|e| {
+                    StoreError::Other(format!(
+                        "{table_name} was rewritten but rebuilding index '{}' on {} failed: {e}",
+                        index.name,
+                        index.columns.join(", ")
+                    ))
+                })?;
+            info!(table = table_name, index = %index.name, "rebuilt index");
+        }
+        Ok(())
+    }
+
     async fn create_table_from_parquet(
         &self,
         table_name: &str,
@@ -199,6 +391,7 @@ impl LanceStore {
         if existing.contains(&table_name.to_string()) {
             self.db.drop_table(table_name, &[]).await?;
         }
+        self.models.lock().unwrap().take();
 
         self.db
             .create_table(table_name, Box::new(reader))
@@ -214,6 +407,133 @@ impl LanceStore {
     }
 }
 
+/// Check staged shadow embeddings before a swap; returns the number of rows checked.
+///
+/// Every row must have a non-null [`SHADOW_EMBEDDING`] of one dimension, with
+/// finite, non-zero values, and a [`SHADOW_EMBEDDING_MODEL`] equal to
+/// `expected_model`.
+pub fn validate_shadow_embeddings(
+    batches: &[RecordBatch],
+    expected_model: &str,
+) -> Result<usize, StoreError> {
+    let mut rows = 0;
+    let mut dim = None;
+
+    for batch in batches {
+        let (vectors, models) = shadow_columns(batch)?;
+        if *dim.get_or_insert(vectors.value_length()) != vectors.value_length() {
+            return Err(StoreError::Other(
+                "shadow embeddings have inconsistent dimensions".into(),
+            ));
+        }
+        let values = vectors
+            .values()
+            .as_any()
+            .downcast_ref::<Float32Array>()
+            .ok_or_else(|| StoreError::Other(format!("{SHADOW_EMBEDDING} is not Float32")))?;
+        let width = vectors.value_length() as usize;
+
+        for row in 0..batch.num_rows() {
+            if vectors.is_null(row) {
+                return Err(StoreError::Other(format!(
+                    "row {} has no staged embedding",
+                    rows + row
+                )));
+            }
+            let model = string_value(models.as_ref(), row);
+            if model != Some(expected_model) {
+                return Err(StoreError::EmbeddingModelMismatch {
+                    query: expected_model.to_string(),
+                    stored: model.map(str::to_string).into_iter().collect(),
+                });
+            }
+            let vector = &values.values()[row * width..(row + 1) * width];
+            if vector.iter().any(|v| !v.is_finite()) || vector.iter().all(|v| *v == 0.0) {
+                return Err(StoreError::Other(format!(
+                    "row {} has a degenerate staged embedding",
+                    rows + row
+                )));
+            }
+        }
+        rows += batch.num_rows();
+    }
+
+    if rows == 0 {
+        return Err(StoreError::Other("no staged embeddings to swap".into()));
+    }
+    Ok(rows)
+}
+
+/// The shadow vector and model columns of a staged batch.
+fn shadow_columns(batch: &RecordBatch) -> Result<(&FixedSizeListArray, &ArrayRef), StoreError> {
+    let vectors = batch
+        .column_by_name(SHADOW_EMBEDDING)
+        .ok_or_else(|| StoreError::Other(format!("no staged '{SHADOW_EMBEDDING}' column")))?
+        .as_any()
+        .downcast_ref::<FixedSizeListArray>()
+        .ok_or_else(|| StoreError::Other(format!("{SHADOW_EMBEDDING} is not FixedSizeList")))?;
+    let models = batch
+        .column_by_name(SHADOW_EMBEDDING_MODEL)
+        .ok_or_else(|| StoreError::Other(format!("no staged '{SHADOW_EMBEDDING_MODEL}' column")))?;
+    Ok((vectors, models))
+}
+
+/// Move the shadow columns into their live columns and drop them.
+///
+/// A live token column with no staged replacement is cleared rather than
+/// left describing the old model's tokenizer.
+fn promote_shadow_embeddings(batch: &RecordBatch) -> Result<RecordBatch, StoreError> {
+    let schema = batch.schema();
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns = Vec::with_capacity(schema.fields().len());
+
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        let name = field.name().as_str();
+        if SHADOW_COLUMNS.iter().any(|&(_, shadow)| shadow == name) {
+            continue;
+        }
+        let Some(&(_, shadow)) = SHADOW_COLUMNS.iter().find(|&&(live, _)| live == name) else {
+            fields.push(field.as_ref().clone());
+            columns.push(column.clone());
+            continue;
+        };
+        match schema.index_of(shadow) {
+            // The new model may have a different dimension, so take the shadow type.
+            Ok(shadow_idx) => {
+                fields.push(Field::new(
+                    name,
+                    schema.field(shadow_idx).data_type().clone(),
+                    true,
+                ));
+                columns.push(batch.column(shadow_idx).clone());
+            }
+            Err(_) => {
+                fields.push(field.as_ref().clone().with_nullable(true));
+                columns.push(new_null_array(field.data_type(), batch.num_rows()));
+            }
+        }
+    }
+
+    Ok(RecordBatch::try_new(
+        Arc::new(Schema::new(fields)),
+        columns,
+    )?)
+}
+
+fn string_value(col: &dyn Array, row: usize) -> Option<&str> {
+    if col.is_null(row) {
+        return None;
+    }
+    col.as_any()
+        .downcast_ref::<StringArray>()
+        .map(|arr| arr.value(row))
+        .or_else(|| {
+            col.as_any()
+                .downcast_ref::<LargeStringArray>()
+                .map(|arr| arr.value(row))
+        })
+}
+
 /// Read a Parquet file into Arrow RecordBatches.
 pub fn read_parquet(path: &Path) -> Result<Vec<RecordBatch>, StoreError> {
     let file = std::fs::File::open(path)?;
@@ -369,6 +689,191 @@ mod tests {
         assert_eq!(count1, count2);
     }
 
+    /// Two rows embedded with `old` (2-dim), staged with `new` (3-dim).
+    /// `staged` controls whether the second row has a shadow vector.
+    fn staged_batch(staged: bool) -> RecordBatch {
+        use arrow::array::{FixedSizeListBuilder, Float32Builder};
+        use arrow::datatypes::DataType;
+
+        let vectors = |dim: i32, rows: &[Option<Vec<f32>>]| {
+            let mut builder = FixedSizeListBuilder::new(Float32Builder::new(), dim);
+            for row in rows {
+                match row {
+                    Some(v) => builder.values().append_slice(v),
+                    None => builder.values().append_slice(&vec![0.0; dim as usize]),
+                }
+                builder.append(row.is_some());
+            }
+            Arc::new(builder.finish()) as ArrayRef
+        };
+        let fsl = |dim| {
+            DataType::FixedSizeList(Arc::new(Field::new("item", DataType::Float32, true)), dim)
+        };
+        let schema = Schema::new(vec![
+            Field::new("section_id", DataType::Utf8, false),
+            Field::new("embedding", fsl(2), true),
+            Field::new("embedding_model", DataType::Utf8, true),
+            Field::new(SHADOW_EMBEDDING, fsl(3), true),
+            Field::new(SHADOW_EMBEDDING_MODEL, DataType::Utf8, true),
+        ]);
+        let second = staged.then(|| vec![0.0, 1.0, 0.0]);
+        RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(StringArray::from(vec!["law:s.1", "law:s.2"])),
+                vectors(2, &[Some(vec![1.0, 0.0]), Some(vec![0.0, 1.0])]),
+                Arc::new(StringArray::from(vec!["old", "old"])),
+                vectors(3, &[Some(vec![1.0, 0.0, 0.0]), second.clone()]),
+                Arc::new(StringArray::from(vec![Some("new"), second.map(|_| "new")])),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn swap_promotes_shadow_embeddings() {
+        let tmp = TempDir::new().unwrap();
+        let store = LanceStore::open(&tmp.path().join("test_lancedb"))
+            .await
+            .unwrap();
+        store
+            .stage_shadow_embeddings(vec![staged_batch(true)])
+            .await
+            .unwrap();
+        assert_eq!(store.embedding_models().await.unwrap(), vec!["old"]);
+
+        // Queries from the new model are refused until the swap.
+        let refused = store.search_text(&[1.0, 0.0, 0.0], "new", 1).await;
+        assert!(matches!(
+            refused,
+            Err(StoreError::EmbeddingModelMismatch { .. })
+        ));
+
+        assert_eq!(store.swap_shadow_embeddings("new").await.unwrap(), 2);
+        assert_eq!(store.embedding_models().await.unwrap(), vec!["new"]);
+
+        let table = store.legislation_text().await.unwrap();
+        let schema = table.schema().await.unwrap();
+        assert!(schema.field_with_name(SHADOW_EMBEDDING).is_err());
+        assert!(schema.field_with_name(SHADOW_EMBEDDING_MODEL).is_err());
+
+        let results = store.search_text(&[0.0, 1.0, 0.0], "new", 1).await.unwrap();
+        let rows: usize = results.iter().map(|b| b.num_rows()).sum();
+        assert_eq!(rows, 1);
+    }
+
+    /// `batch` with live token columns from `old` and, if `staged`, shadow
+    /// token columns from `new`.
+    fn with_token_columns(batch: RecordBatch, staged: bool) -> RecordBatch {
+        use arrow::array::{ListBuilder, UInt32Builder};
+        use arrow::datatypes::DataType;
+
+        let tokens = |rows: &[&[u32]]| {
+            let mut builder = ListBuilder::new(UInt32Builder::new()).with_field(Field::new(
+                "item",
+                DataType::UInt32,
+                false,
+            ));
+            for row in rows {
+                builder.values().append_slice(row);
+                builder.append(true);
+            }
+            Arc::new(builder.finish()) as ArrayRef
+        };
+        let list = DataType::List(Arc::new(Field::new("item", DataType::UInt32, false)));
+        let schema = batch.schema();
+        let mut fields: Vec<Field> = schema.fields().iter().map(|f| f.as_ref().clone()).collect();
+        let mut columns = batch.columns().to_vec();
+        fields.push(Field::new("token_ids", list.clone(), true));
+        columns.push(tokens(&[&[1, 2], &[3]]));
+        fields.push(Field::new("tokenizer_model", DataType::Utf8, true));
+        columns.push(Arc::new(StringArray::from(vec!["old", "old"])));
+        if staged {
+            fields.push(Field::new(SHADOW_TOKEN_IDS, list, true));
+            columns.push(tokens(&[&[7], &[8, 9]]));
+            fields.push(Field::new(SHADOW_TOKENIZER_MODEL, DataType::Utf8, true));
+            columns.push(Arc::new(StringArray::from(vec!["new", "new"])));
+        }
+        RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).unwrap()
+    }
+
+    #[tokio::test]
+    async fn swap_replaces_token_columns_and_keeps_indices() {
+        for staged in [true, false] {
+            let tmp = TempDir::new().unwrap();
+            let store = LanceStore::open(&tmp.path().join("test_lancedb"))
+                .await
+                .unwrap();
+            store
+                .stage_shadow_embeddings(vec![with_token_columns(staged_batch(true), staged)])
+                .await
+                .unwrap();
+            let table = store.legislation_text().await.unwrap();
+            table
+                .create_index(&["section_id"], Index::Auto)
+                .execute()
+                .await
+                .unwrap();
+
+            store.swap_shadow_embeddings("new").await.unwrap();
+
+            let table = store.legislation_text().await.unwrap();
+            let indices = table.list_indices().await.unwrap();
+            assert_eq!(indices.len(), 1);
+            assert_eq!(indices[0].columns, vec!["section_id"]);
+
+            let batches = store.read_legislation_text().await.unwrap();
+            let batch = &batches[0];
+            assert!(batch.column_by_name(SHADOW_TOKEN_IDS).is_none());
+            assert!(batch.column_by_name(SHADOW_TOKENIZER_MODEL).is_none());
+            let tokenizer = batch.column_by_name("tokenizer_model").unwrap();
+            let token_ids = batch.column_by_name("token_ids").unwrap();
+            if staged {
+                assert_eq!(string_value(tokenizer.as_ref(), 0), Some("new"));
+                assert_eq!(token_ids.null_count(), 0);
+            } else {
+                // Nothing staged for the tokenizer: the old model's IDs go.
+                assert_eq!(tokenizer.null_count(), 2);
+                assert_eq!(token_ids.null_count(), 2);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn embedding_models_follow_table_versions() {
+        let tmp = TempDir::new().unwrap();
+        let store = LanceStore::open(&tmp.path().join("test_lancedb"))
+            .await
+            .unwrap();
+        store
+            .stage_shadow_embeddings(vec![staged_batch(true)])
+            .await
+            .unwrap();
+        assert_eq!(store.embedding_models().await.unwrap(), vec!["old"]);
+        assert!(store.models.lock().unwrap().is_some());
+        // Cached for this version, refreshed once the swap writes a new one.
+        assert_eq!(store.embedding_models().await.unwrap(), vec!["old"]);
+        store.swap_shadow_embeddings("new").await.unwrap();
+        assert_eq!(store.embedding_models().await.unwrap(), vec!["new"]);
+    }
+
+    #[tokio::test]
+    async fn swap_rejects_incomplete_shadow() {
+        let tmp = TempDir::new().unwrap();
+        let store = LanceStore::open(&tmp.path().join("test_lancedb"))
+            .await
+            .unwrap();
+        store
+            .stage_shadow_embeddings(vec![staged_batch(false)])
+            .await
+            .unwrap();
+
+        assert!(store.swap_shadow_embeddings("new").await.is_err());
+        assert!(store.swap_shadow_embeddings("other").await.is_err());
+        // Nothing was swapped.
+        assert_eq!(store.embedding_models().await.unwrap(), vec!["old"]);
+    }
+
     #[tokio::test]
     async fn legislation_text_schema_has_expected_columns() {
         let dir = require_lat_data();
@@ -11,7 +11,10 @@ pub use duck::DuckStore;
 #[cfg(feature = "lancedb")]
 mod lance;
 #[cfg(feature = "lancedb")]
-pub use lance::{LanceStore, read_parquet};
+pub use lance::{
+    LanceStore, SHADOW_EMBEDDING, SHADOW_EMBEDDING_MODEL, SHADOW_TOKEN_IDS, SHADOW_TOKENIZER_MODEL,
+    read_parquet, validate_shadow_embeddings,
+};
 
 #[cfg(all(feature = "duckdb", feature = "datafusion"))]
 mod fusion;
@@ -123,7 +123,9 @@ mod tests {
             qualifier: Some("so far as is reasonably practicable".into()),
             clause_ref: "s.2(1)".into(),
             confidence: 0.95,
+            model_confidence: Some(0.91),
             polished_at: "2026-02-21T13:00:00Z".into(),
+            provider: Some("anthropic".into()),
             model: "claude-sonnet-4-5-20250929".into(),
         };
         let json = serde_json::to_string(&entry).unwrap();
@@ -151,6 +153,8 @@ mod tests {
         }"#;
         let parsed: PolishedEntry = serde_json::from_str(json).unwrap();
         assert!(parsed.qualifier.is_none());
+        assert!(parsed.model_confidence.is_none());
+        assert!(parsed.provider.is_none());
     }
 
     #[test]
@@ -16,17 +16,28 @@ You are a legal provision extractor specialising in UK ESH (environment, safety,
 Given a section of legislation text and a DRRP type (duty, right, responsibility, or power), \
 extract the precise provision.
 
-Respond ONLY with a JSON object. No markdown fences, no explanation, just raw JSON:
-{
-  \"holder\": \"the entity that holds this duty/right/responsibility/power\",
-  \"text\": \"the exact provision text, quoted from the source\",
-  \"qualifier\": \"any qualifying phrase (e.g. 'so far as is reasonably practicable')\" or null,
-  \"clause_ref\": \"the specific clause reference (e.g. 's.2(1)')\"
-}
+Respond with a JSON object giving:
+- holder: the entity that holds this duty/right/responsibility/power
+- text: the exact provision text, quoted from the source
+- qualifier: any qualifying phrase (e.g. 'so far as is reasonably practicable'), or null
+- clause_ref: the specific clause reference (e.g. 's.2(1)')
 
 If the source text contains multiple provisions, extract the primary one that best matches the DRRP type.
 If you cannot identify a clear provision, set holder to \"unknown\" and text to the most relevant sentence.";
 
+/// JSON Schema for [`PolishedEntry`]; the host validates replies against it.
+const POLISHED_SCHEMA: &str = r#"{
+  "type": "object",
+  "properties": {
+    "holder": { "type": "string", "minLength": 1 },
+    "text": { "type": "string", "minLength": 1 },
+    "qualifier": { "type": ["string", "null"] },
+    "clause_ref": { "type": "string" }
+  },
+  "required": ["holder", "text", "qualifier", "clause_ref"],
+  "additionalProperties": false
+}"#;
+
 fn build_user_prompt(ann: &Annotation) -> String {
     format!(
         "Law: {law}\n\
@@ -63,6 +74,26 @@ struct PolishedEntry {
     clause_ref: String,
 }
 
+/// Tables drained in order: sertantai's synced annotations, then candidates
+/// queued locally by `fractalaw classify-sections --queue-candidates`.
+const QUEUES: [&str; 2] = ["drrp_annotations", "drrp_candidates"];
+
+/// `ai-error` code for an exhausted inference token budget.
+const TOKEN_BUDGET_EXHAUSTED: u32 = 5;
+
+/// Why an annotation wasn't polished.
+enum PolishError {
+    /// The run's token budget is spent; later annotations would fail too.
+    BudgetExhausted(String),
+    Failed(String),
+}
+
+impl From<String> for PolishError {
+    fn from(message: String) -> Self {
+        PolishError::Failed(message)
+    }
+}
+
 // ── Helpers ──
 
 fn audit(event_type: &str, detail: &str) {
@@ -115,6 +146,19 @@ impl Guest for DrrpPolisher {
             )",
         )?;
 
+        execute(
+            "CREATE TABLE IF NOT EXISTS drrp_candidates (
+                law_name       VARCHAR NOT NULL,
+                provision      VARCHAR NOT NULL,
+                drrp_type      VARCHAR NOT NULL,
+                source_text    VARCHAR NOT NULL,
+                confidence     FLOAT   NOT NULL,
+                scraped_at     TIMESTAMPTZ NOT NULL,
+                polished       BOOLEAN NOT NULL DEFAULT false,
+                queued_at      TIMESTAMPTZ NOT NULL
+            )",
+        )?;
+
         execute(
             "CREATE TABLE IF NOT EXISTS polished_drrp (
                 law_name       VARCHAR NOT NULL,
@@ -127,13 +171,22 @@ impl Guest for DrrpPolisher {
                 confidence     FLOAT   NOT NULL,
                 polished_at    TIMESTAMPTZ NOT NULL,
                 model          VARCHAR NOT NULL,
-                pushed         BOOLEAN NOT NULL DEFAULT false
+                pushed         BOOLEAN NOT NULL DEFAULT false,
+                model_confidence FLOAT,
+                provider       VARCHAR
             )",
         )?;
-
-        // 2. Count unpolished annotations.
-        let count =
-            query_i64("SELECT count(*)::BIGINT FROM drrp_annotations WHERE polished = false")?;
+        execute("ALTER TABLE polished_drrp ADD COLUMN IF NOT EXISTS model_confidence FLOAT")?;
+        execute("ALTER TABLE polished_drrp ADD COLUMN IF NOT EXISTS provider VARCHAR")?;
+
+        // 2. Count unpolished annotations in each queue.
+        let mut pending = Vec::with_capacity(QUEUES.len());
+        for table in QUEUES {
+            pending.push(query_i64(&format!(
+                "SELECT count(*)::BIGINT FROM {table} WHERE polished = false"
+            ))?);
+        }
+        let count: i64 = pending.iter().sum();
 
         if count == 0 {
             audit("batch-empty", "no unpolished annotations found");
@@ -146,30 +199,48 @@ impl Guest for DrrpPolisher {
         let mut polished = 0u64;
         let mut errors = 0u64;
         let mut total_tokens = 0u32;
-
-        for i in 0..count {
-            match process_one(i) {
-                Ok(tokens) => {
-                    polished += 1;
-                    total_tokens += tokens;
-                }
-                Err(e) => {
-                    audit("polish-error", &format!("annotation {i}: {e}"));
-                    errors += 1;
+        let mut skipped = 0i64;
+
+        'queues: for (table, pending) in QUEUES.into_iter().zip(pending) {
+            // A polished row leaves the unpolished set, so the next one takes
+            // its place; only a failed row stays ahead of the rest.
+            let mut offset = 0;
+            for i in 0..pending {
+                match process_one(table, offset) {
+                    Ok(tokens) => {
+                        polished += 1;
+                        total_tokens += tokens;
+                    }
+                    Err(PolishError::BudgetExhausted(e)) => {
+                        skipped = count - polished as i64 - errors as i64;
+                        audit("budget-exhausted", &format!("{table} {i}: {e}"));
+                        break 'queues;
+                    }
+                    Err(PolishError::Failed(e)) => {
+                        audit("polish-error", &format!("{table} {i}: {e}"));
+                        errors += 1;
+                        offset += 1;
+                    }
                 }
             }
         }
 
-        let summary = format!(
+        let mut summary = format!(
             "Polished {polished}/{count} annotations ({errors} errors, {total_tokens} tokens used)"
         );
+        if skipped > 0 {
+            summary.push_str(&format!(
+                "; token budget exhausted, {skipped} left for the next run"
+            ));
+        }
         audit("batch-complete", &summary);
         Ok(summary)
     }
 }
 
-/// Process a single unpolished annotation. Returns tokens used on success.
-fn process_one(offset: i64) -> Result<u32, String> {
+/// Process the unpolished annotation at `offset` in `table`, skipping the
+/// rows that failed earlier in the run. Returns tokens used on success.
+fn process_one(table: &str, offset: i64) -> Result<u32, PolishError> {
     // Query annotation as JSON via DuckDB's to_json + struct_pack.
     let json_str = query_string(&format!(
         "SELECT to_json(struct_pack(
@@ -178,33 +249,37 @@ fn process_one(offset: i64) -> Result<u32, String> {
             drrp_type := drrp_type,
             source_text := source_text,
             confidence := confidence
-        )) FROM drrp_annotations
+        )) FROM {table}
         WHERE polished = false
-        ORDER BY confidence DESC
+        ORDER BY confidence DESC, law_name, provision
         LIMIT 1 OFFSET {offset}"
     ))?;
 
     let ann: Annotation =
         serde_json::from_str(&json_str).map_err(|e| format!("parse annotation JSON: {e}"))?;
 
-    // Call Claude to extract the precise DRRP provision.
+    // Ask the configured model to extract the precise DRRP provision.
     let user_prompt = build_user_prompt(&ann);
     let response = ai_inference::generate(&ai_inference::GenerateRequest {
         system_prompt: Some(SYSTEM_PROMPT.to_string()),
         user_prompt,
         max_tokens: 1024,
         temperature: 0.0,
+        json_schema: Some(POLISHED_SCHEMA.to_string()),
     })
-    .map_err(|e| format!("inference: {} (code {})", e.message, e.code))?;
-
-    // Parse the structured JSON response from Claude.
-    let entry: PolishedEntry = serde_json::from_str(&response.text).map_err(|e| {
-        format!(
-            "parse Claude response: {e}\nraw: {}",
-            &response.text[..response.text.len().min(200)]
-        )
+    .map_err(|e| {
+        let message = format!("inference: {} (code {})", e.message, e.code);
+        if e.code == TOKEN_BUDGET_EXHAUSTED {
+            PolishError::BudgetExhausted(message)
+        } else {
+            PolishError::Failed(message)
+        }
     })?;
 
+    // The host has validated the reply against POLISHED_SCHEMA.
+    let entry: PolishedEntry =
+        serde_json::from_str(&response.text).map_err(|e| format!("parse polished entry: {e}"))?;
+
     // Insert polished result.
     let qualifier_sql = match &entry.qualifier {
         Some(q) => format!("'{}'", sql_escape(q)),
@@ -214,10 +289,11 @@ fn process_one(offset: i64) -> Result<u32, String> {
     execute(&format!(
         "INSERT INTO polished_drrp (
             law_name, provision, drrp_type, holder, text, qualifier,
-            clause_ref, confidence, polished_at, model, pushed
+            clause_ref, confidence, model_confidence, polished_at, provider, model, pushed
         ) VALUES (
             '{law_name}', '{provision}', '{drrp_type}', '{holder}', '{text}', {qualifier},
-            '{clause_ref}', {confidence}, CURRENT_TIMESTAMP, 'claude', false
+            '{clause_ref}', {confidence}, {model_confidence}, CURRENT_TIMESTAMP,
+            '{provider}', '{model}', false
         )",
         law_name = sql_escape(&ann.law_name),
         provision = sql_escape(&ann.provision),
@@ -226,12 +302,19 @@ fn process_one(offset: i64) -> Result<u32, String> {
         text = sql_escape(&entry.text),
         qualifier = qualifier_sql,
         clause_ref = sql_escape(&entry.clause_ref),
-        confidence = response.confidence,
+        // The regex scraper's; the model's own is NULL when the provider
+        // reports no log probabilities.
+        confidence = ann.confidence,
+        model_confidence = response
+            .confidence
+            .map_or_else(|| "NULL".to_string(), |c| c.to_string()),
+        provider = sql_escape(&response.provider),
+        model = sql_escape(&response.model),
     ))?;
 
     // Mark the source annotation as polished.
     execute(&format!(
-        "UPDATE drrp_annotations SET polished = true
+        "UPDATE {table} SET polished = true
          WHERE law_name = '{law_name}' AND provision = '{provision}' AND polished = false",
         law_name = sql_escape(&ann.law_name),
         provision = sql_escape(&ann.provision),
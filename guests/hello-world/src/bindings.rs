@@ -160,6 +160,9 @@ pub mod fractal {
             #[doc(hidden)]
             static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
             use super::super::super::_rt;
+            /// Codes: 1 no store attached, 2 invalid SQL or query failed, 3 encoding
+            /// failed, 403 statement or table not granted, 429 more tables than the
+            /// app's tier allows.
             #[derive(Clone)]
             pub struct QueryError {
                 pub code: u32,
@@ -186,6 +189,7 @@ pub mod fractal {
             }
             impl std::error::Error for QueryError {}
             #[allow(unused_unsafe, clippy::all)]
+            /// Run one query and return its rows as Arrow IPC.
             pub fn query(sql: &str) -> Result<_rt::Vec<u8>, QueryError> {
                 unsafe {
                     #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
@@ -265,6 +269,9 @@ pub mod fractal {
             #[doc(hidden)]
             static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
             use super::super::super::_rt;
+            /// Codes: 1 no store attached, 2 invalid SQL, decoding or statement
+            /// failed, 3 insert failed, 403 statement or table not granted, 429 more
+            /// tables than the app's tier allows.
             #[derive(Clone)]
             pub struct MutateError {
                 pub code: u32,
@@ -372,6 +379,7 @@ pub mod fractal {
                 }
             }
             #[allow(unused_unsafe, clippy::all)]
+            /// Run one statement and return the number of rows it changed.
             pub fn execute(sql: &str) -> Result<u64, MutateError> {
                 unsafe {
                     #[repr(align(8))]
@@ -443,6 +451,8 @@ pub mod fractal {
             #[doc(hidden)]
             static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
             use super::super::super::_rt;
+            /// Codes: 1 backend not configured, 2 backend failed, 3 invalid request or
+            /// unparseable reply, 4 fuel budget exceeded, 5 token budget exhausted.
             #[derive(Clone)]
             pub struct AiError {
                 pub code: u32,
@@ -666,6 +676,829 @@ pub mod fractal {
             }
         }
         #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod ai_classify {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            pub type AiError = super::super::super::fractal::app::ai_embeddings::AiError;
+            #[derive(Clone)]
+            pub struct Classification {
+                pub category: _rt::String,
+                pub score: f32,
+            }
+            impl ::core::fmt::Debug for Classification {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("Classification")
+                        .field("category", &self.category)
+                        .field("score", &self.score)
+                        .finish()
+                }
+            }
+            /// Centroid sets trained by `fractalaw classify`.
+            #[repr(u8)]
+            #[derive(Clone, Copy, Eq, Ord, PartialEq, PartialOrd)]
+            pub enum Taxonomy {
+                Family,
+                Domain,
+            }
+            impl ::core::fmt::Debug for Taxonomy {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    match self {
+                        Taxonomy::Family => f.debug_tuple("Taxonomy::Family").finish(),
+                        Taxonomy::Domain => f.debug_tuple("Taxonomy::Domain").finish(),
+                    }
+                }
+            }
+            impl Taxonomy {
+                #[doc(hidden)]
+                pub unsafe fn _lift(val: u8) -> Taxonomy {
+                    if !cfg!(debug_assertions) {
+                        return ::core::mem::transmute(val);
+                    }
+                    match val {
+                        0 => Taxonomy::Family,
+                        1 => Taxonomy::Domain,
+                        _ => panic!("invalid enum discriminant"),
+                    }
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Zero-shot: rank free-text category descriptions by similarity to `text`.
+            pub fn classify(
+                text: &str,
+                categories: &[_rt::String],
+            ) -> Result<_rt::Vec<Classification>, AiError> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 4 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 4
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = text;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let vec2 = categories;
+                    let len2 = vec2.len();
+                    let layout2 = _rt::alloc::Layout::from_size_align_unchecked(
+                        vec2.len() * (2 * ::core::mem::size_of::<*const u8>()),
+                        ::core::mem::size_of::<*const u8>(),
+                    );
+                    let result2 = if layout2.size() != 0 {
+                        let ptr = _rt::alloc::alloc(layout2).cast::<u8>();
+                        if ptr.is_null() {
+                            _rt::alloc::handle_alloc_error(layout2);
+                        }
+                        ptr
+                    } else {
+                        ::core::ptr::null_mut()
+                    };
+                    for (i, e) in vec2.into_iter().enumerate() {
+                        let base = result2
+                            .add(i * (2 * ::core::mem::size_of::<*const u8>()));
+                        {
+                            let vec1 = e;
+                            let ptr1 = vec1.as_ptr().cast::<u8>();
+                            let len1 = vec1.len();
+                            *base
+                                .add(::core::mem::size_of::<*const u8>())
+                                .cast::<usize>() = len1;
+                            *base.add(0).cast::<*mut u8>() = ptr1.cast_mut();
+                        }
+                    }
+                    let ptr3 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "fractal:app/ai-classify@0.1.0")]
+                    unsafe extern "C" {
+                        #[link_name = "classify"]
+                        fn wit_import4(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import4(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import4(ptr0.cast_mut(), len0, result2, len2, ptr3) };
+                    let l5 = i32::from(*ptr3.add(0).cast::<u8>());
+                    let result17 = match l5 {
+                        0 => {
+                            let e = {
+                                let l6 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l7 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let base12 = l6;
+                                let len12 = l7;
+                                let mut result12 = _rt::Vec::with_capacity(len12);
+                                for i in 0..len12 {
+                                    let base = base12
+                                        .add(i * (3 * ::core::mem::size_of::<*const u8>()));
+                                    let e12 = {
+                                        let l8 = *base.add(0).cast::<*mut u8>();
+                                        let l9 = *base
+                                            .add(::core::mem::size_of::<*const u8>())
+                                            .cast::<usize>();
+                                        let len10 = l9;
+                                        let bytes10 = _rt::Vec::from_raw_parts(
+                                            l8.cast(),
+                                            len10,
+                                            len10,
+                                        );
+                                        let l11 = *base
+                                            .add(2 * ::core::mem::size_of::<*const u8>())
+                                            .cast::<f32>();
+                                        Classification {
+                                            category: _rt::string_lift(bytes10),
+                                            score: l11,
+                                        }
+                                    };
+                                    result12.push(e12);
+                                }
+                                _rt::cabi_dealloc(
+                                    base12,
+                                    len12 * (3 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                );
+                                result12
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l13 = *ptr3
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<i32>();
+                                let l14 = *ptr3
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l15 = *ptr3
+                                    .add(3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len16 = l15;
+                                let bytes16 = _rt::Vec::from_raw_parts(
+                                    l14.cast(),
+                                    len16,
+                                    len16,
+                                );
+                                super::super::super::fractal::app::ai_embeddings::AiError {
+                                    code: l13 as u32,
+                                    message: _rt::string_lift(bytes16),
+                                }
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    if layout2.size() != 0 {
+                        _rt::alloc::dealloc(result2.cast(), layout2);
+                    }
+                    result17
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Rank the trained family or domain centroids by similarity to `text`.
+            pub fn classify_trained(
+                text: &str,
+                taxonomy: Taxonomy,
+            ) -> Result<_rt::Vec<Classification>, AiError> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 4 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 4
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let vec0 = text;
+                    let ptr0 = vec0.as_ptr().cast::<u8>();
+                    let len0 = vec0.len();
+                    let ptr1 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "fractal:app/ai-classify@0.1.0")]
+                    unsafe extern "C" {
+                        #[link_name = "classify-trained"]
+                        fn wit_import2(_: *mut u8, _: usize, _: i32, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import2(
+                        _: *mut u8,
+                        _: usize,
+                        _: i32,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import2(ptr0.cast_mut(), len0, taxonomy.clone() as i32, ptr1)
+                    };
+                    let l3 = i32::from(*ptr1.add(0).cast::<u8>());
+                    let result15 = match l3 {
+                        0 => {
+                            let e = {
+                                let l4 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l5 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let base10 = l4;
+                                let len10 = l5;
+                                let mut result10 = _rt::Vec::with_capacity(len10);
+                                for i in 0..len10 {
+                                    let base = base10
+                                        .add(i * (3 * ::core::mem::size_of::<*const u8>()));
+                                    let e10 = {
+                                        let l6 = *base.add(0).cast::<*mut u8>();
+                                        let l7 = *base
+                                            .add(::core::mem::size_of::<*const u8>())
+                                            .cast::<usize>();
+                                        let len8 = l7;
+                                        let bytes8 = _rt::Vec::from_raw_parts(
+                                            l6.cast(),
+                                            len8,
+                                            len8,
+                                        );
+                                        let l9 = *base
+                                            .add(2 * ::core::mem::size_of::<*const u8>())
+                                            .cast::<f32>();
+                                        Classification {
+                                            category: _rt::string_lift(bytes8),
+                                            score: l9,
+                                        }
+                                    };
+                                    result10.push(e10);
+                                }
+                                _rt::cabi_dealloc(
+                                    base10,
+                                    len10 * (3 * ::core::mem::size_of::<*const u8>()),
+                                    ::core::mem::size_of::<*const u8>(),
+                                );
+                                result10
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l11 = *ptr1
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<i32>();
+                                let l12 = *ptr1
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l13 = *ptr1
+                                    .add(3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len14 = l13;
+                                let bytes14 = _rt::Vec::from_raw_parts(
+                                    l12.cast(),
+                                    len14,
+                                    len14,
+                                );
+                                super::super::super::fractal::app::ai_embeddings::AiError {
+                                    code: l11 as u32,
+                                    message: _rt::string_lift(bytes14),
+                                }
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result15
+                }
+            }
+        }
+        /// --- Events (Phase 3 Session 4) ---
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod events_emit {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            #[derive(Clone)]
+            pub struct EventError {
+                pub code: u32,
+                pub message: _rt::String,
+            }
+            impl ::core::fmt::Debug for EventError {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("EventError")
+                        .field("code", &self.code)
+                        .field("message", &self.message)
+                        .finish()
+                }
+            }
+            impl ::core::fmt::Display for EventError {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    write!(f, "{:?}", self)
+                }
+            }
+            impl std::error::Error for EventError {}
+            #[derive(Clone)]
+            pub struct DomainEvent {
+                pub event_type: _rt::String,
+                pub payload: _rt::Vec<u8>,
+                pub source_app: _rt::String,
+            }
+            impl ::core::fmt::Debug for DomainEvent {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("DomainEvent")
+                        .field("event-type", &self.event_type)
+                        .field("payload", &self.payload)
+                        .field("source-app", &self.source_app)
+                        .finish()
+                }
+            }
+            pub type EventId = u64;
+            #[allow(unused_unsafe, clippy::all)]
+            /// Append `event` to the host's durable event log and queue it for every
+            /// app subscribed to its type. `source-app` may be left empty; the host
+            /// fills in the emitting app and rejects any other name.
+            pub fn emit(event: &DomainEvent) -> Result<EventId, EventError> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 16 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 16
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let DomainEvent {
+                        event_type: event_type0,
+                        payload: payload0,
+                        source_app: source_app0,
+                    } = event;
+                    let vec1 = event_type0;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let vec2 = payload0;
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    let vec3 = source_app0;
+                    let ptr3 = vec3.as_ptr().cast::<u8>();
+                    let len3 = vec3.len();
+                    let ptr4 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "fractal:app/events-emit@0.1.0")]
+                    unsafe extern "C" {
+                        #[link_name = "emit"]
+                        fn wit_import5(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import5(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import5(
+                            ptr1.cast_mut(),
+                            len1,
+                            ptr2.cast_mut(),
+                            len2,
+                            ptr3.cast_mut(),
+                            len3,
+                            ptr4,
+                        )
+                    };
+                    let l6 = i32::from(*ptr4.add(0).cast::<u8>());
+                    let result12 = match l6 {
+                        0 => {
+                            let e = {
+                                let l7 = *ptr4.add(8).cast::<i64>();
+                                l7 as u64
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l8 = *ptr4.add(8).cast::<i32>();
+                                let l9 = *ptr4
+                                    .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l10 = *ptr4
+                                    .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len11 = l10;
+                                let bytes11 = _rt::Vec::from_raw_parts(
+                                    l9.cast(),
+                                    len11,
+                                    len11,
+                                );
+                                EventError {
+                                    code: l8 as u32,
+                                    message: _rt::string_lift(bytes11),
+                                }
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result12
+                }
+            }
+        }
+        /// Runs triggered by a subscription see the event that triggered them.
+        /// Delivery is at-least-once: a run that fails is retried, so handlers
+        /// should be idempotent.
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod events_receive {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            pub type DomainEvent = super::super::super::fractal::app::events_emit::DomainEvent;
+            pub type EventId = super::super::super::fractal::app::events_emit::EventId;
+            #[derive(Clone)]
+            pub struct Delivery {
+                pub id: EventId,
+                pub event: DomainEvent,
+                /// 1 on first delivery; higher when retried after a failed run.
+                pub attempt: u32,
+            }
+            impl ::core::fmt::Debug for Delivery {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("Delivery")
+                        .field("id", &self.id)
+                        .field("event", &self.event)
+                        .field("attempt", &self.attempt)
+                        .finish()
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// The event this run is handling, or none for a run not started by one.
+            pub fn trigger() -> Option<Delivery> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 24 + 6 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 24
+                            + 6 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "fractal:app/events-receive@0.1.0")]
+                    unsafe extern "C" {
+                        #[link_name = "trigger"]
+                        fn wit_import1(_: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(ptr0) };
+                    let l2 = i32::from(*ptr0.add(0).cast::<u8>());
+                    let result14 = match l2 {
+                        0 => None,
+                        1 => {
+                            let e = {
+                                let l3 = *ptr0.add(8).cast::<i64>();
+                                let l4 = *ptr0.add(16).cast::<*mut u8>();
+                                let l5 = *ptr0
+                                    .add(16 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len6 = l5;
+                                let bytes6 = _rt::Vec::from_raw_parts(
+                                    l4.cast(),
+                                    len6,
+                                    len6,
+                                );
+                                let l7 = *ptr0
+                                    .add(16 + 2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l8 = *ptr0
+                                    .add(16 + 3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len9 = l8;
+                                let l10 = *ptr0
+                                    .add(16 + 4 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l11 = *ptr0
+                                    .add(16 + 5 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len12 = l11;
+                                let bytes12 = _rt::Vec::from_raw_parts(
+                                    l10.cast(),
+                                    len12,
+                                    len12,
+                                );
+                                let l13 = *ptr0
+                                    .add(16 + 6 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<i32>();
+                                Delivery {
+                                    id: l3 as u64,
+                                    event: super::super::super::fractal::app::events_emit::DomainEvent {
+                                        event_type: _rt::string_lift(bytes6),
+                                        payload: _rt::Vec::from_raw_parts(l7.cast(), len9, len9),
+                                        source_app: _rt::string_lift(bytes12),
+                                    },
+                                    attempt: l13 as u32,
+                                }
+                            };
+                            Some(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result14
+                }
+            }
+        }
+        /// Runs of another app at a set time or on a recurring schedule. Tasks are
+        /// stored by the host and survive restarts. When a task fires, the target app
+        /// sees it through events-receive: `id` is the task id, the event type is
+        /// `schedule:<name>`, and the source app is the app that scheduled it.
+        /// An app may schedule itself, or apps its app-invoke grant allows it to call
+        /// (code 403 otherwise).
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod events_schedule {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            pub type EventError = super::super::super::fractal::app::events_emit::EventError;
+            #[derive(Clone)]
+            pub struct ScheduledTask {
+                pub name: _rt::String,
+                /// Five-field cron expression (minute hour day month weekday) in UTC,
+                /// or a shorthand such as `@daily`. Exactly one of `cron` and
+                /// `run-at` must be set.
+                pub cron: Option<_rt::String>,
+                /// One-shot run time in Unix seconds.
+                pub run_at: Option<u64>,
+                pub target_app: _rt::String,
+                pub payload: _rt::Vec<u8>,
+            }
+            impl ::core::fmt::Debug for ScheduledTask {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("ScheduledTask")
+                        .field("name", &self.name)
+                        .field("cron", &self.cron)
+                        .field("run-at", &self.run_at)
+                        .field("target-app", &self.target_app)
+                        .field("payload", &self.payload)
+                        .finish()
+                }
+            }
+            pub type TaskId = u64;
+            #[allow(unused_unsafe, clippy::all)]
+            pub fn schedule(task: &ScheduledTask) -> Result<TaskId, EventError> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 16 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 16
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ScheduledTask {
+                        name: name0,
+                        cron: cron0,
+                        run_at: run_at0,
+                        target_app: target_app0,
+                        payload: payload0,
+                    } = task;
+                    let vec1 = name0;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let (result3_0, result3_1, result3_2) = match cron0 {
+                        Some(e) => {
+                            let vec2 = e;
+                            let ptr2 = vec2.as_ptr().cast::<u8>();
+                            let len2 = vec2.len();
+                            (1i32, ptr2.cast_mut(), len2)
+                        }
+                        None => (0i32, ::core::ptr::null_mut(), 0usize),
+                    };
+                    let (result4_0, result4_1) = match run_at0 {
+                        Some(e) => (1i32, _rt::as_i64(e)),
+                        None => (0i32, 0i64),
+                    };
+                    let vec5 = target_app0;
+                    let ptr5 = vec5.as_ptr().cast::<u8>();
+                    let len5 = vec5.len();
+                    let vec6 = payload0;
+                    let ptr6 = vec6.as_ptr().cast::<u8>();
+                    let len6 = vec6.len();
+                    let ptr7 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "fractal:app/events-schedule@0.1.0")]
+                    unsafe extern "C" {
+                        #[link_name = "schedule"]
+                        fn wit_import8(
+                            _: *mut u8,
+                            _: usize,
+                            _: i32,
+                            _: *mut u8,
+                            _: usize,
+                            _: i32,
+                            _: i64,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import8(
+                        _: *mut u8,
+                        _: usize,
+                        _: i32,
+                        _: *mut u8,
+                        _: usize,
+                        _: i32,
+                        _: i64,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import8(
+                            ptr1.cast_mut(),
+                            len1,
+                            result3_0,
+                            result3_1,
+                            result3_2,
+                            result4_0,
+                            result4_1,
+                            ptr5.cast_mut(),
+                            len5,
+                            ptr6.cast_mut(),
+                            len6,
+                            ptr7,
+                        )
+                    };
+                    let l9 = i32::from(*ptr7.add(0).cast::<u8>());
+                    let result15 = match l9 {
+                        0 => {
+                            let e = {
+                                let l10 = *ptr7.add(8).cast::<i64>();
+                                l10 as u64
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l11 = *ptr7.add(8).cast::<i32>();
+                                let l12 = *ptr7
+                                    .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l13 = *ptr7
+                                    .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len14 = l13;
+                                let bytes14 = _rt::Vec::from_raw_parts(
+                                    l12.cast(),
+                                    len14,
+                                    len14,
+                                );
+                                super::super::super::fractal::app::events_emit::EventError {
+                                    code: l11 as u32,
+                                    message: _rt::string_lift(bytes14),
+                                }
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result15
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            /// Cancel a task this app scheduled.
+            pub fn cancel(id: TaskId) -> Result<(), EventError> {
+                unsafe {
+                    #[cfg_attr(target_pointer_width = "64", repr(align(8)))]
+                    #[cfg_attr(target_pointer_width = "32", repr(align(4)))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 4 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 4
+                            * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let ptr0 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "fractal:app/events-schedule@0.1.0")]
+                    unsafe extern "C" {
+                        #[link_name = "cancel"]
+                        fn wit_import1(_: i64, _: *mut u8);
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import1(_: i64, _: *mut u8) {
+                        unreachable!()
+                    }
+                    unsafe { wit_import1(_rt::as_i64(id), ptr0) };
+                    let l2 = i32::from(*ptr0.add(0).cast::<u8>());
+                    let result7 = match l2 {
+                        0 => {
+                            let e = ();
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l3 = *ptr0
+                                    .add(::core::mem::size_of::<*const u8>())
+                                    .cast::<i32>();
+                                let l4 = *ptr0
+                                    .add(2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l5 = *ptr0
+                                    .add(3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len6 = l5;
+                                let bytes6 = _rt::Vec::from_raw_parts(
+                                    l4.cast(),
+                                    len6,
+                                    len6,
+                                );
+                                super::super::super::fractal::app::events_emit::EventError {
+                                    code: l3 as u32,
+                                    message: _rt::string_lift(bytes6),
+                                }
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result7
+                }
+            }
+        }
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
         pub mod ai_inference {
             #[used]
             #[doc(hidden)]
@@ -678,6 +1511,9 @@ pub mod fractal {
                 pub user_prompt: _rt::String,
                 pub max_tokens: u32,
                 pub temperature: f32,
+                /// JSON Schema the reply must satisfy. When set, the host enforces it
+                /// and `generate-response.text` is schema-valid JSON.
+                pub json_schema: Option<_rt::String>,
             }
             impl ::core::fmt::Debug for GenerateRequest {
                 fn fmt(
@@ -689,14 +1525,45 @@ pub mod fractal {
                         .field("user-prompt", &self.user_prompt)
                         .field("max-tokens", &self.max_tokens)
                         .field("temperature", &self.temperature)
+                        .field("json-schema", &self.json_schema)
+                        .finish()
+                }
+            }
+            /// Tokens billed for a `generate` call, summed over any schema repairs.
+            #[repr(C)]
+            #[derive(Clone, Copy)]
+            pub struct TokenUsage {
+                /// Prompt tokens, including those read from the provider's prompt cache.
+                pub input_tokens: u32,
+                pub output_tokens: u32,
+                /// Prompt tokens read from the provider's prompt cache.
+                pub cached_tokens: u32,
+            }
+            impl ::core::fmt::Debug for TokenUsage {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("TokenUsage")
+                        .field("input-tokens", &self.input_tokens)
+                        .field("output-tokens", &self.output_tokens)
+                        .field("cached-tokens", &self.cached_tokens)
                         .finish()
                 }
             }
             #[derive(Clone)]
             pub struct GenerateResponse {
                 pub text: _rt::String,
+                /// Generated tokens; the same as `usage.output-tokens`.
                 pub tokens_used: u32,
-                pub confidence: f32,
+                pub usage: TokenUsage,
+                /// Geometric mean of the generated tokens' probabilities, from the
+                /// provider's log probabilities. None when the provider reports none.
+                pub confidence: Option<f32>,
+                /// Provider that generated the reply, e.g. `anthropic`, `openai` or `local`.
+                pub provider: _rt::String,
+                /// Model that generated the reply.
+                pub model: _rt::String,
             }
             impl ::core::fmt::Debug for GenerateResponse {
                 fn fmt(
@@ -706,7 +1573,10 @@ pub mod fractal {
                     f.debug_struct("GenerateResponse")
                         .field("text", &self.text)
                         .field("tokens-used", &self.tokens_used)
+                        .field("usage", &self.usage)
                         .field("confidence", &self.confidence)
+                        .field("provider", &self.provider)
+                        .field("model", &self.model)
                         .finish()
                 }
             }
@@ -720,17 +1590,18 @@ pub mod fractal {
                     struct RetArea(
                         [::core::mem::MaybeUninit<
                             u8,
-                        >; 8 + 3 * ::core::mem::size_of::<*const u8>()],
+                        >; 24 + 7 * ::core::mem::size_of::<*const u8>()],
                     );
                     let mut ret_area = RetArea(
-                        [::core::mem::MaybeUninit::uninit(); 8
-                            + 3 * ::core::mem::size_of::<*const u8>()],
+                        [::core::mem::MaybeUninit::uninit(); 24
+                            + 7 * ::core::mem::size_of::<*const u8>()],
                     );
                     let GenerateRequest {
                         system_prompt: system_prompt0,
                         user_prompt: user_prompt0,
                         max_tokens: max_tokens0,
                         temperature: temperature0,
+                        json_schema: json_schema0,
                     } = request;
                     let (result2_0, result2_1, result2_2) = match system_prompt0 {
                         Some(e) => {
@@ -744,12 +1615,21 @@ pub mod fractal {
                     let vec3 = user_prompt0;
                     let ptr3 = vec3.as_ptr().cast::<u8>();
                     let len3 = vec3.len();
-                    let ptr4 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    let (result5_0, result5_1, result5_2) = match json_schema0 {
+                        Some(e) => {
+                            let vec4 = e;
+                            let ptr4 = vec4.as_ptr().cast::<u8>();
+                            let len4 = vec4.len();
+                            (1i32, ptr4.cast_mut(), len4)
+                        }
+                        None => (0i32, ::core::ptr::null_mut(), 0usize),
+                    };
+                    let ptr6 = ret_area.0.as_mut_ptr().cast::<u8>();
                     #[cfg(target_arch = "wasm32")]
                     #[link(wasm_import_module = "fractal:app/ai-inference@0.1.0")]
                     unsafe extern "C" {
                         #[link_name = "generate"]
-                        fn wit_import5(
+                        fn wit_import7(
                             _: i32,
                             _: *mut u8,
                             _: usize,
@@ -757,11 +1637,14 @@ pub mod fractal {
                             _: usize,
                             _: i32,
                             _: f32,
+                            _: i32,
+                            _: *mut u8,
+                            _: usize,
                             _: *mut u8,
                         );
                     }
                     #[cfg(not(target_arch = "wasm32"))]
-                    unsafe extern "C" fn wit_import5(
+                    unsafe extern "C" fn wit_import7(
                         _: i32,
                         _: *mut u8,
                         _: usize,
@@ -769,12 +1652,15 @@ pub mod fractal {
                         _: usize,
                         _: i32,
                         _: f32,
+                        _: i32,
+                        _: *mut u8,
+                        _: usize,
                         _: *mut u8,
                     ) {
                         unreachable!()
                     }
                     unsafe {
-                        wit_import5(
+                        wit_import7(
                             result2_0,
                             result2_1,
                             result2_2,
@@ -782,66 +1668,316 @@ pub mod fractal {
                             len3,
                             _rt::as_i32(max_tokens0),
                             _rt::as_f32(temperature0),
-                            ptr4,
+                            result5_0,
+                            result5_1,
+                            result5_2,
+                            ptr6,
                         )
                     };
-                    let l6 = i32::from(*ptr4.add(0).cast::<u8>());
-                    let result16 = match l6 {
+                    let l8 = i32::from(*ptr6.add(0).cast::<u8>());
+                    let result28 = match l8 {
                         0 => {
                             let e = {
-                                let l7 = *ptr4
+                                let l9 = *ptr6
                                     .add(::core::mem::size_of::<*const u8>())
                                     .cast::<*mut u8>();
-                                let l8 = *ptr4
+                                let l10 = *ptr6
                                     .add(2 * ::core::mem::size_of::<*const u8>())
                                     .cast::<usize>();
-                                let len9 = l8;
-                                let bytes9 = _rt::Vec::from_raw_parts(
-                                    l7.cast(),
-                                    len9,
-                                    len9,
+                                let len11 = l10;
+                                let bytes11 = _rt::Vec::from_raw_parts(
+                                    l9.cast(),
+                                    len11,
+                                    len11,
                                 );
-                                let l10 = *ptr4
+                                let l12 = *ptr6
                                     .add(3 * ::core::mem::size_of::<*const u8>())
                                     .cast::<i32>();
-                                let l11 = *ptr4
+                                let l13 = *ptr6
                                     .add(4 + 3 * ::core::mem::size_of::<*const u8>())
-                                    .cast::<f32>();
+                                    .cast::<i32>();
+                                let l14 = *ptr6
+                                    .add(8 + 3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<i32>();
+                                let l15 = *ptr6
+                                    .add(12 + 3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<i32>();
+                                let l16 = i32::from(
+                                    *ptr6
+                                        .add(16 + 3 * ::core::mem::size_of::<*const u8>())
+                                        .cast::<u8>(),
+                                );
+                                let l18 = *ptr6
+                                    .add(24 + 3 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l19 = *ptr6
+                                    .add(24 + 4 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len20 = l19;
+                                let bytes20 = _rt::Vec::from_raw_parts(
+                                    l18.cast(),
+                                    len20,
+                                    len20,
+                                );
+                                let l21 = *ptr6
+                                    .add(24 + 5 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l22 = *ptr6
+                                    .add(24 + 6 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len23 = l22;
+                                let bytes23 = _rt::Vec::from_raw_parts(
+                                    l21.cast(),
+                                    len23,
+                                    len23,
+                                );
                                 GenerateResponse {
-                                    text: _rt::string_lift(bytes9),
-                                    tokens_used: l10 as u32,
-                                    confidence: l11,
+                                    text: _rt::string_lift(bytes11),
+                                    tokens_used: l12 as u32,
+                                    usage: TokenUsage {
+                                        input_tokens: l13 as u32,
+                                        output_tokens: l14 as u32,
+                                        cached_tokens: l15 as u32,
+                                    },
+                                    confidence: match l16 {
+                                        0 => None,
+                                        1 => {
+                                            let e = {
+                                                let l17 = *ptr6
+                                                    .add(20 + 3 * ::core::mem::size_of::<*const u8>())
+                                                    .cast::<f32>();
+                                                l17
+                                            };
+                                            Some(e)
+                                        }
+                                        _ => _rt::invalid_enum_discriminant(),
+                                    },
+                                    provider: _rt::string_lift(bytes20),
+                                    model: _rt::string_lift(bytes23),
                                 }
                             };
                             Ok(e)
                         }
                         1 => {
                             let e = {
-                                let l12 = *ptr4
+                                let l24 = *ptr6
                                     .add(::core::mem::size_of::<*const u8>())
                                     .cast::<i32>();
-                                let l13 = *ptr4
+                                let l25 = *ptr6
                                     .add(2 * ::core::mem::size_of::<*const u8>())
                                     .cast::<*mut u8>();
-                                let l14 = *ptr4
+                                let l26 = *ptr6
                                     .add(3 * ::core::mem::size_of::<*const u8>())
                                     .cast::<usize>();
-                                let len15 = l14;
-                                let bytes15 = _rt::Vec::from_raw_parts(
-                                    l13.cast(),
-                                    len15,
-                                    len15,
+                                let len27 = l26;
+                                let bytes27 = _rt::Vec::from_raw_parts(
+                                    l25.cast(),
+                                    len27,
+                                    len27,
                                 );
                                 super::super::super::fractal::app::ai_embeddings::AiError {
-                                    code: l12 as u32,
-                                    message: _rt::string_lift(bytes15),
+                                    code: l24 as u32,
+                                    message: _rt::string_lift(bytes27),
+                                }
+                            };
+                            Err(e)
+                        }
+                        _ => _rt::invalid_enum_discriminant(),
+                    };
+                    result28
+                }
+            }
+        }
+        /// --- Composition ---
+        /// Run another app and wait for its result. The callee runs in its own
+        /// instance with the grants and tier of its own manifest, sees `payload`
+        /// through events-receive as an event of type `invoke` from the calling app,
+        /// and records its own audit entries; the caller's trail records the call.
+        #[allow(dead_code, async_fn_in_trait, unused_imports, clippy::all)]
+        pub mod app_invoke {
+            #[used]
+            #[doc(hidden)]
+            static __FORCE_SECTION_REF: fn() = super::super::super::__link_custom_section_describing_imports;
+            use super::super::super::_rt;
+            /// Codes: 1 invocation not available, 2 unknown app or calls nested too
+            /// deeply, 3 the callee could not be run, 4 the callee returned an error
+            /// (the message is its error), 403 app not granted, 429 fuel slice
+            /// larger than the caller has left.
+            #[derive(Clone)]
+            pub struct InvokeError {
+                pub code: u32,
+                pub message: _rt::String,
+            }
+            impl ::core::fmt::Debug for InvokeError {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("InvokeError")
+                        .field("code", &self.code)
+                        .field("message", &self.message)
+                        .finish()
+                }
+            }
+            impl ::core::fmt::Display for InvokeError {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    write!(f, "{:?}", self)
+                }
+            }
+            impl std::error::Error for InvokeError {}
+            #[derive(Clone)]
+            pub struct InvokeRequest {
+                pub app: _rt::String,
+                pub payload: _rt::Vec<u8>,
+                /// Fuel the callee may use, charged to the caller. None gives it all
+                /// the caller has left; its tier may lower it further.
+                pub fuel: Option<u64>,
+            }
+            impl ::core::fmt::Debug for InvokeRequest {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("InvokeRequest")
+                        .field("app", &self.app)
+                        .field("payload", &self.payload)
+                        .field("fuel", &self.fuel)
+                        .finish()
+                }
+            }
+            #[derive(Clone)]
+            pub struct InvokeResponse {
+                /// The callee's `run` output.
+                pub output: _rt::String,
+                pub fuel_consumed: u64,
+            }
+            impl ::core::fmt::Debug for InvokeResponse {
+                fn fmt(
+                    &self,
+                    f: &mut ::core::fmt::Formatter<'_>,
+                ) -> ::core::fmt::Result {
+                    f.debug_struct("InvokeResponse")
+                        .field("output", &self.output)
+                        .field("fuel-consumed", &self.fuel_consumed)
+                        .finish()
+                }
+            }
+            #[allow(unused_unsafe, clippy::all)]
+            pub fn invoke(
+                request: &InvokeRequest,
+            ) -> Result<InvokeResponse, InvokeError> {
+                unsafe {
+                    #[repr(align(8))]
+                    struct RetArea(
+                        [::core::mem::MaybeUninit<
+                            u8,
+                        >; 16 + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let mut ret_area = RetArea(
+                        [::core::mem::MaybeUninit::uninit(); 16
+                            + 2 * ::core::mem::size_of::<*const u8>()],
+                    );
+                    let InvokeRequest { app: app0, payload: payload0, fuel: fuel0 } = request;
+                    let vec1 = app0;
+                    let ptr1 = vec1.as_ptr().cast::<u8>();
+                    let len1 = vec1.len();
+                    let vec2 = payload0;
+                    let ptr2 = vec2.as_ptr().cast::<u8>();
+                    let len2 = vec2.len();
+                    let (result3_0, result3_1) = match fuel0 {
+                        Some(e) => (1i32, _rt::as_i64(e)),
+                        None => (0i32, 0i64),
+                    };
+                    let ptr4 = ret_area.0.as_mut_ptr().cast::<u8>();
+                    #[cfg(target_arch = "wasm32")]
+                    #[link(wasm_import_module = "fractal:app/app-invoke@0.1.0")]
+                    unsafe extern "C" {
+                        #[link_name = "invoke"]
+                        fn wit_import5(
+                            _: *mut u8,
+                            _: usize,
+                            _: *mut u8,
+                            _: usize,
+                            _: i32,
+                            _: i64,
+                            _: *mut u8,
+                        );
+                    }
+                    #[cfg(not(target_arch = "wasm32"))]
+                    unsafe extern "C" fn wit_import5(
+                        _: *mut u8,
+                        _: usize,
+                        _: *mut u8,
+                        _: usize,
+                        _: i32,
+                        _: i64,
+                        _: *mut u8,
+                    ) {
+                        unreachable!()
+                    }
+                    unsafe {
+                        wit_import5(
+                            ptr1.cast_mut(),
+                            len1,
+                            ptr2.cast_mut(),
+                            len2,
+                            result3_0,
+                            result3_1,
+                            ptr4,
+                        )
+                    };
+                    let l6 = i32::from(*ptr4.add(0).cast::<u8>());
+                    let result15 = match l6 {
+                        0 => {
+                            let e = {
+                                let l7 = *ptr4.add(8).cast::<*mut u8>();
+                                let l8 = *ptr4
+                                    .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len9 = l8;
+                                let bytes9 = _rt::Vec::from_raw_parts(
+                                    l7.cast(),
+                                    len9,
+                                    len9,
+                                );
+                                let l10 = *ptr4
+                                    .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<i64>();
+                                InvokeResponse {
+                                    output: _rt::string_lift(bytes9),
+                                    fuel_consumed: l10 as u64,
+                                }
+                            };
+                            Ok(e)
+                        }
+                        1 => {
+                            let e = {
+                                let l11 = *ptr4.add(8).cast::<i32>();
+                                let l12 = *ptr4
+                                    .add(8 + 1 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<*mut u8>();
+                                let l13 = *ptr4
+                                    .add(8 + 2 * ::core::mem::size_of::<*const u8>())
+                                    .cast::<usize>();
+                                let len14 = l13;
+                                let bytes14 = _rt::Vec::from_raw_parts(
+                                    l12.cast(),
+                                    len14,
+                                    len14,
+                                );
+                                InvokeError {
+                                    code: l11 as u32,
+                                    message: _rt::string_lift(bytes14),
                                 }
                             };
                             Err(e)
                         }
                         _ => _rt::invalid_enum_discriminant(),
                     };
-                    result16
+                    result15
                 }
             }
         }
@@ -874,6 +2010,29 @@ mod _rt {
         let layout = alloc::Layout::from_size_align_unchecked(size, align);
         alloc::dealloc(ptr, layout);
     }
+    pub fn as_i64<T: AsI64>(t: T) -> i64 {
+        t.as_i64()
+    }
+    pub trait AsI64 {
+        fn as_i64(self) -> i64;
+    }
+    impl<'a, T: Copy + AsI64> AsI64 for &'a T {
+        fn as_i64(self) -> i64 {
+            (*self).as_i64()
+        }
+    }
+    impl AsI64 for i64 {
+        #[inline]
+        fn as_i64(self) -> i64 {
+            self as i64
+        }
+    }
+    impl AsI64 for u64 {
+        #[inline]
+        fn as_i64(self) -> i64 {
+            self as i64
+        }
+    }
     pub fn as_i32<T: AsI32>(t: T) -> i32 {
         t.as_i32()
     }
@@ -991,9 +2150,9 @@ pub(crate) use __export_micro_app_impl as export;
 )]
 #[doc(hidden)]
 #[allow(clippy::octal_escapes)]
-pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 914] = *b"\
-\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\x92\x06\x01A\x02\x01\
-A\x0e\x01B\x04\x01r\x03\x0aevent-types\x08resources\x06details\x04\0\x0baudit-en\
+pub static __WIT_BINDGEN_COMPONENT_TYPE: [u8; 1997] = *b"\
+\0asm\x0d\0\x01\0\0\x19\x16wit-component-encoding\x04\0\x07\xcd\x0e\x01A\x02\x01\
+A\x1b\x01B\x04\x01r\x03\x0aevent-types\x08resources\x06details\x04\0\x0baudit-en\
 try\x03\0\0\x01@\x01\x05entry\x01\x01\0\x04\0\x0crecord-event\x01\x02\x03\0\x1bf\
 ractal:app/audit-log@0.1.0\x05\0\x01B\x06\x01r\x02\x04codey\x07messages\x04\0\x0b\
 query-error\x03\0\0\x01p}\x01j\x01\x02\x01\x01\x01@\x01\x03sqls\0\x03\x04\0\x05q\
@@ -1004,15 +2163,39 @@ codey\x07messages\x04\0\x0cmutate-error\x03\0\0\x01p}\x01j\x01w\x01\x01\x01@\x02
 \x02\x04codey\x07messages\x04\0\x08ai-error\x03\0\0\x01pv\x01j\x01\x02\x01\x01\x01\
 @\x01\x04texts\0\x03\x04\0\x05embed\x01\x04\x01ps\x01p\x02\x01j\x01\x06\x01\x01\x01\
 @\x01\x05texts\x05\0\x07\x04\0\x0bembed-batch\x01\x08\x03\0\x1ffractal:app/ai-em\
-beddings@0.1.0\x05\x03\x02\x03\0\x03\x08ai-error\x01B\x0a\x02\x03\x02\x01\x04\x04\
-\0\x08ai-error\x03\0\0\x01ks\x01r\x04\x0dsystem-prompt\x02\x0buser-prompts\x0ama\
-x-tokensy\x0btemperaturev\x04\0\x10generate-request\x03\0\x03\x01r\x03\x04texts\x0b\
-tokens-usedy\x0aconfidencev\x04\0\x11generate-response\x03\0\x05\x01j\x01\x06\x01\
-\x01\x01@\x01\x07request\x04\0\x07\x04\0\x08generate\x01\x08\x03\0\x1efractal:ap\
-p/ai-inference@0.1.0\x05\x05\x01j\x01s\x01s\x01@\0\0\x06\x04\0\x03run\x01\x07\x04\
-\0\x1bfractal:app/micro-app@0.1.0\x04\0\x0b\x0f\x01\0\x09micro-app\x03\0\0\0G\x09\
-producers\x01\x0cprocessed-by\x02\x0dwit-component\x070.227.1\x10wit-bindgen-rus\
-t\x060.41.0";
+beddings@0.1.0\x05\x03\x02\x03\0\x03\x08ai-error\x01B\x0d\x02\x03\x02\x01\x04\x04\
+\0\x08ai-error\x03\0\0\x01r\x02\x08categorys\x05scorev\x04\0\x0eclassification\x03\
+\0\x02\x01m\x02\x06family\x06domain\x04\0\x08taxonomy\x03\0\x04\x01ps\x01p\x03\x01\
+j\x01\x07\x01\x01\x01@\x02\x04texts\x0acategories\x06\0\x08\x04\0\x08classify\x01\
+\x09\x01@\x02\x04texts\x08taxonomy\x05\0\x08\x04\0\x10classify-trained\x01\x0a\x03\
+\0\x1dfractal:app/ai-classify@0.1.0\x05\x05\x01B\x0a\x01r\x02\x04codey\x07messag\
+es\x04\0\x0bevent-error\x03\0\0\x01p}\x01r\x03\x0aevent-types\x07payload\x02\x0a\
+source-apps\x04\0\x0cdomain-event\x03\0\x03\x01w\x04\0\x08event-id\x03\0\x05\x01\
+j\x01\x06\x01\x01\x01@\x01\x05event\x04\0\x07\x04\0\x04emit\x01\x08\x03\0\x1dfra\
+ctal:app/events-emit@0.1.0\x05\x06\x02\x03\0\x05\x0cdomain-event\x02\x03\0\x05\x08\
+event-id\x01B\x09\x02\x03\x02\x01\x07\x04\0\x0cdomain-event\x03\0\0\x02\x03\x02\x01\
+\x08\x04\0\x08event-id\x03\0\x02\x01r\x03\x02id\x03\x05event\x01\x07attempty\x04\
+\0\x08delivery\x03\0\x04\x01k\x05\x01@\0\0\x06\x04\0\x07trigger\x01\x07\x03\0\x20\
+fractal:app/events-receive@0.1.0\x05\x09\x02\x03\0\x05\x0bevent-error\x01B\x0f\x02\
+\x03\x02\x01\x0a\x04\0\x0bevent-error\x03\0\0\x01ks\x01kw\x01p}\x01r\x05\x04name\
+s\x04cron\x02\x06run-at\x03\x0atarget-apps\x07payload\x04\x04\0\x0escheduled-tas\
+k\x03\0\x05\x01w\x04\0\x07task-id\x03\0\x07\x01j\x01\x08\x01\x01\x01@\x01\x04tas\
+k\x06\0\x09\x04\0\x08schedule\x01\x0a\x01j\0\x01\x01\x01@\x01\x02id\x08\0\x0b\x04\
+\0\x06cancel\x01\x0c\x03\0!fractal:app/events-schedule@0.1.0\x05\x0b\x01B\x0d\x02\
+\x03\x02\x01\x04\x04\0\x08ai-error\x03\0\0\x01ks\x01r\x05\x0dsystem-prompt\x02\x0b\
+user-prompts\x0amax-tokensy\x0btemperaturev\x0bjson-schema\x02\x04\0\x10generate\
+-request\x03\0\x03\x01r\x03\x0cinput-tokensy\x0doutput-tokensy\x0dcached-tokensy\
+\x04\0\x0btoken-usage\x03\0\x05\x01kv\x01r\x06\x04texts\x0btokens-usedy\x05usage\
+\x06\x0aconfidence\x07\x08providers\x05models\x04\0\x11generate-response\x03\0\x08\
+\x01j\x01\x09\x01\x01\x01@\x01\x07request\x04\0\x0a\x04\0\x08generate\x01\x0b\x03\
+\0\x1efractal:app/ai-inference@0.1.0\x05\x0c\x01B\x0b\x01r\x02\x04codey\x07messa\
+ges\x04\0\x0cinvoke-error\x03\0\0\x01p}\x01kw\x01r\x03\x03apps\x07payload\x02\x04\
+fuel\x03\x04\0\x0einvoke-request\x03\0\x04\x01r\x02\x06outputs\x0dfuel-consumedw\
+\x04\0\x0finvoke-response\x03\0\x06\x01j\x01\x07\x01\x01\x01@\x01\x07request\x05\
+\0\x08\x04\0\x06invoke\x01\x09\x03\0\x1cfractal:app/app-invoke@0.1.0\x05\x0d\x01\
+j\x01s\x01s\x01@\0\0\x0e\x04\0\x03run\x01\x0f\x04\0\x1bfractal:app/micro-app@0.1\
+.0\x04\0\x0b\x0f\x01\0\x09micro-app\x03\0\0\0G\x09producers\x01\x0cprocessed-by\x02\
+\x0dwit-component\x070.227.1\x10wit-bindgen-rust\x060.41.0";
 #[inline(never)]
 #[doc(hidden)]
 pub fn __link_custom_section_describing_imports() {
@@ -1,7 +1,7 @@
 #[allow(warnings)]
 mod bindings;
 
-use bindings::fractal::app::{ai_inference, audit_log};
+use bindings::fractal::app::{ai_embeddings, ai_inference, audit_log};
 use bindings::Guest;
 
 struct InferenceTest;
@@ -14,11 +14,37 @@ impl Guest for InferenceTest {
             detail: "AI inference integration test".to_string(),
         });
 
+        let single = ai_embeddings::embed("Health and safety at work")
+            .map_err(|e| format!("Embedding failed: {} (code {})", e.message, e.code))?;
+
+        let texts = [
+            "Chemical exposure limits in the workplace".to_string(),
+            "Fire safety regulations for commercial buildings".to_string(),
+            "Environmental protection and waste disposal".to_string(),
+        ];
+        let batch = ai_embeddings::embed_batch(&texts)
+            .map_err(|e| format!("Batch embedding failed: {} (code {})", e.message, e.code))?;
+
+        if batch.len() != texts.len() || batch.iter().any(|v| v.len() != single.len()) {
+            return Err(format!(
+                "Embedding shape mismatch: {} vectors for {} texts",
+                batch.len(),
+                texts.len()
+            ));
+        }
+
+        audit_log::record_event(&audit_log::AuditEntry {
+            event_type: "embed-complete".to_string(),
+            resource: "inference-test".to_string(),
+            detail: format!("dim={}, batch={}", single.len(), batch.len()),
+        });
+
         let request = ai_inference::GenerateRequest {
             system_prompt: Some("You are a concise assistant. Reply in one short sentence.".into()),
             user_prompt: "What is 2+2?".into(),
             max_tokens: 64,
             temperature: 0.0,
+            json_schema: None,
         };
 
         let response = ai_inference::generate(&request)
@@ -28,8 +54,11 @@ impl Guest for InferenceTest {
             event_type: "inference-complete".to_string(),
             resource: "inference-test".to_string(),
             detail: format!(
-                "tokens_used={}, confidence={}",
-                response.tokens_used, response.confidence
+                "input_tokens={}, output_tokens={}, cached_tokens={}, confidence={:?}",
+                response.usage.input_tokens,
+                response.usage.output_tokens,
+                response.usage.cached_tokens,
+                response.confidence
             ),
         });
 